@@ -0,0 +1,274 @@
+use serde::{Deserialize, Serialize};
+
+use crate::events::{EventKind, RunEvent};
+use crate::metrics::percentiles::PercentileSet;
+use crate::metrics::MetricsSnapshot;
+
+// ─── Thresholds ──────────────────────────────────────────────────
+
+/// A timeline window this many times the run's median is a spike
+const SPIKE_FACTOR: f64 = 3.0;
+
+/// Spikes need this many windows to compare against
+const MIN_TIMELINE_POINTS: usize = 8;
+
+/// Events this close to a spike window count as coinciding with it;
+/// the event log and the timeline start a few ms apart
+const EVENT_SLACK_MS: u64 = 1_000;
+
+/// Rust overhead share of e2e that varies less than this (in points
+/// of percent) across windows is called stable
+const STABLE_SHARE_PTS: f64 = 5.0;
+
+/// Error rate worth calling out
+const ERROR_RATE_PCT: f64 = 1.0;
+
+/// p99.9 / p50 above this is a long tail
+const TAIL_RATIO: f64 = 20.0;
+
+/// Open loop: corrected p99 this far above the plain one means the
+/// server fell behind the schedule
+const LAG_RATIO: f64 = 2.0;
+
+/// Jain's index below this is an uneven spread across workers
+const FAIRNESS_MIN: f64 = 0.9;
+
+// ─── Findings ────────────────────────────────────────────────────
+
+/// One observation about a finished run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    /// Stable rule name, for filtering
+    pub rule: String,
+    pub message: String,
+}
+
+/// First-pass diagnosis of a finished run: fixed rules over the final
+/// snapshot and the run's event log. `events` must share the
+/// snapshot's time base (see [`rebase`]).
+pub fn analyze(snap: &MetricsSnapshot, events: &[RunEvent]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    // Why the run ended early, as the health guard logged it
+    if let Some(abort) = events.iter().find(|e| e.kind == EventKind::Aborted)
+    {
+        findings.push(Finding {
+            rule: "aborted".into(),
+            message: format!("aborted: {}", abort.message),
+        });
+    }
+    if snap.total_requests == 0 {
+        findings.push(Finding {
+            rule: "no_requests".into(),
+            message: "no requests were recorded".into(),
+        });
+        return findings;
+    }
+    let mut note = |rule: &'static str, message: String| {
+        findings.push(Finding {
+            rule: rule.into(),
+            message,
+        });
+    };
+
+    if let Some(message) = p99_layer(snap) {
+        note("p99_layer", message);
+    }
+    if let Some(message) = rust_share(snap) {
+        note("rust_overhead", message);
+    }
+    if let Some(message) = spikes(snap, events) {
+        note("spikes", message);
+    }
+
+    let error_pct =
+        100.0 * snap.total_errors as f64 / snap.total_requests as f64;
+    if error_pct >= ERROR_RATE_PCT {
+        let mut message = format!("{error_pct:.1}% of requests failed");
+        if snap.total_timeouts > 0 {
+            let share = 100.0 * snap.total_timeouts as f64
+                / snap.total_errors as f64;
+            message.push_str(&format!(", {share:.0}% of them timeouts"));
+        }
+        note("errors", message);
+    }
+    if snap.total_backoff_ms > 0 {
+        note(
+            "backoff",
+            format!(
+                "workers spent {:.1}s in total backing off after errors",
+                snap.total_backoff_ms as f64 / 1_000.0
+            ),
+        );
+    }
+
+    let e2e = &snap.e2e;
+    if e2e.p50 > 0 && e2e.p999 as f64 >= TAIL_RATIO * e2e.p50 as f64 {
+        note(
+            "long_tail",
+            format!(
+                "long tail: e2e p99.9 ({}) is {:.0}× p50 ({})",
+                fmt_us(e2e.p999),
+                e2e.p999 as f64 / e2e.p50 as f64,
+                fmt_us(e2e.p50)
+            ),
+        );
+    }
+    let corrected = &snap.e2e_corrected;
+    if e2e.p99 > 0 && corrected.p99 as f64 >= LAG_RATIO * e2e.p99 as f64 {
+        note(
+            "schedule_lag",
+            format!(
+                "the server fell behind the open-loop schedule: corrected \
+                 p99 {} vs {} as measured",
+                fmt_us(corrected.p99),
+                fmt_us(e2e.p99)
+            ),
+        );
+    }
+    if let Some(fairness) =
+        snap.fairness.as_ref().filter(|f| f.jain_index < FAIRNESS_MIN)
+    {
+        note(
+            "fairness",
+            format!(
+                "uneven load across workers (Jain's index {:.2}, {:.0}–{:.0} \
+                 ops/s per worker)",
+                fairness.jain_index,
+                fairness.min_ops_per_sec,
+                fairness.max_ops_per_sec
+            ),
+        );
+    }
+    findings
+}
+
+/// Keep the events at or after `from_ms`, shifted to start there. For
+/// runs that reset metrics mid-log (experiment phases, sweep steps).
+pub fn rebase(events: &[RunEvent], from_ms: u64) -> Vec<RunEvent> {
+    events
+        .iter()
+        .filter(|e| e.timestamp_ms >= from_ms)
+        .cloned()
+        .map(|mut e| {
+            e.timestamp_ms -= from_ms;
+            e
+        })
+        .collect()
+}
+
+// ─── Rules ───────────────────────────────────────────────────────
+
+/// Which measured layer has the largest p99.
+fn p99_layer(snap: &MetricsSnapshot) -> Option<String> {
+    let layers: [(&str, &PercentileSet); 3] = [
+        ("redis_read", &snap.redis_read),
+        ("redis_write", &snap.redis_write),
+        ("rust_overhead", &snap.rust_overhead),
+    ];
+    let (name, top) = layers
+        .into_iter()
+        .filter(|(_, set)| set.count > 0)
+        .max_by_key(|(_, set)| set.p99)?;
+    Some(format!(
+        "p99 dominated by {name} ({} of {} e2e p99)",
+        fmt_us(top.p99),
+        fmt_us(snap.e2e.p99)
+    ))
+}
+
+/// Rust overhead as a share of e2e, and whether it held steady.
+fn rust_share(snap: &MetricsSnapshot) -> Option<String> {
+    if snap.e2e.mean <= 0.0 {
+        return None;
+    }
+    let overall = 100.0 * snap.rust_overhead.mean / snap.e2e.mean;
+    let shares: Vec<f64> = snap
+        .timeline
+        .iter()
+        .filter(|p| p.avg_total_us > 0.0)
+        .map(|p| 100.0 * p.avg_rust_us / p.avg_total_us)
+        .collect();
+    let low = shares.iter().copied().fold(f64::INFINITY, f64::min);
+    let high = shares.iter().copied().fold(0.0, f64::max);
+    Some(match shares.len() >= 2 && high - low >= STABLE_SHARE_PTS {
+        true => format!(
+            "rust overhead averaged {overall:.0}% of e2e but moved between \
+             {low:.0}% and {high:.0}%"
+        ),
+        false => format!("rust overhead stable at ~{overall:.0}% of e2e"),
+    })
+}
+
+/// Count runs of timeline windows far above the median, and which of
+/// them line up with errors, failovers, warnings or setting changes.
+fn spikes(snap: &MetricsSnapshot, events: &[RunEvent]) -> Option<String> {
+    let timeline = &snap.timeline;
+    if timeline.len() < MIN_TIMELINE_POINTS {
+        return None;
+    }
+    let mut totals: Vec<f64> =
+        timeline.iter().map(|p| p.avg_total_us).collect();
+    totals.sort_by(f64::total_cmp);
+    let median = totals[totals.len() / 2];
+    let limit = median * SPIKE_FACTOR;
+
+    // Consecutive hot windows make one spike: (start, end) in ms
+    let mut spans: Vec<(u64, u64)> = Vec::new();
+    let mut previous_hot = false;
+    for point in timeline {
+        let hot = point.avg_total_us > limit;
+        let end = point.timestamp_ms + point.window_ms;
+        match spans.last_mut() {
+            Some(span) if hot && previous_hot => span.1 = end,
+            _ if hot => spans.push((point.timestamp_ms, end)),
+            _ => {}
+        }
+        previous_hot = hot;
+    }
+    if spans.is_empty() {
+        return None;
+    }
+
+    let near = |(start, end): (u64, u64), kind: EventKind| {
+        events.iter().any(|e| {
+            e.kind == kind
+                && e.timestamp_ms + EVENT_SLACK_MS >= start
+                && e.timestamp_ms <= end + EVENT_SLACK_MS
+        })
+    };
+    let total = spans.len();
+    let mut message = format!(
+        "{total} latency spike{} above {SPIKE_FACTOR}× the median window \
+         ({})",
+        if total == 1 { "" } else { "s" },
+        fmt_us(median as u64)
+    );
+    let causes = [
+        (EventKind::Error, "errors"),
+        (EventKind::Failover, "failovers"),
+        (EventKind::Warning, "warnings"),
+        (EventKind::ConfigChange, "setting changes"),
+    ];
+    let matched: Vec<String> = causes
+        .iter()
+        .filter_map(|&(kind, label)| {
+            let n = spans.iter().filter(|s| near(**s, kind)).count();
+            (n > 0).then(|| format!("{label} coincide with {n} of {total}"))
+        })
+        .collect();
+    if !matched.is_empty() {
+        message.push_str("; ");
+        message.push_str(&matched.join(", "));
+    }
+    Some(message)
+}
+
+// ─── Helpers ─────────────────────────────────────────────────────
+
+fn fmt_us(us: u64) -> String {
+    match us {
+        0..=999 => format!("{us}µs"),
+        1_000..=999_999 => format!("{:.2}ms", us as f64 / 1_000.0),
+        _ => format!("{:.2}s", us as f64 / 1_000_000.0),
+    }
+}
@@ -0,0 +1,857 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use hdrhistogram::serialization::{Deserializer, Serializer, V2Serializer};
+use hdrhistogram::Histogram;
+use parking_lot::Mutex;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::analysis::Finding;
+use crate::commandstats::CommandDelta;
+use crate::compare::{self, Tolerance, Verdict};
+use crate::events::RunEvent;
+use crate::handlers::benchmark::RunMetadata;
+use crate::metrics::percentiles::{PercentileMode, PercentileSet};
+use crate::metrics::{MetricsCollector, MetricsSnapshot, WorkerClient};
+use crate::mock_data::DatasetManifest;
+use crate::redis_client::RedisTarget;
+use crate::report::RunReport;
+use crate::slo::SloResult;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// How many finished or imported runs are kept for `/api/runs` unless
+/// `--max-runs` says otherwise
+pub const DEFAULT_MAX_RUNS: usize = 64;
+
+/// Shared history: one hash per run, `<prefix><run_id>`
+const SHARED_RUN_PREFIX: &str = "bench:runs:";
+
+/// Shared history: run ids scored by archive time (epoch ms)
+const SHARED_INDEX_KEY: &str = "bench:runs:index";
+
+/// Most runs one `GET /api/runs/shared` returns
+pub const MAX_SHARED_LIST: usize = 1_000;
+
+/// Saved runs are zstd-compressed JSON, `<run_id>.json.zst`
+const SAVED_EXT: &str = "json.zst";
+
+/// zstd's default level: most of the gain for a fraction of the CPU
+const SAVED_ZSTD_LEVEL: i32 = 3;
+
+// ─── Public types ────────────────────────────────────────────────
+
+/// Which tool produced a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunSource {
+    /// A run of this server's own load generator
+    Native,
+    /// `memtier_benchmark --json-out-file`
+    Memtier,
+    /// `redis-benchmark --csv`
+    RedisBenchmark,
+}
+
+impl RunSource {
+    /// Same spelling as the serialized form
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Native => "native",
+            Self::Memtier => "memtier",
+            Self::RedisBenchmark => "redis_benchmark",
+        }
+    }
+}
+
+/// Tool-neutral summary of one run. Latencies are μs; percentiles the
+/// source tool did not report are 0.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedRun {
+    pub run_id: String,
+    pub source: RunSource,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Native runs: the caller's `metadata` from the run's config
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<RunMetadata>,
+    /// When the run was archived (RFC 3339, UTC)
+    pub archived_at: String,
+    pub duration_secs: Option<f64>,
+    pub total_requests: u64,
+    pub total_errors: u64,
+    pub requests_per_sec: f64,
+    /// Overall per-request latency, when the tool reports one
+    pub latency: Option<PercentileSet>,
+    /// Per-command results, keyed by command (`GET`, `SET`, …)
+    pub ops: BTreeMap<String, ArchivedOp>,
+    /// Native runs: what `INFO commandstats` counted during the run,
+    /// keyed by the server's lowercase command names
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub commandstats: BTreeMap<String, CommandDelta>,
+    /// Native runs with `named_workers`: worker → server client id,
+    /// for matching SLOWLOG or CLIENT LIST entries after the fact
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workers: Vec<WorkerClient>,
+    /// Native runs: the seeded dataset the run read and wrote. Runs
+    /// with different checksums did not see the same data.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dataset: Option<DatasetManifest>,
+    /// Native runs: rule-based first-pass diagnosis
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub analysis: Vec<Finding>,
+    /// Benchmark and scenario runs: how the run held up against the
+    /// baseline set when it finished
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<Verdict>,
+    /// Native runs with an `slo` in their config: pass or fail
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slo: Option<SloResult>,
+    /// Native runs: the raw histograms, so runs can be merged later.
+    /// Not saved to disk.
+    #[serde(skip)]
+    pub histograms: Option<StoredHistograms>,
+    /// Native runs: the final snapshot, until `record` saves it
+    #[serde(skip)]
+    pub snapshot: Option<Box<MetricsSnapshot>>,
+    /// Native runs: the event log, until `record` saves it
+    #[serde(skip)]
+    pub events: Vec<RunEvent>,
+}
+
+/// A native run's end-to-end and per-op HDR histograms in the compact
+/// V2 encoding (a full 60 s-range histogram is ~200 KB in memory).
+#[derive(Debug, Clone)]
+pub struct StoredHistograms {
+    latency: Vec<u8>,
+    ops: BTreeMap<String, Vec<u8>>,
+}
+
+/// Native runs in a trend window, merged histogram by histogram.
+#[derive(Debug, Serialize)]
+pub struct TrendAggregate {
+    /// Runs archived at or after this time (RFC 3339, UTC)
+    pub since: String,
+    /// Merged runs, oldest first
+    pub run_ids: Vec<String>,
+    /// Runs in the window without histograms (imports), left out
+    pub skipped_runs: usize,
+    pub total_requests: u64,
+    pub total_errors: u64,
+    /// End-to-end latency over every request of every merged run
+    pub latency: PercentileSet,
+    pub ops: BTreeMap<String, PercentileSet>,
+    /// Plain mean of the runs' own p99s. Shown for contrast only: it
+    /// is not the p99 of anything.
+    pub mean_of_run_p99s: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedOp {
+    pub requests_per_sec: Option<f64>,
+    pub latency: PercentileSet,
+}
+
+/// The run later runs are judged against, kept whole so it outlives
+/// its place in the archive.
+#[derive(Debug, Clone, Serialize)]
+pub struct Baseline {
+    pub run: ArchivedRun,
+    pub tolerance: Tolerance,
+    /// When it was set (RFC 3339, UTC)
+    pub set_at: String,
+}
+
+/// Bounded store of run summaries, oldest evicted first. Every run is
+/// also saved to `dir` and written to `shared` when they are set.
+pub struct RunArchive {
+    runs: Mutex<VecDeque<ArchivedRun>>,
+    baseline: Mutex<Option<Baseline>>,
+    /// Report of the last benchmark or scenario run to finish
+    report: Mutex<Option<RunReport>>,
+    /// Stamped on every native run
+    dataset: DatasetManifest,
+    dir: Option<PathBuf>,
+    /// Redis holding the history shared between bench instances
+    shared: Option<Arc<RedisTarget>>,
+    /// Runs kept in memory; the oldest is evicted past it
+    max_runs: usize,
+}
+
+// ─── RunArchive impl ─────────────────────────────────────────────
+
+impl RunArchive {
+    pub fn new(
+        dataset: DatasetManifest,
+        dir: Option<PathBuf>,
+        shared: Option<Arc<RedisTarget>>,
+        max_runs: usize,
+    ) -> Self {
+        Self {
+            runs: Mutex::new(VecDeque::with_capacity(max_runs + 1)),
+            baseline: Mutex::new(None),
+            report: Mutex::new(None),
+            dataset,
+            dir,
+            shared,
+            max_runs,
+        }
+    }
+
+    /// Read back the newest `max_runs` runs an earlier process saved;
+    /// returns how many were loaded.
+    pub fn load(&self) -> usize {
+        let Some(entries) =
+            self.dir.as_ref().and_then(|dir| std::fs::read_dir(dir).ok())
+        else {
+            return 0;
+        };
+        let mut saved: Vec<ArchivedRun> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| is_saved_run(path))
+            .filter_map(|path| match read_run(&path) {
+                Ok(run) => Some(run),
+                Err(e) => {
+                    eprintln!("   ⚠️  skipping {}: {e}", path.display());
+                    None
+                }
+            })
+            .collect();
+        // RFC 3339 in UTC sorts as text
+        saved.sort_by(|a, b| a.archived_at.cmp(&b.archived_at));
+        let skip = saved.len().saturating_sub(self.max_runs);
+        let mut runs = self.runs.lock();
+        runs.extend(saved.into_iter().skip(skip));
+        runs.len()
+    }
+
+    pub fn record(&self, mut run: ArchivedRun) {
+        if run.source == RunSource::Native {
+            run.dataset = Some(self.dataset.clone());
+        }
+        let snapshot = run.snapshot.take();
+        let events = std::mem::take(&mut run.events);
+        if let Some(dir) = &self.dir {
+            save(dir, &run, snapshot.as_deref(), &events);
+        }
+        if let Some(redis) = &self.shared {
+            share(redis.manager(), &run);
+        }
+        let mut runs = self.runs.lock();
+        runs.push_back(run);
+        if runs.len() > self.max_runs {
+            runs.pop_front();
+        }
+    }
+
+    /// Every retained run, oldest first.
+    pub fn all(&self) -> Vec<ArchivedRun> {
+        self.runs.lock().iter().cloned().collect()
+    }
+
+    pub fn get(&self, run_id: &str) -> Option<ArchivedRun> {
+        self.runs.lock().iter().find(|r| r.run_id == run_id).cloned()
+    }
+
+    pub fn set_report(&self, report: RunReport) {
+        *self.report.lock() = Some(report);
+    }
+
+    pub fn report(&self) -> Option<RunReport> {
+        self.report.lock().clone()
+    }
+
+    /// Judge later runs against `run`; replaces any earlier baseline.
+    pub fn set_baseline(&self, run: ArchivedRun, tolerance: Tolerance) {
+        *self.baseline.lock() = Some(Baseline {
+            run,
+            tolerance,
+            set_at: chrono::Utc::now().to_rfc3339(),
+        });
+    }
+
+    pub fn baseline(&self) -> Option<Baseline> {
+        self.baseline.lock().clone()
+    }
+
+    pub fn clear_baseline(&self) -> Option<Baseline> {
+        self.baseline.lock().take()
+    }
+
+    /// `run` against the baseline; `None` without one, or for the
+    /// baseline itself.
+    pub fn judge(&self, run: &ArchivedRun) -> Option<Verdict> {
+        let baseline = self.baseline.lock();
+        let baseline = baseline
+            .as_ref()
+            .filter(|b| b.run.run_id != run.run_id)?;
+        Some(compare::verdict(&baseline.run, run, &baseline.tolerance))
+    }
+
+    /// Connection to the shared history, when it is on.
+    pub fn shared(&self) -> Option<ConnectionManager> {
+        self.shared.as_ref().map(|redis| redis.manager())
+    }
+
+    /// The JSON saved for `run_id`, decompressed. `None` when saving is
+    /// off, the run was never saved, or the id could leave the
+    /// directory.
+    pub async fn read_saved(&self, run_id: &str) -> Option<Vec<u8>> {
+        let safe = !run_id.is_empty()
+            && run_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        let dir = self.dir.clone().filter(|_| safe)?;
+        let run_id = run_id.to_owned();
+        tokio::task::spawn_blocking(move || {
+            // Runs saved before compression are plain `.json`
+            [SAVED_EXT, "json"].into_iter().find_map(|ext| {
+                read_saved_file(&dir.join(format!("{run_id}.{ext}"))).ok()
+            })
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+
+    /// Merge the histograms of every native run archived at or after
+    /// `since`. Percentiles come from the merged counts, so a window of
+    /// one busy and one quiet run weighs each request equally.
+    pub fn aggregate(
+        &self,
+        since: DateTime<Utc>,
+        mode: PercentileMode,
+    ) -> Result<TrendAggregate, String> {
+        let in_window: Vec<ArchivedRun> = self
+            .runs
+            .lock()
+            .iter()
+            .filter(|r| {
+                DateTime::parse_from_rfc3339(&r.archived_at)
+                    .is_ok_and(|at| at >= since)
+            })
+            .cloned()
+            .collect();
+
+        let mut latency: Option<Histogram<u64>> = None;
+        let mut ops: BTreeMap<String, Option<Histogram<u64>>> =
+            BTreeMap::new();
+        let mut agg = TrendAggregate {
+            since: since.to_rfc3339(),
+            run_ids: Vec::new(),
+            skipped_runs: 0,
+            total_requests: 0,
+            total_errors: 0,
+            latency: PercentileSet::empty(),
+            ops: BTreeMap::new(),
+            mean_of_run_p99s: 0.0,
+        };
+        let mut p99_sum = 0.0;
+
+        for run in in_window {
+            let Some(stored) = &run.histograms else {
+                agg.skipped_runs += 1;
+                continue;
+            };
+            merge(&mut latency, decode(&stored.latency)?)?;
+            for (op, bytes) in &stored.ops {
+                merge(ops.entry(op.clone()).or_default(), decode(bytes)?)?;
+            }
+            p99_sum += run.latency.as_ref().map_or(0, |l| l.p99) as f64;
+            agg.total_requests += run.total_requests;
+            agg.total_errors += run.total_errors;
+            agg.run_ids.push(run.run_id);
+        }
+
+        if !agg.run_ids.is_empty() {
+            agg.mean_of_run_p99s = p99_sum / agg.run_ids.len() as f64;
+        }
+        if let Some(hist) = &latency {
+            agg.latency = PercentileSet::from_histogram_with(hist, mode);
+        }
+        agg.ops = ops
+            .into_iter()
+            .filter_map(|(op, hist)| {
+                Some((op, PercentileSet::from_histogram_with(&hist?, mode)))
+            })
+            .collect();
+        Ok(agg)
+    }
+}
+
+// ─── Native runs ─────────────────────────────────────────────────
+
+impl ArchivedRun {
+    /// Summarize the collector's final snapshot of a native run.
+    pub fn from_snapshot(run_id: String, snap: &MetricsSnapshot) -> Self {
+        let per_sec = |count: u64| {
+            (snap.elapsed_secs > 0.0).then(|| count as f64 / snap.elapsed_secs)
+        };
+        let ops = snap
+            .ops
+            .iter()
+            .map(|(op, latency)| {
+                let op_summary = ArchivedOp {
+                    requests_per_sec: per_sec(latency.count),
+                    latency: latency.clone(),
+                };
+                (op.clone(), op_summary)
+            })
+            .collect();
+
+        let mut run = Self {
+            run_id,
+            source: RunSource::Native,
+            label: None,
+            metadata: snap.config.as_ref().and_then(|c| c.metadata.clone()),
+            archived_at: chrono::Utc::now().to_rfc3339(),
+            duration_secs: Some(snap.elapsed_secs),
+            total_requests: snap.total_requests,
+            total_errors: snap.total_errors,
+            requests_per_sec: snap.avg_requests_per_sec,
+            latency: Some(snap.e2e.clone()),
+            ops,
+            commandstats: BTreeMap::new(),
+            workers: snap.workers.clone(),
+            dataset: None,
+            analysis: Vec::new(),
+            verdict: None,
+            slo: None,
+            histograms: None,
+            snapshot: Some(Box::new(snap.clone())),
+            events: Vec::new(),
+        };
+        run.slo = snap
+            .config
+            .as_ref()
+            .and_then(|c| c.slo)
+            .map(|slo| slo.evaluate(&run));
+        run
+    }
+
+    fn imported(source: RunSource, label: Option<String>) -> Self {
+        Self {
+            run_id: uuid::Uuid::new_v4().to_string(),
+            source,
+            label,
+            metadata: None,
+            archived_at: chrono::Utc::now().to_rfc3339(),
+            duration_secs: None,
+            total_requests: 0,
+            total_errors: 0,
+            requests_per_sec: 0.0,
+            latency: None,
+            ops: BTreeMap::new(),
+            commandstats: BTreeMap::new(),
+            workers: Vec::new(),
+            dataset: None,
+            analysis: Vec::new(),
+            verdict: None,
+            slo: None,
+            histograms: None,
+            snapshot: None,
+            events: Vec::new(),
+        }
+    }
+}
+
+impl StoredHistograms {
+    /// Encode the collector's histograms at the end of a native run.
+    pub fn capture(metrics: &MetricsCollector) -> Option<Self> {
+        let (latency, ops) = metrics.latency_histograms();
+        Some(Self {
+            latency: encode(&latency)?,
+            ops: ops
+                .iter()
+                .map(|(op, hist)| Some((op.to_string(), encode(hist)?)))
+                .collect::<Option<_>>()?,
+        })
+    }
+}
+
+fn encode(hist: &Histogram<u64>) -> Option<Vec<u8>> {
+    let mut buf = Vec::new();
+    V2Serializer::new().serialize(hist, &mut buf).ok()?;
+    Some(buf)
+}
+
+fn decode(bytes: &[u8]) -> Result<Histogram<u64>, String> {
+    Deserializer::new()
+        .deserialize(&mut &bytes[..])
+        .map_err(|e| format!("stored histogram: {e:?}"))
+}
+
+fn merge(
+    into: &mut Option<Histogram<u64>>,
+    hist: Histogram<u64>,
+) -> Result<(), String> {
+    match into {
+        Some(merged) => merged
+            .add(&hist)
+            .map_err(|e| format!("incompatible histograms: {e:?}")),
+        None => {
+            *into = Some(hist);
+            Ok(())
+        }
+    }
+}
+
+// ─── Saved runs ──────────────────────────────────────────────────
+
+/// `<dir>/<run_id>.json.zst`
+#[derive(Serialize)]
+struct RunFile<'a> {
+    run: &'a ArchivedRun,
+    /// Native runs: the final snapshot, config and metadata included
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snapshot: Option<&'a MetricsSnapshot>,
+    /// Native runs: the event log, served once the in-memory one is gone
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    events: &'a [RunEvent],
+}
+
+/// The part of a `RunFile` read back at startup; the snapshot stays
+/// on disk until `/api/runs/:id/snapshot` asks for it.
+#[derive(Deserialize)]
+struct SavedRun {
+    run: ArchivedRun,
+}
+
+fn read_run(path: &Path) -> Result<ArchivedRun, String> {
+    let bytes = read_saved_file(path).map_err(|e| e.to_string())?;
+    let saved: SavedRun =
+        serde_json::from_slice(&bytes).map_err(|e| e.to_string())?;
+    Ok(saved.run)
+}
+
+/// A saved run file (`.json.zst`, or plain `.json` from before
+/// compression), as JSON.
+fn read_saved_file(path: &Path) -> std::io::Result<Vec<u8>> {
+    let bytes = std::fs::read(path)?;
+    match path.extension().is_some_and(|ext| ext == "zst") {
+        true => zstd::decode_all(&bytes[..]),
+        false => Ok(bytes),
+    }
+}
+
+fn is_saved_run(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    name.ends_with(".json") || name.ends_with(&format!(".{SAVED_EXT}"))
+}
+
+/// Write a run in the background; the in-memory copy already serves
+/// reads, so a failure only costs the copy on disk.
+fn save(
+    dir: &Path,
+    run: &ArchivedRun,
+    snapshot: Option<&MetricsSnapshot>,
+    events: &[RunEvent],
+) {
+    let path = dir.join(format!("{}.{SAVED_EXT}", run.run_id));
+    let file = RunFile {
+        run,
+        snapshot,
+        events,
+    };
+    let bytes = match serde_json::to_vec(&file) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("⚠️  could not save run {}: {e}", run.run_id);
+            return;
+        }
+    };
+    let dir = dir.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        // Via a temporary file so a crash never leaves half a run
+        let tmp = path.with_extension("zst.tmp");
+        let written = zstd::encode_all(&bytes[..], SAVED_ZSTD_LEVEL)
+            .and_then(|packed| {
+                std::fs::create_dir_all(&dir)?;
+                std::fs::write(&tmp, packed)
+            })
+            .and_then(|_| std::fs::rename(&tmp, &path));
+        if let Err(e) = written {
+            eprintln!("⚠️  could not save {}: {e}", path.display());
+        }
+    });
+}
+
+// ─── Shared history in Redis ─────────────────────────────────────
+
+/// Write a run's hash and index entry in the background, in one
+/// transaction so the index never names a missing hash.
+fn share(mut conn: ConnectionManager, run: &ArchivedRun) {
+    let json = match serde_json::to_string(run) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("⚠️  could not share run {}: {e}", run.run_id);
+            return;
+        }
+    };
+    let score = DateTime::parse_from_rfc3339(&run.archived_at)
+        .map_or(0, |at| at.timestamp_millis());
+    let mut fields = vec![
+        ("run", json),
+        ("archived_at", run.archived_at.clone()),
+        ("source", run.source.name().to_owned()),
+        ("requests_per_sec", format!("{:.1}", run.requests_per_sec)),
+    ];
+    if let Some(latency) = &run.latency {
+        fields.push(("p99_us", latency.p99.to_string()));
+    }
+    if let Some(name) = run.metadata.as_ref().and_then(|m| m.name.clone()) {
+        fields.push(("name", name));
+    }
+    let key = format!("{SHARED_RUN_PREFIX}{}", run.run_id);
+    let run_id = run.run_id.clone();
+    tokio::spawn(async move {
+        let written: redis::RedisResult<()> = redis::pipe()
+            .atomic()
+            .hset_multiple(&key, &fields)
+            .ignore()
+            .zadd(SHARED_INDEX_KEY, &run_id, score)
+            .ignore()
+            .query_async(&mut conn)
+            .await;
+        if let Err(e) = written {
+            eprintln!("⚠️  could not share run {run_id}: {e}");
+        }
+    });
+}
+
+/// Shared runs archived at or after `since`, newest first.
+pub async fn shared_runs(
+    conn: &mut ConnectionManager,
+    since: DateTime<Utc>,
+    limit: usize,
+) -> redis::RedisResult<Vec<ArchivedRun>> {
+    let ids: Vec<String> = redis::cmd("ZREVRANGEBYSCORE")
+        .arg(SHARED_INDEX_KEY)
+        .arg("+inf")
+        .arg(since.timestamp_millis())
+        .arg("LIMIT")
+        .arg(0)
+        .arg(limit)
+        .query_async(conn)
+        .await?;
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut pipe = redis::pipe();
+    for id in &ids {
+        pipe.hget(format!("{SHARED_RUN_PREFIX}{id}"), "run");
+    }
+    let bodies: Vec<Option<String>> = pipe.query_async(conn).await?;
+    // A hash deleted by hand leaves its id behind; skip those
+    Ok(bodies
+        .iter()
+        .flatten()
+        .filter_map(|body| serde_json::from_str(body).ok())
+        .collect())
+}
+
+/// One shared run, `None` if no instance has written it.
+pub async fn shared_run(
+    conn: &mut ConnectionManager,
+    run_id: &str,
+) -> redis::RedisResult<Option<ArchivedRun>> {
+    let body: Option<String> = redis::cmd("HGET")
+        .arg(format!("{SHARED_RUN_PREFIX}{run_id}"))
+        .arg("run")
+        .query_async(conn)
+        .await?;
+    Ok(body.and_then(|body| serde_json::from_str(&body).ok()))
+}
+
+// ─── memtier_benchmark JSON ──────────────────────────────────────
+
+/// Parse a `memtier_benchmark --json-out-file` document. The "ALL
+/// STATS" section supplies the totals and one op per command type.
+pub fn import_memtier(
+    body: &str,
+    label: Option<String>,
+) -> Result<ArchivedRun, String> {
+    let doc: Value =
+        serde_json::from_str(body).map_err(|e| format!("invalid JSON: {e}"))?;
+    let stats = doc
+        .get("ALL STATS")
+        .ok_or("missing \"ALL STATS\" section")?;
+
+    let mut run = ArchivedRun::imported(RunSource::Memtier, label);
+    run.duration_secs = stats
+        .pointer("/Runtime/Total duration")
+        .and_then(Value::as_f64)
+        .map(|ms| ms / 1_000.0);
+
+    let totals = stats.get("Totals").ok_or("missing \"Totals\" stats")?;
+    run.requests_per_sec = number(totals, "Ops/sec").unwrap_or(0.0);
+    run.total_requests = memtier_count(totals, run.duration_secs);
+    run.latency = Some(memtier_latency(totals, run.total_requests));
+
+    // memtier names command types in the plural ("Sets", "Gets", …)
+    for (name, op) in [("Gets", "GET"), ("Sets", "SET"), ("Waits", "WAIT")] {
+        let Some(section) = stats.get(name) else {
+            continue;
+        };
+        let count = memtier_count(section, run.duration_secs);
+        if count == 0 {
+            continue;
+        }
+        run.ops.insert(
+            op.into(),
+            ArchivedOp {
+                requests_per_sec: number(section, "Ops/sec"),
+                latency: memtier_latency(section, count),
+            },
+        );
+    }
+    Ok(run)
+}
+
+/// Older memtier versions have no "Count"; derive it from the rate.
+fn memtier_count(section: &Value, duration_secs: Option<f64>) -> u64 {
+    number(section, "Count")
+        .or_else(|| Some(number(section, "Ops/sec")? * duration_secs?))
+        .map_or(0, |n| n.round() as u64)
+}
+
+fn memtier_latency(section: &Value, count: u64) -> PercentileSet {
+    let mut set = PercentileSet::empty();
+    set.count = count;
+    // Newer versions say "Average Latency", older ones "Latency"
+    set.mean = number(section, "Average Latency")
+        .or_else(|| number(section, "Latency"))
+        .unwrap_or(0.0)
+        * 1_000.0;
+    set.min = number(section, "Min Latency").map_or(0, ms_to_us);
+    set.max = number(section, "Max Latency").map_or(0, ms_to_us);
+
+    // Keys look like "p50.00", "p99.90"
+    if let Some(Value::Object(pcts)) = section.get("Percentile Latencies") {
+        for (key, value) in pcts {
+            let (Some(p), Some(ms)) = (
+                key.strip_prefix('p').and_then(|p| p.parse::<f64>().ok()),
+                value.as_f64(),
+            ) else {
+                continue;
+            };
+            if let Some(slot) = percentile_slot(&mut set, p) {
+                *slot = ms_to_us(ms);
+            }
+        }
+    }
+    set
+}
+
+// ─── redis-benchmark CSV ─────────────────────────────────────────
+
+/// Parse `redis-benchmark --csv` output. Redis ≥ 6.2 prints a header
+/// with latency columns; older versions print just `"test","rps"`.
+pub fn import_redis_benchmark(
+    body: &str,
+    label: Option<String>,
+) -> Result<ArchivedRun, String> {
+    let mut rows = body
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(csv_fields)
+        .peekable();
+
+    let header = match rows.peek() {
+        Some(first) if first[0].eq_ignore_ascii_case("test") => rows.next(),
+        Some(_) => None,
+        None => return Err("empty CSV".into()),
+    };
+    let column = |name: &str| -> Option<usize> {
+        header.as_ref()?.iter().position(|h| h == name)
+    };
+    let rps_col = column("rps").unwrap_or(1);
+    let latency_cols: Vec<(&str, Option<usize>)> = [
+        "avg_latency_ms",
+        "min_latency_ms",
+        "p50_latency_ms",
+        "p95_latency_ms",
+        "p99_latency_ms",
+        "max_latency_ms",
+    ]
+    .into_iter()
+    .map(|name| (name, column(name)))
+    .collect();
+
+    let mut run = ArchivedRun::imported(RunSource::RedisBenchmark, label);
+    for (line, fields) in rows.enumerate() {
+        let value = |col: usize| -> Result<f64, String> {
+            fields
+                .get(col)
+                .and_then(|f| f.parse().ok())
+                .ok_or_else(|| format!("row {}: bad number", line + 1))
+        };
+        let rps = value(rps_col)?;
+
+        let mut latency = PercentileSet::empty();
+        for &(name, col) in &latency_cols {
+            let Some(col) = col else { continue };
+            let ms = value(col)?;
+            match name {
+                "avg_latency_ms" => latency.mean = ms * 1_000.0,
+                "min_latency_ms" => latency.min = ms_to_us(ms),
+                "p50_latency_ms" => latency.p50 = ms_to_us(ms),
+                "p95_latency_ms" => latency.p95 = ms_to_us(ms),
+                "p99_latency_ms" => latency.p99 = ms_to_us(ms),
+                _ => latency.max = ms_to_us(ms),
+            }
+        }
+
+        // Each test runs on its own, so the rates are not additive
+        run.requests_per_sec = run.requests_per_sec.max(rps);
+        run.ops.insert(
+            fields[0].clone(),
+            ArchivedOp {
+                requests_per_sec: Some(rps),
+                latency,
+            },
+        );
+    }
+    if run.ops.is_empty() {
+        return Err("no result rows".into());
+    }
+    Ok(run)
+}
+
+/// Split one line of redis-benchmark's CSV. Fields are quoted and
+/// never contain commas.
+fn csv_fields(line: &str) -> Vec<String> {
+    line.split(',')
+        .map(|f| f.trim().trim_matches('"').to_owned())
+        .collect()
+}
+
+// ─── Helpers ─────────────────────────────────────────────────────
+
+fn number(section: &Value, key: &str) -> Option<f64> {
+    section.get(key).and_then(Value::as_f64)
+}
+
+fn ms_to_us(ms: f64) -> u64 {
+    (ms * 1_000.0).round() as u64
+}
+
+fn percentile_slot(set: &mut PercentileSet, p: f64) -> Option<&mut u64> {
+    let close = |target: f64| (p - target).abs() < 1e-6;
+    if close(50.0) {
+        Some(&mut set.p50)
+    } else if close(95.0) {
+        Some(&mut set.p95)
+    } else if close(99.0) {
+        Some(&mut set.p99)
+    } else if close(99.9) {
+        Some(&mut set.p999)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,267 @@
+use serde::{Deserialize, Serialize};
+
+use crate::experiment::ExperimentState;
+use crate::handlers::benchmark::BenchmarkConfig;
+use crate::metrics::MetricsSnapshot;
+use crate::validation::Violations;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// Most steps one search may run, ramp and refinement together
+pub const MAX_STEPS: usize = 40;
+
+/// A step that delivers less than this share of its target rate has
+/// fallen behind, whatever its latency says
+const MIN_ACHIEVED: f64 = 0.9;
+
+/// Open-loop workload for every step: enough workers that a single
+/// slow reply doesn't hold up the schedule
+fn default_step() -> BenchmarkConfig {
+    serde_json::from_value(serde_json::json!({
+        "concurrency": 50,
+        "duration_secs": 10,
+    }))
+    .expect("default step is a valid config")
+}
+
+fn default_start_rps() -> f64 {
+    1_000.0
+}
+
+fn default_growth() -> f64 {
+    2.0
+}
+
+fn default_refine_steps() -> u32 {
+    3
+}
+
+fn default_max_rps() -> f64 {
+    1_000_000.0
+}
+
+// ─── Request ─────────────────────────────────────────────────────
+
+/// Limits a step must stay within to count as sustainable. Either
+/// may be `null` to drop it, not both.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Slo {
+    /// Ceiling on the corrected e2e p99, which includes time spent
+    /// behind the open-loop schedule
+    pub p99_ms: Option<f64>,
+    /// Ceiling on failed requests, in percent
+    pub error_pct: Option<f64>,
+}
+
+impl Default for Slo {
+    fn default() -> Self {
+        Self {
+            p99_ms: Some(2.0),
+            error_pct: Some(0.1),
+        }
+    }
+}
+
+impl Slo {
+    /// `None` when the step met every limit, else what it broke.
+    pub fn violation(
+        &self,
+        target_rps: f64,
+        snap: &MetricsSnapshot,
+    ) -> Option<String> {
+        let p99_ms = snap.e2e_corrected.p99 as f64 / 1_000.0;
+        if let Some(limit) = self.p99_ms.filter(|&limit| p99_ms > limit) {
+            return Some(format!("p99 {p99_ms:.2}ms over {limit}ms"));
+        }
+        let errors = error_pct(snap);
+        if let Some(limit) = self.error_pct.filter(|&limit| errors > limit) {
+            return Some(format!("error rate {errors:.2}% over {limit}%"));
+        }
+        if snap.avg_requests_per_sec < target_rps * MIN_ACHIEVED {
+            return Some(format!(
+                "only {:.0} of {target_rps:.0} req/s delivered",
+                snap.avg_requests_per_sec
+            ));
+        }
+        None
+    }
+}
+
+/// Step up the open-loop rate until the SLO breaks, then bisect
+/// between the last passing and first failing rate.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CapacitySearch {
+    #[serde(default)]
+    pub slo: Slo,
+    /// First rate tried
+    #[serde(default = "default_start_rps")]
+    pub start_rps: f64,
+    /// Each ramp step multiplies the rate by this
+    #[serde(default = "default_growth")]
+    pub growth: f64,
+    /// Bisection steps once a rate has failed
+    #[serde(default = "default_refine_steps")]
+    pub refine_steps: u32,
+    /// The ramp stops here even if the SLO still holds
+    #[serde(default = "default_max_rps")]
+    pub max_rps: f64,
+    /// Workload for every step; its `target_rps` is set by the search
+    #[serde(default = "default_step")]
+    pub step: BenchmarkConfig,
+}
+
+impl CapacitySearch {
+    pub fn check(&self, problems: &mut Violations) {
+        if self.slo.p99_ms.is_none() && self.slo.error_pct.is_none() {
+            problems.push("slo", "slo needs p99_ms, error_pct or both");
+        }
+        if let Some(p99_ms) = self.slo.p99_ms {
+            problems.range("slo.p99_ms", p99_ms, 0.01, 60_000.0);
+        }
+        if let Some(error_pct) = self.slo.error_pct {
+            problems.range("slo.error_pct", error_pct, 0.0, 100.0);
+        }
+        problems.range("start_rps", self.start_rps, 1.0, self.max_rps);
+        problems.range("growth", self.growth, 1.1, 10.0);
+        problems.range("refine_steps", self.refine_steps, 0, 10);
+        problems.range("max_rps", self.max_rps, 1.0, 10_000_000.0);
+        // A ramp sets its own worker count, and the schedule with it
+        if self.step.ramp.is_some() {
+            problems.push("step.ramp", "ramp is not supported in a search");
+        }
+        if self.step.duration_secs == 0 {
+            problems.push(
+                "step.duration_secs",
+                "search steps cannot run until stopped",
+            );
+        }
+        if self.step.client_cache {
+            problems.push(
+                "step.client_cache",
+                "client_cache is not supported in a search",
+            );
+        }
+    }
+}
+
+// ─── Search ──────────────────────────────────────────────────────
+
+/// Which rate to try next, from the results so far.
+#[derive(Debug)]
+pub struct Bisection {
+    growth: f64,
+    max_rps: f64,
+    refine_left: u32,
+    /// Highest rate that passed
+    pub passed: Option<f64>,
+    /// Lowest rate that failed
+    pub failed: Option<f64>,
+}
+
+impl Bisection {
+    pub fn new(search: &CapacitySearch) -> Self {
+        Self {
+            growth: search.growth,
+            max_rps: search.max_rps,
+            refine_left: search.refine_steps,
+            passed: None,
+            failed: None,
+        }
+    }
+
+    /// Record a step at `rps`, then pick the next rate; `None` when
+    /// the search is done.
+    pub fn next(&mut self, rps: f64, ok: bool) -> Option<f64> {
+        if ok {
+            self.passed = Some(self.passed.map_or(rps, |p| p.max(rps)));
+        } else {
+            self.failed = Some(self.failed.map_or(rps, |f| f.min(rps)));
+        }
+        match self.failed {
+            // Still ramping; the last step runs at max_rps itself
+            None if rps >= self.max_rps => None,
+            None => Some((rps * self.growth).min(self.max_rps)),
+            Some(_) if self.refine_left == 0 => None,
+            Some(failed) => {
+                self.refine_left -= 1;
+                let low = self.passed.unwrap_or(0.0);
+                Some(((low + failed) / 2.0).round().max(1.0))
+                    .filter(|&mid| mid > low && mid < failed)
+            }
+        }
+    }
+}
+
+// ─── Report ──────────────────────────────────────────────────────
+
+/// Progress and result, served at `GET /api/experiments/capacity`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CapacityReport {
+    pub run_id: String,
+    pub state: ExperimentState,
+    pub slo: Slo,
+    /// Every step in run order
+    pub steps: Vec<CapacityStep>,
+    /// Highest rate that met the SLO; unset until one has
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_sustainable_rps: Option<f64>,
+    /// What the lowest failing rate broke
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limited_by: Option<String>,
+    /// The ramp reached `max_rps` without breaking the SLO, so the
+    /// real limit is higher
+    pub ceiling_reached: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CapacityStep {
+    pub target_rps: f64,
+    pub achieved_rps: f64,
+    /// Corrected e2e p99
+    pub p99_ms: f64,
+    pub error_pct: f64,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub violation: Option<String>,
+}
+
+impl CapacityReport {
+    /// Record a finished step and update the result.
+    pub fn push(&mut self, step: CapacityStep) {
+        self.steps.push(step);
+        self.max_sustainable_rps = self
+            .steps
+            .iter()
+            .filter(|s| s.passed)
+            .map(|s| s.target_rps)
+            .reduce(f64::max);
+        self.limited_by = self
+            .steps
+            .iter()
+            .filter(|s| !s.passed)
+            .min_by(|a, b| a.target_rps.total_cmp(&b.target_rps))
+            .and_then(|s| s.violation.clone());
+    }
+}
+
+impl CapacityStep {
+    pub fn new(target_rps: f64, slo: &Slo, snap: &MetricsSnapshot) -> Self {
+        let violation = slo.violation(target_rps, snap);
+        Self {
+            target_rps,
+            achieved_rps: snap.avg_requests_per_sec,
+            p99_ms: snap.e2e_corrected.p99 as f64 / 1_000.0,
+            error_pct: error_pct(snap),
+            passed: violation.is_none(),
+            violation,
+        }
+    }
+}
+
+fn error_pct(snap: &MetricsSnapshot) -> f64 {
+    match snap.total_requests {
+        0 => 0.0,
+        n => 100.0 * snap.total_errors as f64 / n as f64,
+    }
+}
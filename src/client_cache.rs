@@ -0,0 +1,159 @@
+use parking_lot::Mutex;
+use redis::aio::MultiplexedConnection;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tokio_stream::StreamExt;
+
+use crate::events::{EventKind, EventLog};
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// Server-side channel that carries tracking invalidations
+const INVALIDATE_CHANNEL: &str = "__redis__:invalidate";
+
+/// Entry cap — the cache is simply cleared when it fills up
+const MAX_ENTRIES: usize = 100_000;
+
+type Hash = HashMap<String, String>;
+
+/// A cached key: a read in flight, or its value. An invalidation removes
+/// either, so a read that loses the race finds its slot gone.
+enum Slot {
+    Pending(u64),
+    Ready(Hash),
+}
+
+// ─── Public types ────────────────────────────────────────────────
+
+/// Invalidation-aware local cache for hash reads, backed by Redis
+/// server-assisted client-side caching (`CLIENT TRACKING`).
+///
+/// The pinned `redis` crate speaks RESP2 only, so tracking uses the
+/// REDIRECT form: a dedicated pub/sub connection subscribes to
+/// `__redis__:invalidate` and the data connection forwards its
+/// invalidations there.
+pub struct ClientCache {
+    entries: Arc<Mutex<HashMap<String, Slot>>>,
+    /// Cleared when the invalidation connection closes: nothing would
+    /// tell us about changes any more, so every read goes to Redis
+    live: Arc<AtomicBool>,
+    /// Tags each `reserve` so only the latest read of a key fills it
+    next_read: AtomicU64,
+    /// Tracked connection — cache misses must be read through it so the
+    /// server remembers which keys we hold
+    conn: MultiplexedConnection,
+    invalidator: JoinHandle<()>,
+}
+
+impl ClientCache {
+    /// Open the invalidation listener and a tracked data connection.
+    /// Losing the listener is logged to `events`.
+    pub async fn start(
+        client: &redis::Client,
+        events: Arc<EventLog>,
+    ) -> redis::RedisResult<Self> {
+        // The listener's CLIENT ID is needed before it switches to
+        // pub/sub mode, which only the legacy connection type allows.
+        #[allow(deprecated)]
+        let mut listener = client.get_async_connection().await?;
+        let listener_id: i64 = redis::cmd("CLIENT")
+            .arg("ID")
+            .query_async(&mut listener)
+            .await?;
+        let mut pubsub = listener.into_pubsub();
+        pubsub.subscribe(INVALIDATE_CHANNEL).await?;
+
+        let mut conn = client.get_multiplexed_async_connection().await?;
+        redis::cmd("CLIENT")
+            .arg("TRACKING")
+            .arg("ON")
+            .arg("REDIRECT")
+            .arg(listener_id)
+            .query_async::<()>(&mut conn)
+            .await?;
+
+        let entries: Arc<Mutex<HashMap<String, Slot>>> = Arc::default();
+        let live = Arc::new(AtomicBool::new(true));
+        let (evict, alive) = (entries.clone(), live.clone());
+        let invalidator = tokio::spawn(async move {
+            let mut messages = pubsub.on_message();
+            while let Some(msg) = messages.next().await {
+                // Payload is the list of invalidated keys, or nil on FLUSHALL
+                match msg.get_payload::<Vec<String>>() {
+                    Ok(keys) => {
+                        let mut map = evict.lock();
+                        for key in keys {
+                            map.remove(&key);
+                        }
+                    }
+                    Err(_) => evict.lock().clear(),
+                }
+            }
+            alive.store(false, Ordering::SeqCst);
+            evict.lock().clear();
+            events.push(
+                EventKind::Warning,
+                "client cache off: the invalidation connection closed, \
+                 reads go to Redis for the rest of the stage",
+            );
+        });
+
+        Ok(Self {
+            entries,
+            live,
+            next_read: AtomicU64::new(0),
+            conn,
+            invalidator,
+        })
+    }
+
+    /// Local lookup — no network round-trip.
+    pub fn get(&self, key: &str) -> Option<Hash> {
+        if !self.live.load(Ordering::SeqCst) {
+            return None;
+        }
+        match self.entries.lock().get(key) {
+            Some(Slot::Ready(value)) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    /// Claim `key` before reading it through the tracked connection;
+    /// pass the result to `insert`. `None` once the cache is off.
+    pub fn reserve(&self, key: &str) -> Option<u64> {
+        if !self.live.load(Ordering::SeqCst) {
+            return None;
+        }
+        let read = self.next_read.fetch_add(1, Ordering::Relaxed);
+        let mut map = self.entries.lock();
+        if map.len() >= MAX_ENTRIES {
+            map.clear();
+        }
+        map.insert(key.to_owned(), Slot::Pending(read));
+        Some(read)
+    }
+
+    /// Remember a value read through the tracked connection, unless an
+    /// invalidation or a later read took the slot `reserve` made.
+    pub fn insert(&self, key: &str, read: u64, value: Hash) {
+        let mut map = self.entries.lock();
+        if let Some(slot) = map.get_mut(key) {
+            if matches!(slot, Slot::Pending(r) if *r == read) {
+                *slot = Slot::Ready(value);
+            }
+        }
+    }
+
+    /// Cheap clone of the tracked connection for cache-miss reads.
+    pub fn connection(&self) -> MultiplexedConnection {
+        self.conn.clone()
+    }
+}
+
+impl Drop for ClientCache {
+    fn drop(&mut self) {
+        self.invalidator.abort();
+    }
+}
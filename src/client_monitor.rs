@@ -0,0 +1,186 @@
+use redis::aio::ConnectionManager;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::events::{EventKind, EventLog};
+use crate::metrics::{ClientStats, MetricsCollector};
+use crate::redis_client::RedisTarget;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// How often `CLIENT LIST` is sampled while a stage runs
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A connection whose output buffer grows this many samples in a row…
+const GROWTH_SAMPLES: u32 = 3;
+
+/// …and holds at least this much is reported as not keeping up
+const WARN_OMEM_BYTES: u64 = 64 * 1024;
+
+/// `lib-name` redis-rs sets on its connections (Redis ≥ 7.2 shows it)
+const LIB_NAME: &str = "redis-rs";
+
+// ─── Monitor task ────────────────────────────────────────────────
+
+/// Samples this tool's connections in `CLIENT LIST` once a second,
+/// publishing output-buffer and age stats to the collector and a
+/// warning event when a buffer keeps growing.
+pub struct ClientMonitor {
+    handle: JoinHandle<()>,
+}
+
+impl ClientMonitor {
+    pub fn start(
+        redis: Arc<RedisTarget>,
+        metrics: Arc<MetricsCollector>,
+        events: Arc<EventLog>,
+    ) -> Self {
+        Self {
+            handle: tokio::spawn(poll(redis, metrics, events)),
+        }
+    }
+
+    pub fn stop(self) {
+        self.handle.abort();
+    }
+}
+
+/// Per-connection growth tracking between samples.
+#[derive(Default)]
+struct Growth {
+    last_omem: u64,
+    streak: u32,
+}
+
+async fn poll(
+    redis: Arc<RedisTarget>,
+    metrics: Arc<MetricsCollector>,
+    events: Arc<EventLog>,
+) {
+    let mut tick = tokio::time::interval(POLL_INTERVAL);
+    tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut growth: HashMap<u64, Growth> = HashMap::new();
+    let mut warned: HashSet<u64> = HashSet::new();
+
+    loop {
+        tick.tick().await;
+        // Servers that disable CLIENT (some managed services) just
+        // leave the stats at zero
+        let Some(clients) = own_clients(&mut redis.manager()).await else {
+            continue;
+        };
+
+        let mut stats = ClientStats::default();
+        let mut seen = HashSet::with_capacity(clients.len());
+        for client in &clients {
+            stats.connections += 1;
+            stats.omem_bytes += client.omem;
+            stats.omem_max_bytes = stats.omem_max_bytes.max(client.omem);
+            stats.oll_max = stats.oll_max.max(client.oll);
+            stats.oldest_age_secs = stats.oldest_age_secs.max(client.age);
+            seen.insert(client.id);
+
+            let g = growth.entry(client.id).or_default();
+            g.streak = if client.omem > g.last_omem {
+                g.streak + 1
+            } else {
+                0
+            };
+            g.last_omem = client.omem;
+            if g.streak >= GROWTH_SAMPLES
+                && client.omem >= WARN_OMEM_BYTES
+                && warned.insert(client.id)
+            {
+                events.push(
+                    EventKind::Warning,
+                    format!(
+                        "client id={} ({}) output buffer grew {} samples in \
+                         a row to {} KiB, {} replies queued; it is not \
+                         reading fast enough",
+                        client.id,
+                        client.label,
+                        g.streak,
+                        client.omem / 1024,
+                        client.oll,
+                    ),
+                );
+            }
+        }
+        growth.retain(|id, _| seen.contains(id));
+        metrics.set_clients(stats);
+    }
+}
+
+// ─── CLIENT LIST ─────────────────────────────────────────────────
+
+struct ClientEntry {
+    id: u64,
+    /// `name` when set (`bench-worker-<id>`), else `addr`
+    label: String,
+    age: u64,
+    oll: u64,
+    omem: u64,
+}
+
+/// `CLIENT LIST` entries that belong to this process: same source
+/// host as our own connection and, where the server reports it, the
+/// redis-rs `lib-name`.
+async fn own_clients(
+    conn: &mut ConnectionManager,
+) -> Option<Vec<ClientEntry>> {
+    let own_id: u64 = redis::cmd("CLIENT")
+        .arg("ID")
+        .query_async(conn)
+        .await
+        .ok()?;
+    let raw: String = redis::cmd("CLIENT")
+        .arg("LIST")
+        .query_async(conn)
+        .await
+        .ok()?;
+
+    let entries: Vec<HashMap<&str, &str>> = raw
+        .lines()
+        .map(|line| {
+            line.split(' ').filter_map(|kv| kv.split_once('=')).collect()
+        })
+        .collect();
+    let own_host = entries
+        .iter()
+        .find(|e| num(e, "id") == own_id)
+        .and_then(|e| host(e.get("addr")?))?
+        .to_owned();
+
+    let clients = entries
+        .iter()
+        .filter(|e| {
+            e.get("addr").and_then(|a| host(a)) == Some(own_host.as_str())
+        })
+        .filter(|e| e.get("lib-name").is_none_or(|lib| *lib == LIB_NAME))
+        .map(|e| ClientEntry {
+            id: num(e, "id"),
+            label: e
+                .get("name")
+                .filter(|name| !name.is_empty())
+                .or(e.get("addr"))
+                .copied()
+                .unwrap_or_default()
+                .to_owned(),
+            age: num(e, "age"),
+            oll: num(e, "oll"),
+            omem: num(e, "omem"),
+        })
+        .collect();
+    Some(clients)
+}
+
+fn num(entry: &HashMap<&str, &str>, key: &str) -> u64 {
+    entry.get(key).and_then(|v| v.parse().ok()).unwrap_or(0)
+}
+
+/// `10.0.0.5:51234` → `10.0.0.5`; `[::1]:51234` → `[::1]`.
+fn host(addr: &str) -> Option<&str> {
+    addr.rsplit_once(':').map(|(host, _)| host)
+}
@@ -0,0 +1,113 @@
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::guard::parse_info;
+
+// ─── Public types ────────────────────────────────────────────────
+
+/// Cumulative per-command counters from one `INFO commandstats` call,
+/// keyed by lowercase command name (`get`, `hgetall`, …).
+pub type CommandStats = BTreeMap<String, Counters>;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Counters {
+    calls: u64,
+    usec: u64,
+    /// Redis ≥ 7.0 only; 0 on older servers
+    failed_calls: u64,
+}
+
+/// What the server saw of one command between two captures.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandDelta {
+    pub calls: u64,
+    /// Total server-side execution time
+    pub usec: u64,
+    pub usec_per_call: f64,
+    pub failed_calls: u64,
+}
+
+// ─── Capture and diff ────────────────────────────────────────────
+
+/// Read `INFO commandstats`. `None` if the server refused (some managed
+/// services disable INFO sections).
+pub async fn capture(conn: &mut ConnectionManager) -> Option<CommandStats> {
+    let raw: String = redis::cmd("INFO")
+        .arg("commandstats")
+        .query_async(conn)
+        .await
+        .ok()?;
+    Some(parse(&raw))
+}
+
+/// Per-command deltas from `before` to `after`, dropping commands
+/// with no new calls. A counter that went backwards (`CONFIG
+/// RESETSTAT` mid-run) counts from zero.
+pub fn diff(
+    before: &CommandStats,
+    after: &CommandStats,
+) -> BTreeMap<String, CommandDelta> {
+    after
+        .iter()
+        .filter_map(|(name, now)| {
+            let then = before.get(name).copied().unwrap_or_default();
+            let then = if now.calls < then.calls {
+                Counters::default()
+            } else {
+                then
+            };
+            let calls = now.calls - then.calls;
+            if calls == 0 {
+                return None;
+            }
+            let usec = now.usec.saturating_sub(then.usec);
+            let delta = CommandDelta {
+                calls,
+                usec,
+                usec_per_call: usec as f64 / calls as f64,
+                failed_calls: now
+                    .failed_calls
+                    .saturating_sub(then.failed_calls),
+            };
+            Some((name.clone(), delta))
+        })
+        .collect()
+}
+
+/// Capture again and diff against `before`; empty if either capture
+/// failed.
+pub async fn since(
+    conn: &mut ConnectionManager,
+    before: &Option<CommandStats>,
+) -> BTreeMap<String, CommandDelta> {
+    match (before, capture(conn).await) {
+        (Some(before), Some(after)) => diff(before, &after),
+        _ => BTreeMap::new(),
+    }
+}
+
+// ─── Parsing ─────────────────────────────────────────────────────
+
+/// `cmdstat_get:calls=12,usec=34,usec_per_call=2.83,…` → `get`.
+/// Subcommands appear as `cmdstat_client|setname`.
+fn parse(raw: &str) -> CommandStats {
+    parse_info(raw)
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let name = key.strip_prefix("cmdstat_")?;
+            let mut counters = Counters::default();
+            let fields = value.split(',').filter_map(|kv| kv.split_once('='));
+            for (field, n) in fields {
+                let n = n.parse().unwrap_or(0);
+                match field {
+                    "calls" => counters.calls = n,
+                    "usec" => counters.usec = n,
+                    "failed_calls" => counters.failed_calls = n,
+                    _ => {}
+                }
+            }
+            Some((name.to_owned(), counters))
+        })
+        .collect()
+}
@@ -0,0 +1,317 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::archive::{ArchivedRun, RunSource};
+use crate::metrics::percentiles::PercentileSet;
+use crate::validation::Violations;
+
+// ─── Report ──────────────────────────────────────────────────────
+
+/// Two archived runs side by side, served at `GET /api/runs/compare`.
+/// Every delta reads as "b relative to a".
+#[derive(Debug, Serialize)]
+pub struct RunComparison {
+    pub a: RunInfo,
+    pub b: RunInfo,
+    pub requests_per_sec: Delta,
+    pub total_requests: Delta,
+    /// Failed requests, in percent
+    pub error_pct: Delta,
+    /// Overall latency; unset unless both runs report one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency: Option<LatencyDelta>,
+    /// Commands both runs issued
+    pub ops: BTreeMap<String, OpDelta>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub only_in_a: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub only_in_b: Vec<String>,
+    /// Reasons the numbers may not be like for like
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunInfo {
+    pub run_id: String,
+    pub source: RunSource,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub archived_at: String,
+}
+
+/// One number from each run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delta {
+    pub a: f64,
+    pub b: f64,
+    /// `b - a`
+    pub change: f64,
+    /// `change` as a percentage of `a`; unset when `a` is 0
+    pub change_pct: Option<f64>,
+}
+
+/// Latency deltas, μs like the percentile sets they come from.
+#[derive(Debug, Serialize)]
+pub struct LatencyDelta {
+    pub min: Delta,
+    pub mean: Delta,
+    pub p50: Delta,
+    pub p95: Delta,
+    pub p99: Delta,
+    pub p999: Delta,
+    pub max: Delta,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OpDelta {
+    /// Unset unless both runs report a rate for the command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requests_per_sec: Option<Delta>,
+    pub latency: LatencyDelta,
+}
+
+// ─── Regression check ────────────────────────────────────────────
+
+/// How far a run may fall behind the baseline before it counts as a
+/// regression. `null` drops a limit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tolerance {
+    /// Largest allowed rise in p99, percent
+    pub p99_pct: Option<f64>,
+    /// Largest allowed rise in p50, percent
+    pub p50_pct: Option<f64>,
+    /// Largest allowed drop in throughput, percent
+    pub throughput_pct: Option<f64>,
+    /// Largest allowed rise in error rate, percentage points
+    pub error_pct: Option<f64>,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            p99_pct: Some(10.0),
+            p50_pct: None,
+            throughput_pct: Some(10.0),
+            error_pct: Some(0.1),
+        }
+    }
+}
+
+impl Tolerance {
+    pub fn check(&self, problems: &mut Violations) {
+        if let Some(pct) = self.p99_pct {
+            problems.range("p99_pct", pct, 0.0, 1_000.0);
+        }
+        if let Some(pct) = self.p50_pct {
+            problems.range("p50_pct", pct, 0.0, 1_000.0);
+        }
+        if let Some(pct) = self.throughput_pct {
+            problems.range("throughput_pct", pct, 0.0, 100.0);
+        }
+        if let Some(points) = self.error_pct {
+            problems.range("error_pct", points, 0.0, 100.0);
+        }
+    }
+}
+
+/// A run judged against the baseline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Verdict {
+    pub baseline_run_id: String,
+    pub tolerance: Tolerance,
+    /// Whether any limit was broken
+    pub regressed: bool,
+    /// Every broken limit, worded for a status line
+    pub regressions: Vec<String>,
+    /// Overall latency; unset unless both runs report one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub p50: Option<Delta>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub p99: Option<Delta>,
+    pub requests_per_sec: Delta,
+    pub error_pct: Delta,
+}
+
+/// Judge `run` against `baseline`.
+pub fn verdict(
+    baseline: &ArchivedRun,
+    run: &ArchivedRun,
+    tolerance: &Tolerance,
+) -> Verdict {
+    let diff = compare(baseline, run);
+    let (p50, p99) = match diff.latency {
+        Some(latency) => (Some(latency.p50), Some(latency.p99)),
+        None => (None, None),
+    };
+    let mut regressions = Vec::new();
+    let rises = [
+        ("p50", &p50, tolerance.p50_pct),
+        ("p99", &p99, tolerance.p99_pct),
+    ];
+    for (stat, delta, limit) in rises {
+        let (Some(delta), Some(limit)) = (delta, limit) else {
+            continue;
+        };
+        if let Some(pct) = delta.change_pct.filter(|&pct| pct > limit) {
+            regressions.push(format!(
+                "{stat} +{pct:.1}% ({:.0} → {:.0} μs), over +{limit}%",
+                delta.a, delta.b
+            ));
+        }
+    }
+    let rps = &diff.requests_per_sec;
+    if let Some(limit) = tolerance.throughput_pct {
+        if let Some(pct) = rps.change_pct.filter(|&pct| -pct > limit) {
+            regressions.push(format!(
+                "throughput {pct:.1}% ({:.0} → {:.0} req/s), over -{limit}%",
+                rps.a, rps.b
+            ));
+        }
+    }
+    let errors = &diff.error_pct;
+    if let Some(limit) = tolerance.error_pct.filter(|&l| errors.change > l) {
+        regressions.push(format!(
+            "error rate +{:.2} points ({:.2}% → {:.2}%), over +{limit}",
+            errors.change, errors.a, errors.b
+        ));
+    }
+
+    Verdict {
+        baseline_run_id: baseline.run_id.clone(),
+        tolerance: tolerance.clone(),
+        regressed: !regressions.is_empty(),
+        regressions,
+        p50,
+        p99,
+        requests_per_sec: diff.requests_per_sec,
+        error_pct: diff.error_pct,
+    }
+}
+
+// ─── Comparison ──────────────────────────────────────────────────
+
+pub fn compare(a: &ArchivedRun, b: &ArchivedRun) -> RunComparison {
+    let mut ops = BTreeMap::new();
+    let mut only_in_a = Vec::new();
+    for (op, a_op) in &a.ops {
+        let Some(b_op) = b.ops.get(op) else {
+            only_in_a.push(op.clone());
+            continue;
+        };
+        let requests_per_sec = a_op
+            .requests_per_sec
+            .zip(b_op.requests_per_sec)
+            .map(|(a, b)| Delta::new(a, b));
+        ops.insert(
+            op.clone(),
+            OpDelta {
+                requests_per_sec,
+                latency: LatencyDelta::new(&a_op.latency, &b_op.latency),
+            },
+        );
+    }
+    let only_in_b = b
+        .ops
+        .keys()
+        .filter(|op| !a.ops.contains_key(*op))
+        .cloned()
+        .collect();
+
+    RunComparison {
+        a: RunInfo::new(a),
+        b: RunInfo::new(b),
+        requests_per_sec: Delta::new(a.requests_per_sec, b.requests_per_sec),
+        total_requests: Delta::new(
+            a.total_requests as f64,
+            b.total_requests as f64,
+        ),
+        error_pct: Delta::new(error_pct(a), error_pct(b)),
+        latency: a
+            .latency
+            .as_ref()
+            .zip(b.latency.as_ref())
+            .map(|(a, b)| LatencyDelta::new(a, b)),
+        ops,
+        only_in_a,
+        only_in_b,
+        warnings: warnings(a, b),
+    }
+}
+
+/// Differences between the runs that make their numbers harder to
+/// compare.
+fn warnings(a: &ArchivedRun, b: &ArchivedRun) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if a.source != b.source {
+        warnings.push(format!(
+            "different tools ({} vs {}): latency is measured differently",
+            a.source.name(),
+            b.source.name()
+        ));
+    }
+    if let (Some(da), Some(db)) = (&a.dataset, &b.dataset) {
+        if da.version != db.version || da.checksum != db.checksum {
+            warnings.push(format!(
+                "different datasets (v{} {} vs v{} {})",
+                da.version, da.checksum, db.version, db.checksum
+            ));
+        }
+    }
+    if a.latency.is_none() || b.latency.is_none() {
+        warnings.push("only one run reports overall latency".into());
+    }
+    warnings
+}
+
+fn error_pct(run: &ArchivedRun) -> f64 {
+    match run.total_requests {
+        0 => 0.0,
+        n => 100.0 * run.total_errors as f64 / n as f64,
+    }
+}
+
+impl RunInfo {
+    fn new(run: &ArchivedRun) -> Self {
+        Self {
+            run_id: run.run_id.clone(),
+            source: run.source,
+            label: run.label.clone(),
+            name: run.metadata.as_ref().and_then(|m| m.name.clone()),
+            archived_at: run.archived_at.clone(),
+        }
+    }
+}
+
+impl Delta {
+    fn new(a: f64, b: f64) -> Self {
+        let change = b - a;
+        Self {
+            a,
+            b,
+            change,
+            change_pct: (a != 0.0).then(|| 100.0 * change / a),
+        }
+    }
+}
+
+impl LatencyDelta {
+    fn new(a: &PercentileSet, b: &PercentileSet) -> Self {
+        let us = |pick: fn(&PercentileSet) -> u64| {
+            Delta::new(pick(a) as f64, pick(b) as f64)
+        };
+        Self {
+            min: us(|p| p.min),
+            mean: Delta::new(a.mean, b.mean),
+            p50: us(|p| p.p50),
+            p95: us(|p| p.p95),
+            p99: us(|p| p.p99),
+            p999: us(|p| p.p999),
+            max: us(|p| p.max),
+        }
+    }
+}
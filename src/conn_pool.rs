@@ -0,0 +1,154 @@
+use parking_lot::Mutex;
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+// ─── Checkout pool ───────────────────────────────────────────────
+
+/// A fixed set of connections handed out one caller at a time, the way
+/// an application behind bb8 / deadpool would use Redis. Each
+/// connection carries at most one caller's commands, so when every
+/// connection is out, callers queue in `checkout`.
+pub struct ConnectionPool {
+    idle: Mutex<Vec<ConnectionManager>>,
+    /// One permit per idle connection
+    permits: Arc<Semaphore>,
+}
+
+impl ConnectionPool {
+    /// Open `size` connections up front so the run doesn't pay for
+    /// connects while it is being measured.
+    pub async fn open(
+        client: &redis::Client,
+        size: u32,
+    ) -> redis::RedisResult<Arc<Self>> {
+        let mut idle = Vec::with_capacity(size as usize);
+        for _ in 0..size {
+            idle.push(ConnectionManager::new(client.clone()).await?);
+        }
+        Ok(Arc::new(Self {
+            idle: Mutex::new(idle),
+            permits: Arc::new(Semaphore::new(size as usize)),
+        }))
+    }
+
+    /// Wait for a free connection. It goes back to the pool when the
+    /// lease is dropped.
+    pub async fn checkout(self: &Arc<Self>) -> Lease {
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("pool semaphore is never closed");
+        let conn = self
+            .idle
+            .lock()
+            .pop()
+            .expect("a permit always has an idle connection");
+        Lease {
+            conn: Some(conn),
+            pool: self.clone(),
+            _permit: permit,
+        }
+    }
+}
+
+/// Exclusive use of one pooled connection.
+pub struct Lease {
+    conn: Option<ConnectionManager>,
+    pool: Arc<ConnectionPool>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Deref for Lease {
+    type Target = ConnectionManager;
+
+    fn deref(&self) -> &ConnectionManager {
+        self.conn.as_ref().expect("lease holds a connection until drop")
+    }
+}
+
+impl DerefMut for Lease {
+    fn deref_mut(&mut self) -> &mut ConnectionManager {
+        self.conn.as_mut().expect("lease holds a connection until drop")
+    }
+}
+
+impl Drop for Lease {
+    fn drop(&mut self) {
+        // Return the connection before the permit is released, so the
+        // next waiter always finds one idle
+        if let Some(conn) = self.conn.take() {
+            self.pool.idle.lock().push(conn);
+        }
+    }
+}
+
+// ─── Connect per operation ───────────────────────────────────────
+
+/// Opens a new connection for every operation, to measure the cost of
+/// not reusing connections. Credentials are kept out of the client so
+/// AUTH can be sent, and timed, on its own.
+pub struct FreshConnector {
+    bare: redis::Client,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+/// A just-opened connection and what it took to set up.
+pub struct Opened {
+    pub conn: ConnectionManager,
+    /// TCP connect, TLS handshake and RESP setup (μs)
+    pub connect_us: u64,
+    /// `None` when no credentials are configured
+    pub auth_us: Option<u64>,
+}
+
+impl FreshConnector {
+    pub fn new(client: &redis::Client) -> redis::RedisResult<Self> {
+        let mut info = client.get_connection_info().clone();
+        let username = info.redis.username.take();
+        let password = info.redis.password.take();
+        Ok(Self {
+            bare: redis::Client::open(info)?,
+            username,
+            password,
+        })
+    }
+
+    pub async fn connect(&self) -> redis::RedisResult<Opened> {
+        let t_connect = Instant::now();
+        // No retries: a failed connect is a result, not something to
+        // hide inside the connect time
+        let config = ConnectionManagerConfig::new()
+            .set_exponent_base(2)
+            .set_factor(100)
+            .set_number_of_retries(0);
+        let mut conn =
+            ConnectionManager::new_with_config(self.bare.clone(), config)
+                .await?;
+        let connect_us = t_connect.elapsed().as_micros() as u64;
+
+        let auth_us = match &self.password {
+            Some(password) => {
+                let t_auth = Instant::now();
+                let mut auth = redis::cmd("AUTH");
+                if let Some(username) = &self.username {
+                    auth.arg(username);
+                }
+                auth.arg(password).query_async::<()>(&mut conn).await?;
+                Some(t_auth.elapsed().as_micros() as u64)
+            }
+            None => None,
+        };
+
+        Ok(Opened {
+            conn,
+            connect_us,
+            auth_us,
+        })
+    }
+}
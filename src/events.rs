@@ -0,0 +1,226 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Instant;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// How many runs keep their event log around for `/api/runs/:id/events`
+const MAX_RUNS: usize = 32;
+
+/// Per-run cap so a flapping server can't grow the log without bound
+const MAX_EVENTS_PER_RUN: usize = 10_000;
+
+// ─── Public types ────────────────────────────────────────────────
+
+/// What happened. Serialized as a snake_case tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// Benchmark accepted and workers spawned
+    Started,
+    /// Load generator finished; message carries the stop reason
+    Stopped,
+    /// A Redis call failed inside the load generator
+    Error,
+    /// Sentinel reported a new master address
+    Failover,
+    /// A worker's connection came back after dropping, or was reopened
+    /// against a promoted master
+    Reconnect,
+    /// Free-form note posted by the user
+    Annotation,
+    /// A scenario moved on to its next stage
+    Stage,
+    /// The health guard stopped the run; message carries the breach
+    Aborted,
+    /// Something worth a look that did not stop the run, e.g. a client
+    /// output buffer that keeps growing
+    Warning,
+    /// A setting changed mid-run; `change` carries old and new values
+    ConfigChange,
+}
+
+/// Where a mid-run setting change came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeSource {
+    /// A scenario stage started with different settings
+    Stage,
+    /// A ramp profile moved on to its next phase
+    Ramp,
+    /// The tool changed a server setting with `CONFIG SET`
+    Redis,
+    /// Someone changed the run through the API while it ran
+    Manual,
+}
+
+/// One setting that changed mid-run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigChange {
+    pub source: ChangeSource,
+    pub key: String,
+    /// `null` when the setting was previously unset
+    pub old: Value,
+    pub new: Value,
+}
+
+impl ConfigChange {
+    pub fn new(
+        source: ChangeSource,
+        key: impl Into<String>,
+        old: impl Into<Value>,
+        new: impl Into<Value>,
+    ) -> Self {
+        Self {
+            source,
+            key: key.into(),
+            old: old.into(),
+            new: new.into(),
+        }
+    }
+}
+
+/// `maxmemory-policy: noeviction → allkeys-lru`
+impl fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |v: &Value| match v {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        write!(f, "{}: {} → {}", self.key, show(&self.old), show(&self.new))
+    }
+}
+
+/// A single entry in a run's append-only log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunEvent {
+    /// Milliseconds since the run started
+    pub timestamp_ms: u64,
+    /// Wall-clock time (RFC 3339, UTC)
+    pub at: String,
+    pub kind: EventKind,
+    pub message: String,
+    /// Consecutive identical events are coalesced into one entry
+    pub count: u64,
+    /// `config_change` events only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change: Option<ConfigChange>,
+}
+
+/// Thread-safe store of per-run event logs, oldest run evicted first.
+pub struct EventLog {
+    inner: Mutex<VecDeque<RunLog>>,
+}
+
+struct RunLog {
+    run_id: String,
+    started: Instant,
+    events: Vec<RunEvent>,
+}
+
+// ─── EventLog impl ───────────────────────────────────────────────
+
+impl EventLog {
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(VecDeque::with_capacity(MAX_RUNS + 1)),
+        }
+    }
+
+    /// Open a fresh log for `run_id`; subsequent `push` calls land here.
+    pub fn begin_run(&self, run_id: &str) {
+        let mut runs = self.inner.lock();
+        runs.push_back(RunLog {
+            run_id: run_id.to_owned(),
+            started: Instant::now(),
+            events: Vec::new(),
+        });
+        if runs.len() > MAX_RUNS {
+            runs.pop_front();
+        }
+    }
+
+    /// Append to the most recent run. No-op before the first run.
+    pub fn push(&self, kind: EventKind, message: impl Into<String>) {
+        let mut runs = self.inner.lock();
+        if let Some(run) = runs.back_mut() {
+            run.push(kind, message.into(), None);
+        }
+    }
+
+    /// Append a `config_change` event to the most recent run.
+    pub fn push_change(&self, change: ConfigChange) {
+        let mut runs = self.inner.lock();
+        if let Some(run) = runs.back_mut() {
+            let message = change.to_string();
+            run.push(EventKind::ConfigChange, message, Some(change));
+        }
+    }
+
+    /// Append to a specific run. Returns false if the run is unknown.
+    pub fn push_to(
+        &self,
+        run_id: &str,
+        kind: EventKind,
+        message: impl Into<String>,
+    ) -> bool {
+        let mut runs = self.inner.lock();
+        match runs.iter_mut().find(|r| r.run_id == run_id) {
+            Some(run) => {
+                run.push(kind, message.into(), None);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Every retained run's log, oldest run first.
+    pub fn all(&self) -> Vec<(String, Vec<RunEvent>)> {
+        self.inner
+            .lock()
+            .iter()
+            .map(|r| (r.run_id.clone(), r.events.clone()))
+            .collect()
+    }
+
+    /// Copy of a run's log, or `None` if it was never recorded / evicted.
+    pub fn events(&self, run_id: &str) -> Option<Vec<RunEvent>> {
+        self.inner
+            .lock()
+            .iter()
+            .find(|r| r.run_id == run_id)
+            .map(|r| r.events.clone())
+    }
+}
+
+impl RunLog {
+    fn push(
+        &mut self,
+        kind: EventKind,
+        message: String,
+        change: Option<ConfigChange>,
+    ) {
+        // Coalesce bursts of the same error into a single counted entry
+        if let Some(last) = self.events.last_mut() {
+            if last.kind == kind && last.message == message {
+                last.count += 1;
+                return;
+            }
+        }
+        if self.events.len() >= MAX_EVENTS_PER_RUN {
+            return;
+        }
+        self.events.push(RunEvent {
+            timestamp_ms: self.started.elapsed().as_millis() as u64,
+            at: chrono::Utc::now().to_rfc3339(),
+            kind,
+            message,
+            count: 1,
+            change,
+        });
+    }
+}
@@ -0,0 +1,242 @@
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+
+use crate::guard::parse_info;
+use crate::handlers::benchmark::BenchmarkConfig;
+use crate::metrics::percentiles::PercentileSet;
+use crate::metrics::MetricsSnapshot;
+use crate::validation::Violations;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// Every `maxmemory-policy` Redis accepts
+pub const POLICIES: &[&str] = &[
+    "noeviction",
+    "allkeys-lru",
+    "allkeys-lfu",
+    "allkeys-random",
+    "volatile-lru",
+    "volatile-lfu",
+    "volatile-random",
+    "volatile-ttl",
+];
+
+/// Compared when the request names none
+const DEFAULT_POLICIES: &[&str] =
+    &["allkeys-lru", "allkeys-lfu", "volatile-ttl"];
+
+/// Without an explicit `maxmemory_mb`, the limit is set this far above
+/// current usage so the write-heavy phases reach it within seconds
+const HEADROOM_MB: u64 = 8;
+
+const MIB: u64 = 1024 * 1024;
+
+// ─── Request ─────────────────────────────────────────────────────
+
+/// Run the same workload once per eviction policy.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EvictionExperiment {
+    #[serde(default = "default_policies")]
+    pub policies: Vec<String>,
+    /// `maxmemory` during the experiment; restored afterwards
+    #[serde(default)]
+    pub maxmemory_mb: Option<u64>,
+    /// Workload for every phase; defaults to write-heavy memory pressure
+    #[serde(default = "default_phase")]
+    pub phase: BenchmarkConfig,
+}
+
+fn default_policies() -> Vec<String> {
+    DEFAULT_POLICIES.iter().map(|p| p.to_string()).collect()
+}
+
+/// Sessions (SET with TTL, so volatile-* policies have candidates) and
+/// fresh user hashes fill memory; a read share shows the miss cost.
+fn default_phase() -> BenchmarkConfig {
+    serde_json::from_value(serde_json::json!({
+        "concurrency": 20,
+        "duration_secs": 60,
+        "mix": { "SET": 50, "HSET": 30, "HGETALL": 20 },
+    }))
+    .expect("default phase is a valid config")
+}
+
+impl EvictionExperiment {
+    pub fn check(&self, problems: &mut Violations) {
+        if self.policies.is_empty() || self.policies.len() > POLICIES.len() {
+            problems.bounds(
+                "policies",
+                format!(
+                    "policies needs between 1 and {} entries",
+                    POLICIES.len()
+                ),
+                1,
+                POLICIES.len(),
+            );
+        }
+        for (i, p) in self.policies.iter().enumerate() {
+            if !POLICIES.contains(&p.as_str()) {
+                problems.push(
+                    &format!("policies[{i}]"),
+                    format!("unknown maxmemory-policy '{p}'"),
+                );
+            }
+        }
+        if self.maxmemory_mb == Some(0) {
+            problems.push("maxmemory_mb", "maxmemory_mb must be positive");
+        }
+        // Phases are switched on the clock
+        if self.phase.duration_secs == 0 {
+            problems.push(
+                "phase.duration_secs",
+                "experiment phases cannot run until stopped",
+            );
+        }
+        if self.phase.client_cache {
+            problems.push(
+                "phase.client_cache",
+                "client_cache is not supported in experiments",
+            );
+        }
+    }
+}
+
+// ─── Report ──────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExperimentState {
+    Running,
+    Complete,
+    Stopped,
+    Failed,
+}
+
+/// Progress and per-policy results, served at
+/// `GET /api/experiments/eviction`.
+#[derive(Debug, Clone, Serialize)]
+pub struct EvictionReport {
+    pub run_id: String,
+    pub state: ExperimentState,
+    pub maxmemory_mb: u64,
+    /// Settings restored once the experiment ends
+    pub original_policy: String,
+    pub original_maxmemory_mb: u64,
+    pub phases: Vec<PolicyResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyResult {
+    pub policy: String,
+    pub total_requests: u64,
+    pub total_errors: u64,
+    pub requests_per_sec: f64,
+    pub e2e: PercentileSet,
+    pub redis_read: PercentileSet,
+    pub redis_write: PercentileSet,
+    /// Keys Redis evicted during the phase
+    pub evicted_keys: u64,
+    /// Reads that found no key during the phase
+    pub keyspace_misses: u64,
+    pub used_memory_mb: u64,
+}
+
+impl PolicyResult {
+    pub fn new(
+        policy: &str,
+        snap: &MetricsSnapshot,
+        before: &Counters,
+        after: &Counters,
+    ) -> Self {
+        Self {
+            policy: policy.to_owned(),
+            total_requests: snap.total_requests,
+            total_errors: snap.total_errors,
+            requests_per_sec: snap.avg_requests_per_sec,
+            e2e: snap.e2e.clone(),
+            redis_read: snap.redis_read.clone(),
+            redis_write: snap.redis_write.clone(),
+            evicted_keys: after
+                .evicted_keys
+                .saturating_sub(before.evicted_keys),
+            keyspace_misses: after
+                .keyspace_misses
+                .saturating_sub(before.keyspace_misses),
+            used_memory_mb: after.used_memory / MIB,
+        }
+    }
+}
+
+// ─── Redis helpers ───────────────────────────────────────────────
+
+/// Eviction-related `INFO` fields, read before and after each phase.
+#[derive(Debug, Default)]
+pub struct Counters {
+    pub evicted_keys: u64,
+    pub keyspace_misses: u64,
+    pub used_memory: u64,
+}
+
+pub async fn counters(conn: &mut ConnectionManager) -> Counters {
+    let raw: String = redis::cmd("INFO")
+        .query_async(conn)
+        .await
+        .unwrap_or_default();
+    let info = parse_info(&raw);
+    let num = |key: &str| {
+        info.get(key).and_then(|v| v.parse::<u64>().ok()).unwrap_or(0)
+    };
+    Counters {
+        evicted_keys: num("evicted_keys"),
+        keyspace_misses: num("keyspace_misses"),
+        used_memory: num("used_memory"),
+    }
+}
+
+/// Current `(maxmemory-policy, maxmemory in MiB)`.
+pub async fn current_settings(
+    conn: &mut ConnectionManager,
+) -> redis::RedisResult<(String, u64)> {
+    let policy = config_get(conn, "maxmemory-policy").await?;
+    let maxmemory = config_get(conn, "maxmemory").await?;
+    Ok((policy, maxmemory.parse::<u64>().unwrap_or(0) / MIB))
+}
+
+/// The limit to run under: as requested, or just above current usage.
+pub async fn pick_maxmemory_mb(
+    conn: &mut ConnectionManager,
+    requested: Option<u64>,
+) -> u64 {
+    match requested {
+        Some(mb) => mb,
+        None => counters(conn).await.used_memory / MIB + HEADROOM_MB,
+    }
+}
+
+async fn config_get(
+    conn: &mut ConnectionManager,
+    name: &str,
+) -> redis::RedisResult<String> {
+    // Reply is [name, value]
+    let reply: Vec<String> = redis::cmd("CONFIG")
+        .arg("GET")
+        .arg(name)
+        .query_async(conn)
+        .await?;
+    Ok(reply.into_iter().nth(1).unwrap_or_default())
+}
+
+pub async fn config_set(
+    conn: &mut ConnectionManager,
+    name: &str,
+    value: impl redis::ToRedisArgs,
+) -> redis::RedisResult<()> {
+    redis::cmd("CONFIG")
+        .arg("SET")
+        .arg(name)
+        .arg(value)
+        .query_async(conn)
+        .await
+}
@@ -0,0 +1,111 @@
+use redis::aio::ConnectionManager;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+use crate::guard::parse_info;
+use crate::metrics::MetricsCollector;
+use crate::redis_client::RedisTarget;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// How often `INFO stats` is sampled while a stage runs
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// ─── Monitor task ────────────────────────────────────────────────
+
+/// Samples the server's expiry and keyspace counters once a second and
+/// feeds the deltas to the collector, so expiry pressure lines up with
+/// latency on the timeline.
+pub struct ExpiryMonitor {
+    handle: JoinHandle<()>,
+}
+
+impl ExpiryMonitor {
+    pub fn start(
+        redis: Arc<RedisTarget>,
+        metrics: Arc<MetricsCollector>,
+    ) -> Self {
+        Self {
+            handle: tokio::spawn(poll(redis, metrics)),
+        }
+    }
+
+    pub fn stop(self) {
+        self.handle.abort();
+    }
+}
+
+/// Counters from one `INFO stats` call.
+#[derive(Clone, Copy)]
+struct Reading {
+    at: Instant,
+    expired_keys: u64,
+    /// Redis ≥ 6.0 only; 0 on older servers
+    cap_reached: u64,
+    stale_pct: f64,
+    keyspace_hits: u64,
+    keyspace_misses: u64,
+}
+
+async fn poll(redis: Arc<RedisTarget>, metrics: Arc<MetricsCollector>) {
+    let mut tick = tokio::time::interval(POLL_INTERVAL);
+    tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut last: Option<Reading> = None;
+    let mut generation = redis.generation();
+
+    loop {
+        tick.tick().await;
+        // Counters on the promoted master don't follow on from the old
+        // one's, so start over after a failover
+        if redis.generation() != generation {
+            generation = redis.generation();
+            last = None;
+        }
+        // A refused INFO leaves the expiry stats at zero
+        let Some(now) = read(&mut redis.manager()).await else {
+            continue;
+        };
+        if let Some(prev) = last {
+            // Counters going backwards mean CONFIG RESETSTAT; start over
+            let expired = now.expired_keys.checked_sub(prev.expired_keys);
+            let cap = now.cap_reached.checked_sub(prev.cap_reached);
+            if let (Some(expired), Some(cap)) = (expired, cap) {
+                let secs = now.at.duration_since(prev.at).as_secs_f64();
+                let per_sec = if secs > 0.0 {
+                    expired as f64 / secs
+                } else {
+                    0.0
+                };
+                metrics.record_expiry(expired, cap, per_sec, now.stale_pct);
+            }
+            let hits = now.keyspace_hits.checked_sub(prev.keyspace_hits);
+            let misses = now.keyspace_misses.checked_sub(prev.keyspace_misses);
+            if let (Some(hits), Some(misses)) = (hits, misses) {
+                metrics.record_keyspace(hits, misses);
+            }
+        }
+        last = Some(now);
+    }
+}
+
+async fn read(conn: &mut ConnectionManager) -> Option<Reading> {
+    let raw: String = redis::cmd("INFO")
+        .arg("stats")
+        .query_async(conn)
+        .await
+        .ok()?;
+    let info = parse_info(&raw);
+    let num = |key: &str| info.get(key).and_then(|v| v.parse().ok());
+    Some(Reading {
+        at: Instant::now(),
+        expired_keys: num("expired_keys")?,
+        cap_reached: num("expired_time_cap_reached_count").unwrap_or(0),
+        stale_pct: info
+            .get("expired_stale_perc")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.0),
+        keyspace_hits: num("keyspace_hits").unwrap_or(0),
+        keyspace_misses: num("keyspace_misses").unwrap_or(0),
+    })
+}
@@ -0,0 +1,203 @@
+use parking_lot::Mutex;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::events::{EventKind, EventLog};
+use crate::metrics::MetricsCollector;
+use crate::redis_client::RedisTarget;
+use crate::validation::Violations;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// How often thresholds are checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Error rate is ignored in windows with fewer requests than this
+const MIN_WINDOW_REQUESTS: u64 = 50;
+
+// ─── Public types ────────────────────────────────────────────────
+
+/// Danger thresholds that abort a run mid-flight. Every field is
+/// optional; an empty guard does nothing.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthGuard {
+    /// Fraction of failed requests per check window (0.0–1.0)
+    pub max_error_rate: Option<f64>,
+    /// `used_memory` as a percentage of `maxmemory` (skipped when
+    /// maxmemory is unset)
+    pub max_memory_pct: Option<f64>,
+    /// Absolute `used_memory` ceiling in MiB
+    pub max_used_memory_mb: Option<u64>,
+    /// Abort as soon as `evicted_keys` grows during the run
+    pub abort_on_evictions: bool,
+    /// Abort when fewer replicas are connected (or, on a replica, when
+    /// the link to its master is down)
+    pub min_connected_replicas: Option<u32>,
+}
+
+impl HealthGuard {
+    pub fn is_active(&self) -> bool {
+        self.max_error_rate.is_some()
+            || self.max_memory_pct.is_some()
+            || self.max_used_memory_mb.is_some()
+            || self.abort_on_evictions
+            || self.min_connected_replicas.is_some()
+    }
+
+    /// Field paths are relative to the guard object.
+    pub fn check(&self, problems: &mut Violations) {
+        if let Some(rate) = self.max_error_rate {
+            problems.range("max_error_rate", rate, 0.0, 1.0);
+        }
+        if let Some(pct) = self.max_memory_pct {
+            if !(pct > 0.0 && pct <= 100.0) {
+                problems.bounds(
+                    "max_memory_pct",
+                    "max_memory_pct must be in (0, 100]",
+                    0.0,
+                    100.0,
+                );
+            }
+        }
+    }
+}
+
+// ─── Watcher ─────────────────────────────────────────────────────
+
+/// Poll thresholds until the run stops. On the first breach, store the
+/// reason in `abort_reason`, log an `Aborted` event and lower `running`.
+pub async fn watch(
+    guard: HealthGuard,
+    running: Arc<AtomicBool>,
+    metrics: Arc<MetricsCollector>,
+    events: Arc<EventLog>,
+    redis: Arc<RedisTarget>,
+    abort_reason: Arc<Mutex<Option<String>>>,
+) {
+    let mut ticker = tokio::time::interval(CHECK_INTERVAL);
+    let mut last_counts = metrics.counts();
+    let mut baseline_evictions = None;
+    let mut generation = redis.generation();
+
+    while running.load(Ordering::SeqCst) {
+        ticker.tick().await;
+
+        // The promoted master has eviction counters of its own
+        if redis.generation() != generation {
+            generation = redis.generation();
+            baseline_evictions = None;
+        }
+        let mut conn = redis.manager();
+        let counts = metrics.counts();
+        let breach = check_error_rate(&guard, last_counts, counts)
+            .or(check_info(&guard, &mut conn, &mut baseline_evictions).await);
+        last_counts = counts;
+
+        if let Some(reason) = breach {
+            events.push(EventKind::Aborted, reason.clone());
+            *abort_reason.lock() = Some(reason);
+            running.store(false, Ordering::SeqCst);
+            return;
+        }
+    }
+}
+
+fn check_error_rate(
+    guard: &HealthGuard,
+    (prev_requests, prev_errors): (u64, u64),
+    (requests, errors): (u64, u64),
+) -> Option<String> {
+    let max = guard.max_error_rate?;
+    let window = requests.saturating_sub(prev_requests);
+    if window < MIN_WINDOW_REQUESTS {
+        return None;
+    }
+    let rate = errors.saturating_sub(prev_errors) as f64 / window as f64;
+    (rate > max).then(|| {
+        format!(
+            "error rate {:.1}% exceeded {:.1}%",
+            rate * 100.0,
+            max * 100.0
+        )
+    })
+}
+
+async fn check_info(
+    guard: &HealthGuard,
+    conn: &mut ConnectionManager,
+    baseline_evictions: &mut Option<u64>,
+) -> Option<String> {
+    let needs_info = guard.max_memory_pct.is_some()
+        || guard.max_used_memory_mb.is_some()
+        || guard.abort_on_evictions
+        || guard.min_connected_replicas.is_some();
+    if !needs_info {
+        return None;
+    }
+
+    // An unreachable server shows up through the error-rate check
+    let raw: String = redis::cmd("INFO").query_async(conn).await.ok()?;
+    let info = parse_info(&raw);
+    let num = |key: &str| info.get(key).and_then(|v| v.parse::<u64>().ok());
+
+    let used = num("used_memory").unwrap_or(0);
+    if let Some(max_mb) = guard.max_used_memory_mb {
+        let used_mb = used / (1024 * 1024);
+        if used_mb > max_mb {
+            return Some(format!(
+                "used_memory {used_mb} MiB exceeded {max_mb} MiB"
+            ));
+        }
+    }
+    if let (Some(max_pct), Some(maxmemory)) =
+        (guard.max_memory_pct, num("maxmemory").filter(|m| *m > 0))
+    {
+        let pct = used as f64 / maxmemory as f64 * 100.0;
+        if pct > max_pct {
+            return Some(format!(
+                "memory at {pct:.1}% of maxmemory, limit {max_pct:.1}%"
+            ));
+        }
+    }
+    if guard.abort_on_evictions {
+        let evicted = num("evicted_keys").unwrap_or(0);
+        let baseline = *baseline_evictions.get_or_insert(evicted);
+        if evicted > baseline {
+            return Some(format!(
+                "{} keys evicted during the run",
+                evicted - baseline
+            ));
+        }
+    }
+    if let Some(min) = guard.min_connected_replicas {
+        if info.get("role").map(String::as_str) == Some("slave") {
+            if info.get("master_link_status").map(String::as_str)
+                == Some("down")
+            {
+                return Some("replica lost its link to the master".into());
+            }
+        } else {
+            let connected = num("connected_slaves").unwrap_or(0);
+            if connected < min as u64 {
+                return Some(format!(
+                    "{connected} replicas connected, need {min}"
+                ));
+            }
+        }
+    }
+    None
+}
+
+/// `INFO` output → `field → value`, skipping `# Section` headers.
+pub fn parse_info(raw: &str) -> HashMap<String, String> {
+    raw.lines()
+        .filter(|l| !l.starts_with('#'))
+        .filter_map(|l| l.split_once(':'))
+        .map(|(k, v)| (k.trim().to_owned(), v.trim().to_owned()))
+        .collect()
+}
@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use moka::sync::Cache;
+
+use crate::handlers::products::Product;
+use crate::handlers::users::User;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// Entries expire after this long so seeded data edited directly in
+/// Redis shows up again without a restart
+const ENTRY_TTL: Duration = Duration::from_secs(30);
+
+// ─── HandlerCache ────────────────────────────────────────────────
+
+/// In-process LRU in front of the user/product read handlers.
+///
+/// Only found entities are cached; a miss always falls through to Redis.
+pub struct HandlerCache {
+    pub users: Cache<String, User>,
+    pub products: Cache<String, Product>,
+}
+
+impl HandlerCache {
+    /// `capacity` bounds each entity cache separately.
+    pub fn new(capacity: u64) -> Self {
+        Self {
+            users: Cache::builder()
+                .max_capacity(capacity)
+                .time_to_live(ENTRY_TTL)
+                .build(),
+            products: Cache::builder()
+                .max_capacity(capacity)
+                .time_to_live(ENTRY_TTL)
+                .build(),
+        }
+    }
+}
@@ -1,30 +1,505 @@
 use axum::{
-    extract::State,
+    extract::{Path, Query, State},
     Json,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use crate::analysis;
+use crate::archive::{ArchivedRun, RunArchive, StoredHistograms};
+use crate::compare::Verdict;
+use crate::client_cache::ClientCache;
+use crate::commandstats;
+use crate::events::{ChangeSource, ConfigChange, EventKind, EventLog};
+use crate::guard::HealthGuard;
+use crate::injection::Injection;
+use crate::metrics::apdex::ApdexTargets;
+use crate::metrics::buckets::LatencyBuckets;
+use crate::metrics::percentiles::PercentileMode;
+use crate::report::RunReport;
+use crate::run_queue::{QueueListing, QueuedRun};
+use crate::slo::{Slo, SloResult};
+use crate::validation::{Violation, Violations};
 use crate::AppState;
 
 use super::AppError;
 
 // ─── Request / response types ────────────────────────────────────
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BenchmarkConfig {
     /// Number of concurrent Tokio tasks generating load
     #[serde(default = "default_concurrency")]
     pub concurrency: u32,
 
-    /// How long the benchmark runs (seconds)
+    /// How long the benchmark runs (seconds); 0 runs until
+    /// `/api/benchmark/stop`
     #[serde(default = "default_duration")]
     pub duration_secs: u64,
 
     /// Percentage of operations that are reads (0–100)
     #[serde(default = "default_read_pct")]
     pub read_pct: u8,
+
+    /// Optional artificial Rust-side overhead (layer-attribution test mode)
+    #[serde(default)]
+    pub inject: Option<Injection>,
+
+    /// Serve repeated reads from an invalidation-aware local cache
+    /// (server-assisted client-side caching via CLIENT TRACKING)
+    #[serde(default)]
+    pub client_cache: bool,
+
+    /// Open-loop mode: total target request rate across all workers.
+    /// Unset = closed loop (each worker fires as fast as it can).
+    #[serde(default)]
+    pub target_rps: Option<f64>,
+
+    /// Inter-arrival distribution used in open-loop mode
+    #[serde(default)]
+    pub arrival: Arrival,
+
+    /// Ramp profile. When set it replaces `concurrency` (peak workers)
+    /// and `duration_secs` (up + hold + down).
+    #[serde(default)]
+    pub ramp: Option<Ramp>,
+
+    /// Weighted command mix, e.g. `{"GET": 40, "HGETALL": 30, "SET": 20,
+    /// "HSET": 10}`. Overrides `read_pct` when set.
+    #[serde(default)]
+    pub mix: Option<BTreeMap<Op, u32>>,
+
+    /// Abort the run if the target crosses any of these thresholds
+    #[serde(default)]
+    pub guard: Option<HealthGuard>,
+
+    /// Stop the run once more than this percentage of requests fail.
+    /// Shorthand for `guard.max_error_rate`; validation moves it there.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub abort_on_error_pct: Option<f64>,
+
+    /// Pass/fail thresholds checked once the run ends; the result is
+    /// archived with the run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slo: Option<Slo>,
+
+    /// Latency targets each layer's Apdex is scored against in every
+    /// snapshot and the final report
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub apdex: Option<ApdexTargets>,
+
+    /// How reported percentiles are computed (`hdr`, `linear`,
+    /// `nearest_rank`) — match the tool you are comparing against
+    #[serde(default)]
+    pub percentile_mode: PercentileMode,
+
+    /// Bucket bounds of `distribution` and `heatmap`: the built-in
+    /// localhost ones, a custom list, or log-scale over what was seen
+    #[serde(default)]
+    pub latency_buckets: LatencyBuckets,
+
+    /// Width of each timeline window (ms) before older windows are
+    /// downsampled; smaller for spike analysis, larger for long soaks
+    #[serde(default = "default_timeline_window_ms")]
+    pub timeline_window_ms: u64,
+
+    /// End-to-end latency (μs) above which a request's full details go
+    /// to the slow log at `/api/metrics/slow`
+    #[serde(default = "default_slow_threshold_us")]
+    pub slow_threshold_us: u64,
+
+    /// How many of the run's slowest requests are kept with full
+    /// detail for the snapshot and the final report; 0 keeps none
+    #[serde(default = "default_top_outliers")]
+    pub top_outliers: usize,
+
+    /// Lua source for `EVALSHA` ops, called with KEYS[1] = a user hash
+    /// and ARGV[1] = 1. Defaults to a read-and-count-visit script that
+    /// mirrors the `MULTI` op.
+    #[serde(default)]
+    pub script: Option<String>,
+
+    /// Consumer-group readers (XREADGROUP + XACK) started when the mix
+    /// contains `XADD`
+    #[serde(default = "default_stream_consumers")]
+    pub stream_consumers: u32,
+
+    /// Job-queue consumers (BRPOP) started when the mix contains `LPUSH`
+    #[serde(default = "default_queue_consumers")]
+    pub queue_consumers: u32,
+
+    /// How workers reach Redis: one shared multiplexed connection, a
+    /// checkout pool, a dedicated connection per worker, or a new
+    /// connection per operation
+    #[serde(default)]
+    pub connection_mode: ConnectionMode,
+
+    /// Connections in the `pool` connection mode
+    #[serde(default = "default_pool_size")]
+    pub pool_size: u32,
+
+    /// Name each worker's connection `bench-worker-<id>` with
+    /// `CLIENT SETNAME`. Implies the `dedicated` connection mode.
+    #[serde(default)]
+    pub named_workers: bool,
+
+    /// Size of the values SET, HSET and MSET write. Unset = the
+    /// built-in payloads (a ~200 B session, a ~130 B user hash).
+    #[serde(default)]
+    pub value_size: Option<ValueSize>,
+
+    /// `MATCH` pattern for `SCAN` ops
+    #[serde(default = "default_scan_match")]
+    pub scan_match: String,
+
+    /// `COUNT` hint for `SCAN` ops
+    #[serde(default = "default_scan_count")]
+    pub scan_count: u32,
+
+    /// TTL on keys written by `SET:churn`, in milliseconds
+    #[serde(default = "default_churn_ttl_ms")]
+    pub churn_ttl_ms: u64,
+
+    /// Keys per `MGET` / `MSET` round trip
+    #[serde(default = "default_batch_size")]
+    pub batch_size: u32,
+
+    /// Entries returned by each `ZRANGE` top-N read
+    #[serde(default = "default_leaderboard_top_n")]
+    pub leaderboard_top_n: u32,
+
+    /// Commands sent per round trip in a single pipeline (1 = none)
+    #[serde(default = "default_pipeline_depth")]
+    pub pipeline_depth: u32,
+
+    /// Per-call Redis timeout in ms, 0 = none. Unset = the server's
+    /// `--op-timeout-ms`, filled in when the run starts.
+    #[serde(default)]
+    pub op_timeout_ms: Option<u64>,
+
+    /// What was being tested; archived with the run, no effect on load
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<RunMetadata>,
+}
+
+/// A load-generator operation, named after its Redis command.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub enum Op {
+    /// Read a session JSON blob
+    #[serde(rename = "GET")]
+    Get,
+    /// Read a user or product hash (even split)
+    #[serde(rename = "HGETALL")]
+    Hgetall,
+    /// Read a user hash
+    #[serde(rename = "HGETALL:user")]
+    HgetallUser,
+    /// Read a product hash
+    #[serde(rename = "HGETALL:product")]
+    HgetallProduct,
+    /// Write a session with a TTL
+    #[serde(rename = "SET")]
+    Set,
+    /// Write a user hash
+    #[serde(rename = "HSET")]
+    Hset,
+    /// Read `batch_size` session blobs in one command
+    #[serde(rename = "MGET")]
+    Mget,
+    /// Write `batch_size` session blobs in one command (no TTL)
+    #[serde(rename = "MSET")]
+    Mset,
+    /// Write a fresh key that expires after `churn_ttl_ms`
+    #[serde(rename = "SET:churn")]
+    SetChurn,
+    /// Run the benchmark's Lua script against a user hash
+    #[serde(rename = "EVALSHA")]
+    Evalsha,
+    /// The built-in script's commands as a MULTI/EXEC transaction
+    #[serde(rename = "MULTI")]
+    Multi,
+    /// Publish a timestamped message to the benchmark channel
+    #[serde(rename = "PUBLISH")]
+    Publish,
+    /// Append a timestamped entry to the benchmark stream
+    #[serde(rename = "XADD")]
+    Xadd,
+    /// Set a player's leaderboard score
+    #[serde(rename = "ZADD")]
+    Zadd,
+    /// Bump a player's leaderboard score
+    #[serde(rename = "ZINCRBY")]
+    Zincrby,
+    /// Read the leaderboard's top N
+    #[serde(rename = "ZRANGE")]
+    Zrange,
+    /// Look up one player's leaderboard rank
+    #[serde(rename = "ZRANK")]
+    Zrank,
+    /// Enqueue a timestamped job on the benchmark queue
+    #[serde(rename = "LPUSH")]
+    Lpush,
+    /// Advance this worker's keyspace cursor by one call
+    #[serde(rename = "SCAN")]
+    Scan,
+}
+
+impl Op {
+    /// Name as written in a `mix`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::HgetallUser => "HGETALL:user",
+            Self::HgetallProduct => "HGETALL:product",
+            Self::SetChurn => "SET:churn",
+            other => other.command(),
+        }
+    }
+
+    pub fn is_read(self) -> bool {
+        matches!(
+            self,
+            Self::Get
+                | Self::Hgetall
+                | Self::HgetallUser
+                | Self::HgetallProduct
+                | Self::Mget
+                | Self::Zrange
+                | Self::Zrank
+                | Self::Scan
+        )
+    }
+
+    /// The Redis command this op issues — the per-op histogram key.
+    pub fn command(self) -> &'static str {
+        match self {
+            Self::Get => "GET",
+            Self::Hgetall | Self::HgetallUser | Self::HgetallProduct => {
+                "HGETALL"
+            }
+            Self::Set | Self::SetChurn => "SET",
+            Self::Hset => "HSET",
+            Self::Mget => "MGET",
+            Self::Mset => "MSET",
+            Self::Evalsha => "EVALSHA",
+            Self::Multi => "MULTI",
+            Self::Publish => "PUBLISH",
+            Self::Xadd => "XADD",
+            Self::Zadd => "ZADD",
+            Self::Zincrby => "ZINCRBY",
+            Self::Zrange => "ZRANGE",
+            Self::Zrank => "ZRANK",
+            Self::Lpush => "LPUSH",
+            Self::Scan => "SCAN",
+        }
+    }
+}
+
+impl BenchmarkConfig {
+    /// The weighted mix workers draw from. Without an explicit `mix` this
+    /// reproduces the classic split: reads 60/40 users/products, writes
+    /// 50/50 sessions/users, in `read_pct` proportion.
+    pub fn op_weights(&self) -> Vec<(Op, u32)> {
+        if let Some(mix) = &self.mix {
+            return mix.iter().map(|(op, w)| (*op, *w)).collect();
+        }
+        read_write_weights(self.read_pct)
+    }
+}
+
+/// The classic split for `read_pct`; workers rebuild their op table
+/// from it when the value is adjusted mid-run.
+pub fn read_write_weights(read_pct: u8) -> Vec<(Op, u32)> {
+    let reads = read_pct.min(100) as u32;
+    let writes = 100 - reads;
+    vec![
+        (Op::HgetallUser, reads * 6),
+        (Op::HgetallProduct, reads * 4),
+        (Op::Set, writes * 5),
+        (Op::Hset, writes * 5),
+    ]
+}
+
+/// Linear ramp-up → hold → linear ramp-down of the worker count.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Ramp {
+    /// Workers active at t = 0
+    #[serde(default)]
+    pub start_workers: u32,
+    /// Workers active during the hold phase
+    pub peak_workers: u32,
+    pub up_secs: u64,
+    #[serde(default)]
+    pub hold_secs: u64,
+    #[serde(default)]
+    pub down_secs: u64,
+}
+
+impl Ramp {
+    pub fn total_secs(&self) -> u64 {
+        self.up_secs + self.hold_secs + self.down_secs
+    }
+
+    /// When worker `id` joins and retires, as offsets from the run start.
+    ///
+    /// Workers above `start_workers` join evenly across the ramp-up;
+    /// the highest ids retire first, evenly across the ramp-down.
+    pub fn window(&self, id: u32) -> (Duration, Duration) {
+        let join = if id < self.start_workers {
+            0.0
+        } else {
+            let ramped = (self.peak_workers - self.start_workers) as f64;
+            self.up_secs as f64 * (id - self.start_workers + 1) as f64
+                / ramped
+        };
+        let remaining = (self.peak_workers - id - 1) as f64;
+        let retire = (self.up_secs + self.hold_secs) as f64
+            + self.down_secs as f64 * remaining / self.peak_workers as f64;
+        (Duration::from_secs_f64(join), Duration::from_secs_f64(retire))
+    }
+}
+
+/// How open-loop send times are spaced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Arrival {
+    /// Fixed interval of `1 / rate`
+    #[default]
+    Uniform,
+    /// Exponentially distributed gaps (Poisson process)
+    Poisson,
+}
+
+/// How load-generator workers get their Redis connection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionMode {
+    /// Every worker multiplexes over the app's one `ConnectionManager`
+    #[default]
+    Shared,
+    /// Workers check out one of `pool_size` connections per iteration
+    /// and hold it exclusively until the iteration ends
+    Pool,
+    /// Each worker opens its own multiplexed connection
+    Dedicated,
+    /// Every iteration opens a new connection and closes it afterwards,
+    /// timing connect and AUTH apart from the command
+    PerOp,
+}
+
+/// Longest `name`, `git_sha` or `redis_version` in run metadata
+const MAX_LABEL_LEN: usize = 200;
+
+/// Longest `notes` in run metadata
+const MAX_NOTES_LEN: usize = 4_000;
+
+/// Free-form labels tying a run to the code and server under test.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Commit of the code under test
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_sha: Option<String>,
+    /// As the caller describes it; not read from the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redis_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+impl RunMetadata {
+    /// Field paths are relative to the `metadata` object.
+    pub fn check(&self, problems: &mut Violations) {
+        let fields = [
+            ("name", &self.name, MAX_LABEL_LEN),
+            ("git_sha", &self.git_sha, MAX_LABEL_LEN),
+            ("redis_version", &self.redis_version, MAX_LABEL_LEN),
+            ("notes", &self.notes, MAX_NOTES_LEN),
+        ];
+        for (field, value, max) in fields {
+            let len = value.as_deref().map_or(0, |v| v.chars().count());
+            if len > max {
+                problems.push(
+                    field,
+                    format!("{field} must be at most {max} characters"),
+                );
+            }
+        }
+    }
+}
+
+/// Largest value a write may carry
+pub const MAX_VALUE_BYTES: u32 = 1024 * 1024;
+
+/// Distribution of written value sizes, in bytes.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "dist", rename_all = "snake_case")]
+pub enum ValueSize {
+    /// Every value is `bytes` long
+    Fixed { bytes: u32 },
+    /// Uniform between `min` and `max`, inclusive
+    Uniform { min: u32, max: u32 },
+    /// Log-normal: half the values are below `median`; `sigma` is the
+    /// spread of the underlying normal (at 1.0 about 1 % of values are
+    /// over 10× the median). Capped at `MAX_VALUE_BYTES`.
+    LogNormal { median: u32, sigma: f64 },
+}
+
+impl ValueSize {
+    /// Field paths are relative to the `value_size` object.
+    pub fn check(&self, problems: &mut Violations) {
+        match *self {
+            Self::Fixed { bytes } => {
+                problems.range("bytes", bytes, 1, MAX_VALUE_BYTES);
+            }
+            Self::Uniform { min, max } => {
+                problems.range("min", min, 1, MAX_VALUE_BYTES);
+                problems.range("max", max, 1, MAX_VALUE_BYTES);
+                if min > max {
+                    problems.push("min", "min must not exceed max");
+                }
+            }
+            Self::LogNormal { median, sigma } => {
+                problems.range("median", median, 1, MAX_VALUE_BYTES);
+                problems.range("sigma", sigma, 0.0, 4.0);
+            }
+        }
+    }
+
+    /// Draw one value size.
+    pub fn sample(&self, rng: &mut impl rand::Rng) -> usize {
+        let bytes = match *self {
+            Self::Fixed { bytes } => bytes,
+            Self::Uniform { min, max } => rng.gen_range(min..=max),
+            Self::LogNormal { median, sigma } => {
+                // Box–Muller: a standard normal from two uniforms
+                let u1: f64 = 1.0 - rng.gen::<f64>();
+                let u2: f64 = rng.gen();
+                let z = (-2.0 * u1.ln()).sqrt()
+                    * (2.0 * std::f64::consts::PI * u2).cos();
+                let size = median as f64 * (sigma * z).exp();
+                size.round().clamp(1.0, MAX_VALUE_BYTES as f64) as u32
+            }
+        };
+        bytes as usize
+    }
+}
+
+impl std::fmt::Display for ValueSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fixed { bytes } => write!(f, "{bytes} B values"),
+            Self::Uniform { min, max } => write!(f, "{min}–{max} B values"),
+            Self::LogNormal { median, sigma } => {
+                write!(f, "log-normal values (median {median} B, σ {sigma})")
+            }
+        }
+    }
 }
 
 fn default_concurrency() -> u32 {
@@ -36,84 +511,580 @@ fn default_duration() -> u64 {
 fn default_read_pct() -> u8 {
     70
 }
+fn default_stream_consumers() -> u32 {
+    2
+}
+fn default_queue_consumers() -> u32 {
+    2
+}
+fn default_scan_match() -> String {
+    "user:*".into()
+}
+fn default_scan_count() -> u32 {
+    100
+}
+fn default_churn_ttl_ms() -> u64 {
+    100
+}
+fn default_batch_size() -> u32 {
+    10
+}
+fn default_leaderboard_top_n() -> u32 {
+    10
+}
+fn default_pool_size() -> u32 {
+    8
+}
+fn default_pipeline_depth() -> u32 {
+    1
+}
+fn default_timeline_window_ms() -> u64 {
+    500
+}
+fn default_slow_threshold_us() -> u64 {
+    5_000
+}
+fn default_top_outliers() -> usize {
+    10
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdjustRequest {
+    /// Workers the running stage should have from now on
+    #[serde(default)]
+    pub concurrency: Option<u32>,
+    /// Read share from now on; only for stages without an explicit `mix`
+    #[serde(default)]
+    pub read_pct: Option<u8>,
+}
 
 #[derive(Debug, Serialize)]
 pub struct BenchmarkStatus {
     pub running: bool,
     pub message: String,
+    /// Current or most recent run — key for `/api/runs/:id/*`
+    pub run_id: Option<String>,
+    /// `/status` only, while a run is going
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<RunProgress>,
+    /// `/status` only, once the run has finished and been judged
+    /// against a baseline
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<Verdict>,
+    /// `/status` only, once a run with an `slo` has finished
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slo: Option<SloResult>,
+}
+
+impl BenchmarkStatus {
+    /// A reply to a start, stop or queue request: no progress, verdict
+    /// or SLO result, which only `/status` fills in.
+    pub fn new(
+        running: bool,
+        message: impl Into<String>,
+        run_id: Option<String>,
+    ) -> Self {
+        Self {
+            running,
+            message: message.into(),
+            run_id,
+            progress: None,
+            verdict: None,
+            slo: None,
+        }
+    }
+}
+
+/// Where the current run stands, for progress bars.
+#[derive(Debug, Serialize)]
+pub struct RunProgress {
+    pub elapsed_secs: f64,
+    /// Unset when the run has no planned length: the last stage runs
+    /// until stopped, or it is a capacity search
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining_secs: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent_complete: Option<f64>,
+    /// Workers running right now
+    pub concurrency: u32,
+    /// Requests so far, across every stage or phase of the run
+    pub total_requests: u64,
+    /// The running stage's config with any `/adjust` changes applied;
+    /// unset between stages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<BenchmarkConfig>,
+}
+
+/// When the current or latest run started and how long it should
+/// take.
+pub struct RunClock {
+    started: Instant,
+    planned_secs: Option<u64>,
 }
 
 // ─── POST /api/benchmark/start ───────────────────────────────────
 
+#[derive(Debug, Default, Deserialize)]
+pub struct StartQuery {
+    /// Wait behind the active run instead of answering 409
+    #[serde(default)]
+    pub queue: bool,
+}
+
 pub async fn start_benchmark(
     State(state): State<Arc<AppState>>,
-    Json(config): Json<BenchmarkConfig>,
+    Query(query): Query<StartQuery>,
+    Json(mut config): Json<BenchmarkConfig>,
 ) -> Result<Json<BenchmarkStatus>, AppError> {
-    // Guard: only one benchmark at a time
-    if state.load_running.load(Ordering::SeqCst) {
+    // Guard: only one benchmark at a time, unless asked to wait
+    let busy = state.load_running.load(Ordering::SeqCst);
+    if busy && !query.queue {
         return Err(AppError::AlreadyRunning);
     }
+    validate(&mut config)?;
+    config.op_timeout_ms.get_or_insert(state.op_timeout_ms);
 
-    // Validate inputs
-    if config.concurrency == 0 || config.concurrency > 500 {
-        return Err(AppError::BadRequest(
-            "concurrency must be between 1 and 500".into(),
-        ));
-    }
-    if config.duration_secs == 0 || config.duration_secs > 300 {
-        return Err(AppError::BadRequest(
-            "duration_secs must be between 1 and 300".into(),
-        ));
-    }
-    if config.read_pct > 100 {
-        return Err(AppError::BadRequest(
-            "read_pct must be between 0 and 100".into(),
-        ));
+    // Behind the active run, and behind anything already waiting
+    if query.queue && (busy || !state.queue.is_empty()) {
+        let run = QueuedRun::new(config);
+        let message = format!("Queued: {}", run.summary);
+        let run_id = run.id.clone();
+        let position = state.queue.push(run)?;
+        let message = format!("{message} (position {position})");
+        return Ok(Json(BenchmarkStatus::new(busy, message, Some(run_id))));
     }
 
-    // Reset metrics for a clean run
-    state.metrics.reset();
+    let run_id = uuid::Uuid::new_v4().to_string();
+    let msg = launch(&state, config, run_id.clone()).await?;
+    Ok(Json(BenchmarkStatus::new(true, msg, Some(run_id))))
+}
 
-    // Flip the flag BEFORE spawning so workers see it immediately
-    state.load_running.store(true, Ordering::SeqCst);
+/// Start a validated config as run `run_id` and return its status
+/// line. Shared by `start` and the run queue.
+pub(crate) async fn launch(
+    state: &Arc<AppState>,
+    config: BenchmarkConfig,
+    run_id: String,
+) -> Result<String, AppError> {
+    if state.load_running.load(Ordering::SeqCst) {
+        return Err(AppError::AlreadyRunning);
+    }
 
-    // Capture values for the status message before the move
-    let msg = format!(
-        "Started: {} workers × {}s, {}% reads / {}% writes",
-        config.concurrency,
-        config.duration_secs,
-        config.read_pct,
-        100u8.saturating_sub(config.read_pct),
-    );
+    // Set up tracking before touching any state so failures are clean
+    let client_cache = if config.client_cache {
+        let events = state.events.clone();
+        let cache = ClientCache::start(&state.redis.client(), events)
+            .await
+            .map_err(|e| AppError::Redis(format!("CLIENT TRACKING: {e}")))?;
+        Some(cache)
+    } else {
+        None
+    };
+
+    let msg = format!("Started: {}", describe(&config));
+    let planned = (config.duration_secs > 0).then_some(config.duration_secs);
+    begin_run_as(state, run_id.clone(), &msg, planned);
+    state.metrics.set_percentile_mode(config.percentile_mode);
 
     // Capture clones for the spawned task
     let running = state.load_running.clone();
     let metrics = state.metrics.clone();
+    let events = state.events.clone();
     let redis = state.redis.clone();
-    let concurrency = config.concurrency;
-    let duration_secs = config.duration_secs;
-    let read_pct = config.read_pct;
+    let archive = state.archive.clone();
+    let workers = state.workers.clone();
+    let webhook = state.webhook.clone();
+    let notifier = state.notifier.clone();
 
+    let archived_id = run_id;
     let handle = tokio::spawn(async move {
+        let stats_before = commandstats::capture(&mut redis.manager()).await;
         crate::load_generator::run(
             running,
-            metrics,
-            redis,
-            concurrency,
-            duration_secs,
-            read_pct,
+            metrics.clone(),
+            events.clone(),
+            redis.clone(),
+            config,
+            client_cache,
+            workers,
         )
         .await;
+        // Keep the final numbers for /api/runs once the metrics move on
+        let snapshot = metrics.snapshot();
+        let log = events.events(&archived_id).unwrap_or_default();
+        let mut archived = ArchivedRun::from_snapshot(archived_id, &snapshot);
+        archived.analysis = analysis::analyze(&snapshot, &log);
+        archived.histograms = StoredHistograms::capture(&metrics);
+        archived.commandstats =
+            commandstats::since(&mut redis.manager(), &stats_before).await;
+        judge(&archive, &events, &mut archived);
+        let report = RunReport::new(&archived, &snapshot, &log);
+        if let Some(webhook) = &webhook {
+            webhook.send(&report);
+        }
+        if let Some(notifier) = &notifier {
+            notifier.send(&report);
+        }
+        archive.set_report(report);
+        archived.events = log;
+        archive.record(archived);
     });
 
     // Stash the handle so `stop` can await clean shutdown
     let mut guard = state.load_handle.lock().await;
     *guard = Some(handle);
 
-    Ok(Json(BenchmarkStatus {
-        running: true,
-        message: msg,
-    }))
+    Ok(msg)
+}
+
+// ─── POST /api/benchmark/plan ────────────────────────────────────
+
+/// What `/api/benchmark/start` would do with a config, without doing it.
+#[derive(Debug, Serialize)]
+pub struct BenchmarkPlan {
+    pub valid: bool,
+    /// Every problem found; empty when `valid`
+    pub violations: Vec<Violation>,
+    /// The config as a start would run it: ramp folded in, defaults and
+    /// the server's op timeout filled
+    pub config: BenchmarkConfig,
+    /// The status line a start would show; `None` while invalid
+    pub summary: Option<String>,
+}
+
+/// Dry run for the dashboard form. Always 200 for a body that parses,
+/// so field errors can be shown inline; nothing touches Redis.
+pub async fn plan_benchmark(
+    State(state): State<Arc<AppState>>,
+    Json(mut config): Json<BenchmarkConfig>,
+) -> Json<BenchmarkPlan> {
+    let problems = check(&mut config);
+    config.op_timeout_ms.get_or_insert(state.op_timeout_ms);
+    let valid = problems.is_empty();
+    Json(BenchmarkPlan {
+        valid,
+        violations: problems.into_vec(),
+        summary: valid.then(|| describe(&config)),
+        config,
+    })
+}
+
+// ─── Shared with the scenario runner ─────────────────────────────
+
+/// Check a config and fold a ramp into `concurrency` / `duration_secs`;
+/// a 400 lists every violation found.
+pub(super) fn validate(config: &mut BenchmarkConfig) -> Result<(), AppError> {
+    check(config).into_result()
+}
+
+/// Every violation in `config`, with field paths relative to it. Also
+/// normalizes it the way a start would (ramp, named-worker connections).
+pub(super) fn check(config: &mut BenchmarkConfig) -> Violations {
+    let mut problems = Violations::new();
+
+    // A ramp defines its own worker ceiling and duration
+    if let Some(ramp) = config.ramp {
+        if ramp.start_workers > ramp.peak_workers {
+            problems.push(
+                "ramp.start_workers",
+                "ramp.start_workers must not exceed ramp.peak_workers",
+            );
+        }
+        config.concurrency = ramp.peak_workers;
+        config.duration_secs = ramp.total_secs();
+    }
+
+    problems.range("concurrency", config.concurrency, 1, 500);
+    // 0 runs until stopped; a ramp always has an end
+    if config.duration_secs != 0 || config.ramp.is_some() {
+        problems.range("duration_secs", config.duration_secs, 1, 300);
+    }
+    problems.range("read_pct", config.read_pct, 0, 100);
+    if let Some(mix) = &config.mix {
+        if mix.values().all(|w| *w == 0) {
+            problems.push(
+                "mix",
+                "mix needs at least one op with a positive weight",
+            );
+        }
+    }
+    problems.range("pipeline_depth", config.pipeline_depth, 1, 1_000);
+    problems.range("stream_consumers", config.stream_consumers, 1, 64);
+    problems.range("queue_consumers", config.queue_consumers, 1, 64);
+    if let Some(size) = &config.value_size {
+        let mut inner = Violations::new();
+        size.check(&mut inner);
+        problems.nest("value_size", "value_size", inner);
+    }
+    if config.scan_match.is_empty() {
+        problems.push("scan_match", "scan_match must not be empty");
+    }
+    problems.range("scan_count", config.scan_count, 1, 10_000);
+    if let Some(ms) = config.op_timeout_ms.filter(|ms| *ms > 60_000) {
+        problems.bounds(
+            "op_timeout_ms",
+            format!("op_timeout_ms must be at most 60000 (0 = none), not {ms}"),
+            0,
+            60_000,
+        );
+    }
+    problems.range("churn_ttl_ms", config.churn_ttl_ms, 1, 60_000);
+    problems.range("batch_size", config.batch_size, 1, 1_000);
+    problems.range(
+        "timeline_window_ms",
+        config.timeline_window_ms,
+        10,
+        60_000,
+    );
+    problems.range("leaderboard_top_n", config.leaderboard_top_n, 1, 1_000);
+    problems.range(
+        "slow_threshold_us",
+        config.slow_threshold_us,
+        1,
+        60_000_000,
+    );
+    problems.range("top_outliers", config.top_outliers, 0, 1_000);
+    let in_mix = |op: Op| {
+        config.mix.as_ref().is_some_and(|m| {
+            m.get(&op).is_some_and(|w| *w > 0)
+        })
+    };
+    if config.pipeline_depth > 1 && in_mix(Op::Multi) {
+        problems.push(
+            "pipeline_depth",
+            "MULTI ops cannot be pipelined; drop pipeline_depth or MULTI",
+        );
+    }
+    // Each call needs the cursor the previous one returned
+    if config.pipeline_depth > 1 && in_mix(Op::Scan) {
+        problems.push(
+            "pipeline_depth",
+            "SCAN ops cannot be pipelined; drop pipeline_depth or SCAN",
+        );
+    }
+    problems.range("pool_size", config.pool_size, 1, 500);
+    // Names are per worker, so they need a connection per worker
+    if config.named_workers {
+        match config.connection_mode {
+            ConnectionMode::Shared => {
+                config.connection_mode = ConnectionMode::Dedicated;
+            }
+            ConnectionMode::Pool | ConnectionMode::PerOp => {
+                problems.push(
+                    "connection_mode",
+                    "named_workers needs the dedicated connection mode",
+                );
+            }
+            ConnectionMode::Dedicated => {}
+        }
+    }
+    if config.pipeline_depth > 1 && config.client_cache {
+        problems.push(
+            "client_cache",
+            "client_cache cannot be combined with pipeline_depth > 1",
+        );
+    }
+    if let Some(pct) = config.abort_on_error_pct.take() {
+        let guard = config.guard.get_or_insert_with(HealthGuard::default);
+        if !(pct > 0.0 && pct <= 100.0) {
+            problems.bounds(
+                "abort_on_error_pct",
+                "abort_on_error_pct must be in (0, 100]",
+                0.0,
+                100.0,
+            );
+        } else if guard.max_error_rate.is_some() {
+            problems.push(
+                "abort_on_error_pct",
+                "set abort_on_error_pct or guard.max_error_rate, not both",
+            );
+        } else {
+            guard.max_error_rate = Some(pct / 100.0);
+        }
+    }
+    if let Some(guard) = &config.guard {
+        let mut inner = Violations::new();
+        guard.check(&mut inner);
+        problems.nest("guard", "guard", inner);
+    }
+    if let Some(slo) = &config.slo {
+        let mut inner = Violations::new();
+        slo.check(&mut inner);
+        problems.nest("slo", "slo", inner);
+    }
+    let mut inner = Violations::new();
+    config.latency_buckets.check(&mut inner);
+    problems.nest("latency_buckets", "latency_buckets", inner);
+    if let Some(apdex) = &config.apdex {
+        let mut inner = Violations::new();
+        apdex.check(&mut inner);
+        problems.nest("apdex", "apdex", inner);
+    }
+    if let Some(rps) = config.target_rps {
+        if !(rps > 0.0 && rps.is_finite()) {
+            problems.push("target_rps", "target_rps must be a positive number");
+        }
+    }
+    if let Some(metadata) = &config.metadata {
+        let mut inner = Violations::new();
+        metadata.check(&mut inner);
+        problems.nest("metadata", "metadata", inner);
+    }
+    problems
+}
+
+/// Judge a finished run against the baseline, if one is set. A
+/// regression or a failed SLO is also logged as a warning on the run.
+pub(super) fn judge(
+    archive: &RunArchive,
+    events: &EventLog,
+    run: &mut ArchivedRun,
+) {
+    if let Some(slo) = run.slo.as_ref().filter(|slo| !slo.passed) {
+        events.push(
+            EventKind::Warning,
+            format!("SLO failed: {}", slo.violations.join("; ")),
+        );
+    }
+    run.verdict = archive.judge(run);
+    if let Some(verdict) = run.verdict.as_ref().filter(|v| v.regressed) {
+        events.push(
+            EventKind::Warning,
+            format!(
+                "regressed against baseline {}: {}",
+                verdict.baseline_run_id,
+                verdict.regressions.join("; ")
+            ),
+        );
+    }
+}
+
+/// One-line summary of a config for status messages and events.
+pub(crate) fn describe(config: &BenchmarkConfig) -> String {
+    let mut msg = match config.duration_secs {
+        0 => format!("{} workers until stopped, ", config.concurrency),
+        secs => format!("{} workers × {secs}s, ", config.concurrency),
+    };
+    let name = config.metadata.as_ref().and_then(|m| m.name.as_deref());
+    if let Some(name) = name {
+        msg.insert_str(0, &format!("\"{name}\": "));
+    }
+    match &config.mix {
+        Some(mix) => {
+            let parts: Vec<String> = mix
+                .iter()
+                .filter(|(_, w)| **w > 0)
+                .map(|(op, w)| format!("{}={w}", op.label()))
+                .collect();
+            msg.push_str(&format!("mix {}", parts.join(" ")));
+        }
+        None => msg.push_str(&format!(
+            "{}% reads / {}% writes",
+            config.read_pct,
+            100u8.saturating_sub(config.read_pct),
+        )),
+    }
+    if let Some(inject) = config.inject.filter(Injection::is_active) {
+        msg.push_str(&format!(" + injected Rust overhead ({inject})"));
+    }
+    if config.client_cache {
+        msg.push_str(" + client-side caching");
+    }
+    match config.connection_mode {
+        ConnectionMode::Shared => {}
+        ConnectionMode::Pool => msg.push_str(&format!(
+            " + pool of {} connections",
+            config.pool_size
+        )),
+        ConnectionMode::Dedicated if config.named_workers => {
+            msg.push_str(" + named dedicated connections");
+        }
+        ConnectionMode::Dedicated => {
+            msg.push_str(" + dedicated connections");
+        }
+        ConnectionMode::PerOp => msg.push_str(" + new connection per op"),
+    }
+    let has_batches = config.mix.as_ref().is_some_and(|m| {
+        [Op::Mget, Op::Mset]
+            .iter()
+            .any(|op| m.get(op).is_some_and(|w| *w > 0))
+    });
+    if let Some(size) = &config.value_size {
+        msg.push_str(&format!(" + {size}"));
+    }
+    if has_batches {
+        msg.push_str(&format!(" + {}-key batches", config.batch_size));
+    }
+    let has_churn = config.mix.as_ref().is_some_and(|m| {
+        m.get(&Op::SetChurn).is_some_and(|w| *w > 0)
+    });
+    if has_churn {
+        msg.push_str(&format!(" + {}ms churn TTL", config.churn_ttl_ms));
+    }
+    if config.pipeline_depth > 1 {
+        msg.push_str(&format!(" + pipelined ×{}", config.pipeline_depth));
+    }
+    if config.guard.is_some_and(|g| g.is_active()) {
+        msg.push_str(" + health guard");
+    }
+    if config.slo.is_some() {
+        msg.push_str(" + SLO");
+    }
+    if let Some(r) = config.ramp {
+        msg.push_str(&format!(
+            " — ramp {}→{} over {}s, hold {}s, down {}s",
+            r.start_workers,
+            r.peak_workers,
+            r.up_secs,
+            r.hold_secs,
+            r.down_secs,
+        ));
+    }
+    if let Some(rps) = config.target_rps {
+        msg.push_str(&format!(
+            " — open loop at {rps} req/s ({:?} arrivals)",
+            config.arrival
+        ));
+    }
+    msg
+}
+
+/// Reset metrics, open a new run log and raise the running flag.
+/// `planned_secs` is the whole run's length, `None` when open-ended.
+/// Returns the new run id.
+pub(super) fn begin_run(
+    state: &AppState,
+    msg: &str,
+    planned_secs: Option<u64>,
+) -> String {
+    let run_id = uuid::Uuid::new_v4().to_string();
+    begin_run_as(state, run_id.clone(), msg, planned_secs);
+    run_id
+}
+
+/// `begin_run` under an id chosen earlier, as a queued run's is.
+fn begin_run_as(
+    state: &AppState,
+    run_id: String,
+    msg: &str,
+    planned_secs: Option<u64>,
+) {
+    // Reset metrics for a clean run
+    state.metrics.reset();
+    *state.run_clock.lock() = Some(RunClock {
+        started: Instant::now(),
+        planned_secs,
+    });
+
+    // Fresh event log under the new run id
+    state.metrics.set_run_id(&run_id);
+    state.events.begin_run(&run_id);
+    *state.run_id.lock() = Some(run_id);
+
+    // Flip the flag BEFORE spawning so workers see it immediately
+    state.load_running.store(true, Ordering::SeqCst);
+
+    state.events.push(EventKind::Started, msg);
 }
 
 // ─── POST /api/benchmark/stop ────────────────────────────────────
@@ -122,10 +1093,11 @@ pub async fn stop_benchmark(
     State(state): State<Arc<AppState>>,
 ) -> Result<Json<BenchmarkStatus>, AppError> {
     if !state.load_running.load(Ordering::SeqCst) {
-        return Ok(Json(BenchmarkStatus {
-            running: false,
-            message: "No benchmark is running".into(),
-        }));
+        return Ok(Json(BenchmarkStatus::new(
+            false,
+            "No benchmark is running",
+            state.run_id.lock().clone(),
+        )));
     }
 
     // Signal all workers to stop
@@ -138,10 +1110,111 @@ pub async fn stop_benchmark(
         let _ = handle.await;
     }
 
-    Ok(Json(BenchmarkStatus {
-        running: false,
-        message: "Benchmark stopped".into(),
-    }))
+    Ok(Json(BenchmarkStatus::new(
+        false,
+        "Benchmark stopped",
+        state.run_id.lock().clone(),
+    )))
+}
+
+// ─── /api/benchmark/queue ────────────────────────────────────────
+
+/// Runs waiting behind the active one, and queued starts that failed.
+pub async fn list_queue(
+    State(state): State<Arc<AppState>>,
+) -> Json<QueueListing> {
+    Json(state.queue.listing())
+}
+
+/// Drop every waiting run; the active run keeps going.
+pub async fn clear_queue(
+    State(state): State<Arc<AppState>>,
+) -> Json<BenchmarkStatus> {
+    let dropped = state.queue.clear();
+    Json(BenchmarkStatus::new(
+        state.load_running.load(Ordering::SeqCst),
+        format!("Cancelled {dropped} queued runs"),
+        state.run_id.lock().clone(),
+    ))
+}
+
+/// Drop one waiting run. A run that has started is stopped with
+/// `/api/benchmark/stop` instead.
+pub async fn cancel_queued(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<QueuedRun>, AppError> {
+    state
+        .queue
+        .cancel(&id)
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("No queued run {id}")))
+}
+
+// ─── POST /api/benchmark/adjust ──────────────────────────────────
+
+/// Move the running stage's worker count and/or read share without
+/// restarting the run or resetting its metrics.
+pub async fn adjust_benchmark(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<AdjustRequest>,
+) -> Result<Json<BenchmarkStatus>, AppError> {
+    if !state.load_running.load(Ordering::SeqCst) {
+        return Err(AppError::BadRequest("No benchmark is running".into()));
+    }
+    let mut problems = Violations::new();
+    if req.concurrency.is_none() && req.read_pct.is_none() {
+        problems.push("", "set concurrency, read_pct or both");
+    }
+    if let Some(concurrency) = req.concurrency {
+        problems.range("concurrency", concurrency, 1, 500);
+    }
+    if let Some(read_pct) = req.read_pct {
+        problems.range("read_pct", read_pct, 0, 100);
+        // Before touching concurrency, so a rejected request changes
+        // nothing
+        if let Err(msg) = state.workers.check_read_pct() {
+            problems.push("read_pct", msg);
+        }
+    }
+    problems.into_result()?;
+
+    let mut changes = Vec::new();
+    if let Some(concurrency) = req.concurrency {
+        let previous = state
+            .workers
+            .adjust(concurrency)
+            .map_err(AppError::BadRequest)?;
+        changes.push(ConfigChange::new(
+            ChangeSource::Manual,
+            "concurrency",
+            previous,
+            concurrency,
+        ));
+    }
+    if let Some(read_pct) = req.read_pct {
+        let previous = state
+            .workers
+            .adjust_read_pct(read_pct)
+            .map_err(AppError::BadRequest)?;
+        changes.push(ConfigChange::new(
+            ChangeSource::Manual,
+            "read_pct",
+            previous,
+            read_pct,
+        ));
+    }
+
+    let applied: Vec<String> = changes.iter().map(|c| c.to_string()).collect();
+    let message = format!("Adjusted: {}", applied.join(", "));
+    for change in changes.into_iter().filter(|c| c.old != c.new) {
+        state.metrics.mark_change(&change);
+        state.events.push_change(change);
+    }
+    if let Some(config) = state.workers.current() {
+        state.metrics.set_config(config);
+    }
+    Ok(Json(BenchmarkStatus::new(true, message, state.run_id.lock().clone())))
 }
 
 // ─── GET /api/benchmark/status ───────────────────────────────────
@@ -150,6 +1223,28 @@ pub async fn benchmark_status(
     State(state): State<Arc<AppState>>,
 ) -> Json<BenchmarkStatus> {
     let running = state.load_running.load(Ordering::SeqCst);
+    let run_id = state.run_id.lock().clone();
+    let progress = match running {
+        true => state.run_clock.lock().as_ref().map(|clock| {
+            let (total_requests, concurrency) = state.metrics.progress();
+            let elapsed = clock.started.elapsed().as_secs_f64();
+            let planned = clock.planned_secs.map(|secs| secs as f64);
+            RunProgress {
+                elapsed_secs: elapsed,
+                remaining_secs: planned.map(|p| (p - elapsed).max(0.0)),
+                percent_complete: planned
+                    .map(|p| (100.0 * elapsed / p).min(100.0)),
+                concurrency,
+                total_requests,
+                config: state.workers.current(),
+            }
+        }),
+        false => None,
+    };
+    let finished = match running {
+        true => None,
+        false => run_id.as_deref().and_then(|id| state.archive.get(id)),
+    };
     Json(BenchmarkStatus {
         running,
         message: if running {
@@ -157,5 +1252,28 @@ pub async fn benchmark_status(
         } else {
             "Idle".into()
         },
+        verdict: finished.as_ref().and_then(|run| run.verdict.clone()),
+        slo: finished.and_then(|run| run.slo),
+        run_id,
+        progress,
     })
-}
\ No newline at end of file
+}
+
+// ─── GET /api/benchmark/report ───────────────────────────────────
+
+/// The current run's frozen end-of-run report. 404 until the run has
+/// finished and been archived, so a script can poll for it.
+pub async fn benchmark_report(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<RunReport>, AppError> {
+    let Some(run_id) = state.run_id.lock().clone() else {
+        return Err(AppError::NotFound("no run has been started".into()));
+    };
+    match state.archive.report() {
+        Some(report) if report.run_id == run_id => Ok(Json(report)),
+        _ => Err(AppError::NotFound(format!(
+            "no report for run '{run_id}' yet; one is built when a \
+             benchmark or scenario run ends"
+        ))),
+    }
+}
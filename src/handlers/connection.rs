@@ -0,0 +1,14 @@
+use axum::{extract::State, Json};
+use std::sync::Arc;
+
+use crate::redis_client::ConnectionProfile;
+use crate::AppState;
+
+// ─── GET /api/connection ─────────────────────────────────────────
+
+/// Transport and handshake timings measured when the server started.
+pub async fn get_connection(
+    State(state): State<Arc<AppState>>,
+) -> Json<ConnectionProfile> {
+    Json(state.connection.clone())
+}
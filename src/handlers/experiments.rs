@@ -0,0 +1,246 @@
+use axum::{extract::State, Json};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use crate::capacity::{CapacityReport, CapacitySearch};
+use crate::experiment::{
+    self, EvictionExperiment, EvictionReport, ExperimentState,
+};
+use crate::sweep::{Sweep, SweepAxis, SweepReport};
+use crate::validation::Violations;
+use crate::AppState;
+
+use super::benchmark::{self, BenchmarkStatus};
+use super::AppError;
+
+// ─── POST /api/experiments/eviction ──────────────────────────────
+
+/// Run the same memory-pressure workload once per `maxmemory-policy`;
+/// `{}` compares the defaults.
+/// Needs `CONFIG SET` on the target; the original policy and limit are
+/// put back when the experiment ends, however it ends.
+pub async fn start_eviction(
+    State(state): State<Arc<AppState>>,
+    Json(mut experiment): Json<EvictionExperiment>,
+) -> Result<Json<BenchmarkStatus>, AppError> {
+    if state.load_running.load(Ordering::SeqCst) {
+        return Err(AppError::AlreadyRunning);
+    }
+    let mut problems = Violations::new();
+    experiment.check(&mut problems);
+    let phase = benchmark::check(&mut experiment.phase);
+    problems.nest("phase", "phase", phase);
+    problems.into_result()?;
+    experiment.phase.op_timeout_ms.get_or_insert(state.op_timeout_ms);
+
+    let mut conn = state.redis.manager();
+    let (original_policy, original_maxmemory_mb) =
+        experiment::current_settings(&mut conn)
+            .await
+            .map_err(|e| AppError::Redis(format!("CONFIG GET: {e}")))?;
+    let maxmemory_mb =
+        experiment::pick_maxmemory_mb(&mut conn, experiment.maxmemory_mb)
+            .await;
+
+    let msg = format!(
+        "Started eviction experiment: {} at maxmemory {maxmemory_mb}MB, {}",
+        experiment.policies.join(" → "),
+        benchmark::describe(&experiment.phase)
+    );
+    let planned =
+        experiment.policies.len() as u64 * experiment.phase.duration_secs;
+    let run_id = benchmark::begin_run(&state, &msg, Some(planned));
+    *state.eviction_report.lock() = Some(EvictionReport {
+        run_id: run_id.clone(),
+        state: ExperimentState::Running,
+        maxmemory_mb,
+        original_policy,
+        original_maxmemory_mb,
+        phases: Vec::new(),
+        error: None,
+    });
+
+    let handle = tokio::spawn(crate::load_generator::run_eviction_experiment(
+        state.load_running.clone(),
+        state.metrics.clone(),
+        state.events.clone(),
+        state.redis.clone(),
+        state.archive.clone(),
+        state.eviction_report.clone(),
+        experiment,
+        state.workers.clone(),
+    ));
+
+    // Stash the handle so `stop` can await clean shutdown
+    let mut guard = state.load_handle.lock().await;
+    *guard = Some(handle);
+
+    Ok(Json(BenchmarkStatus::new(true, msg, Some(run_id))))
+}
+
+// ─── GET /api/experiments/eviction ───────────────────────────────
+
+/// The latest experiment's per-policy results, filled in as each
+/// phase finishes.
+pub async fn get_eviction(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<EvictionReport>, AppError> {
+    state
+        .eviction_report
+        .lock()
+        .clone()
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound("no eviction experiment yet".into()))
+}
+
+// ─── POST /api/experiments/sweep ─────────────────────────────────
+
+/// Run the same workload at each worker count (or pipeline depth) in
+/// `levels` and build a throughput-vs-latency curve.
+pub async fn start_sweep(
+    State(state): State<Arc<AppState>>,
+    Json(mut sweep): Json<Sweep>,
+) -> Result<Json<BenchmarkStatus>, AppError> {
+    if state.load_running.load(Ordering::SeqCst) {
+        return Err(AppError::AlreadyRunning);
+    }
+    sweep.prepare();
+    let mut problems = Violations::new();
+    sweep.check(&mut problems);
+    let step = benchmark::check(&mut sweep.step);
+    problems.nest("step", "step", step);
+    problems.into_result()?;
+    sweep.step.op_timeout_ms.get_or_insert(state.op_timeout_ms);
+
+    let levels: Vec<String> =
+        sweep.levels.iter().map(|l| l.to_string()).collect();
+    let msg = match sweep.axis {
+        SweepAxis::Concurrency => format!(
+            "Started concurrency sweep: {} workers, {}s per step",
+            levels.join(" → "),
+            sweep.step.duration_secs,
+        ),
+        SweepAxis::PipelineDepth => format!(
+            "Started pipeline sweep: depth {} at {} workers, {}s per step",
+            levels.join(" → "),
+            sweep.step.concurrency,
+            sweep.step.duration_secs,
+        ),
+    };
+    let planned = sweep.levels.len() as u64 * sweep.step.duration_secs;
+    let run_id = benchmark::begin_run(&state, &msg, Some(planned));
+    *state.sweep_report.lock() = Some(SweepReport {
+        run_id: run_id.clone(),
+        state: ExperimentState::Running,
+        axis: sweep.axis,
+        steps: Vec::new(),
+        peak_throughput_at: None,
+    });
+
+    let handle = tokio::spawn(crate::load_generator::run_sweep(
+        state.load_running.clone(),
+        state.metrics.clone(),
+        state.events.clone(),
+        state.redis.clone(),
+        state.archive.clone(),
+        state.sweep_report.clone(),
+        sweep,
+        state.workers.clone(),
+    ));
+
+    let mut guard = state.load_handle.lock().await;
+    *guard = Some(handle);
+
+    Ok(Json(BenchmarkStatus::new(true, msg, Some(run_id))))
+}
+
+// ─── GET /api/experiments/sweep ──────────────────────────────────
+
+/// The latest sweep's curve, one point per finished step.
+pub async fn get_sweep(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<SweepReport>, AppError> {
+    state
+        .sweep_report
+        .lock()
+        .clone()
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound("no sweep yet".into()))
+}
+
+// ─── POST /api/experiments/capacity ──────────────────────────────
+
+/// Raise the open-loop rate until `slo` breaks, then bisect to the
+/// highest rate that holds: the run's maximum sustainable throughput.
+pub async fn start_capacity(
+    State(state): State<Arc<AppState>>,
+    Json(mut search): Json<CapacitySearch>,
+) -> Result<Json<BenchmarkStatus>, AppError> {
+    if state.load_running.load(Ordering::SeqCst) {
+        return Err(AppError::AlreadyRunning);
+    }
+    let mut problems = Violations::new();
+    search.check(&mut problems);
+    search.step.target_rps = Some(search.start_rps);
+    let step = benchmark::check(&mut search.step);
+    problems.nest("step", "step", step);
+    problems.into_result()?;
+    search.step.op_timeout_ms.get_or_insert(state.op_timeout_ms);
+
+    let mut limits = Vec::new();
+    if let Some(p99_ms) = search.slo.p99_ms {
+        limits.push(format!("p99 < {p99_ms}ms"));
+    }
+    if let Some(error_pct) = search.slo.error_pct {
+        limits.push(format!("errors < {error_pct}%"));
+    }
+    let msg = format!(
+        "Started capacity search: {} workers from {} req/s, {}s per step, \
+         SLO {}",
+        search.step.concurrency,
+        search.start_rps,
+        search.step.duration_secs,
+        limits.join(", "),
+    );
+    // The number of steps depends on where the SLO breaks
+    let run_id = benchmark::begin_run(&state, &msg, None);
+    *state.capacity_report.lock() = Some(CapacityReport {
+        run_id: run_id.clone(),
+        state: ExperimentState::Running,
+        slo: search.slo.clone(),
+        steps: Vec::new(),
+        max_sustainable_rps: None,
+        limited_by: None,
+        ceiling_reached: false,
+    });
+
+    let handle = tokio::spawn(crate::load_generator::run_capacity_search(
+        state.load_running.clone(),
+        state.metrics.clone(),
+        state.events.clone(),
+        state.redis.clone(),
+        state.archive.clone(),
+        state.capacity_report.clone(),
+        search,
+        state.workers.clone(),
+    ));
+
+    let mut guard = state.load_handle.lock().await;
+    *guard = Some(handle);
+
+    Ok(Json(BenchmarkStatus::new(true, msg, Some(run_id))))
+}
+
+// ─── GET /api/experiments/capacity ───────────────────────────────
+
+/// The latest capacity search: every step and the rate found so far.
+pub async fn get_capacity(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<CapacityReport>, AppError> {
+    state
+        .capacity_report
+        .lock()
+        .clone()
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound("no capacity search yet".into()))
+}
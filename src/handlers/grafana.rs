@@ -0,0 +1,528 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::archive::ArchivedRun;
+use crate::metrics::collector::TimelinePoint;
+use crate::metrics::percentiles::PercentileSet;
+use crate::metrics::MetricsSnapshot;
+use crate::AppState;
+
+// ─── Grafana simple-JSON datasource contract ─────────────────────
+//
+// Point a "JSON API" / simple-json / Infinity datasource at
+// `http://<host>:3000/grafana` and these three endpoints do the rest.
+
+/// Timeline series — one datapoint per timeline window.
+const TIMELINE_TARGETS: &[&str] = &[
+    "timeline.avg_total_us",
+    "timeline.avg_redis_us",
+    "timeline.avg_rust_us",
+    "timeline.p50_total_us",
+    "timeline.p95_total_us",
+    "timeline.p99_total_us",
+    "timeline.count",
+    "timeline.requests_per_sec",
+    "timeline.reads_per_sec",
+    "timeline.writes_per_sec",
+    "timeline.read_errors",
+    "timeline.write_errors",
+    "timeline.active_workers",
+    "timeline.queue_depth",
+    "timeline.payload_bytes",
+    "timeline.client_omem_bytes",
+    "timeline.expired_per_sec",
+    "timeline.timeouts",
+];
+
+/// Layers that can be queried as `<layer>.<stat>`.
+const LAYERS: &[&str] = &[
+    "redis_read",
+    "redis_write",
+    "rust_overhead",
+    "framework_overhead",
+    "e2e",
+    "e2e_corrected",
+    "e2e_last_10s",
+    "e2e_last_60s",
+    "redis_last_10s",
+    "redis_last_60s",
+    "local_cache_hit",
+    "local_cache_miss",
+    "pipeline_batch",
+    "pipeline_per_command",
+    "mget_per_key",
+    "mset_per_key",
+    "scan_iteration",
+    "pool_wait",
+    "connect",
+    "auth",
+    "pubsub",
+    "stream_delivery",
+    "queue_enqueue",
+    "queue_dequeue",
+    "queue_wait",
+];
+const STATS: &[&str] = &[
+    "min", "max", "mean", "stddev", "jitter", "p50", "p95", "p99", "p999",
+];
+
+/// Scalar counters — returned as a single datapoint at "now".
+const SCALAR_TARGETS: &[&str] = &[
+    "requests_per_sec",
+    "avg_requests_per_sec",
+    "total_requests",
+    "total_errors",
+    "total_timeouts",
+    "stream_pending",
+    "queue_depth",
+    "read_bytes_per_sec",
+    "write_bytes_per_sec",
+    "bytes_in_per_sec",
+    "bytes_out_per_sec",
+    "client_omem_bytes",
+    "expired_per_sec",
+    "keyspace_hit_pct",
+    "server_keyspace_hit_pct",
+    "jain_index",
+    "metrics_memory_bytes",
+];
+
+/// Table target: one row per layer, one column per stat.
+const PERCENTILE_TABLE: &str = "percentiles";
+
+/// Stored-run series — one datapoint per archived run, at the time it
+/// was archived, so a dashboard can chart runs against each other.
+const RUN_TARGETS: &[&str] = &[
+    "runs.requests_per_sec",
+    "runs.error_rate",
+    "runs.duration_secs",
+    "runs.mean",
+    "runs.p50",
+    "runs.p95",
+    "runs.p99",
+    "runs.p999",
+];
+
+/// Table target: one row per archived run in the range.
+const RUN_TABLE: &str = "runs";
+
+// ─── Request types ───────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct QueryRequest {
+    #[serde(default)]
+    pub range: Option<TimeRange>,
+    #[serde(default)]
+    pub targets: Vec<QueryTarget>,
+    #[serde(default, rename = "maxDataPoints")]
+    pub max_data_points: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TimeRange {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QueryTarget {
+    pub target: String,
+    /// "timeserie" (default) or "table"
+    #[serde(default, rename = "type")]
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnnotationRequest {
+    #[serde(default)]
+    pub range: Option<TimeRange>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Annotation {
+    pub time: i64,
+    pub title: String,
+    pub text: String,
+    pub tags: Vec<String>,
+}
+
+// ─── GET /grafana ────────────────────────────────────────────────
+
+/// Datasource "Save & test" probe.
+pub async fn health() -> &'static str {
+    "ok"
+}
+
+// ─── POST /grafana/search ────────────────────────────────────────
+
+pub async fn search() -> Json<Vec<String>> {
+    let mut targets: Vec<String> =
+        TIMELINE_TARGETS.iter().map(|t| t.to_string()).collect();
+    for layer in LAYERS {
+        for stat in STATS {
+            targets.push(format!("{layer}.{stat}"));
+        }
+    }
+    targets.extend(SCALAR_TARGETS.iter().map(|t| t.to_string()));
+    targets.extend(RUN_TARGETS.iter().map(|t| t.to_string()));
+    targets.push(PERCENTILE_TABLE.into());
+    targets.push(RUN_TABLE.into());
+    Json(targets)
+}
+
+// ─── POST /grafana/query ─────────────────────────────────────────
+
+pub async fn query(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<QueryRequest>,
+) -> Json<Vec<Value>> {
+    let snap = state.metrics.snapshot();
+    let (from, to) = parse_range(req.range.as_ref());
+    let now = chrono::Utc::now().timestamp_millis();
+    // Only read the archive when a panel asks for stored runs
+    let wants_runs = req
+        .targets
+        .iter()
+        .any(|t| t.target == RUN_TABLE || t.target.starts_with("runs."));
+    let runs = match wants_runs {
+        true => stored_runs(&state, from, to),
+        false => Vec::new(),
+    };
+
+    let results = req
+        .targets
+        .iter()
+        .filter_map(|t| {
+            if t.target == RUN_TABLE {
+                return Some(run_table(&runs));
+            }
+            if t.target == PERCENTILE_TABLE || t.kind.as_deref() == Some("table")
+            {
+                return Some(percentile_table(&snap));
+            }
+            if let Some(field) = t.target.strip_prefix("runs.") {
+                return Some(json!({
+                    "target": t.target,
+                    "datapoints": run_series(&runs, field)?
+                        .into_iter()
+                        .map(|(v, ts)| json!([v, ts]))
+                        .collect::<Vec<_>>(),
+                }));
+            }
+            let datapoints = if let Some(field) =
+                t.target.strip_prefix("timeline.")
+            {
+                timeline_series(&snap, field, from, to, req.max_data_points)?
+            } else {
+                vec![(scalar(&snap, &t.target)?, now)]
+            };
+            Some(json!({
+                "target": t.target,
+                "run_id": snap.run_id,
+                "datapoints": datapoints
+                    .into_iter()
+                    .map(|(v, ts)| json!([v, ts]))
+                    .collect::<Vec<_>>(),
+            }))
+        })
+        .collect();
+
+    Json(results)
+}
+
+// ─── POST /grafana/annotations ───────────────────────────────────
+
+/// Run events (start, stop, failover, config changes, user annotations)
+/// as annotations.
+pub async fn annotations(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<AnnotationRequest>,
+) -> Json<Vec<Annotation>> {
+    let (from, to) = parse_range(req.range.as_ref());
+
+    let annotations = state
+        .events
+        .all()
+        .into_iter()
+        .flat_map(|(run_id, events)| {
+            events.into_iter().filter_map(move |e| {
+                let time = chrono::DateTime::parse_from_rfc3339(&e.at)
+                    .ok()?
+                    .timestamp_millis();
+                let kind = serde_json::to_value(e.kind).ok()?;
+                let kind = kind.as_str()?.to_owned();
+                let mut tags = vec![kind.clone(), format!("run:{run_id}")];
+                if let Some(change) = &e.change {
+                    tags.push(format!("key:{}", change.key));
+                }
+                Some(Annotation {
+                    time,
+                    title: kind,
+                    text: e.message,
+                    tags,
+                })
+            })
+        })
+        .filter(|a| a.time >= from && a.time <= to)
+        .collect();
+
+    Json(annotations)
+}
+
+// ─── Helpers ─────────────────────────────────────────────────────
+
+/// Grafana sends RFC 3339 bounds; a missing range means "everything".
+fn parse_range(range: Option<&TimeRange>) -> (i64, i64) {
+    let parse = |s: &str| {
+        chrono::DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|d| d.timestamp_millis())
+    };
+    match range {
+        Some(r) => (
+            parse(&r.from).unwrap_or(i64::MIN),
+            parse(&r.to).unwrap_or(i64::MAX),
+        ),
+        None => (i64::MIN, i64::MAX),
+    }
+}
+
+fn timeline_series(
+    snap: &MetricsSnapshot,
+    field: &str,
+    from: i64,
+    to: i64,
+    max_points: Option<usize>,
+) -> Option<Vec<(f64, i64)>> {
+    let anchor = snap.started_at_ms.unwrap_or(0);
+    let pick: fn(&TimelinePoint) -> f64 = match field {
+        "avg_total_us" => |p| p.avg_total_us,
+        "avg_redis_us" => |p| p.avg_redis_us,
+        "avg_rust_us" => |p| p.avg_rust_us,
+        "p50_total_us" => |p| p.p50_total_us as f64,
+        "p95_total_us" => |p| p.p95_total_us as f64,
+        "p99_total_us" => |p| p.p99_total_us as f64,
+        "count" => |p| p.count as f64,
+        "requests_per_sec" => |p| p.requests_per_sec,
+        "reads_per_sec" => |p| p.reads_per_sec,
+        "writes_per_sec" => |p| p.writes_per_sec,
+        "read_errors" => |p| p.read_errors as f64,
+        "write_errors" => |p| p.write_errors as f64,
+        "active_workers" => |p| p.active_workers as f64,
+        "queue_depth" => |p| p.queue_depth as f64,
+        "payload_bytes" => |p| p.payload_bytes as f64,
+        "client_omem_bytes" => |p| p.client_omem_bytes as f64,
+        "expired_per_sec" => |p| p.expired_per_sec,
+        "timeouts" => |p| p.timeouts as f64,
+        _ => return None,
+    };
+
+    let points: Vec<(f64, i64)> = snap
+        .timeline
+        .iter()
+        .map(|p| (pick(p), anchor + p.timestamp_ms as i64))
+        .filter(|(_, ts)| *ts >= from && *ts <= to)
+        .collect();
+
+    // Honour maxDataPoints with a simple stride
+    let stride = match max_points {
+        Some(max) if max > 0 && points.len() > max => points.len().div_ceil(max),
+        _ => 1,
+    };
+    Some(points.into_iter().step_by(stride).collect())
+}
+
+fn scalar(snap: &MetricsSnapshot, target: &str) -> Option<f64> {
+    match target {
+        "requests_per_sec" => return Some(snap.requests_per_sec),
+        "avg_requests_per_sec" => {
+            return Some(snap.avg_requests_per_sec)
+        }
+        "total_requests" => return Some(snap.total_requests as f64),
+        "total_errors" => return Some(snap.total_errors as f64),
+        "total_timeouts" => return Some(snap.total_timeouts as f64),
+        "stream_pending" => return Some(snap.stream_pending as f64),
+        "queue_depth" => return Some(snap.queue_depth as f64),
+        "read_bytes_per_sec" => return Some(snap.read_bytes_per_sec),
+        "write_bytes_per_sec" => return Some(snap.write_bytes_per_sec),
+        "bytes_in_per_sec" => return Some(snap.bytes_in_per_sec),
+        "bytes_out_per_sec" => return Some(snap.bytes_out_per_sec),
+        "client_omem_bytes" => return Some(snap.clients.omem_bytes as f64),
+        "expired_per_sec" => return Some(snap.expiry.expired_per_sec),
+        "keyspace_hit_pct" => return Some(snap.keyspace.hit_pct),
+        "server_keyspace_hit_pct" => {
+            return Some(snap.keyspace.server_hit_pct)
+        }
+        "jain_index" => return snap.fairness.as_ref().map(|f| f.jain_index),
+        "metrics_memory_bytes" => {
+            return Some(snap.metrics_memory_bytes as f64)
+        }
+        _ => {}
+    }
+    let (layer, stat) = target.split_once('.')?;
+    stat_of(layer_of(snap, layer)?, stat)
+}
+
+fn layer_of<'a>(
+    snap: &'a MetricsSnapshot,
+    layer: &str,
+) -> Option<&'a PercentileSet> {
+    match layer {
+        "redis_read" => Some(&snap.redis_read),
+        "redis_write" => Some(&snap.redis_write),
+        "rust_overhead" => Some(&snap.rust_overhead),
+        "framework_overhead" => Some(&snap.framework_overhead),
+        "e2e" => Some(&snap.e2e),
+        "e2e_corrected" => Some(&snap.e2e_corrected),
+        "e2e_last_10s" => snap.rolling.get("last_10s").map(|r| &r.e2e),
+        "e2e_last_60s" => snap.rolling.get("last_60s").map(|r| &r.e2e),
+        "redis_last_10s" => snap.rolling.get("last_10s").map(|r| &r.redis),
+        "redis_last_60s" => snap.rolling.get("last_60s").map(|r| &r.redis),
+        "local_cache_hit" => Some(&snap.local_cache_hit),
+        "local_cache_miss" => Some(&snap.local_cache_miss),
+        "pipeline_batch" => Some(&snap.pipeline_batch),
+        "pipeline_per_command" => Some(&snap.pipeline_per_command),
+        "mget_per_key" => Some(&snap.mget_per_key),
+        "mset_per_key" => Some(&snap.mset_per_key),
+        "scan_iteration" => Some(&snap.scan_iteration),
+        "pool_wait" => Some(&snap.pool_wait),
+        "connect" => Some(&snap.connect),
+        "auth" => Some(&snap.auth),
+        "pubsub" => Some(&snap.pubsub),
+        "stream_delivery" => Some(&snap.stream_delivery),
+        "queue_enqueue" => Some(&snap.queue_enqueue),
+        "queue_dequeue" => Some(&snap.queue_dequeue),
+        "queue_wait" => Some(&snap.queue_wait),
+        _ => None,
+    }
+}
+
+fn stat_of(ps: &PercentileSet, stat: &str) -> Option<f64> {
+    Some(match stat {
+        "min" => ps.min as f64,
+        "max" => ps.max as f64,
+        "mean" => ps.mean,
+        "stddev" => ps.stddev,
+        "jitter" => ps.jitter,
+        "p50" => ps.p50 as f64,
+        "p95" => ps.p95 as f64,
+        "p99" => ps.p99 as f64,
+        "p999" => ps.p999 as f64,
+        _ => return None,
+    })
+}
+
+fn percentile_table(snap: &MetricsSnapshot) -> Value {
+    let mut columns = vec![json!({ "text": "layer", "type": "string" })];
+    columns.extend(
+        STATS
+            .iter()
+            .map(|s| json!({ "text": format!("{s}_us"), "type": "number" })),
+    );
+
+    let rows: Vec<Value> = LAYERS
+        .iter()
+        .filter_map(|layer| {
+            let ps = layer_of(snap, layer)?;
+            let mut row = vec![json!(layer)];
+            row.extend(STATS.iter().map(|s| json!(stat_of(ps, s))));
+            Some(Value::Array(row))
+        })
+        .collect();
+
+    json!({
+        "type": "table",
+        "run_id": snap.run_id,
+        "columns": columns,
+        "rows": rows,
+    })
+}
+
+/// Archived runs whose archive time falls in the range, oldest first.
+fn stored_runs(
+    state: &AppState,
+    from: i64,
+    to: i64,
+) -> Vec<(i64, ArchivedRun)> {
+    let mut runs: Vec<(i64, ArchivedRun)> = state
+        .archive
+        .all()
+        .into_iter()
+        .filter_map(|run| {
+            let at = chrono::DateTime::parse_from_rfc3339(&run.archived_at)
+                .ok()?
+                .timestamp_millis();
+            Some((at, run))
+        })
+        .filter(|(at, _)| *at >= from && *at <= to)
+        .collect();
+    runs.sort_by_key(|(at, _)| *at);
+    runs
+}
+
+fn run_series(
+    runs: &[(i64, ArchivedRun)],
+    field: &str,
+) -> Option<Vec<(f64, i64)>> {
+    let pick: fn(&ArchivedRun) -> Option<f64> = match field {
+        "requests_per_sec" => |r| Some(r.requests_per_sec),
+        "error_rate" => |r| match r.total_requests {
+            0 => None,
+            n => Some(r.total_errors as f64 / n as f64),
+        },
+        "duration_secs" => |r| r.duration_secs,
+        "mean" => |r| r.latency.as_ref().map(|l| l.mean),
+        "p50" => |r| r.latency.as_ref().map(|l| l.p50 as f64),
+        "p95" => |r| r.latency.as_ref().map(|l| l.p95 as f64),
+        "p99" => |r| r.latency.as_ref().map(|l| l.p99 as f64),
+        "p999" => |r| r.latency.as_ref().map(|l| l.p999 as f64),
+        _ => return None,
+    };
+    // Runs without the value (imported ones with no overall latency)
+    // leave a gap rather than a zero
+    Some(
+        runs.iter()
+            .filter_map(|(at, run)| Some((pick(run)?, *at)))
+            .collect(),
+    )
+}
+
+fn run_table(runs: &[(i64, ArchivedRun)]) -> Value {
+    let columns = json!([
+        { "text": "time", "type": "time" },
+        { "text": "run_id", "type": "string" },
+        { "text": "label", "type": "string" },
+        { "text": "source", "type": "string" },
+        { "text": "requests_per_sec", "type": "number" },
+        { "text": "total_errors", "type": "number" },
+        { "text": "p99_us", "type": "number" },
+        { "text": "slo", "type": "string" },
+    ]);
+    let rows: Vec<Value> = runs
+        .iter()
+        .map(|(at, run)| {
+            let slo = run.slo.as_ref().map(|s| match s.passed {
+                true => "pass",
+                false => "fail",
+            });
+            json!([
+                at,
+                run.run_id,
+                run.label,
+                run.source.name(),
+                run.requests_per_sec,
+                run.total_errors,
+                run.latency.as_ref().map(|l| l.p99),
+                slo,
+            ])
+        })
+        .collect();
+
+    json!({
+        "type": "table",
+        "columns": columns,
+        "rows": rows,
+    })
+}
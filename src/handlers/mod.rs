@@ -1,7 +1,17 @@
 pub mod benchmark;
+pub mod connection;
+pub mod experiments;
+pub mod grafana;
+pub mod probe;
 pub mod products;
+pub mod runs;
+pub mod scenario;
+pub mod seed;
+pub mod selftest;
 pub mod sessions;
+pub mod trends;
 pub mod users;
+pub mod workload;
 
 use axum::{
     http::StatusCode,
@@ -9,6 +19,13 @@ use axum::{
     Json,
 };
 use serde::Serialize;
+use std::future::Future;
+use std::time::Instant;
+
+use crate::metrics::{CacheOutcome, Sample, SampleError};
+use crate::redis_client::{op_timeout, timed};
+use crate::validation::Violation;
+use crate::AppState;
 
 // ─── Shared response envelope ────────────────────────────────────
 
@@ -20,6 +37,17 @@ pub struct TimedResponse<T: Serialize> {
     pub timing: RequestTiming,
 }
 
+/// Serializes as JSON and attaches the timing as a response extension,
+/// so the timing middleware can tell handler time from framework time.
+impl<T: Serialize> IntoResponse for TimedResponse<T> {
+    fn into_response(self) -> Response {
+        let timing = self.timing;
+        let mut response = Json(self).into_response();
+        response.extensions_mut().insert(timing);
+        response
+    }
+}
+
 /// Microsecond-precision breakdown of where wall-clock time was spent.
 #[derive(Debug, Clone, Copy, Serialize)]
 pub struct RequestTiming {
@@ -37,23 +65,49 @@ pub struct RequestTiming {
 pub enum AppError {
     NotFound(String),
     Redis(String),
+    /// A Redis call outlived the per-op timeout
+    Timeout(String),
     BadRequest(String),
+    /// A config with one or more invalid fields; the body lists them all
+    Invalid(Vec<Violation>),
     Internal(String),
     AlreadyRunning,
+    /// Request shed by a per-endpoint concurrency limit
+    Overloaded,
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        if let Self::Invalid(violations) = self {
+            let status = StatusCode::BAD_REQUEST;
+            let message: Vec<&str> =
+                violations.iter().map(|v| v.message.as_str()).collect();
+            let body = serde_json::json!({
+                "error":      message.join("; "),
+                "status":     status.as_u16(),
+                "violations": violations,
+            });
+            return (status, Json(body)).into_response();
+        }
+
         let (status, message) = match self {
             Self::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
             Self::Redis(msg) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis: {msg}"))
             }
+            Self::Timeout(msg) => {
+                (StatusCode::GATEWAY_TIMEOUT, format!("Redis: {msg}"))
+            }
             Self::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            Self::Invalid(_) => unreachable!("answered above"),
             Self::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
             Self::AlreadyRunning => {
                 (StatusCode::CONFLICT, "Benchmark already running".into())
             }
+            Self::Overloaded => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Endpoint concurrency limit reached, request shed".into(),
+            ),
         };
 
         let body = serde_json::json!({
@@ -63,4 +117,44 @@ impl IntoResponse for AppError {
 
         (status, Json(body)).into_response()
     }
-}
\ No newline at end of file
+}
+
+// ─── Redis calls ─────────────────────────────────────────────────
+
+/// Run one handler Redis call under the per-op timeout. A timeout is
+/// recorded as a failed sample for `endpoint` and answered with a 504;
+/// other failures stay a 500 and, as before, are not sampled.
+pub async fn redis_call<T>(
+    state: &AppState,
+    endpoint: &str,
+    op: &'static str,
+    key: &str,
+    is_read: bool,
+    call: impl Future<Output = redis::RedisResult<T>>,
+) -> Result<T, AppError> {
+    let started = Instant::now();
+    let result = timed(op_timeout(state.op_timeout_ms), call).await;
+    result.map_err(|e| {
+        if !e.is_timeout() {
+            return AppError::Redis(e.to_string());
+        }
+        let waited_us = started.elapsed().as_micros() as u64;
+        state.metrics.record(Sample {
+            endpoint: endpoint.into(),
+            op,
+            key: Some(key.to_owned()),
+            redis_us: waited_us,
+            rust_us: 0,
+            total_us: waited_us,
+            is_read,
+            success: false,
+            timed_out: true,
+            error: Some(SampleError::redis(&e)),
+            http_status: Some(504),
+            payload_bytes: 0,
+            cache: CacheOutcome::Bypass,
+            schedule_lag_us: 0,
+        });
+        AppError::Timeout(e.to_string())
+    })
+}
@@ -0,0 +1,15 @@
+use axum::{extract::State, Json};
+use std::sync::Arc;
+
+use crate::metrics::probe::ProbeSnapshot;
+use crate::AppState;
+
+// ─── GET /api/probe ──────────────────────────────────────────────
+
+/// Baseline Redis health from the background prober (long horizon,
+/// unaffected by benchmark resets).
+pub async fn get_probe(
+    State(state): State<Arc<AppState>>,
+) -> Json<ProbeSnapshot> {
+    Json(state.probe.snapshot())
+}
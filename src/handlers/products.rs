@@ -1,17 +1,14 @@
-use axum::{
-    extract::{Path, State},
-    Json,
-};
+use axum::extract::{Path, State};
 use redis::AsyncCommands;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Instant;
 
-use crate::metrics::Sample;
+use crate::metrics::{hash_bytes, CacheOutcome, Sample, SampleError};
 use crate::AppState;
 
-use super::{AppError, RequestTiming, TimedResponse};
+use super::{redis_call, AppError, RequestTiming, TimedResponse};
 
 // ─── Domain type ─────────────────────────────────────────────────
 
@@ -31,56 +28,114 @@ pub struct Product {
 pub async fn get_product(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
-) -> Result<Json<TimedResponse<Product>>, AppError> {
+) -> Result<TimedResponse<Product>, AppError> {
     let t0 = Instant::now();
 
     let key = format!("product:{id}");
 
+    // ── Handler cache ───────────────────────────────────────────
+    let cached = state.handler_cache.as_ref().map(|c| c.products.get(&id));
+    if let Some(Some(product)) = cached {
+        let total_us = t0.elapsed().as_micros() as u64;
+        state.metrics.record(Sample {
+            endpoint: "GET /api/products/:id".into(),
+            op: "HGETALL",
+            key: Some(key),
+            redis_us: 0,
+            rust_us: total_us,
+            total_us,
+            is_read: true,
+            success: true,
+            timed_out: false,
+            error: None,
+            http_status: Some(200),
+            payload_bytes: 0,
+            cache: CacheOutcome::Hit,
+            schedule_lag_us: 0,
+        });
+        return Ok(TimedResponse {
+            data: product,
+            timing: RequestTiming {
+                total_us,
+                redis_us: 0,
+                rust_overhead_us: total_us,
+            },
+        });
+    }
+    let cache = match cached {
+        Some(_) => CacheOutcome::Miss,
+        None => CacheOutcome::Bypass,
+    };
+
     // ── Redis READ ──────────────────────────────────────────────
     let t_redis = Instant::now();
-    let mut conn = state.redis.clone();
-    let map: HashMap<String, String> = conn
-        .hgetall(&key)
-        .await
-        .map_err(|e| AppError::Redis(e.to_string()))?;
+    let mut conn = state.redis.manager();
+    let map: HashMap<String, String> = redis_call(
+        &state,
+        "GET /api/products/:id",
+        "HGETALL",
+        &key,
+        true,
+        conn.hgetall(&key),
+    )
+    .await?;
     let redis_us = t_redis.elapsed().as_micros() as u64;
     // ────────────────────────────────────────────────────────────
 
     if map.is_empty() {
         state.metrics.record(Sample {
             endpoint: "GET /api/products/:id".into(),
+            op: "HGETALL",
+            key: Some(key),
             redis_us,
             rust_us: 0,
             total_us: t0.elapsed().as_micros() as u64,
             is_read: true,
             success: false,
+            timed_out: false,
+            error: Some(SampleError::not_found()),
+            http_status: Some(404),
+            payload_bytes: 0,
+            cache,
+            schedule_lag_us: 0,
         });
         return Err(AppError::NotFound(format!("product '{id}' not found")));
     }
 
     // Rust work: parse hash fields into typed struct
     let product = product_from_map(&map);
+    if let Some(c) = &state.handler_cache {
+        c.products.insert(id, product.clone());
+    }
 
     let total_us = t0.elapsed().as_micros() as u64;
     let rust_us = total_us.saturating_sub(redis_us);
 
     state.metrics.record(Sample {
         endpoint: "GET /api/products/:id".into(),
+        op: "HGETALL",
+        key: Some(key),
         redis_us,
         rust_us,
         total_us,
         is_read: true,
         success: true,
+        timed_out: false,
+        error: None,
+        http_status: Some(200),
+        payload_bytes: hash_bytes(&map),
+        cache,
+        schedule_lag_us: 0,
     });
 
-    Ok(Json(TimedResponse {
+    Ok(TimedResponse {
         data: product,
         timing: RequestTiming {
             total_us,
             redis_us,
             rust_overhead_us: rust_us,
         },
-    }))
+    })
 }
 
 // ─── Helpers ─────────────────────────────────────────────────────
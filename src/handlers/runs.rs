@@ -0,0 +1,326 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::archive::{self, ArchivedRun};
+use crate::archive::Baseline;
+use crate::compare::{self, RunComparison, Tolerance};
+use crate::validation::Violations;
+use crate::events::{EventKind, RunEvent};
+use crate::report::{self, Format, Numbers, SavedDetail};
+use crate::AppState;
+
+use super::trends::parse_window;
+use super::AppError;
+
+// ─── Request types ───────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct AnnotationRequest {
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BaselineRequest {
+    pub run_id: String,
+    #[serde(default)]
+    pub tolerance: Tolerance,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompareParams {
+    /// Baseline run id
+    pub a: String,
+    /// Run compared against it
+    pub b: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportParams {
+    /// `md` (the default) or `html`
+    pub format: Option<String>,
+    /// Number separators, e.g. `de` for `1.234,5`; `en` by default
+    pub locale: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SharedParams {
+    /// How far back to look, e.g. `7d` (the default), `24h`
+    pub window: Option<String>,
+    /// Most runs to return, newest first
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportParams {
+    /// `memtier` or `redis-benchmark`; guessed from the body if omitted
+    pub format: Option<String>,
+    /// Free-form name shown alongside the run, e.g. "memtier 16 threads"
+    pub label: Option<String>,
+}
+
+// ─── GET /api/runs ───────────────────────────────────────────────
+
+/// Archived runs, native and imported, oldest first.
+pub async fn list_runs(
+    State(state): State<Arc<AppState>>,
+) -> Json<Vec<ArchivedRun>> {
+    Json(state.archive.all())
+}
+
+// ─── GET /api/runs/shared ────────────────────────────────────────
+
+/// Runs any bench instance wrote to the shared history in Redis,
+/// newest first.
+pub async fn list_shared_runs(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SharedParams>,
+) -> Result<Json<Vec<ArchivedRun>>, AppError> {
+    let mut conn = state.archive.shared().ok_or_else(|| {
+        AppError::BadRequest(
+            "shared run history is off; start with --runs-in-redis".into(),
+        )
+    })?;
+    let window = params.window.as_deref().unwrap_or("7d");
+    let span = parse_window(window).ok_or_else(|| {
+        AppError::BadRequest(format!(
+            "bad window '{window}' (expected e.g. 7d, 24h, 90m, 30s)"
+        ))
+    })?;
+    let limit = params.limit.unwrap_or(100);
+    if !(1..=archive::MAX_SHARED_LIST).contains(&limit) {
+        return Err(AppError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            archive::MAX_SHARED_LIST
+        )));
+    }
+    let since = chrono::Utc::now() - span;
+    archive::shared_runs(&mut conn, since, limit)
+        .await
+        .map(Json)
+        .map_err(|e| AppError::Redis(e.to_string()))
+}
+
+// ─── GET /api/runs/:id/snapshot ──────────────────────────────────
+
+/// The final metrics snapshot saved with a native run, config and
+/// metadata included. Read from disk, so it outlives a restart.
+pub async fn get_run_snapshot(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let not_found =
+        || AppError::NotFound(format!("no saved snapshot for run '{id}'"));
+    let bytes = state.archive.read_saved(&id).await.ok_or_else(not_found)?;
+    let mut saved: serde_json::Value = serde_json::from_slice(&bytes)
+        .map_err(|e| AppError::Internal(format!("saved run '{id}': {e}")))?;
+    match saved.get_mut("snapshot") {
+        Some(snapshot) => Ok(Json(snapshot.take())),
+        None => Err(not_found()),
+    }
+}
+
+// ─── GET /api/runs/:id/report ────────────────────────────────────
+
+/// A human-readable report of one run, as Markdown or a standalone
+/// HTML page. Per-layer latency, the distribution and the config come
+/// from the run's saved snapshot when there is one.
+pub async fn get_run_report(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(params): Query<ReportParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let (format, content_type) = match params.format.as_deref() {
+        None | Some("md") => {
+            (Format::Markdown, "text/markdown; charset=utf-8")
+        }
+        Some("html") => (Format::Html, "text/html; charset=utf-8"),
+        Some(other) => {
+            return Err(AppError::BadRequest(format!(
+                "unknown format '{other}' (expected md or html)"
+            )))
+        }
+    };
+    let numbers = match params.locale.as_deref() {
+        None => Numbers::EN,
+        Some(tag) => Numbers::for_locale(tag).ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "unknown locale '{tag}' (expected one of {})",
+                Numbers::locales().join(", ")
+            ))
+        })?,
+    };
+    let run = find_run(&state, &id).await?;
+    let detail = saved_detail(&state, &id).await;
+    let body = report::render(&run, detail.as_ref(), format, numbers);
+    Ok(([(header::CONTENT_TYPE, content_type)], body))
+}
+
+/// The `snapshot` half of a saved run file; `None` when the run was
+/// never saved or the file can't be read.
+async fn saved_detail(state: &AppState, id: &str) -> Option<SavedDetail> {
+    #[derive(Deserialize)]
+    struct Saved {
+        snapshot: Option<SavedDetail>,
+    }
+    let bytes = state.archive.read_saved(id).await?;
+    serde_json::from_slice::<Saved>(&bytes).ok()?.snapshot
+}
+
+// ─── GET /api/runs/:id ───────────────────────────────────────────
+
+pub async fn get_run(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<ArchivedRun>, AppError> {
+    find_run(&state, &id).await.map(Json)
+}
+
+/// From this instance's archive, else from the shared history.
+async fn find_run(state: &AppState, id: &str) -> Result<ArchivedRun, AppError> {
+    if let Some(run) = state.archive.get(id) {
+        return Ok(run);
+    }
+    let shared = match state.archive.shared() {
+        Some(mut conn) => archive::shared_run(&mut conn, id)
+            .await
+            .map_err(|e| AppError::Redis(e.to_string()))?,
+        None => None,
+    };
+    shared.ok_or_else(|| AppError::NotFound(format!("run '{id}' not found")))
+}
+
+// ─── /api/runs/baseline ──────────────────────────────────────────
+
+/// Judge every later benchmark and scenario run against this one.
+pub async fn set_baseline(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<BaselineRequest>,
+) -> Result<Json<Baseline>, AppError> {
+    let mut problems = Violations::new();
+    req.tolerance.check(&mut problems);
+    let mut nested = Violations::new();
+    nested.nest("tolerance", "tolerance", problems);
+    nested.into_result()?;
+    let run = find_run(&state, &req.run_id).await?;
+    state.archive.set_baseline(run, req.tolerance);
+    state
+        .archive
+        .baseline()
+        .map(Json)
+        .ok_or_else(|| AppError::Internal("baseline vanished".into()))
+}
+
+pub async fn get_baseline(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Baseline>, AppError> {
+    state
+        .archive
+        .baseline()
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound("no baseline is set".into()))
+}
+
+pub async fn clear_baseline(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Baseline>, AppError> {
+    state
+        .archive
+        .clear_baseline()
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound("no baseline is set".into()))
+}
+
+// ─── GET /api/runs/compare ───────────────────────────────────────
+
+/// Percentiles, throughput and error rate of run `b` against run `a`.
+pub async fn compare_runs(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<CompareParams>,
+) -> Result<Json<RunComparison>, AppError> {
+    let a = find_run(&state, &params.a).await?;
+    let b = find_run(&state, &params.b).await?;
+    Ok(Json(compare::compare(&a, &b)))
+}
+
+// ─── POST /api/runs/import ───────────────────────────────────────
+
+/// Archive a memtier_benchmark JSON or redis-benchmark CSV result so it
+/// sits next to native runs.
+pub async fn import_run(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ImportParams>,
+    body: String,
+) -> Result<Json<ArchivedRun>, AppError> {
+    let format = params.format.as_deref().unwrap_or(
+        // memtier only writes JSON, redis-benchmark only CSV
+        if body.trim_start().starts_with('{') {
+            "memtier"
+        } else {
+            "redis-benchmark"
+        },
+    );
+    let run = match format {
+        "memtier" => archive::import_memtier(&body, params.label),
+        "redis-benchmark" => {
+            archive::import_redis_benchmark(&body, params.label)
+        }
+        other => {
+            return Err(AppError::BadRequest(format!(
+                "unknown format '{other}' (expected memtier or redis-benchmark)"
+            )))
+        }
+    }
+    .map_err(|e| AppError::BadRequest(format!("{format}: {e}")))?;
+
+    state.archive.record(run.clone());
+    Ok(Json(run))
+}
+
+// ─── GET /api/runs/:id/events ────────────────────────────────────
+
+pub async fn get_run_events(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<RunEvent>>, AppError> {
+    if let Some(log) = state.events.events(&id) {
+        return Ok(Json(log));
+    }
+    saved_events(&state, &id)
+        .await
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("run '{id}' not found")))
+}
+
+/// The `events` half of a saved run file, for a run whose in-memory
+/// log was evicted or predates a restart.
+async fn saved_events(state: &AppState, id: &str) -> Option<Vec<RunEvent>> {
+    #[derive(Deserialize)]
+    struct Saved {
+        events: Option<Vec<RunEvent>>,
+    }
+    let bytes = state.archive.read_saved(id).await?;
+    serde_json::from_slice::<Saved>(&bytes).ok()?.events
+}
+
+// ─── POST /api/runs/:id/events ───────────────────────────────────
+
+/// Attach a free-form annotation (e.g. "switched replica to AOF") to a run.
+pub async fn annotate_run(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<AnnotationRequest>,
+) -> Result<Json<Vec<RunEvent>>, AppError> {
+    if req.message.trim().is_empty() {
+        return Err(AppError::BadRequest("message must not be empty".into()));
+    }
+    if !state.events.push_to(&id, EventKind::Annotation, req.message) {
+        return Err(AppError::NotFound(format!("run '{id}' not found")));
+    }
+    get_run_events(State(state), Path(id)).await
+}
@@ -0,0 +1,200 @@
+use axum::{
+    extract::State,
+    http::{header, HeaderMap},
+    Json,
+};
+use serde::Deserialize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use crate::analysis;
+use crate::archive::{ArchivedRun, StoredHistograms};
+use crate::commandstats;
+use crate::guard::HealthGuard;
+use crate::metrics::percentiles::PercentileMode;
+use crate::report::RunReport;
+use crate::slo::Slo;
+use crate::validation::Violations;
+use crate::AppState;
+
+use super::benchmark::{self, BenchmarkConfig, BenchmarkStatus};
+use super::AppError;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// Upper bound on stages so a typo can't queue hours of load
+const MAX_STAGES: usize = 20;
+
+// ─── Scenario format ─────────────────────────────────────────────
+
+/// A scripted sequence of benchmark stages run back to back,
+/// e.g. warmup → steady → spike → recovery.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scenario {
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Health guard spanning the whole scenario; per-stage guards are
+    /// ignored
+    #[serde(default)]
+    pub guard: Option<HealthGuard>,
+    /// Percentile method for the whole scenario; per-stage values are
+    /// ignored
+    #[serde(default)]
+    pub percentile_mode: PercentileMode,
+    /// Pass/fail thresholds for the scenario as a whole, checked once
+    /// it ends; per-stage values are ignored
+    #[serde(default)]
+    pub slo: Option<Slo>,
+    pub stages: Vec<Stage>,
+}
+
+/// One stage: a label plus any `/api/benchmark/start` settings.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Stage {
+    pub name: String,
+    #[serde(flatten)]
+    pub config: BenchmarkConfig,
+}
+
+// ─── POST /api/benchmark/scenario ────────────────────────────────
+
+/// Accepts the scenario as JSON, or as TOML when the request's
+/// `Content-Type` mentions `toml`.
+pub async fn run_scenario(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Json<BenchmarkStatus>, AppError> {
+    if state.load_running.load(Ordering::SeqCst) {
+        return Err(AppError::AlreadyRunning);
+    }
+
+    let mut scenario = parse(&headers, &body)?;
+    let mut problems = Violations::new();
+    if scenario.stages.is_empty() || scenario.stages.len() > MAX_STAGES {
+        problems.bounds(
+            "stages",
+            format!("a scenario needs between 1 and {MAX_STAGES} stages"),
+            1,
+            MAX_STAGES,
+        );
+    }
+    if let Some(guard) = &scenario.guard {
+        let mut inner = Violations::new();
+        guard.check(&mut inner);
+        problems.nest("guard", "guard", inner);
+    }
+    if let Some(slo) = &scenario.slo {
+        let mut inner = Violations::new();
+        slo.check(&mut inner);
+        problems.nest("slo", "slo", inner);
+    }
+    let last = scenario.stages.len().saturating_sub(1);
+    for (i, stage) in scenario.stages.iter_mut().enumerate() {
+        let label = format!("stage \"{}\"", stage.name);
+        let mut inner = benchmark::check(&mut stage.config);
+        // A stage that runs until stopped would starve the ones after it
+        if stage.config.duration_secs == 0 && i != last {
+            inner.push(
+                "duration_secs",
+                "only the last stage may run until stopped (0)",
+            );
+        }
+        problems.nest(&format!("stages[{i}]"), &label, inner);
+        stage.config.op_timeout_ms.get_or_insert(state.op_timeout_ms);
+    }
+    problems.into_result()?;
+
+    let total_secs: u64 =
+        scenario.stages.iter().map(|s| s.config.duration_secs).sum();
+    let open_ended =
+        scenario.stages.last().is_some_and(|s| s.config.duration_secs == 0);
+    let mut msg = format!(
+        "Started scenario: {} stages, {total_secs}s{}",
+        scenario.stages.len(),
+        match open_ended {
+            true => " then until stopped",
+            false => " total",
+        }
+    );
+    if let Some(name) = &scenario.name {
+        msg = format!("{msg} ({name})");
+    }
+    for stage in &scenario.stages {
+        msg.push_str(&format!(
+            "\n  • {}: {}",
+            stage.name,
+            benchmark::describe(&stage.config)
+        ));
+    }
+    let planned = (!open_ended).then_some(total_secs);
+    let run_id = benchmark::begin_run(&state, &msg, planned);
+    state.metrics.set_percentile_mode(scenario.percentile_mode);
+
+    let running = state.load_running.clone();
+    let metrics = state.metrics.clone();
+    let events = state.events.clone();
+    let redis = state.redis.clone();
+    let archive = state.archive.clone();
+    let workers = state.workers.clone();
+    let webhook = state.webhook.clone();
+    let notifier = state.notifier.clone();
+
+    let slo = scenario.slo;
+    let archived_id = run_id.clone();
+    let handle = tokio::spawn(async move {
+        let stats_before = commandstats::capture(&mut redis.manager()).await;
+        crate::load_generator::run_scenario(
+            running,
+            metrics.clone(),
+            events.clone(),
+            redis.clone(),
+            scenario,
+            workers,
+        )
+        .await;
+        // Keep the final numbers for /api/runs once the metrics move on
+        let snapshot = metrics.snapshot();
+        let log = events.events(&archived_id).unwrap_or_default();
+        let mut archived = ArchivedRun::from_snapshot(archived_id, &snapshot);
+        archived.analysis = analysis::analyze(&snapshot, &log);
+        archived.slo = slo.map(|slo| slo.evaluate(&archived));
+        archived.histograms = StoredHistograms::capture(&metrics);
+        archived.commandstats =
+            commandstats::since(&mut redis.manager(), &stats_before).await;
+        benchmark::judge(&archive, &events, &mut archived);
+        let report = RunReport::new(&archived, &snapshot, &log);
+        if let Some(webhook) = &webhook {
+            webhook.send(&report);
+        }
+        if let Some(notifier) = &notifier {
+            notifier.send(&report);
+        }
+        archive.set_report(report);
+        archived.events = log;
+        archive.record(archived);
+    });
+
+    // Stash the handle so `stop` can await clean shutdown
+    let mut guard = state.load_handle.lock().await;
+    *guard = Some(handle);
+
+    Ok(Json(BenchmarkStatus::new(true, msg, Some(run_id))))
+}
+
+// ─── Helpers ─────────────────────────────────────────────────────
+
+fn parse(headers: &HeaderMap, body: &str) -> Result<Scenario, AppError> {
+    let is_toml = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.contains("toml"));
+
+    if is_toml {
+        toml::from_str(body)
+            .map_err(|e| AppError::BadRequest(format!("invalid TOML: {e}")))
+    } else {
+        serde_json::from_str(body)
+            .map_err(|e| AppError::BadRequest(format!("invalid JSON: {e}")))
+    }
+}
@@ -0,0 +1,125 @@
+use axum::{extract::State, Json};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::mock_data::{self, DatasetManifest};
+use crate::AppState;
+
+use super::AppError;
+
+// ─── Response types ──────────────────────────────────────────────
+
+#[derive(Debug, Serialize)]
+pub struct RestoreReport {
+    /// `debug_reload` (the startup snapshot was loaded back) or
+    /// `reseed` (the seeded keys were written again)
+    pub method: &'static str,
+    pub secs: f64,
+    /// Sample checksum after the restore
+    pub checksum: String,
+    /// Whether it equals the startup manifest's checksum
+    pub matches: bool,
+    /// Why the snapshot was not used, and what would allow it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+// ─── GET /api/seed/manifest ──────────────────────────────────────
+
+/// The dataset this server seeded at startup; every native run it
+/// archives records the same manifest.
+pub async fn get_manifest(
+    State(state): State<Arc<AppState>>,
+) -> Json<DatasetManifest> {
+    Json(state.dataset.clone())
+}
+
+// ─── POST /api/seed/restore ──────────────────────────────────────
+
+/// Put the seeded dataset back: reload the startup RDB snapshot with
+/// `DEBUG RELOAD NOSAVE` when there is one and the server allows it,
+/// otherwise reseed. Either way the sample checksum is verified.
+pub async fn restore(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<RestoreReport>, AppError> {
+    // Hold the run flag throughout, so no benchmark starts mid-reload
+    let _busy = Busy::claim(&state.load_running)?;
+    let start = Instant::now();
+    let mut conn = state.redis.manager();
+
+    let note = match &state.dataset.snapshot {
+        None => Some(
+            "no snapshot was taken at startup; start the server with \
+             --seed-snapshot to restore from one"
+                .to_string(),
+        ),
+        Some(snapshot) => {
+            let lastsave: i64 = redis::cmd("LASTSAVE")
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| AppError::Redis(format!("LASTSAVE: {e}")))?;
+            if lastsave != snapshot.lastsave {
+                Some(
+                    "the RDB file was rewritten after the startup snapshot, \
+                     so it no longer holds just the seeded data"
+                        .to_string(),
+                )
+            } else {
+                let reloaded: redis::RedisResult<String> =
+                    redis::cmd("DEBUG")
+                        .arg("RELOAD")
+                        .arg("NOSAVE")
+                        .query_async(&mut conn)
+                        .await;
+                reloaded.err().map(|e| {
+                    format!(
+                        "DEBUG RELOAD NOSAVE failed ({e}). Redis 7 disables \
+                         DEBUG by default: set enable-debug-command to \
+                         local or yes, or restart Redis to load the RDB. \
+                         Managed services do not allow DEBUG at all."
+                    )
+                })
+            }
+        }
+    };
+
+    if note.is_some() {
+        let connections = state.dataset.load.connections;
+        mock_data::seed(&state.redis.client(), &conn, connections, false)
+            .await
+            .map_err(|e| AppError::Redis(format!("reseed: {e}")))?;
+    }
+
+    let (checksum, _) = mock_data::sample_checksum(&mut conn)
+        .await
+        .map_err(|e| AppError::Redis(format!("checksum: {e}")))?;
+    Ok(Json(RestoreReport {
+        method: match note {
+            None => "debug_reload",
+            Some(_) => "reseed",
+        },
+        secs: start.elapsed().as_secs_f64(),
+        matches: checksum == state.dataset.checksum,
+        checksum,
+        note,
+    }))
+}
+
+/// `load_running`, set for as long as this is alive.
+struct Busy(Arc<AtomicBool>);
+
+impl Busy {
+    fn claim(flag: &Arc<AtomicBool>) -> Result<Self, AppError> {
+        flag.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .map_err(|_| AppError::AlreadyRunning)?;
+        Ok(Self(flag.clone()))
+    }
+}
+
+impl Drop for Busy {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,317 @@
+use axum::{
+    body::Body,
+    extract::State,
+    http::{Method, Request, StatusCode},
+    Json,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Instant;
+use tower::ServiceExt;
+
+use crate::metrics::percentiles::PercentileSet;
+use crate::metrics::MetricsSnapshot;
+use crate::AppState;
+
+use super::benchmark::{self, BenchmarkConfig};
+use super::AppError;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// Keys written and read back by the Redis check
+const PROBE_KEYS: u32 = 5;
+
+/// The short load run: small enough to be harmless on any target
+const LOAD_CONCURRENCY: u32 = 2;
+const LOAD_SECS: u64 = 2;
+
+// ─── Report types ────────────────────────────────────────────────
+
+#[derive(Debug, Serialize)]
+pub struct SelfTestReport {
+    pub passed: bool,
+    pub duration_ms: u64,
+    /// Run id of the load phase, for `/api/runs/:id/events`
+    pub run_id: String,
+    pub checks: Vec<Check>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub name: String,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl Check {
+    fn new(name: impl Into<String>, result: Result<(), String>) -> Self {
+        Self {
+            name: name.into(),
+            passed: result.is_ok(),
+            detail: result.err(),
+        }
+    }
+}
+
+// ─── POST /api/selftest ──────────────────────────────────────────
+
+/// Exercise the whole pipeline once: Redis round trips, a short load
+/// run, snapshot invariants, and every API endpoint. Answers 200 when
+/// every check passes, 500 otherwise.
+pub async fn run_selftest(
+    State(state): State<Arc<AppState>>,
+) -> Result<(StatusCode, Json<SelfTestReport>), AppError> {
+    if state.load_running.load(Ordering::SeqCst) {
+        return Err(AppError::AlreadyRunning);
+    }
+    let t0 = Instant::now();
+    let mut checks =
+        vec![Check::new("redis round trip", redis_check(&state).await)];
+
+    // ── Short load run (resets metrics like any other run) ──────
+    let mut config: BenchmarkConfig = serde_json::from_value(json!({
+        "concurrency": LOAD_CONCURRENCY,
+        "duration_secs": LOAD_SECS,
+    }))
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+    benchmark::validate(&mut config)?;
+    let run_id = benchmark::begin_run(
+        &state,
+        &format!("Started: self-test, {}", benchmark::describe(&config)),
+        Some(config.duration_secs),
+    );
+    crate::load_generator::run(
+        state.load_running.clone(),
+        state.metrics.clone(),
+        state.events.clone(),
+        state.redis.clone(),
+        config,
+        None,
+        state.workers.clone(),
+    )
+    .await;
+
+    let snap = state.metrics.snapshot();
+    checks.extend(snapshot_checks(&snap));
+    let next_id = state.metrics.snapshot().snapshot_id;
+    checks.push(Check::new(
+        "snapshot ids increase",
+        expect(next_id > snap.snapshot_id, || {
+            format!("{} then {next_id}", snap.snapshot_id)
+        }),
+    ));
+
+    checks.extend(endpoint_checks(&state, &run_id).await);
+
+    let passed = checks.iter().all(|c| c.passed);
+    let status = if passed {
+        StatusCode::OK
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+    Ok((
+        status,
+        Json(SelfTestReport {
+            passed,
+            duration_ms: t0.elapsed().as_millis() as u64,
+            run_id,
+            checks,
+        }),
+    ))
+}
+
+// ─── Checks ──────────────────────────────────────────────────────
+
+/// Write a handful of keys, read them back, delete them.
+async fn redis_check(state: &AppState) -> Result<(), String> {
+    let mut conn = state.redis.manager();
+    let keys: Vec<String> =
+        (0..PROBE_KEYS).map(|i| format!("selftest:{i}")).collect();
+
+    let mut pipe = redis::pipe();
+    for (i, key) in keys.iter().enumerate() {
+        pipe.cmd("SET").arg(key).arg(i).arg("EX").arg(60).ignore();
+    }
+    let _: () = pipe
+        .query_async(&mut conn)
+        .await
+        .map_err(|e| format!("SET: {e}"))?;
+
+    let values: Vec<Option<usize>> = redis::cmd("MGET")
+        .arg(&keys)
+        .query_async(&mut conn)
+        .await
+        .map_err(|e| format!("MGET: {e}"))?;
+    let _: Result<(), _> =
+        redis::cmd("DEL").arg(&keys).query_async(&mut conn).await;
+
+    let expected: Vec<Option<usize>> = (0..keys.len()).map(Some).collect();
+    expect(values == expected, || format!("read back {values:?}"))
+}
+
+fn snapshot_checks(snap: &MetricsSnapshot) -> Vec<Check> {
+    let timeline_count: u64 = snap.timeline.iter().map(|p| p.count).sum();
+    let mut checks = vec![
+        Check::new(
+            "load generated requests",
+            expect(snap.total_requests > 0, || "no requests".into()),
+        ),
+        Check::new(
+            "no errors during load",
+            expect(snap.total_errors == 0, || {
+                format!("{} errors", snap.total_errors)
+            }),
+        ),
+        Check::new(
+            "reads + writes = total",
+            expect(
+                snap.total_reads + snap.total_writes == snap.total_requests,
+                || {
+                    format!(
+                        "{} + {} ≠ {}",
+                        snap.total_reads, snap.total_writes, snap.total_requests
+                    )
+                },
+            ),
+        ),
+        Check::new(
+            "every request in e2e",
+            expect(snap.e2e.count == snap.total_requests, || {
+                format!("{} ≠ {}", snap.e2e.count, snap.total_requests)
+            }),
+        ),
+        Check::new(
+            "timeline covers every request",
+            expect(timeline_count == snap.total_requests, || {
+                format!("{timeline_count} ≠ {}", snap.total_requests)
+            }),
+        ),
+    ];
+    for (name, set) in [
+        ("redis_read", &snap.redis_read),
+        ("redis_write", &snap.redis_write),
+        ("rust_overhead", &snap.rust_overhead),
+        ("e2e", &snap.e2e),
+    ] {
+        checks.push(Check::new(
+            format!("{name} percentiles ordered"),
+            ordered(set),
+        ));
+    }
+    checks
+}
+
+fn ordered(set: &PercentileSet) -> Result<(), String> {
+    if set.count == 0 {
+        return Ok(());
+    }
+    let chain = [set.min, set.p50, set.p95, set.p99, set.p999, set.max];
+    expect(chain.windows(2).all(|w| w[0] <= w[1]), || {
+        format!("min..max = {chain:?}")
+    })
+}
+
+/// One request per API route through the real router and middleware.
+async fn endpoint_checks(state: &Arc<AppState>, run_id: &str) -> Vec<Check> {
+    let app = crate::server::create_router(state.clone(), None);
+    let mut checks = Vec::new();
+
+    let user = call(&app, Method::POST, "/api/users", Some(json!({
+        "name": "Self Test",
+        "email": "selftest@example.com",
+    })))
+    .await;
+    let user_id = created_id(&user);
+    checks.push(Check::new("POST /api/users", user.map(|_| ())));
+
+    let session = call(&app, Method::POST, "/api/sessions", Some(json!({
+        "user_id": "usr_00000001",
+        "ttl_secs": 60,
+    })))
+    .await;
+    let session_id = created_id(&session);
+    checks.push(Check::new("POST /api/sessions", session.map(|_| ())));
+
+    let mut gets = vec![
+        "/api/users/usr_00000001".to_owned(),
+        "/api/products/prod_0001".to_owned(),
+        "/api/benchmark/status".to_owned(),
+        "/api/connection".to_owned(),
+        "/api/probe".to_owned(),
+        "/api/metrics".to_owned(),
+        "/api/metrics/stream".to_owned(),
+        "/api/runs".to_owned(),
+        format!("/api/runs/{run_id}/events"),
+        "/grafana".to_owned(),
+    ];
+    if let Some(id) = session_id {
+        gets.push(format!("/api/sessions/{id}"));
+    }
+    for path in gets {
+        let result = call(&app, Method::GET, &path, None).await;
+        checks.push(Check::new(format!("GET {path}"), result.map(|_| ())));
+    }
+
+    // Don't leave the test user behind
+    if let Some(id) = user_id {
+        let mut conn = state.redis.manager();
+        let _: Result<(), _> = redis::cmd("DEL")
+            .arg(format!("user:{id}"))
+            .query_async(&mut conn)
+            .await;
+    }
+    checks
+}
+
+/// Send one request; Ok carries the JSON body (Null for streams).
+async fn call(
+    app: &axum::Router,
+    method: Method,
+    path: &str,
+    body: Option<Value>,
+) -> Result<Value, String> {
+    let request = Request::builder()
+        .method(method)
+        .uri(path)
+        .header("content-type", "application/json")
+        .body(match body {
+            Some(json) => Body::from(json.to_string()),
+            None => Body::empty(),
+        })
+        .map_err(|e| e.to_string())?;
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("status {status}"));
+    }
+    // SSE never ends — a 200 is all we can check
+    if path.ends_with("/stream") {
+        return Ok(Value::Null);
+    }
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .map_err(|e| e.to_string())?;
+    serde_json::from_slice(&bytes).or(Ok(Value::Null))
+}
+
+/// `data.id` of a create-endpoint response.
+fn created_id(response: &Result<Value, String>) -> Option<String> {
+    let body = response.as_ref().ok()?;
+    body.pointer("/data/id")?.as_str().map(str::to_owned)
+}
+
+fn expect(ok: bool, detail: impl FnOnce() -> String) -> Result<(), String> {
+    if ok {
+        Ok(())
+    } else {
+        Err(detail())
+    }
+}
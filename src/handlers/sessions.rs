@@ -7,10 +7,10 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::Instant;
 
-use crate::metrics::Sample;
+use crate::metrics::{write_bytes, CacheOutcome, Sample, SampleError};
 use crate::AppState;
 
-use super::{AppError, RequestTiming, TimedResponse};
+use super::{redis_call, AppError, RequestTiming, TimedResponse};
 
 // ─── Domain types ────────────────────────────────────────────────
 
@@ -45,18 +45,23 @@ fn default_ttl() -> u64 {
 pub async fn get_session(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
-) -> Result<Json<TimedResponse<Session>>, AppError> {
+) -> Result<TimedResponse<Session>, AppError> {
     let t0 = Instant::now();
 
     let key = format!("session:{id}");
 
     // ── Redis READ ──────────────────────────────────────────────
     let t_redis = Instant::now();
-    let mut conn = state.redis.clone();
-    let maybe_json: Option<String> = conn
-        .get(&key)
-        .await
-        .map_err(|e| AppError::Redis(e.to_string()))?;
+    let mut conn = state.redis.manager();
+    let maybe_json: Option<String> = redis_call(
+        &state,
+        "GET /api/sessions/:id",
+        "GET",
+        &key,
+        true,
+        conn.get(&key),
+    )
+    .await?;
     let redis_us = t_redis.elapsed().as_micros() as u64;
     // ────────────────────────────────────────────────────────────
 
@@ -65,11 +70,19 @@ pub async fn get_session(
         None => {
             state.metrics.record(Sample {
                 endpoint: "GET /api/sessions/:id".into(),
+                op: "GET",
+                key: Some(key),
                 redis_us,
                 rust_us: 0,
                 total_us: t0.elapsed().as_micros() as u64,
                 is_read: true,
                 success: false,
+                timed_out: false,
+                error: Some(SampleError::not_found()),
+                http_status: Some(404),
+                payload_bytes: 0,
+                cache: CacheOutcome::Bypass,
+                schedule_lag_us: 0,
             });
             return Err(AppError::NotFound(format!(
                 "session '{id}' not found or expired"
@@ -86,21 +99,29 @@ pub async fn get_session(
 
     state.metrics.record(Sample {
         endpoint: "GET /api/sessions/:id".into(),
+        op: "GET",
+        key: Some(key),
         redis_us,
         rust_us,
         total_us,
         is_read: true,
         success: true,
+        timed_out: false,
+        error: None,
+        http_status: Some(200),
+        payload_bytes: json_str.len() as u64,
+        cache: CacheOutcome::Bypass,
+        schedule_lag_us: 0,
     });
 
-    Ok(Json(TimedResponse {
+    Ok(TimedResponse {
         data: session,
         timing: RequestTiming {
             total_us,
             redis_us,
             rust_overhead_us: rust_us,
         },
-    }))
+    })
 }
 
 // ─── POST /api/sessions ──────────────────────────────────────────
@@ -108,7 +129,7 @@ pub async fn get_session(
 pub async fn create_session(
     State(state): State<Arc<AppState>>,
     Json(req): Json<CreateSessionRequest>,
-) -> Result<Json<TimedResponse<Session>>, AppError> {
+) -> Result<TimedResponse<Session>, AppError> {
     let t0 = Instant::now();
 
     // Rust work: build entity + serialize to JSON
@@ -127,16 +148,21 @@ pub async fn create_session(
 
     // ── Redis WRITE (with TTL) ──────────────────────────────────
     let t_redis = Instant::now();
-    let mut conn = state.redis.clone();
+    let mut conn = state.redis.manager();
     let mut cmd = redis::cmd("SET");
     cmd.arg(&key)
         .arg(&json_str)
         .arg("EX")
         .arg(session.ttl_secs);
-    let _: () = cmd
-        .query_async(&mut conn)
-        .await
-        .map_err(|e| AppError::Redis(e.to_string()))?;
+    let _: () = redis_call(
+        &state,
+        "POST /api/sessions",
+        "SET",
+        &key,
+        false,
+        cmd.query_async(&mut conn),
+    )
+    .await?;
     let redis_us = t_redis.elapsed().as_micros() as u64;
     // ────────────────────────────────────────────────────────────
 
@@ -145,19 +171,27 @@ pub async fn create_session(
 
     state.metrics.record(Sample {
         endpoint: "POST /api/sessions".into(),
+        op: "SET",
+        key: Some(key),
         redis_us,
         rust_us,
         total_us,
         is_read: false,
         success: true,
+        timed_out: false,
+        error: None,
+        http_status: Some(200),
+        payload_bytes: write_bytes(&cmd),
+        cache: CacheOutcome::Bypass,
+        schedule_lag_us: 0,
     });
 
-    Ok(Json(TimedResponse {
+    Ok(TimedResponse {
         data: session,
         timing: RequestTiming {
             total_us,
             redis_us,
             rust_overhead_us: rust_us,
         },
-    }))
+    })
 }
\ No newline at end of file
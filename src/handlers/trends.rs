@@ -0,0 +1,61 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::archive::TrendAggregate;
+use crate::metrics::percentiles::PercentileMode;
+use crate::AppState;
+
+use super::AppError;
+
+// ─── Request types ───────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct AggregateParams {
+    /// How far back to look, e.g. `7d`, `24h`, `90m`; default `7d`
+    pub window: Option<String>,
+    #[serde(default)]
+    pub percentile_mode: PercentileMode,
+}
+
+// ─── GET /api/trends/aggregate ───────────────────────────────────
+
+/// Latency over every native run archived in the window, computed from
+/// the merged histograms rather than by averaging each run's
+/// percentiles.
+pub async fn aggregate(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<AggregateParams>,
+) -> Result<Json<TrendAggregate>, AppError> {
+    let window = params.window.as_deref().unwrap_or("7d");
+    let span = parse_window(window).ok_or_else(|| {
+        AppError::BadRequest(format!(
+            "bad window '{window}' (expected e.g. 7d, 24h, 90m, 30s)"
+        ))
+    })?;
+    let since = chrono::Utc::now() - span;
+    state
+        .archive
+        .aggregate(since, params.percentile_mode)
+        .map(Json)
+        .map_err(AppError::Internal)
+}
+
+/// `<n><unit>` with unit `s`, `m`, `h` or `d`.
+pub(super) fn parse_window(window: &str) -> Option<chrono::Duration> {
+    let unit = window.chars().last()?;
+    let n: i64 = window[..window.len() - unit.len_utf8()].parse().ok()?;
+    if n <= 0 {
+        return None;
+    }
+    match unit {
+        's' => chrono::Duration::try_seconds(n),
+        'm' => chrono::Duration::try_minutes(n),
+        'h' => chrono::Duration::try_hours(n),
+        'd' => chrono::Duration::try_days(n),
+        _ => None,
+    }
+}
@@ -8,10 +8,12 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Instant;
 
-use crate::metrics::Sample;
+use crate::metrics::{
+    hash_bytes, write_bytes, CacheOutcome, Sample, SampleError,
+};
 use crate::AppState;
 
-use super::{AppError, RequestTiming, TimedResponse};
+use super::{redis_call, AppError, RequestTiming, TimedResponse};
 
 // ─── Domain types ────────────────────────────────────────────────
 
@@ -47,57 +49,115 @@ fn default_prefs() -> String {
 pub async fn get_user(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
-) -> Result<Json<TimedResponse<User>>, AppError> {
+) -> Result<TimedResponse<User>, AppError> {
     let t0 = Instant::now();
 
     // Rust work: build key
     let key = format!("user:{id}");
 
+    // ── Handler cache ───────────────────────────────────────────
+    let cached = state.handler_cache.as_ref().map(|c| c.users.get(&id));
+    if let Some(Some(user)) = cached {
+        let total_us = t0.elapsed().as_micros() as u64;
+        state.metrics.record(Sample {
+            endpoint: "GET /api/users/:id".into(),
+            op: "HGETALL",
+            key: Some(key),
+            redis_us: 0,
+            rust_us: total_us,
+            total_us,
+            is_read: true,
+            success: true,
+            timed_out: false,
+            error: None,
+            http_status: Some(200),
+            payload_bytes: 0,
+            cache: CacheOutcome::Hit,
+            schedule_lag_us: 0,
+        });
+        return Ok(TimedResponse {
+            data: user,
+            timing: RequestTiming {
+                total_us,
+                redis_us: 0,
+                rust_overhead_us: total_us,
+            },
+        });
+    }
+    let cache = match cached {
+        Some(_) => CacheOutcome::Miss,
+        None => CacheOutcome::Bypass,
+    };
+
     // ── Redis READ ──────────────────────────────────────────────
     let t_redis = Instant::now();
-    let mut conn = state.redis.clone();
-    let map: HashMap<String, String> = conn
-        .hgetall(&key)
-        .await
-        .map_err(|e| AppError::Redis(e.to_string()))?;
+    let mut conn = state.redis.manager();
+    let map: HashMap<String, String> = redis_call(
+        &state,
+        "GET /api/users/:id",
+        "HGETALL",
+        &key,
+        true,
+        conn.hgetall(&key),
+    )
+    .await?;
     let redis_us = t_redis.elapsed().as_micros() as u64;
     // ────────────────────────────────────────────────────────────
 
     if map.is_empty() {
         state.metrics.record(Sample {
             endpoint: "GET /api/users/:id".into(),
+            op: "HGETALL",
+            key: Some(key),
             redis_us,
             rust_us: 0,
             total_us: t0.elapsed().as_micros() as u64,
             is_read: true,
             success: false,
+            timed_out: false,
+            error: Some(SampleError::not_found()),
+            http_status: Some(404),
+            payload_bytes: 0,
+            cache,
+            schedule_lag_us: 0,
         });
         return Err(AppError::NotFound(format!("user '{id}' not found")));
     }
 
     // Rust work: deserialize hash → struct
     let user = user_from_map(&map);
+    if let Some(c) = &state.handler_cache {
+        c.users.insert(id, user.clone());
+    }
 
     let total_us = t0.elapsed().as_micros() as u64;
     let rust_us = total_us.saturating_sub(redis_us);
 
     state.metrics.record(Sample {
         endpoint: "GET /api/users/:id".into(),
+        op: "HGETALL",
+        key: Some(key),
         redis_us,
         rust_us,
         total_us,
         is_read: true,
         success: true,
+        timed_out: false,
+        error: None,
+        http_status: Some(200),
+        payload_bytes: hash_bytes(&map),
+        cache,
+        schedule_lag_us: 0,
     });
 
-    Ok(Json(TimedResponse {
+    Ok(TimedResponse {
         data: user,
         timing: RequestTiming {
             total_us,
             redis_us,
             rust_overhead_us: rust_us,
         },
-    }))
+    })
 }
 
 // ─── POST /api/users ─────────────────────────────────────────────
@@ -105,7 +165,7 @@ pub async fn get_user(
 pub async fn create_user(
     State(state): State<Arc<AppState>>,
     Json(req): Json<CreateUserRequest>,
-) -> Result<Json<TimedResponse<User>>, AppError> {
+) -> Result<TimedResponse<User>, AppError> {
     let t0 = Instant::now();
 
     // Rust work: build entity
@@ -122,7 +182,7 @@ pub async fn create_user(
 
     // ── Redis WRITE ─────────────────────────────────────────────
     let t_redis = Instant::now();
-    let mut conn = state.redis.clone();
+    let mut conn = state.redis.manager();
     let mut cmd = redis::cmd("HSET");
     cmd.arg(&key)
         .arg("id")
@@ -137,10 +197,15 @@ pub async fn create_user(
         .arg(&user.prefs)
         .arg("created_at")
         .arg(&user.created_at);
-    let _: () = cmd
-        .query_async(&mut conn)
-        .await
-        .map_err(|e| AppError::Redis(e.to_string()))?;
+    let _: () = redis_call(
+        &state,
+        "POST /api/users",
+        "HSET",
+        &key,
+        false,
+        cmd.query_async(&mut conn),
+    )
+    .await?;
     let redis_us = t_redis.elapsed().as_micros() as u64;
     // ────────────────────────────────────────────────────────────
 
@@ -149,21 +214,29 @@ pub async fn create_user(
 
     state.metrics.record(Sample {
         endpoint: "POST /api/users".into(),
+        op: "HSET",
+        key: Some(key),
         redis_us,
         rust_us,
         total_us,
         is_read: false,
         success: true,
+        timed_out: false,
+        error: None,
+        http_status: Some(200),
+        payload_bytes: write_bytes(&cmd),
+        cache: CacheOutcome::Bypass,
+        schedule_lag_us: 0,
     });
 
-    Ok(Json(TimedResponse {
+    Ok(TimedResponse {
         data: user,
         timing: RequestTiming {
             total_us,
             redis_us,
             rust_overhead_us: rust_us,
         },
-    }))
+    })
 }
 
 // ─── Helpers ─────────────────────────────────────────────────────
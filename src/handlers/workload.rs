@@ -0,0 +1,255 @@
+use axum::{http::header, response::IntoResponse, Json};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+use crate::mock_data;
+
+use super::benchmark::{self, BenchmarkConfig, Op};
+use super::AppError;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// Fields in a seeded user hash (id, name, email, role, prefs,
+/// created_at) — the record YCSB's `fieldcount` describes
+const USER_FIELD_COUNT: u32 = 6;
+
+/// Average bytes per user-hash field across the seeded dataset
+const USER_FIELD_BYTES: u32 = 21;
+
+/// Proportions are turned into integer mix weights at this scale
+const WEIGHT_SCALE: f64 = 1_000.0;
+
+// ─── YCSB operation classes ──────────────────────────────────────
+
+/// CoreWorkload operation classes and the ops they correspond to.
+/// Reads import as user-hash reads, updates as session overwrites
+/// (existing keys), inserts as new user hashes, and read-modify-writes
+/// as the EVALSHA script.
+const CLASSES: &[(&str, Op)] = &[
+    ("readproportion", Op::HgetallUser),
+    ("updateproportion", Op::Set),
+    ("insertproportion", Op::Hset),
+    ("readmodifywriteproportion", Op::Evalsha),
+];
+
+fn class_of(op: Op) -> Option<&'static str> {
+    match op {
+        Op::Get | Op::Hgetall | Op::HgetallUser | Op::HgetallProduct => {
+            Some("readproportion")
+        }
+        Op::Set => Some("updateproportion"),
+        Op::Hset => Some("insertproportion"),
+        Op::Evalsha | Op::Multi => Some("readmodifywriteproportion"),
+        Op::Mget
+        | Op::Mset
+        | Op::Publish
+        | Op::Xadd
+        | Op::Zadd
+        | Op::Zincrby
+        | Op::Zrange
+        | Op::Zrank
+        | Op::Lpush
+        | Op::Scan
+        | Op::SetChurn => None,
+    }
+}
+
+// ─── POST /api/workload/ycsb/export ──────────────────────────────
+
+/// Describe a benchmark config as a YCSB CoreWorkload property file.
+pub async fn export_ycsb(
+    Json(mut config): Json<BenchmarkConfig>,
+) -> Result<impl IntoResponse, AppError> {
+    let ramp = config.ramp;
+    benchmark::validate(&mut config)?;
+
+    let mut shares: BTreeMap<&str, u64> = BTreeMap::new();
+    let mut skipped = Vec::new();
+    for (op, weight) in config.op_weights() {
+        match class_of(op) {
+            Some(class) => *shares.entry(class).or_default() += weight as u64,
+            None if weight > 0 => skipped.push(op.command()),
+            None => {}
+        }
+    }
+    let total: u64 = shares.values().sum();
+    if total == 0 {
+        return Err(AppError::BadRequest(
+            "the mix has no operation YCSB can express".into(),
+        ));
+    }
+
+    let mut out = format!(
+        "# rust-redis-bench workload, exported {}\n",
+        chrono::Utc::now().to_rfc3339()
+    );
+    if let Some(m) = &config.metadata {
+        let labels = [
+            ("name", &m.name),
+            ("git_sha", &m.git_sha),
+            ("redis_version", &m.redis_version),
+            ("notes", &m.notes),
+        ];
+        for (key, value) in labels {
+            if let Some(value) = value {
+                // Keep multi-line notes inside one comment
+                let value = value.replace(['\r', '\n'], " ");
+                out.push_str(&format!("# {key}: {value}\n"));
+            }
+        }
+    }
+    if ramp.is_some() {
+        out.push_str("# ramp profile flattened: threadcount is the peak\n");
+    }
+    if !skipped.is_empty() {
+        out.push_str(&format!(
+            "# no YCSB equivalent, left out of the proportions: {}\n",
+            skipped.join(", ")
+        ));
+    }
+    out.push_str("workload=site.ycsb.workloads.CoreWorkload\n");
+    out.push_str(&format!("recordcount={}\n", mock_data::NUM_USERS));
+    out.push_str(&format!("fieldcount={USER_FIELD_COUNT}\n"));
+    out.push_str(&format!("fieldlength={USER_FIELD_BYTES}\n"));
+    out.push_str("readallfields=true\nwriteallfields=true\n");
+    out.push_str("requestdistribution=uniform\n");
+    for (class, _) in CLASSES {
+        let share = shares.get(class).copied().unwrap_or(0);
+        out.push_str(&format!(
+            "{class}={:.4}\n",
+            share as f64 / total as f64
+        ));
+    }
+    out.push_str("scanproportion=0\n");
+    out.push_str(&format!("threadcount={}\n", config.concurrency));
+    out.push_str(&format!("maxexecutiontime={}\n", config.duration_secs));
+    // 0 = unlimited; the run ends on maxexecutiontime
+    out.push_str("operationcount=0\n");
+    if let Some(rps) = config.target_rps {
+        out.push_str(&format!("target={}\n", rps.round() as u64));
+    }
+
+    Ok(([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], out))
+}
+
+// ─── POST /api/workload/ycsb/import ──────────────────────────────
+
+#[derive(Debug, Serialize)]
+pub struct YcsbImport {
+    /// Ready to POST to `/api/benchmark/start`
+    pub config: Value,
+    /// Properties that were ignored or approximated
+    pub notes: Vec<String>,
+}
+
+/// Translate a YCSB CoreWorkload property file into a benchmark config.
+pub async fn import_ycsb(body: String) -> Result<Json<YcsbImport>, AppError> {
+    let props = parse_properties(&body);
+    let number = |key: &str| -> Result<Option<f64>, AppError> {
+        props
+            .get(key)
+            .map(|v| {
+                v.parse::<f64>().map_err(|_| {
+                    AppError::BadRequest(format!("{key}: not a number"))
+                })
+            })
+            .transpose()
+    };
+    let mut notes = Vec::new();
+
+    if number("scanproportion")?.unwrap_or(0.0) > 0.0 {
+        return Err(AppError::BadRequest(
+            "scanproportion has no equivalent here".into(),
+        ));
+    }
+    match props.get("requestdistribution").map(String::as_str) {
+        None | Some("uniform") => {}
+        Some(other) => {
+            return Err(AppError::BadRequest(format!(
+                "requestdistribution={other} is not supported; keys are \
+                 chosen uniformly"
+            )))
+        }
+    }
+
+    let mut mix = BTreeMap::new();
+    for (class, op) in CLASSES {
+        // YCSB's CoreWorkload defaults to a 95/5 read/update split
+        let default = match *class {
+            "readproportion" => 0.95,
+            "updateproportion" => 0.05,
+            _ => 0.0,
+        };
+        let share = number(class)?.unwrap_or(default);
+        let weight = (share * WEIGHT_SCALE).round() as u32;
+        if weight > 0 {
+            mix.insert(*op, weight);
+        }
+    }
+    if mix.is_empty() {
+        return Err(AppError::BadRequest(
+            "all operation proportions are zero".into(),
+        ));
+    }
+
+    let mut config = json!({
+        "mix": mix,
+        "concurrency": number("threadcount")?.unwrap_or(1.0) as u64,
+    });
+    let target = number("target")?.filter(|t| *t > 0.0);
+    let duration = match (number("maxexecutiontime")?, target) {
+        (Some(secs), _) => Some(secs),
+        // A capped op count at a fixed rate implies a duration
+        (None, Some(rate)) => number("operationcount")?
+            .filter(|n| *n > 0.0)
+            .map(|n| (n / rate).ceil()),
+        (None, None) => None,
+    };
+    match duration {
+        Some(secs) => config["duration_secs"] = json!(secs as u64),
+        None => notes.push(
+            "no maxexecutiontime; using the default duration".into(),
+        ),
+    }
+    if let Some(rate) = target {
+        config["target_rps"] = json!(rate);
+    }
+
+    if let Some(count) = number("recordcount")? {
+        if count as usize != mock_data::NUM_USERS {
+            notes.push(format!(
+                "recordcount={count} ignored: the seeded dataset has {} users",
+                mock_data::NUM_USERS
+            ));
+        }
+    }
+    for key in ["fieldcount", "fieldlength"] {
+        if props.contains_key(key) {
+            notes.push(format!(
+                "{key} ignored: records are the seeded user hashes \
+                 ({USER_FIELD_COUNT} fields, ~{USER_FIELD_BYTES} bytes each)"
+            ));
+        }
+    }
+
+    // Fail here, not on /start, if the result isn't runnable
+    let mut check: BenchmarkConfig = serde_json::from_value(config.clone())
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    benchmark::validate(&mut check)?;
+
+    Ok(Json(YcsbImport { config, notes }))
+}
+
+/// Java `.properties` subset: `key=value` or `key: value`, `#`/`!`
+/// comments.
+fn parse_properties(body: &str) -> BTreeMap<String, String> {
+    body.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#') && !l.starts_with('!'))
+        .filter_map(|l| {
+            let (key, value) = l.split_once(['=', ':'])?;
+            Some((key.trim().to_owned(), value.trim().to_owned()))
+        })
+        .collect()
+}
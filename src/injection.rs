@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Artificial Rust-side work injected into every load-generator operation,
+/// outside the Redis timed section.  Used to prove the dashboard blames
+/// the Rust layer when the Rust layer is the slow one.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Injection {
+    /// Busy-spin this many microseconds per operation
+    #[serde(default)]
+    pub busy_us: u64,
+
+    /// Allocate and touch a buffer of this many bytes per operation
+    #[serde(default)]
+    pub alloc_bytes: usize,
+
+    /// Serialize + deserialize a JSON object with this many fields
+    #[serde(default)]
+    pub serde_fields: usize,
+}
+
+impl Injection {
+    /// True when at least one knob is non-zero.
+    pub fn is_active(&self) -> bool {
+        self.busy_us > 0 || self.alloc_bytes > 0 || self.serde_fields > 0
+    }
+
+    /// Burn CPU / memory according to the configured knobs.
+    pub fn apply(&self) {
+        if self.busy_us > 0 {
+            let until = Instant::now() + Duration::from_micros(self.busy_us);
+            while Instant::now() < until {
+                std::hint::spin_loop();
+            }
+        }
+
+        if self.alloc_bytes > 0 {
+            let mut buf = vec![0u8; self.alloc_bytes];
+            // Touch one byte per page so the allocation is really faulted in
+            for i in (0..buf.len()).step_by(4096) {
+                buf[i] = i as u8;
+            }
+            black_box(&buf);
+        }
+
+        if self.serde_fields > 0 {
+            let obj: serde_json::Map<String, serde_json::Value> = (0..self
+                .serde_fields)
+                .map(|i| (format!("field_{i}"), serde_json::json!(i)))
+                .collect();
+            let json = serde_json::to_string(&obj).unwrap_or_default();
+            let back: serde_json::Value =
+                serde_json::from_str(&json).unwrap_or_default();
+            black_box(back);
+        }
+    }
+}
+
+impl std::fmt::Display for Injection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "busy {}μs, alloc {}B, serde {} fields",
+            self.busy_us, self.alloc_bytes, self.serde_fields
+        )
+    }
+}
@@ -1,200 +1,2808 @@
+use rand::distributions::{Distribution, WeightedIndex};
 use rand::rngs::StdRng;
 use rand::Rng;
 use rand::SeedableRng;
 use redis::aio::ConnectionManager;
-use redis::AsyncCommands;
+use redis::{AsyncCommands, ErrorKind};
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU8, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tokio_stream::StreamExt;
 
-use crate::metrics::{MetricsCollector, Sample};
+use crate::analysis;
+use crate::archive::{ArchivedRun, RunArchive, StoredHistograms};
+use crate::capacity::{
+    self, Bisection, CapacityReport, CapacitySearch, CapacityStep,
+};
+use crate::client_cache::ClientCache;
+use crate::client_monitor::ClientMonitor;
+use crate::commandstats;
+use crate::conn_pool::{ConnectionPool, FreshConnector};
+use crate::events::{ChangeSource, ConfigChange, EventKind, EventLog, RunEvent};
+use crate::experiment::{
+    self, EvictionExperiment, EvictionReport, ExperimentState, PolicyResult,
+};
+use crate::expiry_monitor::ExpiryMonitor;
+use crate::guard::{self, HealthGuard};
+use crate::handlers::benchmark::{
+    read_write_weights, Arrival, BenchmarkConfig, ConnectionMode, Op, Ramp,
+    ValueSize,
+};
+use crate::handlers::scenario::Scenario;
+use crate::injection::Injection;
+use crate::metrics::{
+    command_key, hash_bytes, reply_bytes, reply_type, sample_error,
+    timed_out, write_bytes,
+    CacheOutcome, MetricsCollector, Sample, SampleError,
+};
+use crate::mock_data::LEADERBOARD_KEY;
+use crate::sweep::{Sweep, SweepPoint, SweepReport};
+use crate::redis_client::{op_timeout, timed, RedisTarget};
 
-// ─── Public entry point ──────────────────────────────────────────
+// ─── Configuration ───────────────────────────────────────────────
 
-/// Spawns `concurrency` Tokio tasks that hammer Redis until the
-/// deadline or the `running` flag is set to false.
+/// First backoff after a Redis error; doubles per consecutive failure
+const BACKOFF_BASE_MS: u64 = 5;
+
+/// Ceiling so a recovered server is picked up again within a second
+const BACKOFF_MAX_MS: u64 = 1_000;
+
+/// How often a worker waiting for its ramp slot checks for a stop request
+const RAMP_POLL_MS: u64 = 100;
+
+/// Used by `EVALSHA` ops unless the config supplies its own script.
+/// `MULTI` ops run the same two commands as a transaction.
+const DEFAULT_SCRIPT: &str = "\
+local name = redis.call('HGET', KEYS[1], 'name')
+redis.call('HINCRBY', KEYS[1], 'visits', ARGV[1])
+return name";
+
+/// Session ids written by SET and read by GET are drawn from this range
+/// so reads have a realistic chance of finding a live key
+const SESSION_KEYSPACE: u32 = 10_000;
+
+/// `CLIENT SETNAME` prefix for `named_workers` connections
+const WORKER_NAME_PREFIX: &str = "bench-worker-";
+
+/// Stand-in length for `duration_secs: 0` (until stopped); a year is
+/// never reached and keeps `Instant` arithmetic far from overflow
+const UNTIL_STOPPED: Duration = Duration::from_secs(365 * 24 * 3600);
+
+// ─── Public entry points ─────────────────────────────────────────
+
+/// Spawns `config.concurrency` Tokio tasks that hammer Redis until the
+/// deadline or the `running` flag is set to false. With a ramp profile
+/// each task joins and retires at its own offset instead.
+#[allow(clippy::too_many_arguments)]
 pub async fn run(
     running: Arc<AtomicBool>,
     metrics: Arc<MetricsCollector>,
-    redis: ConnectionManager,
-    concurrency: u32,
-    duration_secs: u64,
-    read_pct: u8,
+    events: Arc<EventLog>,
+    redis: Arc<RedisTarget>,
+    config: BenchmarkConfig,
+    client_cache: Option<ClientCache>,
+    workers: Arc<WorkerControl>,
+) {
+    let guard = start_guard(config.guard, &running, &metrics, &events, &redis);
+    run_stage(
+        &running,
+        &metrics,
+        &events,
+        &redis,
+        config,
+        client_cache,
+        &workers,
+    )
+    .await;
+    finish(&running, &events, "duration elapsed", guard);
+}
+
+/// Runs each stage of `scenario` back to back, marking every boundary
+/// on the timeline and in the run's event log.
+pub async fn run_scenario(
+    running: Arc<AtomicBool>,
+    metrics: Arc<MetricsCollector>,
+    events: Arc<EventLog>,
+    redis: Arc<RedisTarget>,
+    scenario: Scenario,
+    workers: Arc<WorkerControl>,
+) {
+    let total = scenario.stages.len();
+    let guard =
+        start_guard(scenario.guard, &running, &metrics, &events, &redis);
+
+    let mut previous: Option<BenchmarkConfig> = None;
+    for (i, stage) in scenario.stages.into_iter().enumerate() {
+        if !running.load(Ordering::SeqCst) {
+            break;
+        }
+        metrics.mark_stage(&stage.name);
+        events.push(
+            EventKind::Stage,
+            format!("stage {}/{total} \"{}\"", i + 1, stage.name),
+        );
+        if let Some(previous) = &previous {
+            for change in stage_changes(previous, &stage.config) {
+                annotate(&metrics, &events, change);
+            }
+        }
+        previous = Some(stage.config.clone());
+
+        // Tracking connections are per stage so each starts cold
+        let client_cache = if stage.config.client_cache {
+            match ClientCache::start(&redis.client(), events.clone()).await {
+                Ok(cache) => Some(cache),
+                Err(e) => {
+                    events.push(
+                        EventKind::Error,
+                        format!("CLIENT TRACKING: {e}"),
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        run_stage(
+            &running,
+            &metrics,
+            &events,
+            &redis,
+            stage.config,
+            client_cache,
+            &workers,
+        )
+        .await;
+    }
+
+    finish(&running, &events, "scenario complete", guard);
+}
+
+/// Settings that differ between two consecutive scenario stages. The
+/// scenario-wide `guard`, `percentile_mode` and `slo` are left out since
+/// stages can't change them.
+fn stage_changes(
+    previous: &BenchmarkConfig,
+    next: &BenchmarkConfig,
+) -> Vec<ConfigChange> {
+    let as_map = |config| match serde_json::to_value(config) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    let (old, new) = (as_map(previous), as_map(next));
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter(|key| {
+            !matches!(key.as_str(), "guard" | "percentile_mode" | "slo")
+        })
+        .filter(|key| old.get(*key) != new.get(*key))
+        .map(|key| {
+            let value = |map: &serde_json::Map<_, _>| {
+                map.get(key).cloned().unwrap_or_default()
+            };
+            ConfigChange::new(
+                ChangeSource::Stage,
+                key.as_str(),
+                value(&old),
+                value(&new),
+            )
+        })
+        .collect()
+}
+
+/// Put a setting change on the timeline and in the run's event log.
+fn annotate(
+    metrics: &MetricsCollector,
+    events: &EventLog,
+    change: ConfigChange,
+) {
+    metrics.mark_change(&change);
+    events.push_change(change);
+}
+
+// ─── Live worker count ───────────────────────────────────────────
+
+/// `read_pct` value meaning the stage draws from an explicit mix, or
+/// no stage is running
+const NO_READ_PCT: u8 = u8::MAX;
+
+const NO_READ_PCT_ERR: &str =
+    "the running stage uses an explicit mix, or is between stages";
+
+/// Worker count and read share of the running stage, moved mid-run by
+/// `POST /api/benchmark/adjust`.
+pub struct WorkerControl {
+    /// Workers with an id at or above this exit after their current
+    /// iteration
+    target: AtomicU32,
+    /// Off between stages and while a ramp runs
+    adjustable: AtomicBool,
+    /// Wakes the stage to spawn workers after a raise
+    changed: Notify,
+    /// Re-read by every worker each iteration
+    read_pct: AtomicU8,
+    /// Config of the running stage, as started
+    stage: parking_lot::Mutex<Option<BenchmarkConfig>>,
+}
+
+impl WorkerControl {
+    pub fn new() -> Self {
+        Self {
+            target: AtomicU32::new(u32::MAX),
+            adjustable: AtomicBool::new(false),
+            changed: Notify::new(),
+            read_pct: AtomicU8::new(NO_READ_PCT),
+            stage: parking_lot::Mutex::new(None),
+        }
+    }
+
+    /// Set the worker count for `concurrency`; returns the previous
+    /// one.
+    pub fn adjust(&self, concurrency: u32) -> Result<u32, String> {
+        if !self.adjustable.load(Ordering::SeqCst) {
+            return Err("the running stage uses a ramp profile, or is \
+                        between stages; try again shortly"
+                .into());
+        }
+        let previous = self.target.swap(concurrency, Ordering::SeqCst);
+        self.changed.notify_one();
+        Ok(previous)
+    }
+
+    /// Set the running stage's `read_pct`; returns the previous one.
+    pub fn adjust_read_pct(&self, read_pct: u8) -> Result<u8, String> {
+        let previous = self.read_pct.load(Ordering::SeqCst);
+        if previous == NO_READ_PCT {
+            return Err(NO_READ_PCT_ERR.into());
+        }
+        self.read_pct
+            .compare_exchange(
+                previous,
+                read_pct,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            )
+            // Lost to a stage change; the new stage has its own split
+            .map_err(|_| "the stage changed; try again shortly".to_string())
+    }
+
+    /// Whether `adjust_read_pct` can succeed right now.
+    pub fn check_read_pct(&self) -> Result<(), String> {
+        match self.read_pct() {
+            Some(_) => Ok(()),
+            None => Err(NO_READ_PCT_ERR.into()),
+        }
+    }
+
+    /// The running stage's config with any `/adjust` changes applied;
+    /// `None` between stages.
+    pub fn current(&self) -> Option<BenchmarkConfig> {
+        let mut config = self.stage.lock().clone()?;
+        if self.adjustable.load(Ordering::SeqCst) {
+            config.concurrency = self.target();
+        }
+        if let Some(read_pct) = self.read_pct() {
+            config.read_pct = read_pct;
+        }
+        Some(config)
+    }
+
+    fn begin(&self, config: &BenchmarkConfig, adjustable: bool) {
+        // Only a stage without an explicit `mix` has a split to move
+        let read_pct = config.mix.is_none().then_some(config.read_pct);
+        self.target.store(config.concurrency, Ordering::SeqCst);
+        self.adjustable.store(adjustable, Ordering::SeqCst);
+        self.read_pct
+            .store(read_pct.unwrap_or(NO_READ_PCT), Ordering::SeqCst);
+        *self.stage.lock() = Some(config.clone());
+    }
+
+    fn end(&self) {
+        self.adjustable.store(false, Ordering::SeqCst);
+        self.target.store(u32::MAX, Ordering::SeqCst);
+        self.read_pct.store(NO_READ_PCT, Ordering::SeqCst);
+        *self.stage.lock() = None;
+    }
+
+    fn target(&self) -> u32 {
+        self.target.load(Ordering::Relaxed)
+    }
+
+    fn read_pct(&self) -> Option<u8> {
+        match self.read_pct.load(Ordering::Relaxed) {
+            NO_READ_PCT => None,
+            pct => Some(pct),
+        }
+    }
+}
+
+/// Runs `experiment.phase` once per eviction policy, switching
+/// `maxmemory-policy` between phases and restoring the original
+/// settings at the end. Metrics are reset per phase so each policy's
+/// percentiles stand alone; results land in `report` and the archive.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_eviction_experiment(
+    running: Arc<AtomicBool>,
+    metrics: Arc<MetricsCollector>,
+    events: Arc<EventLog>,
+    redis: Arc<RedisTarget>,
+    archive: Arc<RunArchive>,
+    report: Arc<parking_lot::Mutex<Option<EvictionReport>>>,
+    experiment: EvictionExperiment,
+    workers: Arc<WorkerControl>,
+) {
+    let (run_id, maxmemory_mb, original_policy, original_mb) = {
+        let report = report.lock();
+        let r = report.as_ref().expect("report created before start");
+        (
+            r.run_id.clone(),
+            r.maxmemory_mb,
+            r.original_policy.clone(),
+            r.original_maxmemory_mb,
+        )
+    };
+    let guard = start_guard(
+        experiment.phase.guard,
+        &running,
+        &metrics,
+        &events,
+        &redis,
+    );
+
+    let mut failure = match experiment::config_set(
+        &mut redis.manager(),
+        "maxmemory",
+        maxmemory_mb * 1024 * 1024,
+    )
+    .await
+    {
+        Ok(()) => {
+            annotate(
+                &metrics,
+                &events,
+                ConfigChange::new(
+                    ChangeSource::Redis,
+                    "maxmemory",
+                    original_mb * 1024 * 1024,
+                    maxmemory_mb * 1024 * 1024,
+                ),
+            );
+            None
+        }
+        Err(e) => Some(format!("CONFIG SET maxmemory: {e}")),
+    };
+
+    let total = experiment.policies.len();
+    let mut current_policy = original_policy.clone();
+    for (i, policy) in experiment.policies.iter().enumerate() {
+        if failure.is_some() || !running.load(Ordering::SeqCst) {
+            break;
+        }
+        if let Err(e) =
+            experiment::config_set(
+                &mut redis.manager(),
+                "maxmemory-policy",
+                policy,
+            )
+            .await
+        {
+            failure = Some(format!("CONFIG SET maxmemory-policy: {e}"));
+            break;
+        }
+
+        metrics.next_phase();
+        metrics.set_percentile_mode(experiment.phase.percentile_mode);
+        metrics.mark_stage(policy);
+        events.push(
+            EventKind::Stage,
+            format!("policy {}/{total} {policy}", i + 1),
+        );
+        // After the reset so the mark lands on this phase's timeline
+        annotate(
+            &metrics,
+            &events,
+            ConfigChange::new(
+                ChangeSource::Redis,
+                "maxmemory-policy",
+                std::mem::replace(&mut current_policy, policy.clone()),
+                policy.as_str(),
+            ),
+        );
+
+        let before = experiment::counters(&mut redis.manager()).await;
+        let stats_before = commandstats::capture(&mut redis.manager()).await;
+        run_stage(
+            &running,
+            &metrics,
+            &events,
+            &redis,
+            experiment.phase.clone(),
+            None,
+            &workers,
+        )
+        .await;
+        let after = experiment::counters(&mut redis.manager()).await;
+
+        let snap = metrics.snapshot();
+        let mut archived =
+            ArchivedRun::from_snapshot(format!("{run_id}-{policy}"), &snap);
+        archived.label = Some(format!("maxmemory-policy={policy}"));
+        let log = phase_events(&events, &run_id);
+        archived.analysis = analysis::analyze(&snap, &log);
+        archived.events = log;
+        archived.histograms = StoredHistograms::capture(&metrics);
+        archived.commandstats =
+            commandstats::since(&mut redis.manager(), &stats_before).await;
+        archive.record(archived);
+        if let Some(r) = report.lock().as_mut() {
+            r.phases.push(PolicyResult::new(policy, &snap, &before, &after));
+        }
+    }
+
+    // Put the server back the way we found it
+    let restore = [
+        ("maxmemory-policy", current_policy, original_policy),
+        (
+            "maxmemory",
+            (maxmemory_mb * 1024 * 1024).to_string(),
+            (original_mb * 1024 * 1024).to_string(),
+        ),
+    ];
+    for (name, set, value) in restore {
+        let set_back =
+            experiment::config_set(&mut redis.manager(), name, &value).await;
+        match set_back {
+            // Nothing to annotate if the experiment never changed it
+            Ok(()) if set != value => annotate(
+                &metrics,
+                &events,
+                ConfigChange::new(ChangeSource::Redis, name, set, value),
+            ),
+            Ok(()) => {}
+            Err(e) => events.push(
+                EventKind::Error,
+                format!("restoring {name} to {value}: {e}"),
+            ),
+        }
+    }
+
+    let (state, completed) = match &failure {
+        Some(error) => {
+            events.push(EventKind::Error, error.clone());
+            (ExperimentState::Failed, "experiment failed")
+        }
+        None if running.load(Ordering::SeqCst) => {
+            (ExperimentState::Complete, "experiment complete")
+        }
+        None => (ExperimentState::Stopped, "experiment complete"),
+    };
+    if let Some(r) = report.lock().as_mut() {
+        r.state = state;
+        r.error = failure;
+    }
+    finish(&running, &events, completed, guard);
+}
+
+/// Runs `sweep.step` once per level of `sweep.levels` on `sweep.axis`,
+/// resetting metrics between steps so each point on the curve stands
+/// alone. Points land in `report` and every step in the archive.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_sweep(
+    running: Arc<AtomicBool>,
+    metrics: Arc<MetricsCollector>,
+    events: Arc<EventLog>,
+    redis: Arc<RedisTarget>,
+    archive: Arc<RunArchive>,
+    report: Arc<parking_lot::Mutex<Option<SweepReport>>>,
+    sweep: Sweep,
+    workers: Arc<WorkerControl>,
+) {
+    let run_id = report
+        .lock()
+        .as_ref()
+        .map(|r| r.run_id.clone())
+        .expect("report created before start");
+    let guard =
+        start_guard(sweep.step.guard, &running, &metrics, &events, &redis);
+
+    let total = sweep.levels.len();
+    for (i, &level) in sweep.levels.iter().enumerate() {
+        if !running.load(Ordering::SeqCst) {
+            break;
+        }
+        let mut step = sweep.step.clone();
+        sweep.axis.apply(&mut step, level);
+        let name = sweep.axis.describe(level);
+        metrics.next_phase();
+        metrics.set_percentile_mode(step.percentile_mode);
+        metrics.mark_stage(&name);
+        events.push(
+            EventKind::Stage,
+            format!("step {}/{total}: {name}", i + 1),
+        );
+
+        let stats_before = commandstats::capture(&mut redis.manager()).await;
+        run_stage(
+            &running,
+            &metrics,
+            &events,
+            &redis,
+            step.clone(),
+            None,
+            &workers,
+        )
+        .await;
+
+        let snap = metrics.snapshot();
+        let id = format!("{run_id}-{}", sweep.axis.id_suffix(level));
+        let mut archived = ArchivedRun::from_snapshot(id, &snap);
+        archived.label = Some(format!("{}={level}", sweep.axis.field()));
+        let log = phase_events(&events, &run_id);
+        archived.analysis = analysis::analyze(&snap, &log);
+        archived.events = log;
+        archived.histograms = StoredHistograms::capture(&metrics);
+        archived.commandstats =
+            commandstats::since(&mut redis.manager(), &stats_before).await;
+        archive.record(archived);
+        if let Some(r) = report.lock().as_mut() {
+            r.push(SweepPoint::new(level, &step, &snap));
+        }
+    }
+
+    let (state, completed) = match running.load(Ordering::SeqCst) {
+        true => (ExperimentState::Complete, "sweep complete"),
+        false => (ExperimentState::Stopped, "sweep complete"),
+    };
+    if let Some(r) = report.lock().as_mut() {
+        r.state = state;
+    }
+    finish(&running, &events, completed, guard);
+}
+
+/// Capacity search: runs `search.step` open loop at rising target
+/// rates until one breaks the SLO, then bisects towards the highest
+/// rate that holds. Metrics are reset per step and every step is
+/// archived.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_capacity_search(
+    running: Arc<AtomicBool>,
+    metrics: Arc<MetricsCollector>,
+    events: Arc<EventLog>,
+    redis: Arc<RedisTarget>,
+    archive: Arc<RunArchive>,
+    report: Arc<parking_lot::Mutex<Option<CapacityReport>>>,
+    search: CapacitySearch,
+    workers: Arc<WorkerControl>,
+) {
+    let run_id = report
+        .lock()
+        .as_ref()
+        .map(|r| r.run_id.clone())
+        .expect("report created before start");
+    let guard =
+        start_guard(search.step.guard, &running, &metrics, &events, &redis);
+
+    let mut bisection = Bisection::new(&search);
+    let mut rate = Some(search.start_rps);
+    let mut n = 0;
+    while let Some(rps) = rate.filter(|_| n < capacity::MAX_STEPS) {
+        if !running.load(Ordering::SeqCst) {
+            break;
+        }
+        n += 1;
+        let mut step = search.step.clone();
+        step.target_rps = Some(rps);
+        metrics.next_phase();
+        metrics.set_percentile_mode(step.percentile_mode);
+        metrics.mark_stage(&format!("{rps:.0} req/s"));
+        events.push(EventKind::Stage, format!("step {n}: {rps:.0} req/s"));
+
+        let stats_before = commandstats::capture(&mut redis.manager()).await;
+        run_stage(
+            &running,
+            &metrics,
+            &events,
+            &redis,
+            step,
+            None,
+            &workers,
+        )
+        .await;
+
+        let snap = metrics.snapshot();
+        let result = CapacityStep::new(rps, &search.slo, &snap);
+        let mut archived =
+            ArchivedRun::from_snapshot(format!("{run_id}-r{rps:.0}"), &snap);
+        archived.label = Some(format!("target_rps={rps:.0}"));
+        let log = phase_events(&events, &run_id);
+        archived.analysis = analysis::analyze(&snap, &log);
+        archived.events = log;
+        archived.histograms = StoredHistograms::capture(&metrics);
+        archived.commandstats =
+            commandstats::since(&mut redis.manager(), &stats_before).await;
+        archive.record(archived);
+
+        // A step cut short by a stop says nothing about the rate
+        if !running.load(Ordering::SeqCst) {
+            break;
+        }
+        rate = bisection.next(rps, result.passed);
+        if let Some(r) = report.lock().as_mut() {
+            r.push(result);
+        }
+    }
+
+    let state = match running.load(Ordering::SeqCst) {
+        true => ExperimentState::Complete,
+        false => ExperimentState::Stopped,
+    };
+    if let Some(r) = report.lock().as_mut() {
+        r.state = state;
+        r.ceiling_reached = bisection.failed.is_none()
+            && bisection.passed == Some(search.max_rps);
+    }
+    let completed = match bisection.passed {
+        Some(rps) => format!(
+            "capacity search complete: {rps:.0} req/s sustainable"
+        ),
+        None => "capacity search complete: no rate met the SLO".into(),
+    };
+    finish(&running, &events, &completed, guard);
+}
+
+/// Spawn one stage's workers and wait for all of them to exit.
+#[allow(clippy::too_many_arguments)]
+async fn run_stage(
+    running: &Arc<AtomicBool>,
+    metrics: &Arc<MetricsCollector>,
+    events: &Arc<EventLog>,
+    target: &Arc<RedisTarget>,
+    config: BenchmarkConfig,
+    client_cache: Option<ClientCache>,
+    workers: &Arc<WorkerControl>,
+) {
+    // Read before the connections, so a failover while the stage
+    // starts is caught by the workers' first check
+    let generation = target.generation();
+    let redis = &target.manager();
+    let redis_client = &target.client();
+
+    // Pooled connections are opened before the clock starts; if that
+    // fails the stage runs on the shared connection instead
+    let pool = match config.connection_mode {
+        ConnectionMode::Pool => {
+            match ConnectionPool::open(redis_client, config.pool_size).await {
+                Ok(pool) => Some(pool),
+                Err(e) => {
+                    events.push(
+                        EventKind::Error,
+                        format!(
+                            "connection pool failed, using the shared \
+                             connection: {e}"
+                        ),
+                    );
+                    None
+                }
+            }
+        }
+        ConnectionMode::Shared
+        | ConnectionMode::Dedicated
+        | ConnectionMode::PerOp => None,
+    };
+    let connector = match config.connection_mode {
+        ConnectionMode::PerOp => match FreshConnector::new(redis_client) {
+            Ok(connector) => Some(Arc::new(connector)),
+            Err(e) => {
+                events.push(
+                    EventKind::Error,
+                    format!(
+                        "per-op connector failed, using the shared \
+                         connection: {e}"
+                    ),
+                );
+                None
+            }
+        },
+        _ => None,
+    };
+    let dedicated = config.connection_mode == ConnectionMode::Dedicated;
+
+    let started = Instant::now();
+    let (ops, weights): (Vec<Op>, Vec<u32>) =
+        config.op_weights().into_iter().unzip();
+    // Preload the script so the first EVALSHA doesn't pay for it
+    let script = if ops.contains(&Op::Evalsha) {
+        let script = LuaScript::new(
+            config.script.as_deref().unwrap_or(DEFAULT_SCRIPT),
+        );
+        if let Err(e) = script.load(&mut redis.clone()).await {
+            events.push(EventKind::Error, format!("SCRIPT LOAD: {e}"));
+        }
+        Some(Arc::new(script))
+    } else {
+        None
+    };
+
+    // Publishers stamp messages relative to `started`; one subscriber
+    // turns them into publish → receive latency
+    let subscriber = if ops.contains(&Op::Publish) {
+        match Subscriber::start(redis_client, metrics.clone(), started).await
+        {
+            Ok(subscriber) => Some(subscriber),
+            Err(e) => {
+                events.push(EventKind::Error, format!("SUBSCRIBE: {e}"));
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // Producers stamp entries the same way; the group's consumers
+    // record delivery latency and XACK
+    let consumers = if ops.contains(&Op::Xadd) {
+        match StreamConsumers::start(
+            redis,
+            redis_client,
+            metrics.clone(),
+            events.clone(),
+            started,
+            config.stream_consumers,
+        )
+        .await
+        {
+            Ok(consumers) => Some(consumers),
+            Err(e) => {
+                events.push(EventKind::Error, format!("XGROUP: {e}"));
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let queue = if ops.contains(&Op::Lpush) {
+        match QueueConsumers::start(
+            redis,
+            redis_client,
+            metrics.clone(),
+            events.clone(),
+            started,
+            config.queue_consumers,
+        )
+        .await
+        {
+            Ok(queue) => Some(queue),
+            Err(e) => {
+                events.push(EventKind::Error, format!("QUEUE: {e}"));
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // Watches our connections' output buffers for the whole stage
+    let monitor =
+        ClientMonitor::start(target.clone(), metrics.clone(), events.clone());
+    // Server-wide expiry rate, for lining TTL churn up with latency
+    let expiry = ExpiryMonitor::start(target.clone(), metrics.clone());
+    let ramp_marks = config.ramp.map(|ramp| {
+        tokio::spawn(mark_ramp_phases(
+            ramp,
+            started,
+            metrics.clone(),
+            events.clone(),
+        ))
+    });
+
+    let plan = WorkerPlan {
+        deadline: started + stage_length(&config),
+        started,
+        leaderboard_top_n: config.leaderboard_top_n,
+        batch_size: config.batch_size,
+        value_size: config.value_size,
+        scan_match: config.scan_match.clone(),
+        scan_count: config.scan_count,
+        churn_ttl_ms: config.churn_ttl_ms,
+        op_timeout: op_timeout(config.op_timeout_ms.unwrap_or(0)),
+        named_workers: config.named_workers,
+        script,
+        ops,
+        op_dist: WeightedIndex::new(weights)
+            .expect("validated mix has a positive weight"),
+        pipeline_depth: config.pipeline_depth,
+        inject: config.inject.filter(Injection::is_active),
+        client_cache: client_cache.map(Arc::new),
+        target_rps: config.target_rps,
+        arrival: config.arrival,
+        read_pct: config.mix.is_none().then_some(config.read_pct),
+        control: workers.clone(),
+        target: target.clone(),
+        generation,
+        connection_mode: config.connection_mode,
+        pool: pool.clone().map(|pool| {
+            Arc::new(PoolSlot::new(config.pool_size, generation, pool))
+        }),
+    };
+
+    // A ramp schedules every worker itself, so it can't be adjusted
+    let adjustable = config.ramp.is_none();
+    workers.begin(&config, adjustable);
+    metrics.set_config(config.clone());
+
+    let spawn = |worker_id: u32| {
+        let running = running.clone();
+        let metrics = metrics.clone();
+        let events = events.clone();
+        let conn = redis.clone();
+        let pool = pool.clone();
+        let connector = connector.clone();
+        let redis_client = redis_client.clone();
+        let mut plan = plan.clone();
+
+        let join_at = match config.ramp {
+            Some(ramp) => {
+                let (join, retire) = ramp.window(worker_id);
+                plan.deadline = started + retire;
+                started + join
+            }
+            None => started,
+        };
+
+        tokio::spawn(async move {
+            if !wait_for_slot(&running, join_at).await {
+                return;
+            }
+            let mut source = match (pool, connector) {
+                (Some(pool), _) => WorkerConn::Pooled(pool),
+                (_, Some(connector)) => WorkerConn::PerOp(connector),
+                (None, None) => WorkerConn::Fixed(conn),
+            };
+            if dedicated {
+                let named = plan.named_workers;
+                match own_connection(&redis_client, worker_id, named, &metrics)
+                    .await
+                {
+                    Ok(own) => source = WorkerConn::Fixed(own),
+                    Err(e) => {
+                        // Keep the run going on the shared connection
+                        events.push(
+                            EventKind::Error,
+                            format!(
+                                "worker {worker_id}: own connection failed, \
+                                 using the shared one: {e}"
+                            ),
+                        );
+                        plan.named_workers = false;
+                    }
+                }
+            }
+            metrics.worker_started();
+            worker(worker_id, running, metrics.clone(), events, source, plan)
+                .await;
+            metrics.worker_stopped();
+        })
+    };
+
+    // One slot per worker id. Lowering the target makes the workers
+    // above it exit; raising it refills the empty slots below it.
+    let mut slots: Vec<Option<JoinHandle<()>>> =
+        (0..config.concurrency).map(|id| Some(spawn(id))).collect();
+    let deadline = started + stage_length(&config);
+    while adjustable
+        && running.load(Ordering::Relaxed)
+        && Instant::now() < deadline
+    {
+        let target = workers.target() as usize;
+        if slots.len() < target {
+            slots.resize_with(target, || None);
+        }
+        for (id, slot) in slots.iter_mut().enumerate().take(target) {
+            if slot.as_ref().is_none_or(JoinHandle::is_finished) {
+                *slot = Some(spawn(id as u32));
+            }
+        }
+        tokio::select! {
+            _ = workers.changed.notified() => {}
+            _ = tokio::time::sleep(Duration::from_millis(RAMP_POLL_MS)) => {}
+        }
+    }
+
+    // Wait for all workers to finish
+    for h in slots.into_iter().flatten() {
+        let _ = h.await;
+    }
+    workers.end();
+
+    if let Some(subscriber) = subscriber {
+        subscriber.drain().await;
+    }
+    if let Some(consumers) = consumers {
+        consumers.drain(redis).await;
+    }
+    if let Some(queue) = queue {
+        queue.drain(redis).await;
+    }
+    monitor.stop();
+    expiry.stop();
+    if let Some(marks) = ramp_marks {
+        marks.abort();
+    }
+}
+
+/// Annotate each ramp phase boundary (up → hold → down) as it passes.
+/// Zero-length phases are skipped.
+async fn mark_ramp_phases(
+    ramp: Ramp,
+    started: Instant,
+    metrics: Arc<MetricsCollector>,
+    events: Arc<EventLog>,
+) {
+    let phases = [
+        ("up", ramp.up_secs),
+        ("hold", ramp.hold_secs),
+        ("down", ramp.down_secs),
+    ];
+    let mut phases = phases.into_iter().filter(|(_, secs)| *secs > 0);
+    let Some((mut current, mut ends_at)) = phases.next() else {
+        return;
+    };
+    for (next, secs) in phases {
+        let at = started + Duration::from_secs(ends_at);
+        tokio::time::sleep_until(at.into()).await;
+        annotate(
+            &metrics,
+            &events,
+            ConfigChange::new(ChangeSource::Ramp, "ramp_phase", current, next),
+        );
+        current = next;
+        ends_at += secs;
+    }
+}
+
+/// A health-guard watcher plus the slot it reports a breach into.
+type GuardTask = (JoinHandle<()>, Arc<parking_lot::Mutex<Option<String>>>);
+
+fn start_guard(
+    guard: Option<HealthGuard>,
+    running: &Arc<AtomicBool>,
+    metrics: &Arc<MetricsCollector>,
+    events: &Arc<EventLog>,
+    redis: &Arc<RedisTarget>,
+) -> Option<GuardTask> {
+    let guard = guard.filter(HealthGuard::is_active)?;
+    let reason = Arc::new(parking_lot::Mutex::new(None));
+    let handle = tokio::spawn(guard::watch(
+        guard,
+        running.clone(),
+        metrics.clone(),
+        events.clone(),
+        redis.clone(),
+        reason.clone(),
+    ));
+    Some((handle, reason))
+}
+
+/// The events of one experiment phase or sweep step. Metrics were reset
+/// when it began, at its `stage` event, so only the events from there
+/// on count, rebased to that moment.
+fn phase_events(events: &EventLog, run_id: &str) -> Vec<RunEvent> {
+    let log = events.events(run_id).unwrap_or_default();
+    let began = log
+        .iter()
+        .rev()
+        .find(|e| e.kind == EventKind::Stage)
+        .map_or(0, |e| e.timestamp_ms);
+    analysis::rebase(&log, began)
+}
+
+/// How long a stage's workers run; `duration_secs: 0` means until
+/// stopped.
+fn stage_length(config: &BenchmarkConfig) -> Duration {
+    match config.duration_secs {
+        0 => UNTIL_STOPPED,
+        secs => Duration::from_secs(secs),
+    }
+}
+
+/// Reason on the `stopped` event of a run ended by `/stop`.
+pub const STOP_REQUESTED: &str = "stopped by request";
+
+/// Record why the run ended and lower the running flag.
+fn finish(
+    running: &AtomicBool,
+    events: &EventLog,
+    completed: &str,
+    guard: Option<GuardTask>,
+) {
+    let aborted = guard.and_then(|(handle, reason)| {
+        handle.abort();
+        reason.lock().take()
+    });
+
+    // Still flagged as running → it ran to completion, not a stop request
+    let reason = if aborted.is_some() {
+        "aborted by health guard"
+    } else if running.load(Ordering::SeqCst) {
+        completed
+    } else {
+        STOP_REQUESTED
+    };
+    events.push(EventKind::Stopped, reason);
+
+    // Mark benchmark as finished
+    running.store(false, Ordering::SeqCst);
+}
+
+// ─── Worker loop ─────────────────────────────────────────────────
+
+/// Per-run knobs every worker needs, cloned into each task.
+#[derive(Clone)]
+struct WorkerPlan {
+    deadline: Instant,
+    /// Stage start; PUBLISH payloads are μs since this instant
+    started: Instant,
+    /// Entries per ZRANGE top-N read
+    leaderboard_top_n: u32,
+    /// Keys per MGET / MSET
+    batch_size: u32,
+    /// Written value sizes; `None` = the built-in payloads
+    value_size: Option<ValueSize>,
+    /// SCAN `MATCH` pattern and `COUNT` hint
+    scan_match: String,
+    scan_count: u32,
+    /// TTL on `SET:churn` keys
+    churn_ttl_ms: u64,
+    /// Per-call limit; `None` = wait forever
+    op_timeout: Option<Duration>,
+    /// Worker's dedicated connection is named `bench-worker-<id>`
+    named_workers: bool,
+    /// Ops to draw from, indexed by `op_dist`
+    ops: Vec<Op>,
+    op_dist: WeightedIndex<u32>,
+    /// Commands per round trip; 1 = no pipelining
+    pipeline_depth: u32,
+    /// Loaded when the mix contains EVALSHA
+    script: Option<Arc<LuaScript>>,
+    inject: Option<Injection>,
+    /// Shared local cache when client-side caching mode is on
+    client_cache: Option<Arc<ClientCache>>,
+    /// Open-loop rate for the whole stage; `None` = closed loop
+    target_rps: Option<f64>,
+    arrival: Arrival,
+    /// Split `ops` was built from; `None` with an explicit mix
+    read_pct: Option<u8>,
+    /// Workers at or above its target exit
+    control: Arc<WorkerControl>,
+    /// Master the worker's connection is reopened against after a
+    /// Sentinel failover, and the generation it currently points at
+    target: Arc<RedisTarget>,
+    generation: u64,
+    connection_mode: ConnectionMode,
+    /// The stage's pool, when it opened
+    pool: Option<Arc<PoolSlot>>,
+}
+
+impl WorkerPlan {
+    /// Rebuild the op table after `/adjust` moved `read_pct`.
+    fn follow_read_pct(&mut self) {
+        let Some(current) = self.read_pct else {
+            return;
+        };
+        let Some(live) = self.control.read_pct().filter(|p| *p != current)
+        else {
+            return;
+        };
+        let (ops, weights): (Vec<Op>, Vec<u32>) =
+            read_write_weights(live).into_iter().unzip();
+        self.ops = ops;
+        self.op_dist = WeightedIndex::new(weights)
+            .expect("a read/write split has a positive weight");
+        self.read_pct = Some(live);
+    }
+}
+
+/// Where a worker's commands go.
+// One per worker, so the size gap isn't worth a box
+#[allow(clippy::large_enum_variant)]
+enum WorkerConn {
+    /// The shared connection or the worker's dedicated one, held for
+    /// the whole stage
+    Fixed(ConnectionManager),
+    /// A pooled connection checked out for each iteration
+    Pooled(Arc<ConnectionPool>),
+    /// A new connection opened for each iteration and closed after it
+    PerOp(Arc<FreshConnector>),
+}
+
+async fn worker(
+    id: u32,
+    running: Arc<AtomicBool>,
+    metrics: Arc<MetricsCollector>,
+    events: Arc<EventLog>,
+    mut source: WorkerConn,
+    mut plan: WorkerPlan,
 ) {
-    let deadline = Instant::now() + Duration::from_secs(duration_secs);
+    let deadline = plan.deadline;
+
+    // Each worker gets its own deterministic RNG seeded uniquely.
+    let mut rng = StdRng::seed_from_u64(1000 + id as u64);
+    let mut consecutive_errors = 0u32;
+    // A dropped connection since the last success, reported on recovery
+    let mut disconnected = false;
+    let mut next_send = Instant::now();
+    let mut scan = ScanCursor::default();
+
+    while running.load(Ordering::Relaxed)
+        && Instant::now() < deadline
+        && id < plan.control.target()
+    {
+        plan.follow_read_pct();
+        if plan.target.generation() != plan.generation {
+            follow_failover(id, &mut source, &mut plan, &metrics, &events)
+                .await;
+        }
+
+        // ── Open loop: wait for the intended send time ──────────
+        // Latency is later corrected by how late we actually sent,
+        // so a stalled server can't hide queueing (coordinated omission).
+        let lag_us = match plan.target_rps {
+            Some(rps) => {
+                if next_send >= deadline {
+                    break;
+                }
+                tokio::time::sleep_until(next_send.into()).await;
+                let lag = next_send.elapsed().as_micros() as u64;
+                // An equal share of the rate among the workers wanted
+                // now, so `/adjust` keeps the total where it was
+                let rate = rps / plan.control.target().max(1) as f64;
+                next_send += next_gap(&mut rng, rate, plan.arrival);
+                lag
+            }
+            None => 0,
+        };
+
+        let t_iter = Instant::now();
+        let mut lease = None;
+        let mut fresh = None;
+        let picked = match &mut source {
+            WorkerConn::Fixed(conn) => Some((conn, lag_us)),
+            WorkerConn::Pooled(pool) => {
+                // Waiting for a connection is part of what the caller
+                // sees, so it counts like a late send
+                let t_wait = Instant::now();
+                let leased = lease.insert(pool.checkout().await);
+                let wait_us = t_wait.elapsed().as_micros() as u64;
+                metrics.record_pool_wait(wait_us);
+                Some((&mut **leased, lag_us + wait_us))
+            }
+            WorkerConn::PerOp(connector) => match timed(
+                plan.op_timeout,
+                connector.connect(),
+            )
+            .await
+            {
+                Ok(opened) => {
+                    metrics.record_connect(opened.connect_us, opened.auth_us);
+                    // Setup counts like a pool wait: the caller sees it
+                    let setup_us =
+                        opened.connect_us + opened.auth_us.unwrap_or(0);
+                    Some((fresh.insert(opened.conn), lag_us + setup_us))
+                }
+                Err(e) => {
+                    metrics.record_connect_error();
+                    events.push(EventKind::Error, format!("connect: {e}"));
+                    None
+                }
+            },
+        };
+        let outcome = match picked {
+            Some((conn, lag_us)) => {
+                dispatch(
+                    &mut rng, &metrics, &events, conn, &plan, &mut scan,
+                    lag_us,
+                )
+                .await
+            }
+            // Nothing was sent; back off as for a failed command
+            None => Outcome::Disconnected,
+        };
+
+        metrics.record_worker_op(
+            id,
+            t_iter.elapsed().as_micros() as u64,
+            plan.pipeline_depth.max(1),
+            outcome != Outcome::Ok,
+        );
+        // Hand a pooled connection back, or close a fresh one, before
+        // any backoff
+        drop(lease);
+        drop(fresh);
+
+        if outcome == Outcome::Ok {
+            // One message for every worker, so a blip that drops them
+            // all coalesces in the event log
+            if std::mem::take(&mut disconnected) {
+                events.push(
+                    EventKind::Reconnect,
+                    "worker connection re-established",
+                );
+            }
+            consecutive_errors = 0;
+            continue;
+        }
+
+        // ── Back off instead of spinning on a dead server ───────
+        disconnected |= outcome == Outcome::Disconnected;
+        consecutive_errors = consecutive_errors.saturating_add(1);
+        let delay = backoff_delay(&mut rng, consecutive_errors)
+            .min(deadline.saturating_duration_since(Instant::now()));
+        tokio::time::sleep(delay).await;
+        metrics.record_backoff(delay.as_micros() as u64);
+
+        // The connection manager may have reconnected, which drops
+        // the name; a failure here is retried after the next error
+        if let (true, WorkerConn::Fixed(conn)) =
+            (plan.named_workers, &mut source)
+        {
+            let _ = name_connection(conn, id, &metrics).await;
+        }
+    }
+}
+
+/// A stage's connection pool, reopened once per failover and shared by
+/// every pooled worker.
+struct PoolSlot {
+    size: u32,
+    current: tokio::sync::Mutex<(u64, Arc<ConnectionPool>)>,
+}
+
+impl PoolSlot {
+    fn new(size: u32, generation: u64, pool: Arc<ConnectionPool>) -> Self {
+        Self {
+            size,
+            current: tokio::sync::Mutex::new((generation, pool)),
+        }
+    }
+
+    /// The pool for `generation`; the first worker to ask opens it.
+    async fn get(
+        &self,
+        generation: u64,
+        client: &redis::Client,
+    ) -> redis::RedisResult<Arc<ConnectionPool>> {
+        let mut current = self.current.lock().await;
+        if current.0 != generation {
+            let pool = ConnectionPool::open(client, self.size).await?;
+            *current = (generation, pool);
+        }
+        Ok(current.1.clone())
+    }
+}
+
+/// Sentinel promoted a new master: reopen `source` against it the way
+/// `run_stage` opened it. Anything that fails carries on over the
+/// shared connection, as it does at the start of a stage.
+async fn follow_failover(
+    id: u32,
+    source: &mut WorkerConn,
+    plan: &mut WorkerPlan,
+    metrics: &MetricsCollector,
+    events: &EventLog,
+) {
+    plan.generation = plan.target.generation();
+    let client = plan.target.client();
+    let reopened = match (plan.connection_mode, &plan.pool) {
+        (ConnectionMode::Dedicated, _) => {
+            own_connection(&client, id, plan.named_workers, metrics)
+                .await
+                .map(WorkerConn::Fixed)
+        }
+        (ConnectionMode::Pool, Some(pool)) => pool
+            .get(plan.generation, &client)
+            .await
+            .map(WorkerConn::Pooled),
+        (ConnectionMode::PerOp, _) => FreshConnector::new(&client)
+            .map(|connector| WorkerConn::PerOp(Arc::new(connector))),
+        _ => Ok(WorkerConn::Fixed(plan.target.manager())),
+    };
+    if reopened.is_ok() {
+        events.push(
+            EventKind::Reconnect,
+            "worker connection reopened against the promoted master",
+        );
+    }
+    *source = reopened.unwrap_or_else(|e| {
+        events.push(
+            EventKind::Error,
+            format!(
+                "worker {id}: reconnect after failover failed, using the \
+                 shared connection: {e}"
+            ),
+        );
+        plan.named_workers = false;
+        WorkerConn::Fixed(plan.target.manager())
+    });
+}
+
+/// Run one iteration — an op, or a pipeline of them — on `conn`.
+/// Returns how it went.
+async fn dispatch(
+    rng: &mut StdRng,
+    metrics: &Arc<MetricsCollector>,
+    events: &EventLog,
+    conn: &mut ConnectionManager,
+    plan: &WorkerPlan,
+    scan: &mut ScanCursor,
+    lag_us: u64,
+) -> Outcome {
+    if plan.pipeline_depth > 1 {
+        return do_pipeline(rng, metrics, events, conn, plan, lag_us).await;
+    }
+    let op = plan.ops[plan.op_dist.sample(rng)];
+    match op {
+        Op::Get => do_get(rng, metrics, events, conn, plan, lag_us).await,
+        Op::Hgetall | Op::HgetallUser | Op::HgetallProduct => {
+            do_read(rng, metrics, events, conn, plan, op, lag_us).await
+        }
+        Op::Set | Op::Hset => {
+            do_write(rng, metrics, events, conn, plan, op, lag_us).await
+        }
+        Op::Evalsha | Op::Multi => {
+            do_atomic(rng, metrics, events, conn, plan, op, lag_us).await
+        }
+        Op::Mget
+        | Op::Mset
+        | Op::SetChurn
+        | Op::Zadd
+        | Op::Zincrby
+        | Op::Zrange
+        | Op::Zrank => {
+            do_command(rng, metrics, events, conn, plan, op, lag_us).await
+        }
+        Op::Publish | Op::Xadd | Op::Lpush => {
+            do_produce(metrics, events, conn, plan, op, lag_us).await
+        }
+        Op::Scan => do_scan(metrics, events, conn, plan, scan, lag_us).await,
+    }
+}
+
+/// Open a dedicated connection for one worker, named if asked.
+async fn own_connection(
+    client: &redis::Client,
+    worker_id: u32,
+    named: bool,
+    metrics: &MetricsCollector,
+) -> redis::RedisResult<ConnectionManager> {
+    let mut conn = ConnectionManager::new(client.clone()).await?;
+    if named {
+        name_connection(&mut conn, worker_id, metrics).await?;
+    }
+    Ok(conn)
+}
+
+/// `CLIENT SETNAME bench-worker-<id>`, then record the connection's
+/// `CLIENT ID` so server-side tools can be matched to the worker.
+async fn name_connection(
+    conn: &mut ConnectionManager,
+    worker_id: u32,
+    metrics: &MetricsCollector,
+) -> redis::RedisResult<()> {
+    let name = format!("{WORKER_NAME_PREFIX}{worker_id}");
+    redis::cmd("CLIENT")
+        .arg("SETNAME")
+        .arg(&name)
+        .query_async::<()>(conn)
+        .await?;
+    let client_id: u64 =
+        redis::cmd("CLIENT").arg("ID").query_async(conn).await?;
+    metrics.set_worker_client(worker_id, name, client_id);
+    Ok(())
+}
+
+/// Sleep until a ramped worker's join time. Returns false if the run was
+/// stopped first.
+async fn wait_for_slot(running: &AtomicBool, join_at: Instant) -> bool {
+    while Instant::now() < join_at {
+        if !running.load(Ordering::Relaxed) {
+            return false;
+        }
+        let step = Instant::now() + Duration::from_millis(RAMP_POLL_MS);
+        tokio::time::sleep_until(step.min(join_at).into()).await;
+    }
+    running.load(Ordering::Relaxed)
+}
+
+/// Gap until the next intended send for one worker at `rate` req/s.
+fn next_gap(rng: &mut StdRng, rate: f64, arrival: Arrival) -> Duration {
+    let secs = match arrival {
+        Arrival::Uniform => 1.0 / rate,
+        // Inverse-CDF sample of Exp(rate); 1 − U keeps ln() away from 0
+        Arrival::Poisson => -(1.0 - rng.gen::<f64>()).ln() / rate,
+    };
+    Duration::from_secs_f64(secs)
+}
+
+/// How one iteration's Redis call went, for backoff and reconnects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Ok,
+    /// Redis answered with an error, or the call timed out
+    Failed,
+    /// The connection dropped or could not be opened
+    Disconnected,
+}
+
+impl Outcome {
+    fn of<T>(result: &redis::RedisResult<T>) -> Self {
+        match result {
+            Ok(_) => Self::Ok,
+            Err(e)
+                if e.is_connection_dropped() || e.is_connection_refusal() =>
+            {
+                Self::Disconnected
+            }
+            Err(_) => Self::Failed,
+        }
+    }
+}
+
+/// Capped exponential backoff with ±50 % jitter so workers don't retry
+/// in lock-step.
+fn backoff_delay(rng: &mut StdRng, consecutive_errors: u32) -> Duration {
+    let exp = consecutive_errors.saturating_sub(1).min(16);
+    let ms = BACKOFF_BASE_MS.saturating_mul(1 << exp).min(BACKOFF_MAX_MS);
+    let jittered = rng.gen_range(ms / 2..=ms + ms / 2);
+    Duration::from_millis(jittered.min(BACKOFF_MAX_MS))
+}
+
+// ─── Read operations ─────────────────────────────────────────────
+
+/// GET a session blob. Returns how the Redis call went; an expired or
+/// never-written session is a miss, not a failure.
+async fn do_get(
+    rng: &mut StdRng,
+    metrics: &Arc<MetricsCollector>,
+    events: &EventLog,
+    conn: &mut ConnectionManager,
+    plan: &WorkerPlan,
+    lag_us: u64,
+) -> Outcome {
+    let t0 = Instant::now();
+    let key = session_key(rng);
+
+    let t_redis = Instant::now();
+    let result: redis::RedisResult<Option<String>> =
+        timed(plan.op_timeout, conn.get(&key)).await;
+    let redis_us = t_redis.elapsed().as_micros() as u64;
+
+    if let Some(inject) = &plan.inject {
+        inject.apply();
+    }
+
+    let total_us = t0.elapsed().as_micros() as u64;
+    let rust_us = total_us.saturating_sub(redis_us);
+
+    if let Err(e) = &result {
+        events.push(EventKind::Error, format!("GET: {e}"));
+    }
+
+    metrics.record(Sample {
+        endpoint: "GET /api/sessions/:id".into(),
+        op: Op::Get.command(),
+        key: Some(key),
+        redis_us,
+        rust_us,
+        total_us,
+        is_read: true,
+        success: matches!(result, Ok(Some(_))),
+        timed_out: timed_out(&result),
+        error: match &result {
+            Ok(Some(_)) => None,
+            Ok(None) => Some(SampleError::not_found()),
+            Err(e) => Some(SampleError::redis(e)),
+        },
+        http_status: None,
+        payload_bytes: result
+            .as_ref()
+            .map_or(0, |v| v.as_ref().map_or(0, |s| s.len() as u64)),
+        cache: CacheOutcome::Bypass,
+        schedule_lag_us: lag_us,
+    });
+    Outcome::of(&result)
+}
+
+/// HGETALL a user or product hash. Returns how the Redis call itself
+/// went (a missing key is not a failure for backoff purposes).
+async fn do_read(
+    rng: &mut StdRng,
+    metrics: &Arc<MetricsCollector>,
+    events: &EventLog,
+    conn: &mut ConnectionManager,
+    plan: &WorkerPlan,
+    op: Op,
+    lag_us: u64,
+) -> Outcome {
+    let t0 = Instant::now();
+
+    let (key, endpoint) = hash_read_target(rng, op);
+
+    // ── Local cache (client-side caching mode) ──────────────────
+    if let Some(cache) = &plan.client_cache {
+        if let Some(map) = cache.get(&key) {
+            if let Some(inject) = &plan.inject {
+                inject.apply();
+            }
+            let total_us = t0.elapsed().as_micros() as u64;
+            metrics.record(Sample {
+                endpoint: endpoint.into(),
+                op: op.command(),
+                key: Some(key),
+                redis_us: 0,
+                rust_us: total_us,
+                total_us,
+                is_read: true,
+                success: !map.is_empty(),
+                timed_out: false,
+                error: map.is_empty().then(SampleError::not_found),
+                http_status: None,
+                payload_bytes: 0,
+                cache: CacheOutcome::Hit,
+                schedule_lag_us: lag_us,
+            });
+            return Outcome::Ok;
+        }
+    }
+
+    // Claimed before the read, so an invalidation that lands while it is
+    // in flight keeps the stale reply out of the cache
+    let reserved = plan.client_cache.as_ref().and_then(|c| c.reserve(&key));
+
+    // ── Redis timed section ─────────────────────────────────────
+    let t_redis = Instant::now();
+    let reply: redis::RedisResult<redis::Value> =
+        match &plan.client_cache {
+            // Misses go through the tracked connection so the server
+            // sends us an invalidation when the key changes
+            Some(cache) => {
+                let mut tracked = cache.connection();
+                timed(plan.op_timeout, tracked.hgetall(&key)).await
+            }
+            None => timed(plan.op_timeout, conn.hgetall(&key)).await,
+        };
+    let redis_us = t_redis.elapsed().as_micros() as u64;
+    // ────────────────────────────────────────────────────────────
+
+    // An array of fields and values over RESP2, a map over RESP3
+    let result = reply
+        .and_then(|reply| decode::<HashMap<String, String>>(metrics, &reply));
+
+    if let Some(inject) = &plan.inject {
+        inject.apply();
+    }
+
+    let total_us = t0.elapsed().as_micros() as u64;
+    let rust_us = total_us.saturating_sub(redis_us);
+
+    let outcome = Outcome::of(&result);
+    let timed_out = timed_out(&result);
+    let (error, payload_bytes) = match result {
+        Ok(map) => {
+            let found = (
+                map.is_empty().then(SampleError::not_found),
+                hash_bytes(&map),
+            );
+            if let (Some(cache), Some(read)) = (&plan.client_cache, reserved)
+            {
+                cache.insert(&key, read, map);
+            }
+            found
+        }
+        Err(e) => {
+            events.push(EventKind::Error, format!("HGETALL: {e}"));
+            (Some(SampleError::redis(&e)), 0)
+        }
+    };
+
+    metrics.record(Sample {
+        endpoint: endpoint.into(),
+        op: op.command(),
+        key: Some(key),
+        redis_us,
+        rust_us,
+        total_us,
+        is_read: true,
+        success: error.is_none(),
+        timed_out,
+        error,
+        http_status: None,
+        payload_bytes,
+        cache: match plan.client_cache {
+            Some(_) => CacheOutcome::Miss,
+            None => CacheOutcome::Bypass,
+        },
+        schedule_lag_us: lag_us,
+    });
+
+    outcome
+}
+
+// ─── Write operation ─────────────────────────────────────────────
+
+/// SET a session or HSET a user. Returns how the Redis call went.
+async fn do_write(
+    rng: &mut StdRng,
+    metrics: &Arc<MetricsCollector>,
+    events: &EventLog,
+    conn: &mut ConnectionManager,
+    plan: &WorkerPlan,
+    op: Op,
+    lag_us: u64,
+) -> Outcome {
+    let t0 = Instant::now();
+
+    if op == Op::Set {
+        // ── Create session (SET with TTL) ───────────────────────
+        let cmd = session_set_cmd(rng, plan.value_size.as_ref());
+
+        let t_redis = Instant::now();
+        let result: redis::RedisResult<()> =
+            timed(plan.op_timeout, cmd.query_async(conn)).await;
+        let redis_us = t_redis.elapsed().as_micros() as u64;
+
+        if let Some(inject) = &plan.inject {
+            inject.apply();
+        }
+
+        let total_us = t0.elapsed().as_micros() as u64;
+        let rust_us = total_us.saturating_sub(redis_us);
+
+        if let Err(e) = &result {
+            events.push(EventKind::Error, format!("SET: {e}"));
+        }
+
+        metrics.record(Sample {
+            endpoint: "POST /api/sessions".into(),
+            op: Op::Set.command(),
+            key: command_key(&cmd),
+            redis_us,
+            rust_us,
+            total_us,
+            is_read: false,
+            success: result.is_ok(),
+            timed_out: timed_out(&result),
+            error: sample_error(&result),
+            http_status: None,
+            payload_bytes: write_bytes(&cmd),
+            cache: CacheOutcome::Bypass,
+            schedule_lag_us: lag_us,
+        });
+        Outcome::of(&result)
+    } else {
+        // ── Create user (HSET) ──────────────────────────────────
+        let cmd = user_hset_cmd(rng, plan.value_size.as_ref());
+
+        let t_redis = Instant::now();
+        let result: redis::RedisResult<()> =
+            timed(plan.op_timeout, cmd.query_async(conn)).await;
+        let redis_us = t_redis.elapsed().as_micros() as u64;
+
+        if let Some(inject) = &plan.inject {
+            inject.apply();
+        }
+
+        let total_us = t0.elapsed().as_micros() as u64;
+        let rust_us = total_us.saturating_sub(redis_us);
+
+        if let Err(e) = &result {
+            events.push(EventKind::Error, format!("HSET: {e}"));
+        }
+
+        metrics.record(Sample {
+            endpoint: "POST /api/users".into(),
+            op: Op::Hset.command(),
+            key: command_key(&cmd),
+            redis_us,
+            rust_us,
+            total_us,
+            is_read: false,
+            success: result.is_ok(),
+            timed_out: timed_out(&result),
+            error: sample_error(&result),
+            http_status: None,
+            payload_bytes: write_bytes(&cmd),
+            cache: CacheOutcome::Bypass,
+            schedule_lag_us: lag_us,
+        });
+        Outcome::of(&result)
+    }
+}
+
+// ─── Atomic operations (Lua vs MULTI/EXEC) ───────────────────────
+
+const SCRIPT_ENDPOINT: &str = "EVALSHA visit";
+const MULTI_ENDPOINT: &str = "MULTI/EXEC visit";
+
+/// A Lua script and its SHA1, as used by EVALSHA.
+struct LuaScript {
+    source: String,
+    sha: String,
+}
+
+impl LuaScript {
+    fn new(source: &str) -> Self {
+        Self {
+            source: source.to_owned(),
+            sha: redis::Script::new(source).get_hash().to_owned(),
+        }
+    }
+
+    /// SCRIPT LOAD — needed at start and again after FLUSHALL /
+    /// SCRIPT FLUSH empties the server's script cache.
+    async fn load(
+        &self,
+        conn: &mut ConnectionManager,
+    ) -> redis::RedisResult<()> {
+        redis::cmd("SCRIPT")
+            .arg("LOAD")
+            .arg(&self.source)
+            .query_async::<String>(conn)
+            .await
+            .map(|_| ())
+    }
+
+    fn evalsha_cmd(&self, key: &str) -> redis::Cmd {
+        let mut cmd = redis::cmd("EVALSHA");
+        cmd.arg(&self.sha).arg(1).arg(key).arg(1);
+        cmd
+    }
+}
+
+/// EVALSHA the script, or run its MULTI/EXEC equivalent, against one
+/// user. Returns how the Redis call went.
+async fn do_atomic(
+    rng: &mut StdRng,
+    metrics: &Arc<MetricsCollector>,
+    events: &EventLog,
+    conn: &mut ConnectionManager,
+    plan: &WorkerPlan,
+    op: Op,
+    lag_us: u64,
+) -> Outcome {
+    let t0 = Instant::now();
+    let key = user_key(rng);
+
+    // ── Redis timed section ─────────────────────────────────────
+    let t_redis = Instant::now();
+    let call = async {
+        match (op, &plan.script) {
+        (Op::Evalsha, Some(script)) => {
+            let first = script
+                .evalsha_cmd(&key)
+                .query_async::<redis::Value>(conn)
+                .await;
+            match first {
+                // Cache was flushed mid-run: reload and retry once
+                Err(e) if e.kind() == ErrorKind::NoScriptError => {
+                    events.push(
+                        EventKind::Error,
+                        "NOSCRIPT: script cache flushed, reloading",
+                    );
+                    match script.load(conn).await {
+                        Ok(()) => script
+                            .evalsha_cmd(&key)
+                            .query_async::<redis::Value>(conn)
+                            .await
+                            .map(|_| ()),
+                        Err(e) => Err(e),
+                    }
+                }
+                other => other.map(|_| ()),
+            }
+        }
+        _ => redis::pipe()
+            .atomic()
+            .cmd("HGET")
+            .arg(&key)
+            .arg("name")
+            .cmd("HINCRBY")
+            .arg(&key)
+            .arg("visits")
+            .arg(1)
+            .query_async::<(Option<String>, i64)>(conn)
+            .await
+            .map(|_| ()),
+        }
+    };
+    let result = timed(plan.op_timeout, call).await;
+    let redis_us = t_redis.elapsed().as_micros() as u64;
+    // ────────────────────────────────────────────────────────────
+
+    if let Some(inject) = &plan.inject {
+        inject.apply();
+    }
+
+    let total_us = t0.elapsed().as_micros() as u64;
+    let rust_us = total_us.saturating_sub(redis_us);
+
+    if let Err(e) = &result {
+        events.push(EventKind::Error, format!("{}: {e}", op.command()));
+    }
+
+    metrics.record(Sample {
+        endpoint: match op {
+            Op::Evalsha => SCRIPT_ENDPOINT,
+            _ => MULTI_ENDPOINT,
+        }
+        .into(),
+        op: op.command(),
+        key: Some(key),
+        redis_us,
+        rust_us,
+        total_us,
+        is_read: false,
+        success: result.is_ok(),
+        timed_out: timed_out(&result),
+        error: sample_error(&result),
+        http_status: None,
+        payload_bytes: 0,
+        cache: CacheOutcome::Bypass,
+        schedule_lag_us: lag_us,
+    });
+    Outcome::of(&result)
+}
+
+// ─── Batches ─────────────────────────────────────────────────────
+
+const MGET_ENDPOINT: &str = "MGET session:*";
+const MSET_ENDPOINT: &str = "MSET session:*";
+
+/// MGET or MSET over `plan.batch_size` random session keys. MSET
+/// writes the same blobs as SET, without the TTL.
+fn batch_cmd(rng: &mut StdRng, op: Op, plan: &WorkerPlan) -> redis::Cmd {
+    let mut cmd = redis::cmd(op.command());
+    for _ in 0..plan.batch_size {
+        if op == Op::Mset {
+            let (key, json) = session_blob(rng, plan.value_size.as_ref());
+            cmd.arg(key).arg(json);
+        } else {
+            cmd.arg(session_key(rng));
+        }
+    }
+    cmd
+}
+
+// ─── TTL churn ───────────────────────────────────────────────────
+
+const CHURN_ENDPOINT: &str = "SET churn:*";
+
+/// Value size for churn keys without a `value_size`
+const CHURN_VALUE_BYTES: usize = 32;
+
+/// SET a never-reused key that expires after `churn_ttl_ms`, so the
+/// server's expiry cycle has a steady stream of keys to reclaim.
+fn churn_cmd(rng: &mut StdRng, plan: &WorkerPlan) -> redis::Cmd {
+    let bytes = plan
+        .value_size
+        .as_ref()
+        .map_or(CHURN_VALUE_BYTES, |size| size.sample(rng));
+    let mut cmd = redis::cmd("SET");
+    cmd.arg(format!("churn:{:016x}", rng.gen::<u64>()))
+        .arg("x".repeat(bytes))
+        .arg("PX")
+        .arg(plan.churn_ttl_ms);
+    cmd
+}
+
+// ─── Keyspace scans ──────────────────────────────────────────────
+
+const SCAN_ENDPOINT: &str = "SCAN keyspace";
+
+/// One worker's position in its current pass over the keyspace.
+#[derive(Default)]
+struct ScanCursor {
+    cursor: u64,
+    /// When the pass began; `None` until its first call
+    started: Option<Instant>,
+    /// Keys returned so far this pass, duplicates included
+    keys: u64,
+}
+
+fn scan_cmd(cursor: u64, plan: &WorkerPlan) -> redis::Cmd {
+    let mut cmd = redis::cmd("SCAN");
+    cmd.arg(cursor)
+        .arg("MATCH")
+        .arg(&plan.scan_match)
+        .arg("COUNT")
+        .arg(plan.scan_count);
+    cmd
+}
+
+/// Advance the worker's cursor by one SCAN call. A cursor back at 0
+/// closes the pass and records its duration; a failed call keeps the
+/// cursor so the pass resumes where it stopped.
+async fn do_scan(
+    metrics: &Arc<MetricsCollector>,
+    events: &EventLog,
+    conn: &mut ConnectionManager,
+    plan: &WorkerPlan,
+    scan: &mut ScanCursor,
+    lag_us: u64,
+) -> Outcome {
+    let t0 = Instant::now();
+    let started = *scan.started.get_or_insert(t0);
+    let cmd = scan_cmd(scan.cursor, plan);
+
+    let t_redis = Instant::now();
+    let result: redis::RedisResult<(u64, Vec<String>)> =
+        timed(plan.op_timeout, cmd.query_async(conn)).await;
+    let redis_us = t_redis.elapsed().as_micros() as u64;
 
-    let mut handles = Vec::with_capacity(concurrency as usize);
+    if let Some(inject) = &plan.inject {
+        inject.apply();
+    }
 
-    for worker_id in 0..concurrency {
-        let running = running.clone();
-        let metrics = metrics.clone();
-        let conn = redis.clone();
+    let payload_bytes = match &result {
+        Ok((next, keys)) => {
+            scan.keys += keys.len() as u64;
+            scan.cursor = *next;
+            if *next == 0 {
+                metrics.record_scan_iteration(
+                    started.elapsed().as_micros() as u64,
+                    scan.keys,
+                );
+                *scan = ScanCursor::default();
+            }
+            keys.iter().map(|k| k.len() as u64).sum()
+        }
+        Err(e) => {
+            events.push(EventKind::Error, format!("SCAN: {e}"));
+            0
+        }
+    };
 
-        handles.push(tokio::spawn(async move {
-            worker(worker_id, running, metrics, conn, deadline, read_pct).await;
-        }));
+    let total_us = t0.elapsed().as_micros() as u64;
+    metrics.record(Sample {
+        endpoint: SCAN_ENDPOINT.into(),
+        op: Op::Scan.command(),
+        key: None,
+        redis_us,
+        rust_us: total_us.saturating_sub(redis_us),
+        total_us,
+        is_read: true,
+        success: result.is_ok(),
+        timed_out: timed_out(&result),
+        error: sample_error(&result),
+        http_status: None,
+        payload_bytes,
+        cache: CacheOutcome::Bypass,
+        schedule_lag_us: lag_us,
+    });
+    Outcome::of(&result)
+}
+
+// ─── Leaderboard ─────────────────────────────────────────────────
+
+const LEADERBOARD_ENDPOINT: &str = "leaderboard:global";
+
+fn leaderboard_cmd(
+    rng: &mut StdRng,
+    op: Op,
+    plan: &WorkerPlan,
+) -> redis::Cmd {
+    let player = format!("usr_{:08}", rng.gen_range(1..=10_000u32));
+    let mut cmd = redis::cmd(op.command());
+    cmd.arg(LEADERBOARD_KEY);
+    match op {
+        Op::Zadd => {
+            cmd.arg(rng.gen_range(0..1_000_000u32)).arg(player);
+        }
+        Op::Zincrby => {
+            cmd.arg(rng.gen_range(1..=100u32)).arg(player);
+        }
+        // Highest scores first (ZRANGE … REV needs Redis 6.2+)
+        Op::Zrange => {
+            cmd.arg(0)
+                .arg(plan.leaderboard_top_n - 1)
+                .arg("REV")
+                .arg("WITHSCORES");
+        }
+        _ => {
+            cmd.arg(player);
+        }
     }
+    cmd
+}
 
-    // Wait for all workers to finish
-    for h in handles {
-        let _ = h.await;
+/// Issue one self-contained command from `build_command` and record
+/// it. Returns how the Redis call went.
+async fn do_command(
+    rng: &mut StdRng,
+    metrics: &Arc<MetricsCollector>,
+    events: &EventLog,
+    conn: &mut ConnectionManager,
+    plan: &WorkerPlan,
+    op: Op,
+    lag_us: u64,
+) -> Outcome {
+    let t0 = Instant::now();
+    let (cmd, endpoint) = build_command(rng, op, plan);
+
+    let t_redis = Instant::now();
+    let result: redis::RedisResult<redis::Value> =
+        timed(plan.op_timeout, cmd.query_async(conn)).await;
+    let redis_us = t_redis.elapsed().as_micros() as u64;
+
+    if let Some(inject) = &plan.inject {
+        inject.apply();
     }
 
-    // Mark benchmark as finished
-    running.store(false, Ordering::SeqCst);
+    let total_us = t0.elapsed().as_micros() as u64;
+    let rust_us = total_us.saturating_sub(redis_us);
+
+    if let Err(e) = &result {
+        events.push(EventKind::Error, format!("{}: {e}", op.command()));
+    }
+    // Counted like the same reply in a pipeline
+    let error = match &result {
+        Ok(reply) if op.is_read() && is_missing(reply) => {
+            Some(SampleError::not_found())
+        }
+        _ => sample_error(&result),
+    };
+
+    metrics.record(Sample {
+        endpoint: endpoint.into(),
+        op: op.command(),
+        key: command_key(&cmd),
+        redis_us,
+        rust_us,
+        total_us,
+        is_read: op.is_read(),
+        success: error.is_none(),
+        timed_out: timed_out(&result),
+        error,
+        http_status: None,
+        payload_bytes: match &result {
+            Ok(reply) if op.is_read() => reply_bytes(reply),
+            _ if op.is_read() => 0,
+            _ => write_bytes(&cmd),
+        },
+        cache: CacheOutcome::Bypass,
+        schedule_lag_us: lag_us,
+    });
+    if matches!(op, Op::Mget | Op::Mset) {
+        metrics.record_batch(op == Op::Mset, redis_us, plan.batch_size);
+    }
+    Outcome::of(&result)
 }
 
-// ─── Worker loop ─────────────────────────────────────────────────
+// ─── Pub/Sub ─────────────────────────────────────────────────────
 
-async fn worker(
-    id: u32,
-    running: Arc<AtomicBool>,
-    metrics: Arc<MetricsCollector>,
-    mut conn: ConnectionManager,
-    deadline: Instant,
-    read_pct: u8,
-) {
-    // Each worker gets its own deterministic RNG seeded uniquely.
-    let mut rng = StdRng::seed_from_u64(1000 + id as u64);
+const PUBSUB_CHANNEL: &str = "bench:pubsub";
+const PUBLISH_ENDPOINT: &str = "PUBLISH bench:pubsub";
 
-    while running.load(Ordering::Relaxed) && Instant::now() < deadline {
-        let is_read = rng.gen_range(0u8..100) < read_pct;
+/// How long subscribers and stream consumers keep reading after the
+/// last producer exits, so in-flight messages are still measured
+const DELIVERY_DRAIN_MS: u64 = 250;
 
-        if is_read {
-            do_read(&mut rng, &metrics, &mut conn).await;
-        } else {
-            do_write(&mut rng, &metrics, &mut conn).await;
-        }
+/// Dedicated subscriber that records publish → receive latency.
+struct Subscriber {
+    handle: JoinHandle<()>,
+}
+
+impl Subscriber {
+    async fn start(
+        client: &redis::Client,
+        metrics: Arc<MetricsCollector>,
+        started: Instant,
+    ) -> redis::RedisResult<Self> {
+        let mut pubsub = client.get_async_pubsub().await?;
+        pubsub.subscribe(PUBSUB_CHANNEL).await?;
+
+        let handle = tokio::spawn(async move {
+            let mut messages = pubsub.on_message();
+            while let Some(msg) = messages.next().await {
+                let Ok(sent_us) = msg.get_payload::<u64>() else {
+                    continue;
+                };
+                let now_us = started.elapsed().as_micros() as u64;
+                metrics.record_pubsub(now_us.saturating_sub(sent_us));
+            }
+        });
+        Ok(Self { handle })
+    }
+
+    async fn drain(self) {
+        tokio::time::sleep(Duration::from_millis(DELIVERY_DRAIN_MS)).await;
+        self.handle.abort();
     }
 }
 
-// ─── Read operation ──────────────────────────────────────────────
+/// PUBLISH carrying the send time, in μs since the stage started.
+fn publish_cmd(plan: &WorkerPlan) -> redis::Cmd {
+    let mut cmd = redis::cmd("PUBLISH");
+    cmd.arg(PUBSUB_CHANNEL)
+        .arg(plan.started.elapsed().as_micros() as u64);
+    cmd
+}
 
-async fn do_read(
-    rng: &mut StdRng,
+/// Publish a message, append a stream entry or enqueue a job. The
+/// sample covers
+/// the command's round trip only; delivery latency is recorded by the
+/// receiving side. Returns how the Redis call went.
+async fn do_produce(
     metrics: &Arc<MetricsCollector>,
+    events: &EventLog,
     conn: &mut ConnectionManager,
-) {
+    plan: &WorkerPlan,
+    op: Op,
+    lag_us: u64,
+) -> Outcome {
     let t0 = Instant::now();
-
-    // 60 % user lookups, 40 % product lookups
-    let (key, endpoint) = if rng.gen_bool(0.6) {
-        let id = rng.gen_range(1..=10_000u32);
-        (
-            format!("user:usr_{:08}", id),
-            "GET /api/users/:id",
-        )
-    } else {
-        let id = rng.gen_range(1..=500u32);
-        (
-            format!("product:prod_{:04}", id),
-            "GET /api/products/:id",
-        )
+    let (cmd, endpoint) = match op {
+        Op::Publish => (publish_cmd(plan), PUBLISH_ENDPOINT),
+        Op::Lpush => (lpush_cmd(plan), LPUSH_ENDPOINT),
+        _ => (xadd_cmd(plan), XADD_ENDPOINT),
     };
 
-    // ── Redis timed section ─────────────────────────────────────
     let t_redis = Instant::now();
-    let result: redis::RedisResult<HashMap<String, String>> =
-        conn.hgetall(&key).await;
+    let result: redis::RedisResult<redis::Value> =
+        timed(plan.op_timeout, cmd.query_async(conn)).await;
     let redis_us = t_redis.elapsed().as_micros() as u64;
-    // ────────────────────────────────────────────────────────────
+
+    if let Some(inject) = &plan.inject {
+        inject.apply();
+    }
 
     let total_us = t0.elapsed().as_micros() as u64;
     let rust_us = total_us.saturating_sub(redis_us);
 
+    match &result {
+        Ok(_) if op == Op::Lpush => metrics.record_queue_enqueue(redis_us),
+        Ok(_) => {}
+        Err(e) => {
+            events.push(EventKind::Error, format!("{}: {e}", op.command()))
+        }
+    }
+
     metrics.record(Sample {
         endpoint: endpoint.into(),
+        op: op.command(),
+        key: command_key(&cmd),
         redis_us,
         rust_us,
         total_us,
-        is_read: true,
-        success: result.is_ok() && result.unwrap().len() > 0,
+        is_read: false,
+        success: result.is_ok(),
+        timed_out: timed_out(&result),
+        error: sample_error(&result),
+        http_status: None,
+        payload_bytes: write_bytes(&cmd),
+        cache: CacheOutcome::Bypass,
+        schedule_lag_us: lag_us,
     });
+    Outcome::of(&result)
 }
 
-// ─── Write operation ─────────────────────────────────────────────
+// ─── Streams ─────────────────────────────────────────────────────
 
-async fn do_write(
+const STREAM_KEY: &str = "bench:stream";
+const STREAM_GROUP: &str = "bench";
+const XADD_ENDPOINT: &str = "XADD bench:stream";
+
+/// Approximate cap on stream length so long runs don't grow memory
+const STREAM_MAXLEN: u64 = 100_000;
+
+/// Entries fetched per XREADGROUP call
+const STREAM_READ_COUNT: u64 = 100;
+
+/// XREADGROUP block time — also how quickly consumers notice shutdown
+const STREAM_BLOCK_MS: u64 = 100;
+
+/// How often the pending-entries gauge is refreshed
+const PENDING_POLL_MS: u64 = 1_000;
+
+/// XADD with the send time, in μs since the stage started.
+fn xadd_cmd(plan: &WorkerPlan) -> redis::Cmd {
+    let mut cmd = redis::cmd("XADD");
+    cmd.arg(STREAM_KEY)
+        .arg("MAXLEN")
+        .arg("~")
+        .arg(STREAM_MAXLEN)
+        .arg("*")
+        .arg("ts")
+        .arg(plan.started.elapsed().as_micros() as u64);
+    cmd
+}
+
+/// A consumer group reading `bench:stream`, plus a task sampling its
+/// pending-entries list.
+struct StreamConsumers {
+    handles: Vec<JoinHandle<()>>,
+    metrics: Arc<MetricsCollector>,
+}
+
+impl StreamConsumers {
+    /// Recreate the stream and group, then start `count` consumers on
+    /// connections of their own (XREADGROUP BLOCK would otherwise stall
+    /// the shared one).
+    async fn start(
+        redis: &ConnectionManager,
+        client: &redis::Client,
+        metrics: Arc<MetricsCollector>,
+        events: Arc<EventLog>,
+        started: Instant,
+        count: u32,
+    ) -> redis::RedisResult<Self> {
+        let mut conn = redis.clone();
+        redis::cmd("DEL")
+            .arg(STREAM_KEY)
+            .query_async::<()>(&mut conn)
+            .await?;
+        redis::cmd("XGROUP")
+            .arg("CREATE")
+            .arg(STREAM_KEY)
+            .arg(STREAM_GROUP)
+            .arg("$")
+            .arg("MKSTREAM")
+            .query_async::<()>(&mut conn)
+            .await?;
+
+        let mut handles = Vec::with_capacity(count as usize + 1);
+        for id in 0..count {
+            let conn = client.get_multiplexed_async_connection().await?;
+            handles.push(tokio::spawn(consume(
+                conn,
+                format!("consumer-{id}"),
+                metrics.clone(),
+                events.clone(),
+                started,
+            )));
+        }
+        let gauge = metrics.clone();
+        handles.push(tokio::spawn(async move {
+            let mut tick = tokio::time::interval(Duration::from_millis(
+                PENDING_POLL_MS,
+            ));
+            loop {
+                tick.tick().await;
+                if let Ok(pending) = pending_count(&mut conn).await {
+                    gauge.set_stream_pending(pending);
+                }
+            }
+        }));
+        Ok(Self { handles, metrics })
+    }
+
+    /// Let consumers catch up on in-flight entries, take a final
+    /// pending reading, then stop and drop the stream.
+    async fn drain(self, redis: &ConnectionManager) {
+        tokio::time::sleep(Duration::from_millis(DELIVERY_DRAIN_MS)).await;
+        for handle in &self.handles {
+            handle.abort();
+        }
+        let mut conn = redis.clone();
+        if let Ok(pending) = pending_count(&mut conn).await {
+            self.metrics.set_stream_pending(pending);
+        }
+        let _ = redis::cmd("DEL")
+            .arg(STREAM_KEY)
+            .query_async::<()>(&mut conn)
+            .await;
+    }
+}
+
+async fn consume(
+    mut conn: redis::aio::MultiplexedConnection,
+    name: String,
+    metrics: Arc<MetricsCollector>,
+    events: Arc<EventLog>,
+    started: Instant,
+) {
+    type Entry = (String, Vec<(String, String)>);
+    type Reply = Option<Vec<(String, Vec<Entry>)>>;
+
+    loop {
+        let reply: redis::RedisResult<Reply> = redis::cmd("XREADGROUP")
+            .arg("GROUP")
+            .arg(STREAM_GROUP)
+            .arg(&name)
+            .arg("COUNT")
+            .arg(STREAM_READ_COUNT)
+            .arg("BLOCK")
+            .arg(STREAM_BLOCK_MS)
+            .arg("STREAMS")
+            .arg(STREAM_KEY)
+            .arg(">")
+            .query_async(&mut conn)
+            .await;
+        let entries = match reply {
+            Ok(streams) => streams
+                .into_iter()
+                .flatten()
+                .flat_map(|(_, entries)| entries)
+                .collect::<Vec<_>>(),
+            Err(e) => {
+                events.push(EventKind::Error, format!("XREADGROUP: {e}"));
+                tokio::time::sleep(Duration::from_millis(STREAM_BLOCK_MS))
+                    .await;
+                continue;
+            }
+        };
+        if entries.is_empty() {
+            continue;
+        }
+
+        let now_us = started.elapsed().as_micros() as u64;
+        let mut ack = redis::cmd("XACK");
+        ack.arg(STREAM_KEY).arg(STREAM_GROUP);
+        for (id, fields) in &entries {
+            let sent_us = fields
+                .iter()
+                .find(|(field, _)| field == "ts")
+                .and_then(|(_, v)| v.parse::<u64>().ok());
+            if let Some(sent_us) = sent_us {
+                metrics.record_stream_delivery(now_us.saturating_sub(sent_us));
+            }
+            ack.arg(id);
+        }
+        if let Err(e) = ack.query_async::<u64>(&mut conn).await {
+            events.push(EventKind::Error, format!("XACK: {e}"));
+        }
+    }
+}
+
+/// Size of the group's pending-entries list (delivered, not yet acked).
+async fn pending_count(
+    conn: &mut ConnectionManager,
+) -> redis::RedisResult<u64> {
+    let (count, ..): (u64, redis::Value, redis::Value, redis::Value) =
+        redis::cmd("XPENDING")
+            .arg(STREAM_KEY)
+            .arg(STREAM_GROUP)
+            .query_async(conn)
+            .await?;
+    Ok(count)
+}
+
+// ─── Job queue ───────────────────────────────────────────────────
+
+const QUEUE_KEY: &str = "bench:queue";
+const LPUSH_ENDPOINT: &str = "LPUSH bench:queue";
+
+/// BRPOP timeout (seconds, the unit BRPOP takes)
+const QUEUE_BLOCK_SECS: u64 = 1;
+
+/// LPUSH a job stamped with its enqueue time, in μs since stage start.
+fn lpush_cmd(plan: &WorkerPlan) -> redis::Cmd {
+    let mut cmd = redis::cmd("LPUSH");
+    cmd.arg(QUEUE_KEY)
+        .arg(plan.started.elapsed().as_micros() as u64);
+    cmd
+}
+
+/// BRPOP consumers draining `bench:queue`, plus a task sampling its
+/// length.
+struct QueueConsumers {
+    handles: Vec<JoinHandle<()>>,
+    metrics: Arc<MetricsCollector>,
+}
+
+impl QueueConsumers {
+    /// Empty the queue, then start `count` consumers on connections of
+    /// their own (BRPOP would otherwise stall the shared one).
+    async fn start(
+        redis: &ConnectionManager,
+        client: &redis::Client,
+        metrics: Arc<MetricsCollector>,
+        events: Arc<EventLog>,
+        started: Instant,
+        count: u32,
+    ) -> redis::RedisResult<Self> {
+        let mut conn = redis.clone();
+        redis::cmd("DEL")
+            .arg(QUEUE_KEY)
+            .query_async::<()>(&mut conn)
+            .await?;
+
+        let mut handles = Vec::with_capacity(count as usize + 1);
+        for _ in 0..count {
+            let conn = client.get_multiplexed_async_connection().await?;
+            handles.push(tokio::spawn(dequeue(
+                conn,
+                metrics.clone(),
+                events.clone(),
+                started,
+            )));
+        }
+        let gauge = metrics.clone();
+        handles.push(tokio::spawn(async move {
+            let mut tick = tokio::time::interval(Duration::from_millis(
+                PENDING_POLL_MS,
+            ));
+            loop {
+                tick.tick().await;
+                if let Ok(depth) = conn.llen::<_, u64>(QUEUE_KEY).await {
+                    gauge.set_queue_depth(depth);
+                }
+            }
+        }));
+        Ok(Self { handles, metrics })
+    }
+
+    /// Let consumers catch up, take a final depth reading, then stop
+    /// and drop whatever is left in the queue.
+    async fn drain(self, redis: &ConnectionManager) {
+        tokio::time::sleep(Duration::from_millis(DELIVERY_DRAIN_MS)).await;
+        for handle in &self.handles {
+            handle.abort();
+        }
+        let mut conn = redis.clone();
+        if let Ok(depth) = conn.llen::<_, u64>(QUEUE_KEY).await {
+            self.metrics.set_queue_depth(depth);
+        }
+        let _ = redis::cmd("DEL")
+            .arg(QUEUE_KEY)
+            .query_async::<()>(&mut conn)
+            .await;
+    }
+}
+
+async fn dequeue(
+    mut conn: redis::aio::MultiplexedConnection,
+    metrics: Arc<MetricsCollector>,
+    events: Arc<EventLog>,
+    started: Instant,
+) {
+    loop {
+        let t_pop = Instant::now();
+        let reply: redis::RedisResult<Option<(String, u64)>> =
+            redis::cmd("BRPOP")
+                .arg(QUEUE_KEY)
+                .arg(QUEUE_BLOCK_SECS)
+                .query_async(&mut conn)
+                .await;
+        match reply {
+            Ok(Some((_, enqueued_us))) => {
+                let wait_us = t_pop.elapsed().as_micros() as u64;
+                let now_us = started.elapsed().as_micros() as u64;
+                metrics.record_queue_dequeue(
+                    now_us.saturating_sub(enqueued_us),
+                    wait_us,
+                );
+            }
+            Ok(None) => metrics.record_queue_empty_poll(),
+            Err(e) => {
+                events.push(EventKind::Error, format!("BRPOP: {e}"));
+                tokio::time::sleep(Duration::from_millis(STREAM_BLOCK_MS))
+                    .await;
+            }
+        }
+    }
+}
+
+// ─── Pipelined batch ─────────────────────────────────────────────
+
+/// Send `plan.pipeline_depth` commands drawn from the mix in a single
+/// pipeline. Each command is recorded with its amortized share of the
+/// batch; the whole batch is recorded separately. Returns how the
+/// pipeline went.
+async fn do_pipeline(
     rng: &mut StdRng,
     metrics: &Arc<MetricsCollector>,
+    events: &EventLog,
     conn: &mut ConnectionManager,
-) {
+    plan: &WorkerPlan,
+    lag_us: u64,
+) -> Outcome {
     let t0 = Instant::now();
+    let depth = plan.pipeline_depth as usize;
 
-    if rng.gen_bool(0.5) {
-        // ── Create session (SET with TTL) ───────────────────────
-        let sess_id = format!("sess_{:08x}", rng.gen::<u32>());
-        let user_id = format!("usr_{:08}", rng.gen_range(1..=10_000u32));
-        let key = format!("session:{}", sess_id);
-
-        let json = serde_json::json!({
-            "id":         sess_id,
-            "user_id":    user_id,
-            "token":      format!("tok_{:016x}", rng.gen::<u64>()),
-            "ip":         format!("10.0.{}.{}", rng.gen_range(0u8..=255),
-                                                 rng.gen_range(1u8..=254)),
-            "created_at": "2025-06-19T00:00:00Z",
-            "ttl_secs":   300,
-        })
-        .to_string();
+    let mut pipe = redis::pipe();
+    let mut batch = Vec::with_capacity(depth);
+    for _ in 0..depth {
+        let op = plan.ops[plan.op_dist.sample(rng)];
+        let (cmd, endpoint) = build_command(rng, op, plan);
+        let sent = if op.is_read() { 0 } else { write_bytes(&cmd) };
+        let key = command_key(&cmd);
+        pipe.add_command(cmd);
+        batch.push((op, endpoint, sent, key));
+    }
 
-        let t_redis = Instant::now();
-        let result: redis::RedisResult<()> = redis::cmd("SET")
-            .arg(&key)
-            .arg(&json)
-            .arg("EX")
-            .arg(300u64)
-            .query_async(conn)
-            .await;
-        let redis_us = t_redis.elapsed().as_micros() as u64;
+    // ── Redis timed section ─────────────────────────────────────
+    let t_redis = Instant::now();
+    let result: redis::RedisResult<Vec<redis::Value>> =
+        timed(plan.op_timeout, pipe.query_async(conn)).await;
+    let batch_us = t_redis.elapsed().as_micros() as u64;
+    // ────────────────────────────────────────────────────────────
 
-        let total_us = t0.elapsed().as_micros() as u64;
-        let rust_us = total_us.saturating_sub(redis_us);
+    if let Some(inject) = &plan.inject {
+        for _ in 0..depth {
+            inject.apply();
+        }
+    }
+
+    let total_us = t0.elapsed().as_micros() as u64;
+    metrics.record_pipeline(batch_us, plan.pipeline_depth);
+
+    let replies = match &result {
+        Ok(values) => values.as_slice(),
+        Err(e) => {
+            events.push(EventKind::Error, format!("PIPELINE: {e}"));
+            // Script cache flushed — reload so the next batch succeeds
+            if let (ErrorKind::NoScriptError, Some(script)) =
+                (e.kind(), &plan.script)
+            {
+                let _ = script.load(conn).await;
+            }
+            &[]
+        }
+    };
 
+    let batch_timed_out = timed_out(&result);
+    let redis_us = batch_us / depth as u64;
+    let total_us = total_us / depth as u64;
+    let rust_us = total_us.saturating_sub(redis_us);
+    for (i, (op, endpoint, sent, key)) in batch.into_iter().enumerate() {
+        let reply = replies.get(i);
+        let error = match (&result, reply) {
+            (Err(e), _) => Some(SampleError::redis(e)),
+            (_, None) => Some(SampleError::not_found()),
+            (_, Some(reply)) if op.is_read() && is_missing(reply) => {
+                Some(SampleError::not_found())
+            }
+            _ => None,
+        };
         metrics.record(Sample {
-            endpoint: "POST /api/sessions".into(),
+            endpoint: endpoint.into(),
+            op: op.command(),
+            key,
             redis_us,
             rust_us,
             total_us,
-            is_read: false,
-            success: result.is_ok(),
+            is_read: op.is_read(),
+            success: error.is_none(),
+            timed_out: batch_timed_out,
+            error,
+            http_status: None,
+            payload_bytes: match reply {
+                Some(reply) if op.is_read() => reply_bytes(reply),
+                _ => sent,
+            },
+            cache: CacheOutcome::Bypass,
+            schedule_lag_us: lag_us,
         });
+        if matches!(op, Op::Mget | Op::Mset) {
+            metrics.record_batch(op == Op::Mset, redis_us, plan.batch_size);
+        }
+    }
+
+    Outcome::of(&result)
+}
+
+/// Missing keys come back as nil (GET, ZRANK), an empty array, map or
+/// set (HGETALL, ZRANGE, SMEMBERS) or an array of nils (MGET); any of
+/// them is a miss, not a success. An MGET that finds some of its keys
+/// is a hit. Only asked of reads: a write's nil or empty reply is not
+/// a miss.
+fn is_missing(reply: &redis::Value) -> bool {
+    use redis::Value;
+    match reply {
+        Value::Nil => true,
+        Value::Array(items) | Value::Set(items) => {
+            items.iter().all(|item| *item == Value::Nil)
+        }
+        Value::Map(pairs) => pairs.is_empty(),
+        Value::Attribute { data, .. } => is_missing(data),
+        _ => false,
+    }
+}
+
+/// Convert a raw reply, timing the conversion under its RESP type.
+fn decode<T: redis::FromRedisValue>(
+    metrics: &MetricsCollector,
+    reply: &redis::Value,
+) -> redis::RedisResult<T> {
+    let t_decode = Instant::now();
+    let decoded = T::from_redis_value(reply);
+    let decode_ns = t_decode.elapsed().as_nanos() as u64;
+    metrics.record_decode(reply_type(reply), decode_ns);
+    decoded
+}
+
+// ─── Command builders ────────────────────────────────────────────
+
+/// Build the command for `op` plus the endpoint it stands in for.
+/// `MULTI` is rejected for pipelined runs at validation time.
+fn build_command(
+    rng: &mut StdRng,
+    op: Op,
+    plan: &WorkerPlan,
+) -> (redis::Cmd, &'static str) {
+    match op {
+        Op::Get => {
+            let mut cmd = redis::cmd("GET");
+            cmd.arg(session_key(rng));
+            (cmd, "GET /api/sessions/:id")
+        }
+        Op::Hgetall | Op::HgetallUser | Op::HgetallProduct => {
+            let (key, endpoint) = hash_read_target(rng, op);
+            let mut cmd = redis::cmd("HGETALL");
+            cmd.arg(key);
+            (cmd, endpoint)
+        }
+        Op::Set => (
+            session_set_cmd(rng, plan.value_size.as_ref()),
+            "POST /api/sessions",
+        ),
+        Op::Hset => (
+            user_hset_cmd(rng, plan.value_size.as_ref()),
+            "POST /api/users",
+        ),
+        Op::Mget => (batch_cmd(rng, op, plan), MGET_ENDPOINT),
+        Op::Mset => (batch_cmd(rng, op, plan), MSET_ENDPOINT),
+        Op::SetChurn => (churn_cmd(rng, plan), CHURN_ENDPOINT),
+        Op::Evalsha | Op::Multi => {
+            let script = plan.script.as_ref().expect("script loaded");
+            (script.evalsha_cmd(&user_key(rng)), SCRIPT_ENDPOINT)
+        }
+        Op::Publish => (publish_cmd(plan), PUBLISH_ENDPOINT),
+        Op::Xadd => (xadd_cmd(plan), XADD_ENDPOINT),
+        Op::Lpush => (lpush_cmd(plan), LPUSH_ENDPOINT),
+        // Never pipelined; a fresh pass for completeness
+        Op::Scan => (scan_cmd(0, plan), SCAN_ENDPOINT),
+        Op::Zadd | Op::Zincrby | Op::Zrange | Op::Zrank => {
+            (leaderboard_cmd(rng, op, plan), LEADERBOARD_ENDPOINT)
+        }
+    }
+}
+
+fn user_key(rng: &mut StdRng) -> String {
+    format!("user:usr_{:08}", rng.gen_range(1..=10_000u32))
+}
+
+fn session_key(rng: &mut StdRng) -> String {
+    format!("session:sess_{:08}", rng.gen_range(1..=SESSION_KEYSPACE))
+}
+
+/// Key and endpoint for an HGETALL; bare `HGETALL` splits evenly.
+fn hash_read_target(rng: &mut StdRng, op: Op) -> (String, &'static str) {
+    let user = match op {
+        Op::HgetallUser => true,
+        Op::HgetallProduct => false,
+        _ => rng.gen_bool(0.5),
+    };
+    if user {
+        (user_key(rng), "GET /api/users/:id")
     } else {
-        // ── Create user (HSET) ──────────────────────────────────
-        let i = rng.gen_range(10_001..=99_999u32);
-        let id = format!("usr_{:08}", i);
-        let key = format!("user:{}", id);
+        let id = rng.gen_range(1..=500u32);
+        (
+            format!("product:prod_{:04}", id),
+            "GET /api/products/:id",
+        )
+    }
+}
 
-        let t_redis = Instant::now();
-        let result: redis::RedisResult<()> = redis::cmd("HSET")
-            .arg(&key)
-            .arg("id")
-            .arg(&id)
-            .arg("name")
-            .arg("Bench User")
-            .arg("email")
-            .arg(format!("bench{}@test.com", i))
-            .arg("role")
-            .arg("viewer")
-            .arg("prefs")
-            .arg(r#"{"theme":"dark","lang":"en","notifications":false}"#)
-            .arg("created_at")
-            .arg("2025-06-19T00:00:00Z")
-            .query_async(conn)
-            .await;
-        let redis_us = t_redis.elapsed().as_micros() as u64;
+fn session_set_cmd(
+    rng: &mut StdRng,
+    value_size: Option<&ValueSize>,
+) -> redis::Cmd {
+    let (key, json) = session_blob(rng, value_size);
+    let mut cmd = redis::cmd("SET");
+    cmd.arg(key).arg(json).arg("EX").arg(300u64);
+    cmd
+}
 
-        let total_us = t0.elapsed().as_micros() as u64;
-        let rust_us = total_us.saturating_sub(redis_us);
+/// Key and JSON body of a random session. With a `value_size` the
+/// body gets a `data` field padding it out to the sampled size.
+fn session_blob(
+    rng: &mut StdRng,
+    value_size: Option<&ValueSize>,
+) -> (String, String) {
+    let sess_id = format!("sess_{:08}", rng.gen_range(1..=SESSION_KEYSPACE));
+    let user_id = format!("usr_{:08}", rng.gen_range(1..=10_000u32));
+    let key = format!("session:{}", sess_id);
 
-        metrics.record(Sample {
-            endpoint: "POST /api/users".into(),
-            redis_us,
-            rust_us,
-            total_us,
-            is_read: false,
-            success: result.is_ok(),
-        });
+    let mut json = serde_json::json!({
+        "id":         sess_id,
+        "user_id":    user_id,
+        "token":      format!("tok_{:016x}", rng.gen::<u64>()),
+        "ip":         format!("10.0.{}.{}", rng.gen_range(0u8..=255),
+                                             rng.gen_range(1u8..=254)),
+        "created_at": "2025-06-19T00:00:00Z",
+        "ttl_secs":   300,
+    });
+    if let Some(size) = value_size {
+        let target = size.sample(rng);
+        // `,"data":""` wraps the padding in 10 more bytes
+        let base = json.to_string().len() + 10;
+        json["data"] = "x".repeat(target.saturating_sub(base)).into();
+    }
+    (key, json.to_string())
+}
+
+/// HSET a new user hash. With a `value_size` a `bio` field pads the
+/// fields and values out to the sampled size.
+fn user_hset_cmd(
+    rng: &mut StdRng,
+    value_size: Option<&ValueSize>,
+) -> redis::Cmd {
+    let i = rng.gen_range(10_001..=99_999u32);
+    let id = format!("usr_{:08}", i);
+    let key = format!("user:{}", id);
+
+    let mut cmd = redis::cmd("HSET");
+    cmd.arg(key)
+        .arg("id")
+        .arg(&id)
+        .arg("name")
+        .arg("Bench User")
+        .arg("email")
+        .arg(format!("bench{}@test.com", i))
+        .arg("role")
+        .arg("viewer")
+        .arg("prefs")
+        .arg(r#"{"theme":"dark","lang":"en","notifications":false}"#)
+        .arg("created_at")
+        .arg("2025-06-19T00:00:00Z");
+    if let Some(size) = value_size {
+        let target = size.sample(rng) as u64;
+        let base = write_bytes(&cmd) + "bio".len() as u64;
+        let pad = target.saturating_sub(base) as usize;
+        cmd.arg("bio").arg("x".repeat(pad));
+    }
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_missing;
+    use redis::Value;
+
+    fn data(s: &str) -> Value {
+        Value::BulkString(s.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn nil_is_missing() {
+        assert!(is_missing(&Value::Nil));
+    }
+
+    #[test]
+    fn empty_array_is_missing() {
+        assert!(is_missing(&Value::Array(Vec::new())));
+    }
+
+    #[test]
+    fn empty_resp3_collections_are_missing() {
+        assert!(is_missing(&Value::Map(Vec::new())));
+        assert!(is_missing(&Value::Set(Vec::new())));
+    }
+
+    #[test]
+    fn all_nil_mget_is_missing() {
+        assert!(is_missing(&Value::Array(vec![Value::Nil, Value::Nil])));
+    }
+
+    #[test]
+    fn partial_mget_is_a_hit() {
+        assert!(!is_missing(&Value::Array(vec![Value::Nil, data("1")])));
+    }
+
+    #[test]
+    fn values_are_hits() {
+        assert!(!is_missing(&data("alice")));
+        assert!(!is_missing(&Value::Int(0)));
+        assert!(!is_missing(&Value::Okay));
+        assert!(!is_missing(&Value::Map(vec![(data("name"), data("a"))])));
+        assert!(!is_missing(&Value::Double(0.0)));
     }
-}
\ No newline at end of file
+}
@@ -1,18 +1,46 @@
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
+mod analysis;
+mod archive;
+mod capacity;
+mod client_cache;
+mod client_monitor;
+mod commandstats;
+mod compare;
+mod config;
+mod conn_pool;
+mod events;
+mod experiment;
+mod expiry_monitor;
+mod guard;
+mod handler_cache;
 mod handlers;
+mod injection;
 mod load_generator;
 mod metrics;
 mod middleware;
 mod mock_data;
+mod notify;
+mod prober;
 mod redis_client;
+mod report;
+mod run_queue;
 mod server;
+mod slo;
+mod sweep;
+mod validation;
+mod webhook;
 
 /// Shared application state available to every handler via `State<Arc<AppState>>`.
 pub struct AppState {
-    /// Cloneable async Redis connection (auto-reconnects).
-    pub redis: redis::aio::ConnectionManager,
+    /// Current Redis master: the shared auto-reconnecting connection,
+    /// and the client it was built from for modes that need dedicated
+    /// connections of their own. Swapped after a Sentinel failover.
+    pub redis: Arc<redis_client::RedisTarget>,
+
+    /// Transport and connect/handshake timings measured at startup.
+    pub connection: redis_client::ConnectionProfile,
 
     /// Central metrics engine — handlers push samples, SSE reads snapshots.
     pub metrics: Arc<metrics::MetricsCollector>,
@@ -22,6 +50,57 @@ pub struct AppState {
 
     /// Handle to the spawned load-generator task so we can await clean shutdown.
     pub load_handle: tokio::sync::Mutex<Option<tokio::task::JoinHandle<()>>>,
+
+    /// Live worker count of the running stage (`/api/benchmark/adjust`).
+    pub workers: Arc<load_generator::WorkerControl>,
+
+    /// Configs started one after another by `run_queue::drive`.
+    pub queue: run_queue::RunQueue,
+
+    /// Id of the current (or most recent) benchmark run.
+    pub run_id: parking_lot::Mutex<Option<String>>,
+
+    /// Start and planned length of that run, for `/status` progress.
+    pub run_clock: parking_lot::Mutex<Option<handlers::benchmark::RunClock>>,
+
+    /// Append-only per-run log of state transitions, errors and annotations.
+    pub events: Arc<events::EventLog>,
+
+    /// Summaries of finished runs and results imported from other tools.
+    pub archive: Arc<archive::RunArchive>,
+
+    /// Progress and results of the latest eviction-policy experiment.
+    pub eviction_report:
+        Arc<parking_lot::Mutex<Option<experiment::EvictionReport>>>,
+
+    /// Progress and curve of the latest sweep.
+    pub sweep_report: Arc<parking_lot::Mutex<Option<sweep::SweepReport>>>,
+
+    /// Steps and result of the latest capacity search.
+    pub capacity_report:
+        Arc<parking_lot::Mutex<Option<capacity::CapacityReport>>>,
+
+    /// What was seeded at startup, recorded with every native run.
+    pub dataset: mock_data::DatasetManifest,
+
+    /// Optional in-process LRU in front of user/product reads.
+    pub handler_cache: Option<handler_cache::HandlerCache>,
+
+    /// Long-horizon results of the background health prober.
+    pub probe: Arc<metrics::probe::ProbeMetrics>,
+
+    /// Per-call Redis timeout (ms) for handlers, and for runs that don't
+    /// set their own; 0 = none.
+    pub op_timeout_ms: u64,
+
+    /// Connected SSE subscribers; decides how much each stream sends.
+    pub stream_clients: Arc<metrics::stream::StreamClients>,
+
+    /// Receives each benchmark and scenario run's final report.
+    pub webhook: Option<Arc<webhook::Webhook>>,
+
+    /// Posts a summary of each benchmark and scenario run to chat.
+    pub notifier: Option<Arc<notify::Notifier>>,
 }
 
 #[tokio::main]
@@ -32,26 +111,191 @@ async fn main() {
     println!("╚══════════════════════════════════════════════════╝");
     println!();
 
+    let settings = config::Settings::from_env();
+    let events = Arc::new(events::EventLog::new());
+
     // ── 1. Connect to Redis ──────────────────────────────────────
-    println!("🔌 Connecting to Redis at 127.0.0.1:6379...");
-    let redis_conn = redis_client::connect("redis://127.0.0.1:6379/").await;
-    println!("   ✓ connected");
+    let (connected, sentinel_addr) = match &settings.sentinel {
+        Some(sentinel) => {
+            println!(
+                "🔌 Resolving master \"{}\" via Sentinel {}...",
+                sentinel.master_name,
+                sentinel.nodes.join(", ")
+            );
+            let (connected, addr) = redis_client::connect_sentinel(
+                sentinel,
+                &settings.auth,
+                &settings.tls,
+            )
+            .await;
+            println!("   ✓ connected to master at {addr}");
+            (connected, Some(addr))
+        }
+        None => {
+            println!("🔌 Connecting to Redis at {}...", settings.redis_url);
+            let connected = redis_client::connect(&settings).await;
+            println!("   ✓ connected");
+            (connected, None)
+        }
+    };
+    let redis_conn = connected.manager;
+    let connection = connected.profile;
+    println!(
+        "   ✓ {} connect {}μs (bare socket {}μs)",
+        connection.transport,
+        connection.client_connect_us,
+        connection.tcp_connect_us,
+    );
+    if let Some(tls_us) = connection.tls_handshake_us {
+        println!("   ✓ TLS handshake ≈ {tls_us}μs");
+    }
 
     // ── 2. Seed mock data ────────────────────────────────────────
-    mock_data::seed(&redis_conn).await;
+    let dataset = mock_data::seed(
+        &connected.client,
+        &redis_conn,
+        settings.seed_connections,
+        settings.seed_snapshot,
+    )
+    .await
+    .unwrap_or_else(|e| {
+        eprintln!("❌ Seeding failed: {e}");
+        std::process::exit(1);
+    });
 
     // ── 3. Build shared state ────────────────────────────────────
+    let probe = Arc::new(metrics::probe::ProbeMetrics::new(
+        settings.probe_interval_ms,
+    ));
+    let webhook = settings.webhook.as_ref().map(|hook| {
+        let webhook = webhook::Webhook::new(hook).unwrap_or_else(|e| {
+            eprintln!("❌ {e}");
+            std::process::exit(2);
+        });
+        println!("📤 Posting final run reports to {}", hook.url);
+        Arc::new(webhook)
+    });
+    let notifier = settings.notify.as_ref().map(|notify| {
+        let notifier = notify::Notifier::new(notify).unwrap_or_else(|e| {
+            eprintln!("❌ {e}");
+            std::process::exit(2);
+        });
+        println!("💬 Posting run summaries to chat");
+        Arc::new(notifier)
+    });
+    let redis = Arc::new(redis_client::RedisTarget::new(
+        connected.client,
+        redis_conn,
+    ));
+    let shared_runs = settings.runs_in_redis.then(|| redis.clone());
+    if let (Some(sentinel), Some(addr)) = (&settings.sentinel, sentinel_addr) {
+        tokio::spawn(redis_client::watch_sentinel_master(
+            sentinel.clone(),
+            settings.auth.clone(),
+            settings.tls.clone(),
+            addr,
+            redis.clone(),
+            events.clone(),
+        ));
+    }
     let state = Arc::new(AppState {
-        redis: redis_conn,
-        metrics: Arc::new(metrics::MetricsCollector::new()),
+        redis,
+        connection,
+        metrics: Arc::new(metrics::MetricsCollector::new(
+            settings.metrics_limits,
+        )),
         load_running: Arc::new(AtomicBool::new(false)),
         load_handle: tokio::sync::Mutex::new(None),
+        workers: Arc::new(load_generator::WorkerControl::new()),
+        queue: run_queue::RunQueue::new(),
+        run_id: parking_lot::Mutex::new(None),
+        run_clock: parking_lot::Mutex::new(None),
+        events,
+        archive: Arc::new(archive::RunArchive::new(
+            dataset.clone(),
+            settings.runs_dir.as_ref().map(std::path::PathBuf::from),
+            shared_runs,
+            settings.max_runs,
+        )),
+        eviction_report: Arc::new(parking_lot::Mutex::new(None)),
+        sweep_report: Arc::new(parking_lot::Mutex::new(None)),
+        capacity_report: Arc::new(parking_lot::Mutex::new(None)),
+        dataset,
+        handler_cache: settings
+            .handler_cache
+            .map(handler_cache::HandlerCache::new),
+        probe: probe.clone(),
+        op_timeout_ms: settings.op_timeout_ms,
+        stream_clients: Arc::new(metrics::stream::StreamClients::new(
+            settings.stream_trim_after,
+        )),
+        webhook,
+        notifier,
     });
+    if let Some(statsd) = settings.statsd.clone() {
+        let (addr, tags) = (statsd.addr.clone(), statsd.tags);
+        state
+            .metrics
+            .subscribe(metrics::statsd::StatsdSink::start(statsd));
+        let flavor = if tags { "DogStatsD" } else { "StatsD" };
+        println!("📤 Sending samples to {flavor} at {addr}");
+    }
+    if let Some(influx) = settings.influx.clone() {
+        // The query string may carry v1 credentials
+        let url = influx.url.split('?').next().unwrap_or_default();
+        let url = url.to_owned();
+        let sink = metrics::influx::InfluxSink::start(
+            influx,
+            state.metrics.clone(),
+        )
+        .unwrap_or_else(|e| {
+            eprintln!("❌ {e}");
+            std::process::exit(2);
+        });
+        state.metrics.subscribe(sink);
+        println!("📤 Writing samples and timeline to InfluxDB at {url}");
+    }
+    if let Some(remote_write) = settings.remote_write.clone() {
+        let (url, every) = (remote_write.url.clone(), remote_write.interval_ms);
+        metrics::remote_write::start(remote_write, state.clone())
+            .unwrap_or_else(|e| {
+                eprintln!("❌ {e}");
+                std::process::exit(2);
+            });
+        println!("📤 Pushing Prometheus series to {url} every {every}ms");
+    }
+    if let Some(capacity) = settings.handler_cache {
+        println!("🗄️  Handler cache on: {capacity} entries per entity");
+    }
+    if let Some(dir) = &settings.runs_dir {
+        let loaded = state.archive.load();
+        println!("💾 Saving runs to {dir}/ ({loaded} loaded)");
+    }
+    if settings.runs_in_redis {
+        println!("💾 Sharing run history under bench:runs:* in Redis");
+    }
+
+    // ── 4. Background health prober ─────────────────────────────
+    if settings.probe_interval_ms > 0 {
+        tokio::spawn(prober::run(
+            state.redis.clone(),
+            probe,
+            state.load_running.clone(),
+            std::time::Duration::from_millis(settings.probe_interval_ms),
+        ));
+        println!(
+            "🩺 Background probe every {}ms",
+            settings.probe_interval_ms
+        );
+    }
+
+    // ── 5. Run queue ─────────────────────────────────────────────
+    tokio::spawn(run_queue::drive(state.clone()));
 
-    // ── 4. Build Axum router ─────────────────────────────────────
-    let app = server::create_router(state);
+    // ── 6. Build Axum router ─────────────────────────────────────
+    let app = server::create_router(state, settings.endpoint_concurrency);
 
-    // ── 5. Bind & serve ──────────────────────────────────────────
+    // ── 7. Bind & serve ──────────────────────────────────────────
     let addr = "0.0.0.0:3000";
     let listener = tokio::net::TcpListener::bind(addr)
         .await
@@ -62,6 +306,7 @@ async fn main() {
     println!("Dashboard       → http://localhost:3000");
     println!("Metrics SSE     → http://localhost:3000/api/metrics/stream");
     println!("Metrics JSON    → http://localhost:3000/api/metrics");
+    println!("Baseline probe  → http://localhost:3000/api/probe");
     println!();
 
     axum::serve(listener, app)
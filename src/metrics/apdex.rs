@@ -0,0 +1,67 @@
+use hdrhistogram::Histogram;
+use serde::{Deserialize, Serialize};
+
+use crate::validation::Violations;
+
+/// Latency targets a run's Apdex is scored against. A request is
+/// satisfied at or under `satisfied_us`, tolerating at or under
+/// `tolerating_us` and frustrated above it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ApdexTargets {
+    pub satisfied_us: u64,
+    /// Defaults to 4 × `satisfied_us`, as the Apdex spec does
+    #[serde(default)]
+    pub tolerating_us: Option<u64>,
+}
+
+/// One layer's score against `ApdexTargets`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApdexScore {
+    /// (satisfied + tolerating / 2) / count: 1.0 = every request
+    /// satisfied, 0.0 = every request frustrated
+    pub score: f64,
+    /// Share of requests at or under `satisfied_us`, 0–100
+    pub within_target_pct: f64,
+    pub satisfied: u64,
+    pub tolerating: u64,
+    pub frustrated: u64,
+}
+
+impl ApdexTargets {
+    /// Field paths are relative to the targets object.
+    pub fn check(&self, problems: &mut Violations) {
+        if self.satisfied_us == 0 {
+            problems.push("satisfied_us", "satisfied_us must be ≥ 1");
+        }
+        if self.tolerating_us.is_some_and(|t| t < self.satisfied_us) {
+            problems.push(
+                "tolerating_us",
+                "tolerating_us must be ≥ satisfied_us",
+            );
+        }
+    }
+
+    pub fn tolerating_us(&self) -> u64 {
+        self.tolerating_us
+            .unwrap_or(self.satisfied_us.saturating_mul(4))
+    }
+
+    /// Score every value in `hist`; `None` when it is empty.
+    pub fn score(&self, hist: &Histogram<u64>) -> Option<ApdexScore> {
+        let count = hist.len();
+        if count == 0 {
+            return None;
+        }
+        let satisfied = hist.count_between(0, self.satisfied_us);
+        let tolerating =
+            hist.count_between(0, self.tolerating_us()) - satisfied;
+        Some(ApdexScore {
+            score: (satisfied as f64 + tolerating as f64 / 2.0)
+                / count as f64,
+            within_target_pct: 100.0 * satisfied as f64 / count as f64,
+            satisfied,
+            tolerating,
+            frustrated: count - satisfied - tolerating,
+        })
+    }
+}
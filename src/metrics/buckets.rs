@@ -0,0 +1,136 @@
+use hdrhistogram::Histogram;
+use serde::{Deserialize, Serialize};
+
+use super::collector::{DIST_BOUNDARIES, HIST_HIGH};
+use crate::validation::Violations;
+
+/// Most bounds a `custom` or `auto` layout may have
+pub const MAX_BUCKETS: usize = 64;
+
+/// Steps per doubling of the grid `auto` bounds are picked from
+const GRID_STEPS_PER_DOUBLING: f64 = 4.0;
+
+/// How latency is bucketed for `distribution` and `heatmap`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum LatencyBuckets {
+    /// `DIST_BOUNDARIES`: 25 μs to 50 ms, sized for a local Redis
+    #[default]
+    Localhost,
+    /// Upper bounds in μs, strictly ascending
+    Custom { bounds_us: Vec<u64> },
+    /// About `buckets` log-scale buckets spanning the run's observed
+    /// end-to-end min and max, recomputed every snapshot
+    Auto {
+        #[serde(default = "default_auto_buckets")]
+        buckets: usize,
+    },
+}
+
+fn default_auto_buckets() -> usize {
+    16
+}
+
+impl LatencyBuckets {
+    /// Field paths are relative to the `latency_buckets` object.
+    pub fn check(&self, problems: &mut Violations) {
+        match self {
+            Self::Localhost => {}
+            Self::Custom { bounds_us } => {
+                problems.range("bounds_us", bounds_us.len(), 1, MAX_BUCKETS);
+                if bounds_us.first().is_some_and(|&b| b == 0) {
+                    problems.push("bounds_us", "bounds_us must be ≥ 1");
+                }
+                if bounds_us.windows(2).any(|w| w[0] >= w[1]) {
+                    problems.push(
+                        "bounds_us",
+                        "bounds_us must be strictly ascending",
+                    );
+                }
+            }
+            Self::Auto { buckets } => {
+                problems.range("buckets", *buckets, 2, MAX_BUCKETS);
+            }
+        }
+    }
+
+    /// Bounds timeline windows are counted against. `auto` counts on a
+    /// fine log-scale grid, regrouped into its bounds every snapshot,
+    /// so columns closed earlier stay exact as the range grows.
+    pub fn grid(&self) -> Vec<u64> {
+        match self {
+            Self::Localhost => DIST_BOUNDARIES.to_vec(),
+            Self::Custom { bounds_us } => bounds_us.clone(),
+            Self::Auto { .. } => log_grid(),
+        }
+    }
+
+    /// Bounds to report for a run whose end-to-end latency is `hist`.
+    pub fn bounds(&self, hist: &Histogram<u64>) -> Vec<u64> {
+        let Self::Auto { buckets } = *self else {
+            return self.grid();
+        };
+        let grid = log_grid();
+        if hist.is_empty() {
+            return grid;
+        }
+        // Grid edges closing the buckets that hold the min and the max
+        let lo = grid.partition_point(|&edge| edge < hist.min());
+        let hi = grid
+            .partition_point(|&edge| edge < hist.max())
+            .min(grid.len() - 1);
+        let step = (hi - lo + 1).div_ceil(buckets).max(1);
+        let mut bounds: Vec<u64> =
+            grid[lo..=hi].iter().step_by(step).copied().collect();
+        if bounds.last() != Some(&grid[hi]) {
+            bounds.push(grid[hi]);
+        }
+        bounds
+    }
+}
+
+/// Edges 2^(i/4) from 1 μs up to the histogram's highest value,
+/// rounded and deduplicated.
+fn log_grid() -> Vec<u64> {
+    let mut grid: Vec<u64> = Vec::new();
+    let mut i = 0.0;
+    loop {
+        let edge = 2f64.powf(i / GRID_STEPS_PER_DOUBLING).round() as u64;
+        if edge >= HIST_HIGH {
+            grid.push(HIST_HIGH);
+            return grid;
+        }
+        if grid.last() != Some(&edge) {
+            grid.push(edge);
+        }
+        i += 1.0;
+    }
+}
+
+/// Count `hist`'s values per bucket of `bounds`, overflow last.
+pub fn bucket_counts(hist: &Histogram<u64>, bounds: &[u64]) -> Vec<u64> {
+    let mut counts = vec![0u64; bounds.len() + 1];
+    for iv in hist.iter_recorded() {
+        // First bound ≥ the value; past the end means overflow
+        let idx = bounds.partition_point(|&b| b < iv.value_iterated_to());
+        counts[idx] += iv.count_at_value();
+    }
+    counts
+}
+
+/// Re-bucket `counts`, taken against `grid`, into `bounds`. Exact when
+/// every bound is a grid edge, as `auto` bounds are.
+pub fn regroup(counts: &[u64], grid: &[u64], bounds: &[u64]) -> Vec<u64> {
+    if grid == bounds {
+        return counts.to_vec();
+    }
+    let mut out = vec![0u64; bounds.len() + 1];
+    for (i, &n) in counts.iter().enumerate() {
+        let idx = match grid.get(i) {
+            Some(&edge) => bounds.partition_point(|&b| b < edge),
+            None => bounds.len(),
+        };
+        out[idx] += n;
+    }
+    out
+}
@@ -1,25 +1,52 @@
-use std::collections::VecDeque;
-use std::time::Instant;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use hdrhistogram::Histogram;
 use parking_lot::Mutex;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use super::percentiles::PercentileSet;
-use super::Sample;
+use super::apdex::ApdexScore;
+use super::buckets::{bucket_counts, LatencyBuckets};
+use super::percentiles::{PercentileMode, PercentileSet};
+use super::sink::{
+    hist_bytes, new_hist, new_size_hist, ErrorSink, Gauges, Jitter, LatencySink,
+    RecentFeed, KeyspaceSink, Outliers, RollingSink, SampleEvent,
+    SampleSink, SlowLog, Subscription, TimelineSink,
+};
+use super::prometheus::{Family, PrometheusSink};
+use super::{ErrorCategory, Sample};
+use crate::events::ConfigChange;
+use crate::handlers::benchmark::BenchmarkConfig;
 
 // ─── Configuration ───────────────────────────────────────────────
 
-/// How many individual request records we keep for the live feed
-const MAX_RECENT_SAMPLES: usize = 200;
+/// HdrHistogram range: 1 μs → 60 s, 3 significant figures
+pub(super) const HIST_LOW: u64 = 1;
+pub(super) const HIST_HIGH: u64 = 60_000_000;
+pub(super) const HIST_SIGFIG: u8 = 3;
 
-/// Aggregate timeline resolution (one point per window)
-const TIMELINE_WINDOW_MS: u64 = 500;
+/// Per-worker histograms are coarser so 500 workers stay small
+const WORKER_HIST_SIGFIG: u8 = 2;
 
-/// HdrHistogram range: 1 μs → 60 s, 3 significant figures
-const HIST_LOW: u64 = 1;
-const HIST_HIGH: u64 = 60_000_000;
-const HIST_SIGFIG: u8 = 3;
+/// Default upper bounds (μs) of the distribution and heatmap buckets;
+/// a last, open-ended bucket takes everything above. Covers the typical
+/// localhost Redis latency range with good resolution.
+pub(super) const DIST_BOUNDARIES: [u64; 16] = [
+    25, 50, 100, 150, 200, 300, 400, 500, 750, 1_000, 1_500, 2_000, 3_000,
+    5_000, 10_000, 50_000,
+];
+
+/// Closed timeline windows kept; about 15 hours at the default tiers
+/// before the coarsest one widens
+pub const DEFAULT_MAX_TIMELINE_POINTS: usize = 2_400;
+
+/// Individual request records kept for the live feed
+pub const DEFAULT_MAX_RECENT_SAMPLES: usize = 200;
+
+/// Requests kept in the slow log
+pub const DEFAULT_MAX_SLOW_REQUESTS: usize = 500;
 
 // ─── Public types ────────────────────────────────────────────────
 
@@ -27,6 +54,28 @@ const HIST_SIGFIG: u8 = 3;
 /// Handlers call `record()`, the SSE stream calls `snapshot()`.
 pub struct MetricsCollector {
     inner: Mutex<Inner>,
+    /// Subscribed for the whole process, so Prometheus sees counters
+    /// that only go up
+    prometheus: PrometheusSink,
+}
+
+/// Caps on what the collector keeps as a run goes on; the oldest
+/// entries are evicted past them.
+#[derive(Debug, Clone, Copy)]
+pub struct MetricsLimits {
+    pub max_timeline_points: usize,
+    pub max_recent_samples: usize,
+    pub max_slow_requests: usize,
+}
+
+impl Default for MetricsLimits {
+    fn default() -> Self {
+        Self {
+            max_timeline_points: DEFAULT_MAX_TIMELINE_POINTS,
+            max_recent_samples: DEFAULT_MAX_RECENT_SAMPLES,
+            max_slow_requests: DEFAULT_MAX_SLOW_REQUESTS,
+        }
+    }
 }
 
 /// A single entry in the live request feed.
@@ -39,91 +88,625 @@ pub struct SampleRecord {
     pub total_us: u64,
     pub is_read: bool,
     pub success: bool,
+    pub timed_out: bool,
+    /// Redis or client error text, or `key not found` for a miss;
+    /// `null` on success
+    pub error: Option<String>,
+    /// HTTP status of a request made through the API; `null` for
+    /// load-generator traffic
+    pub status: Option<u16>,
+    pub payload_bytes: u64,
+    /// `null` when no local cache sat in front of the request
+    pub cache_hit: Option<bool>,
 }
 
-/// One aggregated point on the timeline chart (per 500 ms window).
+/// Failed requests by category; they add up to `total_errors`.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ErrorCounts {
+    pub not_found: u64,
+    pub connection: u64,
+    pub timeout: u64,
+    pub wrong_type: u64,
+    pub parse: u64,
+    pub other: u64,
+}
+
+impl ErrorCounts {
+    pub fn get_mut(&mut self, category: ErrorCategory) -> &mut u64 {
+        match category {
+            ErrorCategory::NotFound => &mut self.not_found,
+            ErrorCategory::Connection => &mut self.connection,
+            ErrorCategory::Timeout => &mut self.timeout,
+            ErrorCategory::WrongType => &mut self.wrong_type,
+            ErrorCategory::Parse => &mut self.parse,
+            ErrorCategory::Other => &mut self.other,
+        }
+    }
+}
+
+/// One failed request, kept for the snapshot's `recent_errors`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorRecord {
+    pub timestamp_ms: u64,
+    pub endpoint: String,
+    pub op: &'static str,
+    pub category: ErrorCategory,
+    /// Redis or client error text; `key not found` for misses
+    pub message: String,
+}
+
+/// One request with everything known about it, as the slow log and
+/// the run's outliers keep it.
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestDetail {
+    pub timestamp_ms: u64,
+    pub endpoint: String,
+    pub op: &'static str,
+    /// Redis key touched; `null` for keyless commands
+    pub key: Option<String>,
+    pub redis_us: u64,
+    pub rust_us: u64,
+    pub total_us: u64,
+    /// Open-loop only: how late the request was sent
+    pub schedule_lag_us: u64,
+    pub is_read: bool,
+    pub success: bool,
+    pub timed_out: bool,
+    /// `null` on success
+    pub error_category: Option<ErrorCategory>,
+    /// Redis or client error text; `null` on success
+    pub error: Option<String>,
+    /// HTTP status; `null` for load-generator requests
+    pub status: Option<u16>,
+    pub payload_bytes: u64,
+    /// `null` when no local cache was consulted
+    pub cache_hit: Option<bool>,
+    /// Load workers running when it was recorded
+    pub active_workers: u32,
+}
+
+/// Body of `GET /api/metrics/slow`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SlowRequests {
+    /// End-to-end latency a request must exceed to be logged
+    pub threshold_us: u64,
+    /// Slow requests this run, including those evicted from `requests`
+    pub total: u64,
+    /// The latest slow requests, oldest first
+    pub requests: Vec<RequestDetail>,
+}
+
+/// One aggregated point on the timeline chart (per 500 ms window, wider
+/// once older windows have been downsampled).
 #[derive(Debug, Clone, Serialize)]
 pub struct TimelinePoint {
     pub timestamp_ms: u64,
+    /// Width of the window this point covers
+    pub window_ms: u64,
     pub avg_redis_us: f64,
     pub avg_rust_us: f64,
     pub avg_total_us: f64,
+    /// End-to-end percentiles within the window (HDR method). Once
+    /// downsampled, the highest of the merged windows' values.
+    pub p50_total_us: u64,
+    pub p95_total_us: u64,
+    pub p99_total_us: u64,
     pub count: u64,
+    /// Requests per second over the window; the window still filling
+    /// is measured up to its latest request
+    pub requests_per_sec: f64,
+    /// `requests_per_sec` split by kind; cache hits count as reads
+    pub reads_per_sec: f64,
+    pub writes_per_sec: f64,
+    /// Failed requests during the window, by kind
+    pub read_errors: u64,
+    pub write_errors: u64,
+    /// Most load-generator workers active at any point in the window
+    pub active_workers: u32,
+    /// Deepest job queue seen during the window
+    pub queue_depth: u64,
+    /// Payload bytes read and written during the window
+    pub payload_bytes: u64,
+    /// Largest combined client output buffer seen during the window
+    pub client_omem_bytes: u64,
+    /// Requests that hit the per-op timeout during the window
+    pub timeouts: u64,
+    /// Highest server-wide key expiry rate sampled during the window
+    pub expired_per_sec: f64,
+    /// Id of the first snapshot carrying this point in its final form;
+    /// `null` while the window is still filling
+    pub finalized_in: Option<u64>,
 }
 
-/// A bucket in the latency distribution histogram.
+/// Summary of one `CLIENT LIST` sample, filtered to this tool's
+/// connections.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ClientStats {
+    pub connections: u32,
+    /// Output buffer memory across all connections (`omem`)
+    pub omem_bytes: u64,
+    /// Largest single connection's output buffer
+    pub omem_max_bytes: u64,
+    /// Longest reply list waiting on one connection (`oll`)
+    pub oll_max: u64,
+    /// Age of the oldest connection
+    pub oldest_age_secs: u64,
+}
+
+/// Server-wide key expiry during the run, from `INFO stats`.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ExpiryStats {
+    /// Keys expired since the run started
+    pub expired_keys: u64,
+    /// Rate over the latest one-second sample, and the highest seen
+    pub expired_per_sec: f64,
+    pub expired_per_sec_peak: f64,
+    /// Active-expiry cycles that stopped at their CPU time limit
+    /// (`expired_time_cap_reached_count`, Redis ≥ 6.0)
+    pub cycle_cap_hits: u64,
+    /// Server's estimate of already-expired keys still in memory
+    /// (`expired_stale_perc`)
+    pub stale_pct: f64,
+}
+
+/// Reads that found their key, as this bench saw them and as the
+/// server counts them.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct KeyspaceStats {
+    /// This bench's reads that found their key, and those that got
+    /// nothing back; failed calls count as neither
+    pub hits: u64,
+    pub misses: u64,
+    /// hits / (hits + misses), 0–100
+    pub hit_pct: f64,
+    /// The same split per endpoint, for endpoints that read
+    pub endpoints: BTreeMap<String, KeyHits>,
+    /// `keyspace_hits` and `keyspace_misses` from `INFO stats` since the
+    /// run started; every client of the server counts
+    pub server_hits: u64,
+    pub server_misses: u64,
+    pub server_hit_pct: f64,
+}
+
+/// One endpoint's reads that found their key.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct KeyHits {
+    pub hits: u64,
+    pub misses: u64,
+    /// hits / (hits + misses), 0–100
+    pub hit_pct: f64,
+}
+
+impl KeyHits {
+    /// Count one read: found (`hit`) or absent.
+    pub fn add(&mut self, hit: bool) {
+        if hit {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+        self.hit_pct = hit_pct(self.hits, self.misses);
+    }
+}
+
+fn hit_pct(hits: u64, misses: u64) -> f64 {
+    match hits + misses {
+        0 => 0.0,
+        reads => 100.0 * hits as f64 / reads as f64,
+    }
+}
+
+/// How evenly the load-generator workers shared the work.
 #[derive(Debug, Clone, Serialize)]
+pub struct Fairness {
+    /// Jain's index over per-worker ops/sec: 1.0 = perfectly even,
+    /// 1/n = one worker did everything
+    pub jain_index: f64,
+    pub min_ops_per_sec: f64,
+    pub max_ops_per_sec: f64,
+    pub workers: Vec<WorkerShare>,
+}
+
+/// One worker's share of a run.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerShare {
+    pub worker_id: u32,
+    pub ops: u64,
+    pub errors: u64,
+    /// Over the worker's own active span, so ramped workers that joined
+    /// late still compare fairly
+    pub ops_per_sec: f64,
+    /// Per-iteration latency; a whole batch when pipelining
+    pub latency: PercentileSet,
+}
+
+/// Which server-side client a load-generator worker is running as.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerClient {
+    pub worker_id: u32,
+    /// `CLIENT SETNAME` value, `bench-worker-<id>`
+    pub name: String,
+    /// `CLIENT ID` of the worker's current connection
+    pub client_id: u64,
+    /// Times the connection came back with a new id
+    pub reconnects: u32,
+}
+
+/// Where a scenario stage began on the timeline.
+#[derive(Debug, Clone, Serialize)]
+pub struct StageMark {
+    pub timestamp_ms: u64,
+    pub name: String,
+}
+
+/// Where a mid-run setting change landed on the timeline.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeMark {
+    pub timestamp_ms: u64,
+    #[serde(flatten)]
+    pub change: ConfigChange,
+}
+
+/// Percentiles over one rolling window.
+#[derive(Debug, Clone, Serialize)]
+pub struct RollingSet {
+    pub e2e: PercentileSet,
+    /// Redis round trip; local cache hits are left out
+    pub redis: PercentileSet,
+}
+
+/// One Redis command's share of the run.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandStats {
+    pub calls: u64,
+    /// Calls that failed or found nothing; `PIPELINE` leaves these to
+    /// the commands in each batch
+    pub errors: u64,
+    pub timeouts: u64,
+    pub calls_per_sec: f64,
+    /// Redis round trip
+    pub latency: PercentileSet,
+    /// `payload_bytes` moved per second over the stage; `PIPELINE`
+    /// leaves this to the commands in each batch
+    pub bytes_per_sec: f64,
+    /// Distribution of each call's `payload_bytes`
+    pub payload: SizeSet,
+}
+
+/// Distribution of payload sizes in bytes, or of decode times in
+/// nanoseconds (HDR method, 1%). Unlike a `PercentileSet` it is never
+/// converted by `?units=ms`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SizeSet {
+    pub min: u64,
+    pub max: u64,
+    pub mean: f64,
+    pub p50: u64,
+    pub p95: u64,
+    pub p99: u64,
+    pub count: u64,
+}
+
+impl SizeSet {
+    fn from_histogram(hist: &Histogram<u64>) -> Self {
+        if hist.is_empty() {
+            return Self::default();
+        }
+        Self {
+            min: hist.min(),
+            max: hist.max(),
+            mean: hist.mean(),
+            p50: hist.value_at_quantile(0.50),
+            p95: hist.value_at_quantile(0.95),
+            p99: hist.value_at_quantile(0.99),
+            count: hist.len(),
+        }
+    }
+}
+
+/// A bucket in the latency distribution histogram.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DistBucket {
     pub range_start_us: u64,
     pub range_end_us: u64,
     pub count: u64,
 }
 
+/// Latency over time: one column per timeline window, one count per
+/// `distribution` bucket.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Heatmap {
+    /// Upper bound of each bucket; `counts` has one more entry, for
+    /// everything above the last bound
+    pub bounds_us: Vec<u64>,
+    pub columns: Vec<HeatmapColumn>,
+}
+
+/// One timeline window of the heatmap.
+#[derive(Debug, Clone, Serialize)]
+pub struct HeatmapColumn {
+    /// Same time base and width as the matching `timeline` point
+    pub timestamp_ms: u64,
+    pub window_ms: u64,
+    /// End-to-end requests per latency bucket
+    pub counts: Vec<u64>,
+}
+
 /// Complete snapshot shipped to the dashboard on every SSE tick.
 #[derive(Debug, Clone, Serialize)]
 pub struct MetricsSnapshot {
+    /// Increases by one per snapshot, across runs and across the
+    /// polling and SSE endpoints
+    pub snapshot_id: u64,
+    /// Run these numbers belong to; unset before the first run
+    pub run_id: Option<String>,
+    /// Config of the stage that produced these numbers, as resolved
+    /// at start and with any `/adjust` changes applied; unset until a
+    /// stage starts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<BenchmarkConfig>,
+
     // Percentile breakdowns per measurement layer
     pub redis_read: PercentileSet,
     pub redis_write: PercentileSet,
     pub rust_overhead: PercentileSet,
     pub e2e: PercentileSet,
+    /// `e2e` and Redis round trip over the last 10 s and 60 s only,
+    /// keyed `last_10s` and `last_60s`
+    pub rolling: BTreeMap<String, RollingSet>,
+    /// HTTP only: middleware wall time minus handler-reported total
+    /// (extraction, routing, response serialization)
+    pub framework_overhead: PercentileSet,
+    /// Redis latency per command (GET, HGETALL, SET, HSET, …); reads
+    /// served from a local cache are left out
+    pub ops: BTreeMap<String, PercentileSet>,
+    /// Calls, failures, rate and latency per Redis command, whatever
+    /// endpoint issued it. `PIPELINE` counts pipelined batches.
+    pub per_command: BTreeMap<String, CommandStats>,
+    /// Pipelined runs: Redis round trip of a whole batch
+    pub pipeline_batch: PercentileSet,
+    /// Pipelined runs: batch round trip divided by its depth
+    pub pipeline_per_command: PercentileSet,
+    /// MGET round trip divided by its batch size (the whole batch is
+    /// under `ops.MGET`)
+    pub mget_per_key: PercentileSet,
+    /// MSET round trip divided by its batch size
+    pub mset_per_key: PercentileSet,
+    /// Time for one SCAN cursor to go from 0 back to 0
+    pub scan_iteration: PercentileSet,
+    /// `pool` connection mode: wait to check out a connection
+    pub pool_wait: PercentileSet,
+    /// `per_op` connection mode: opening the connection (TCP, TLS and
+    /// RESP setup)
+    pub connect: PercentileSet,
+    /// `per_op` connection mode: AUTH on the new connection
+    pub auth: PercentileSet,
+    /// PUBLISH → subscriber receive latency
+    pub pubsub: PercentileSet,
+    /// XADD → XREADGROUP delivery latency
+    pub stream_delivery: PercentileSet,
+    /// Job queue: LPUSH round trip
+    pub queue_enqueue: PercentileSet,
+    /// Job queue: LPUSH → BRPOP return (time spent queued + delivery)
+    pub queue_dequeue: PercentileSet,
+    /// Job queue: how long a consumer blocked in BRPOP before a job came
+    pub queue_wait: PercentileSet,
+    /// End-to-end latency of reads served from a local cache (no Redis)
+    pub local_cache_hit: PercentileSet,
+    /// End-to-end latency of reads that checked a local cache and missed
+    pub local_cache_miss: PercentileSet,
+    /// `e2e` measured from the intended send time (open-loop runs);
+    /// identical to `e2e` in closed-loop mode
+    pub e2e_corrected: PercentileSet,
+    /// Apdex of each layer above against `config.apdex`, keyed like
+    /// the layers; empty when no targets are set
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub apdex: BTreeMap<String, ApdexScore>,
 
     // Counters
     pub total_requests: u64,
     pub total_errors: u64,
+    /// Errors that were per-op timeouts (a subset of `total_errors`)
+    pub total_timeouts: u64,
+    /// `total_errors` split by what went wrong
+    pub errors: ErrorCounts,
     pub total_reads: u64,
     pub total_writes: u64,
+    pub total_cache_hits: u64,
+    pub total_cache_misses: u64,
+    /// Value bytes returned by reads and sent by writes
+    pub total_bytes_read: u64,
+    pub total_bytes_written: u64,
+    /// Requests per second over the last 4–5 s, so it follows load
+    /// changes
     pub requests_per_sec: f64,
+    /// Requests per second over the whole stage
+    pub avg_requests_per_sec: f64,
+    /// Over the whole stage
+    pub read_bytes_per_sec: f64,
+    pub write_bytes_per_sec: f64,
+    /// Payload bytes per second received from Redis (reads) and sent
+    /// to it (writes), over the same 4–5 s as `requests_per_sec`
+    pub bytes_in_per_sec: f64,
+    pub bytes_out_per_sec: f64,
     pub elapsed_secs: f64,
+    /// Unix epoch (ms) of the first sample — anchors `timestamp_ms` values
+    pub started_at_ms: Option<i64>,
+    /// Cumulative time workers spent backing off after Redis errors
+    pub total_backoff_ms: u64,
+    /// Requests rejected by per-endpoint concurrency limits
+    pub shed_requests: BTreeMap<String, u64>,
+    /// Load-generator workers currently issuing requests
+    pub active_workers: u32,
+    /// Stream entries delivered to the consumer group but not yet acked
+    pub stream_pending: u64,
+    /// Highest `stream_pending` seen during the run
+    pub stream_pending_max: u64,
+    /// Jobs waiting in the queue (LLEN), sampled every second
+    pub queue_depth: u64,
+    pub queue_depth_max: u64,
+    /// BRPOP calls that timed out with the queue empty
+    pub queue_empty_polls: u64,
+    /// Full SCAN iterations finished, and the keys they returned
+    /// (duplicates included)
+    pub scan_iterations: u64,
+    pub scan_keys: u64,
+    /// `per_op` connection mode: connections opened, and attempts
+    /// that failed
+    pub connects: u64,
+    pub connect_errors: u64,
+    /// This tool's connections as `CLIENT LIST` last reported them
+    pub clients: ClientStats,
+    /// Highest `clients.omem_bytes` seen during the run
+    pub client_omem_peak_bytes: u64,
+    /// Time to convert a read's raw reply into Rust values, by RESP
+    /// reply type (`array`, `map`, …)
+    pub reply_decode_ns: BTreeMap<String, SizeSet>,
+    pub expiry: ExpiryStats,
+    pub keyspace: KeyspaceStats,
+    /// Named worker connections (`named_workers`), by worker id
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub workers: Vec<WorkerClient>,
+    /// Per-worker throughput and latency; absent until a worker runs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fairness: Option<Fairness>,
 
     // Visual data
     pub recent_samples: Vec<SampleRecord>,
+    /// The latest failed requests with their error text, oldest first
+    pub recent_errors: Vec<ErrorRecord>,
+    /// The run's slowest requests end to end, slowest first
+    pub outliers: Vec<RequestDetail>,
     pub timeline: Vec<TimelinePoint>,
+    /// Width of the timeline windows now being filled: the config's
+    /// `timeline_window_ms`
+    pub timeline_window_ms: u64,
+    /// How the percentiles above were computed
+    pub percentile_mode: PercentileMode,
+    /// Scenario stage boundaries, in the timeline's time base
+    pub stages: Vec<StageMark>,
+    /// Mid-run setting changes, in the timeline's time base
+    pub changes: Vec<ChangeMark>,
     pub distribution: Vec<DistBucket>,
+    /// `timeline` windows bucketed like `distribution`
+    pub heatmap: Heatmap,
+    /// Estimated bytes the collector holds: histograms, timeline and
+    /// feeds, without allocator overhead
+    pub metrics_memory_bytes: u64,
+    /// Samples subscribed exporters missed by falling too far behind,
+    /// since the process started
+    pub exporter_dropped: u64,
 }
 
 // ─── Internal state ──────────────────────────────────────────────
 
 struct Inner {
-    // One HdrHistogram per measurement layer
-    redis_read_hist: Histogram<u64>,
-    redis_write_hist: Histogram<u64>,
-    rust_overhead_hist: Histogram<u64>,
-    e2e_hist: Histogram<u64>,
+    // The snapshot's sinks, fed by every `record` call
+    latency: LatencySink,
+    timeline: TimelineSink,
+    feed: RecentFeed,
+    rolling: RollingSink,
+    errors: ErrorSink,
+    slow: SlowLog,
+    outliers: Outliers,
+    keyspace: KeyspaceSink,
+    // Subscribed outputs, carried over by `reset` and `next_phase`
+    outputs: Vec<Box<dyn SampleSink + Send>>,
+    /// Shared with every subscription
+    exporter_dropped: Arc<AtomicU64>,
+
+    // One HdrHistogram per other measurement layer
+    framework_hist: Histogram<u64>,
+    pipeline_batch_hist: Histogram<u64>,
+    pipeline_per_cmd_hist: Histogram<u64>,
+    mget_per_key_hist: Histogram<u64>,
+    mset_per_key_hist: Histogram<u64>,
+    scan_iteration_hist: Histogram<u64>,
+    pool_wait_hist: Histogram<u64>,
+    connect_hist: Histogram<u64>,
+    auth_hist: Histogram<u64>,
+    pubsub_hist: Histogram<u64>,
+    stream_hist: Histogram<u64>,
+    queue_enqueue_hist: Histogram<u64>,
+    queue_dequeue_hist: Histogram<u64>,
+    queue_wait_hist: Histogram<u64>,
+    /// Reply decode time in ns, by RESP type
+    decode_hists: BTreeMap<&'static str, Histogram<u64>>,
 
     // Counters
-    total_requests: u64,
-    total_errors: u64,
-    total_reads: u64,
-    total_writes: u64,
+    total_backoff_us: u64,
+    shed_requests: BTreeMap<String, u64>,
+    active_workers: u32,
+    stream_pending: u64,
+    stream_pending_max: u64,
+    queue_depth: u64,
+    queue_depth_max: u64,
+    queue_empty_polls: u64,
+    scan_iterations: u64,
+    scan_keys: u64,
+    connects: u64,
+    connect_errors: u64,
+    clients: ClientStats,
+    client_omem_peak: u64,
+    expiry: ExpiryStats,
+    /// `INFO stats` keyspace counters since the run started
+    server_hits: u64,
+    server_misses: u64,
+    workers: BTreeMap<u32, WorkerClient>,
+    worker_loads: BTreeMap<u32, WorkerLoad>,
+
+    // Timeline annotations
+    stages: Vec<StageMark>,
+    changes: Vec<ChangeMark>,
 
-    // Rolling window of recent individual requests
-    recent_samples: VecDeque<SampleRecord>,
+    percentile_mode: PercentileMode,
+    /// How `distribution` and `heatmap` bucket latency
+    buckets: LatencyBuckets,
 
-    // Timeline aggregation
-    timeline: Vec<TimelinePoint>,
-    current_window: Option<WindowAccumulator>,
+    /// Running stage's config, kept after it ends
+    config: Option<BenchmarkConfig>,
+    limits: MetricsLimits,
+    run_id: Option<String>,
 
     // Wall-clock anchor for elapsed time
     start_time: Option<Instant>,
+    start_epoch_ms: Option<i64>,
+
+    /// Id handed to the most recent snapshot
+    last_snapshot_id: u64,
+    /// Requests recorded in earlier phases of this run, before
+    /// `next_phase` reset the histograms
+    earlier_phase_requests: u64,
 }
 
-/// Running totals for the current 500 ms timeline window.
-struct WindowAccumulator {
-    window_start_ms: u64,
-    redis_sum: u64,
-    rust_sum: u64,
-    total_sum: u64,
-    count: u64,
+/// Running totals for one load-generator worker.
+struct WorkerLoad {
+    /// Start of its first iteration and end of its latest
+    first: Instant,
+    last: Instant,
+    ops: u64,
+    errors: u64,
+    hist: Histogram<u64>,
 }
 
 // ─── MetricsCollector impl ───────────────────────────────────────
 
 impl MetricsCollector {
-    pub fn new() -> Self {
-        Self {
-            inner: Mutex::new(Inner::new()),
-        }
+    pub fn new(limits: MetricsLimits) -> Self {
+        let collector = Self {
+            inner: Mutex::new(Inner::new(limits)),
+            prometheus: PrometheusSink::default(),
+        };
+        collector.subscribe(collector.prometheus.clone());
+        collector
+    }
+
+    /// Feed every sample from now on to `sink`, on a task of its own.
+    pub fn subscribe(&self, sink: impl SampleSink + Send + 'static) {
+        let mut inner = self.inner.lock();
+        let dropped = inner.exporter_dropped.clone();
+        let subscription = Subscription::spawn(sink, dropped);
+        inner.outputs.push(Box::new(subscription));
     }
 
     /// Record a single request observation. Called from every handler.
@@ -131,228 +714,796 @@ impl MetricsCollector {
         self.inner.lock().record(sample);
     }
 
+    /// Record one pipelined round trip of `depth` commands.
+    pub fn record_pipeline(&self, batch_us: u64, depth: u32) {
+        let mut inner = self.inner.lock();
+        let _ = inner.pipeline_batch_hist.record(batch_us.max(1));
+        let _ = inner
+            .pipeline_per_cmd_hist
+            .record((batch_us / depth.max(1) as u64).max(1));
+    }
+
+    /// Record one MGET or MSET round trip covering `keys` keys.
+    pub fn record_batch(&self, is_write: bool, batch_us: u64, keys: u32) {
+        let per_key_us = (batch_us / keys.max(1) as u64).max(1);
+        let mut inner = self.inner.lock();
+        let hist = if is_write {
+            &mut inner.mset_per_key_hist
+        } else {
+            &mut inner.mget_per_key_hist
+        };
+        let _ = hist.record(per_key_us);
+    }
+
+    /// Record one message's PUBLISH → receive latency (subscriber task).
+    pub fn record_pubsub(&self, latency_us: u64) {
+        let _ = self.inner.lock().pubsub_hist.record(latency_us.max(1));
+    }
+
+    /// Record one stream entry's XADD → XREADGROUP delivery latency.
+    pub fn record_stream_delivery(&self, latency_us: u64) {
+        let _ = self.inner.lock().stream_hist.record(latency_us.max(1));
+    }
+
+    /// Latest size of the consumer group's pending-entries list.
+    pub fn set_stream_pending(&self, pending: u64) {
+        let mut inner = self.inner.lock();
+        inner.stream_pending = pending;
+        inner.stream_pending_max = inner.stream_pending_max.max(pending);
+    }
+
+    /// Record one successful LPUSH round trip.
+    pub fn record_queue_enqueue(&self, latency_us: u64) {
+        let _ = self.inner.lock().queue_enqueue_hist.record(latency_us.max(1));
+    }
+
+    /// Record one popped job: time since LPUSH and time BRPOP blocked.
+    pub fn record_queue_dequeue(&self, dequeue_us: u64, wait_us: u64) {
+        let mut inner = self.inner.lock();
+        let _ = inner.queue_dequeue_hist.record(dequeue_us.max(1));
+        let _ = inner.queue_wait_hist.record(wait_us.max(1));
+    }
+
+    /// One SCAN cursor completed a full pass over the keyspace.
+    pub fn record_scan_iteration(&self, duration_us: u64, keys: u64) {
+        let mut inner = self.inner.lock();
+        // A pass can outlast the histogram's range on a big keyspace
+        inner.scan_iteration_hist.saturating_record(duration_us.max(1));
+        inner.scan_iterations += 1;
+        inner.scan_keys += keys;
+    }
+
+    /// Time spent converting a reply of RESP type `reply_type`.
+    pub fn record_decode(&self, reply_type: &'static str, decode_ns: u64) {
+        let mut inner = self.inner.lock();
+        let hist = inner
+            .decode_hists
+            .entry(reply_type)
+            .or_insert_with(new_size_hist);
+        hist.saturating_record(decode_ns.max(1));
+    }
+
+    /// Time a worker waited for a pooled connection.
+    pub fn record_pool_wait(&self, wait_us: u64) {
+        let _ = self.inner.lock().pool_wait_hist.record(wait_us.max(1));
+    }
+
+    /// A `per_op` connection was opened; `auth_us` is `None` when no
+    /// credentials are configured.
+    pub fn record_connect(&self, connect_us: u64, auth_us: Option<u64>) {
+        let mut inner = self.inner.lock();
+        let _ = inner.connect_hist.record(connect_us.max(1));
+        if let Some(auth_us) = auth_us {
+            let _ = inner.auth_hist.record(auth_us.max(1));
+        }
+        inner.connects += 1;
+    }
+
+    /// A `per_op` connection could not be opened or authenticated.
+    pub fn record_connect_error(&self) {
+        self.inner.lock().connect_errors += 1;
+    }
+
+    /// A BRPOP timed out with nothing to pop.
+    pub fn record_queue_empty_poll(&self) {
+        self.inner.lock().queue_empty_polls += 1;
+    }
+
+    /// Latest job-queue length (LLEN).
+    pub fn set_queue_depth(&self, depth: u64) {
+        let mut inner = self.inner.lock();
+        inner.queue_depth = depth;
+        inner.queue_depth_max = inner.queue_depth_max.max(depth);
+    }
+
+    /// Latest `CLIENT LIST` reading for this tool's connections.
+    pub fn set_clients(&self, clients: ClientStats) {
+        let mut inner = self.inner.lock();
+        inner.clients = clients;
+        inner.client_omem_peak =
+            inner.client_omem_peak.max(clients.omem_bytes);
+    }
+
+    /// One `INFO stats` interval: keys and time-capped expiry cycles
+    /// since the previous sample, plus the current rate and stale share.
+    pub fn record_expiry(
+        &self,
+        expired: u64,
+        cap_hits: u64,
+        per_sec: f64,
+        stale_pct: f64,
+    ) {
+        let mut inner = self.inner.lock();
+        let expiry = &mut inner.expiry;
+        expiry.expired_keys += expired;
+        expiry.cycle_cap_hits += cap_hits;
+        expiry.expired_per_sec = per_sec;
+        expiry.expired_per_sec_peak = expiry.expired_per_sec_peak.max(per_sec);
+        expiry.stale_pct = stale_pct;
+    }
+
+    /// One `INFO stats` interval's `keyspace_hits` and
+    /// `keyspace_misses`.
+    pub fn record_keyspace(&self, hits: u64, misses: u64) {
+        let mut inner = self.inner.lock();
+        inner.server_hits += hits;
+        inner.server_misses += misses;
+    }
+
+    /// A worker (re)named its connection; a new `client_id` means the
+    /// connection manager reconnected since the last call.
+    pub fn set_worker_client(&self, worker_id: u32, name: String, id: u64) {
+        let mut inner = self.inner.lock();
+        let worker = inner.workers.entry(worker_id).or_insert(WorkerClient {
+            worker_id,
+            name,
+            client_id: id,
+            reconnects: 0,
+        });
+        if worker.client_id != id {
+            worker.client_id = id;
+            worker.reconnects += 1;
+        }
+    }
+
+    /// Record framework overhead for one HTTP request (timing middleware).
+    pub fn record_framework(&self, framework_us: u64) {
+        let _ = self.inner.lock().framework_hist.record(framework_us.max(1));
+    }
+
+    /// `(total_requests, total_errors)` without building a snapshot.
+    pub fn counts(&self) -> (u64, u64) {
+        let inner = self.inner.lock();
+        (inner.latency.total_requests, inner.latency.total_errors)
+    }
+
+    /// Account time a worker slept after a failed Redis call.
+    pub fn record_backoff(&self, backoff_us: u64) {
+        self.inner.lock().total_backoff_us += backoff_us;
+    }
+
+    /// Count a request rejected by an endpoint concurrency limit.
+    pub fn record_shed(&self, endpoint: &str) {
+        *self
+            .inner
+            .lock()
+            .shed_requests
+            .entry(endpoint.to_owned())
+            .or_insert(0) += 1;
+    }
+
+    /// A load-generator worker joined the run (ramp profiles vary this).
+    pub fn worker_started(&self) {
+        self.inner.lock().active_workers += 1;
+    }
+
+    /// One worker loop iteration: `ops` commands (the pipeline depth, or
+    /// 1) that took `iteration_us` between them.
+    pub fn record_worker_op(
+        &self,
+        worker_id: u32,
+        iteration_us: u64,
+        ops: u32,
+        failed: bool,
+    ) {
+        let now = Instant::now();
+        let mut inner = self.inner.lock();
+        let load = inner.worker_loads.entry(worker_id).or_insert_with(|| {
+            WorkerLoad {
+                first: now - Duration::from_micros(iteration_us),
+                last: now,
+                ops: 0,
+                errors: 0,
+                hist: Histogram::<u64>::new_with_bounds(
+                    HIST_LOW,
+                    HIST_HIGH,
+                    WORKER_HIST_SIGFIG,
+                )
+                .expect("histogram creation"),
+            }
+        });
+        load.last = now;
+        load.ops += ops as u64;
+        if failed {
+            load.errors += ops as u64;
+        }
+        load.hist.saturating_record(iteration_us.max(1));
+    }
+
+    /// A load-generator worker retired or was stopped.
+    pub fn worker_stopped(&self) {
+        let mut inner = self.inner.lock();
+        inner.active_workers = inner.active_workers.saturating_sub(1);
+    }
+
+    /// Mark a setting change at the current timeline offset.
+    pub fn mark_change(&self, change: &ConfigChange) {
+        let mut inner = self.inner.lock();
+        let timestamp_ms = inner.anchor().elapsed().as_millis() as u64;
+        inner.changes.push(ChangeMark {
+            timestamp_ms,
+            change: change.clone(),
+        });
+    }
+
+    /// Mark the start of a scenario stage at the current timeline offset.
+    pub fn mark_stage(&self, name: &str) {
+        let mut inner = self.inner.lock();
+        let timestamp_ms = inner.anchor().elapsed().as_millis() as u64;
+        inner.stages.push(StageMark {
+            timestamp_ms,
+            name: name.to_owned(),
+        });
+    }
+
     /// Wipe all data — called when a new benchmark run starts.
     pub fn reset(&self) {
-        *self.inner.lock() = Inner::new();
+        self.inner.lock().renew();
+    }
+
+    /// Wipe all data for the next phase of the same run (experiment
+    /// phases, sweep steps); the run's request count carries over.
+    pub fn next_phase(&self) {
+        let mut inner = self.inner.lock();
+        let earlier = inner.earlier_phase_requests
+            + inner.latency.total_requests;
+        let run_id = inner.run_id.take();
+        inner.renew();
+        inner.earlier_phase_requests = earlier;
+        inner.run_id = run_id;
+    }
+
+    /// `(run id, run start as epoch ms, closed timeline windows)`, for
+    /// exporters that write each window once.
+    pub fn closed_timeline(
+        &self,
+    ) -> (Option<String>, Option<i64>, Vec<TimelinePoint>) {
+        let inner = self.inner.lock();
+        (
+            inner.run_id.clone(),
+            inner.start_epoch_ms,
+            inner.timeline.closed().to_vec(),
+        )
+    }
+
+    /// Every per-endpoint Prometheus series.
+    pub fn prometheus(&self) -> Vec<Family> {
+        self.prometheus.families()
+    }
+
+    /// `(requests so far in the whole run, active workers)` without
+    /// building a snapshot.
+    pub fn progress(&self) -> (u64, u32) {
+        let inner = self.inner.lock();
+        let requests =
+            inner.earlier_phase_requests + inner.latency.total_requests;
+        (requests, inner.active_workers)
+    }
+
+    /// Run id reported in every snapshot; set right after `reset`.
+    pub fn set_run_id(&self, run_id: &str) {
+        self.inner.lock().run_id = Some(run_id.to_owned());
+    }
+
+    /// Config reported in every snapshot (kept until reset).
+    pub fn set_config(&self, config: BenchmarkConfig) {
+        let mut inner = self.inner.lock();
+        // Window width and bucket layout are fixed once samples have
+        // been counted
+        if inner.latency.total_requests == 0 {
+            inner.timeline.configure(
+                config.timeline_window_ms,
+                config.latency_buckets.grid(),
+            );
+            inner.buckets = config.latency_buckets.clone();
+        }
+        inner.slow.set_threshold(config.slow_threshold_us);
+        inner.outliers.set_capacity(config.top_outliers);
+        inner.config = Some(config);
+    }
+
+    /// Choose how snapshot percentiles are computed (kept until reset).
+    pub fn set_percentile_mode(&self, mode: PercentileMode) {
+        self.inner.lock().percentile_mode = mode;
+    }
+
+    /// Copies of the end-to-end and per-op histograms, for archiving a
+    /// finished run.
+    pub fn latency_histograms(
+        &self,
+    ) -> (Histogram<u64>, BTreeMap<&'static str, Histogram<u64>>) {
+        let inner = self.inner.lock();
+        let latency = &inner.latency;
+        (latency.e2e_hist.clone(), latency.op_hists.clone())
     }
 
     /// Produce a read-only snapshot for the dashboard.
     pub fn snapshot(&self) -> MetricsSnapshot {
         self.inner.lock().snapshot()
     }
+
+    /// The slow-request log, for `GET /api/metrics/slow`.
+    pub fn slow_requests(&self) -> SlowRequests {
+        self.inner.lock().slow.report()
+    }
 }
 
 // ─── Inner impl ──────────────────────────────────────────────────
 
 impl Inner {
-    fn new() -> Self {
+    fn new(limits: MetricsLimits) -> Self {
         Self {
-            redis_read_hist: Histogram::<u64>::new_with_bounds(
-                HIST_LOW, HIST_HIGH, HIST_SIGFIG,
-            )
-            .expect("histogram creation"),
-            redis_write_hist: Histogram::<u64>::new_with_bounds(
-                HIST_LOW, HIST_HIGH, HIST_SIGFIG,
-            )
-            .expect("histogram creation"),
-            rust_overhead_hist: Histogram::<u64>::new_with_bounds(
-                HIST_LOW, HIST_HIGH, HIST_SIGFIG,
-            )
-            .expect("histogram creation"),
-            e2e_hist: Histogram::<u64>::new_with_bounds(
-                HIST_LOW, HIST_HIGH, HIST_SIGFIG,
-            )
-            .expect("histogram creation"),
-            total_requests: 0,
-            total_errors: 0,
-            total_reads: 0,
-            total_writes: 0,
-            recent_samples: VecDeque::with_capacity(MAX_RECENT_SAMPLES + 1),
-            timeline: Vec::with_capacity(1024),
-            current_window: None,
+            latency: LatencySink::new(),
+            timeline: TimelineSink::new(limits.max_timeline_points),
+            feed: RecentFeed::new(limits.max_recent_samples),
+            rolling: RollingSink::default(),
+            errors: ErrorSink::default(),
+            slow: SlowLog::new(limits.max_slow_requests),
+            outliers: Outliers::default(),
+            keyspace: KeyspaceSink::default(),
+            outputs: Vec::new(),
+            exporter_dropped: Arc::new(AtomicU64::new(0)),
+            framework_hist: new_hist(),
+            pipeline_batch_hist: new_hist(),
+            pipeline_per_cmd_hist: new_hist(),
+            mget_per_key_hist: new_hist(),
+            mset_per_key_hist: new_hist(),
+            scan_iteration_hist: new_hist(),
+            pool_wait_hist: new_hist(),
+            connect_hist: new_hist(),
+            auth_hist: new_hist(),
+            pubsub_hist: new_hist(),
+            stream_hist: new_hist(),
+            queue_enqueue_hist: new_hist(),
+            queue_dequeue_hist: new_hist(),
+            queue_wait_hist: new_hist(),
+            decode_hists: BTreeMap::new(),
+            total_backoff_us: 0,
+            shed_requests: BTreeMap::new(),
+            active_workers: 0,
+            stream_pending: 0,
+            stream_pending_max: 0,
+            queue_depth: 0,
+            queue_depth_max: 0,
+            queue_empty_polls: 0,
+            scan_iterations: 0,
+            scan_keys: 0,
+            connects: 0,
+            connect_errors: 0,
+            clients: ClientStats::default(),
+            client_omem_peak: 0,
+            expiry: ExpiryStats::default(),
+            server_hits: 0,
+            server_misses: 0,
+            workers: BTreeMap::new(),
+            worker_loads: BTreeMap::new(),
+            stages: Vec::new(),
+            changes: Vec::new(),
+            percentile_mode: PercentileMode::default(),
+            buckets: LatencyBuckets::default(),
+            config: None,
+            limits,
+            run_id: None,
             start_time: None,
+            start_epoch_ms: None,
+            last_snapshot_id: 0,
+            earlier_phase_requests: 0,
         }
     }
 
+    /// Hand the sample to every sink, then to every subscription.
     fn record(&mut self, sample: Sample) {
         // Lazily set the anchor on the very first sample
-        let start = *self.start_time.get_or_insert_with(Instant::now);
-        let elapsed_ms = start.elapsed().as_millis() as u64;
-
-        // ── Counters ────────────────────────────────────────────
-        self.total_requests += 1;
-        if !sample.success {
-            self.total_errors += 1;
-        }
-
-        // ── Histograms (clamp to ≥ 1 μs) ───────────────────────
-        let redis_us = sample.redis_us.max(1);
-        let rust_us = sample.rust_us.max(1);
-        let total_us = sample.total_us.max(1);
-
-        if sample.is_read {
-            self.total_reads += 1;
-            let _ = self.redis_read_hist.record(redis_us);
-        } else {
-            self.total_writes += 1;
-            let _ = self.redis_write_hist.record(redis_us);
+        let start = self.anchor();
+        let elapsed = start.elapsed();
+        let epoch_ms = self.start_epoch_ms.unwrap_or(0);
+        let event = SampleEvent {
+            elapsed_ms: elapsed.as_millis() as u64,
+            at_ns: epoch_ms * 1_000_000 + elapsed.as_nanos() as i64,
+            sample: &sample,
+            gauges: Gauges {
+                active_workers: self.active_workers,
+                queue_depth: self.queue_depth,
+                client_omem_bytes: self.clients.omem_bytes,
+                expired_per_sec: self.expiry.expired_per_sec,
+            },
+            next_snapshot_id: self.last_snapshot_id + 1,
+        };
+        for sink in self.sinks() {
+            sink.record(&event);
         }
-        let _ = self.rust_overhead_hist.record(rust_us);
-        let _ = self.e2e_hist.record(total_us);
-
-        // ── Timeline aggregation ────────────────────────────────
-        self.push_to_timeline(elapsed_ms, redis_us, rust_us, total_us);
-
-        // ── Live request feed ───────────────────────────────────
-        self.recent_samples.push_back(SampleRecord {
-            timestamp_ms: elapsed_ms,
-            endpoint: sample.endpoint,
-            redis_us: sample.redis_us,
-            rust_us: sample.rust_us,
-            total_us: sample.total_us,
-            is_read: sample.is_read,
-            success: sample.success,
-        });
-        if self.recent_samples.len() > MAX_RECENT_SAMPLES {
-            self.recent_samples.pop_front();
+        for output in &mut self.outputs {
+            output.record(&event);
         }
     }
 
-    /// Bucket the sample into the current 500 ms window, or roll over.
-    fn push_to_timeline(
-        &mut self,
-        elapsed_ms: u64,
-        redis_us: u64,
-        rust_us: u64,
-        total_us: u64,
-    ) {
-        let window_start = (elapsed_ms / TIMELINE_WINDOW_MS) * TIMELINE_WINDOW_MS;
-
-        match &mut self.current_window {
-            // Same window — accumulate
-            Some(w) if w.window_start_ms == window_start => {
-                w.redis_sum += redis_us;
-                w.rust_sum += rust_us;
-                w.total_sum += total_us;
-                w.count += 1;
-            }
-            // New window — finalize the old one, start fresh
-            Some(_) => {
-                let old = self.current_window.take().unwrap();
-                self.finalize_window(old);
-                self.current_window = Some(WindowAccumulator {
-                    window_start_ms: window_start,
-                    redis_sum: redis_us,
-                    rust_sum: rust_us,
-                    total_sum: total_us,
-                    count: 1,
-                });
-            }
-            // Very first sample
-            None => {
-                self.current_window = Some(WindowAccumulator {
-                    window_start_ms: window_start,
-                    redis_sum: redis_us,
-                    rust_sum: rust_us,
-                    total_sum: total_us,
-                    count: 1,
-                });
-            }
-        }
+    /// Start over, keeping what outlives a run: snapshot ids stay
+    /// monotonic and the subscriptions stay in place.
+    fn renew(&mut self) {
+        let last_snapshot_id = self.last_snapshot_id;
+        let outputs = std::mem::take(&mut self.outputs);
+        let exporter_dropped = self.exporter_dropped.clone();
+        *self = Inner::new(self.limits);
+        self.last_snapshot_id = last_snapshot_id;
+        self.outputs = outputs;
+        self.exporter_dropped = exporter_dropped;
     }
 
-    fn finalize_window(&mut self, w: WindowAccumulator) {
-        if w.count == 0 {
-            return;
-        }
-        self.timeline.push(TimelinePoint {
-            timestamp_ms: w.window_start_ms,
-            avg_redis_us: w.redis_sum as f64 / w.count as f64,
-            avg_rust_us: w.rust_sum as f64 / w.count as f64,
-            avg_total_us: w.total_sum as f64 / w.count as f64,
-            count: w.count,
-        });
+    /// The sinks the snapshot is built from, in the order samples reach
+    /// them. They hold the run's state, so `renew` starts them over;
+    /// outputs that only export samples are subscribed instead.
+    fn sinks(&mut self) -> [&mut dyn SampleSink; 8] {
+        [
+            &mut self.latency,
+            &mut self.timeline,
+            &mut self.feed,
+            &mut self.rolling,
+            &mut self.errors,
+            &mut self.slow,
+            &mut self.outliers,
+            &mut self.keyspace,
+        ]
+    }
+
+    /// Time zero for the run, set by whichever comes first: the first
+    /// sample or the first stage mark.
+    fn anchor(&mut self) -> Instant {
+        self.start_epoch_ms
+            .get_or_insert_with(|| chrono::Utc::now().timestamp_millis());
+        *self.start_time.get_or_insert_with(Instant::now)
     }
 
     /// Build a complete read-only snapshot for the SSE stream.
-    fn snapshot(&self) -> MetricsSnapshot {
+    fn snapshot(&mut self) -> MetricsSnapshot {
+        self.last_snapshot_id += 1;
+        let latency = &self.latency;
         let elapsed_secs = self
             .start_time
             .map(|t| t.elapsed().as_secs_f64())
             .unwrap_or(0.0);
 
-        let rps = if elapsed_secs > 0.0 {
-            self.total_requests as f64 / elapsed_secs
-        } else {
-            0.0
+        let per_sec = |n: u64| {
+            if elapsed_secs > 0.0 {
+                n as f64 / elapsed_secs
+            } else {
+                0.0
+            }
         };
+        let now_ms = (elapsed_secs * 1_000.0) as u64;
+        let (bytes_in_per_sec, bytes_out_per_sec) =
+            self.rolling.bytes_per_sec(now_ms);
+        let rolling = self
+            .rolling
+            .merged(now_ms)
+            .into_iter()
+            .map(|window| {
+                let set = RollingSet {
+                    e2e: self.percentiles(&window.e2e),
+                    redis: self.percentiles(&window.redis),
+                };
+                (format!("last_{}s", window.window_secs), set)
+            })
+            .collect();
 
-        // Include the current (partial) window in the timeline
-        let mut timeline = self.timeline.clone();
-        if let Some(w) = &self.current_window {
-            if w.count > 0 {
-                timeline.push(TimelinePoint {
-                    timestamp_ms: w.window_start_ms,
-                    avg_redis_us: w.redis_sum as f64 / w.count as f64,
-                    avg_rust_us: w.rust_sum as f64 / w.count as f64,
-                    avg_total_us: w.total_sum as f64 / w.count as f64,
-                    count: w.count,
-                });
+        let mut per_command: BTreeMap<String, CommandStats> = latency
+            .op_hists
+            .iter()
+            .map(|(op, hist)| {
+                let counts =
+                    latency.op_counts.get(op).copied().unwrap_or_default();
+                let stats = CommandStats {
+                    calls: counts.calls,
+                    errors: counts.errors,
+                    timeouts: counts.timeouts,
+                    calls_per_sec: per_sec(counts.calls),
+                    latency: self.op_percentiles(op, hist),
+                    bytes_per_sec: per_sec(counts.bytes),
+                    payload: latency
+                        .op_sizes
+                        .get(op)
+                        .map(SizeSet::from_histogram)
+                        .unwrap_or_default(),
+                };
+                (op.to_string(), stats)
+            })
+            .collect();
+        let batches = self.pipeline_batch_hist.len();
+        if batches > 0 {
+            let stats = CommandStats {
+                calls: batches,
+                errors: 0,
+                timeouts: 0,
+                calls_per_sec: per_sec(batches),
+                latency: self.percentiles(&self.pipeline_batch_hist),
+                bytes_per_sec: 0.0,
+                payload: SizeSet::default(),
+            };
+            per_command.insert("PIPELINE".into(), stats);
+        }
+
+        let bounds = self.buckets.bounds(&latency.e2e_hist);
+
+        let mut apdex = BTreeMap::new();
+        if let Some(targets) = self.config.as_ref().and_then(|c| c.apdex) {
+            let layers = [
+                ("e2e", &latency.e2e_hist),
+                ("e2e_corrected", &latency.e2e_corrected_hist),
+                ("redis_read", &latency.redis_read_hist),
+                ("redis_write", &latency.redis_write_hist),
+                ("rust_overhead", &latency.rust_overhead_hist),
+            ];
+            for (layer, hist) in layers {
+                if let Some(score) = targets.score(hist) {
+                    apdex.insert(layer.to_owned(), score);
+                }
             }
         }
 
         MetricsSnapshot {
-            redis_read: PercentileSet::from_histogram(&self.redis_read_hist),
-            redis_write: PercentileSet::from_histogram(&self.redis_write_hist),
-            rust_overhead: PercentileSet::from_histogram(
-                &self.rust_overhead_hist,
+            snapshot_id: self.last_snapshot_id,
+            run_id: self.run_id.clone(),
+            config: self.config.clone(),
+            redis_read: self.layer(
+                &latency.redis_read_hist,
+                &latency.redis_read_jitter,
             ),
-            e2e: PercentileSet::from_histogram(&self.e2e_hist),
+            redis_write: self.layer(
+                &latency.redis_write_hist,
+                &latency.redis_write_jitter,
+            ),
+            rust_overhead: self.layer(
+                &latency.rust_overhead_hist,
+                &latency.rust_overhead_jitter,
+            ),
+            e2e: self.layer(&latency.e2e_hist, &latency.e2e_jitter),
+            rolling,
+            framework_overhead: self.percentiles(
+                &self.framework_hist,
+            ),
+            ops: latency
+                .op_hists
+                .iter()
+                .map(|(op, h)| (op.to_string(), self.op_percentiles(op, h)))
+                .collect(),
+            per_command,
+            pipeline_batch: self.percentiles(
+                &self.pipeline_batch_hist,
+            ),
+            pipeline_per_command: self.percentiles(
+                &self.pipeline_per_cmd_hist,
+            ),
+            mget_per_key: self.percentiles(&self.mget_per_key_hist),
+            mset_per_key: self.percentiles(&self.mset_per_key_hist),
+            scan_iteration: self.percentiles(&self.scan_iteration_hist),
+            pool_wait: self.percentiles(&self.pool_wait_hist),
+            connect: self.percentiles(&self.connect_hist),
+            auth: self.percentiles(&self.auth_hist),
+            pubsub: self.percentiles(&self.pubsub_hist),
+            stream_delivery: self.percentiles(&self.stream_hist),
+            queue_enqueue: self.percentiles(&self.queue_enqueue_hist),
+            queue_dequeue: self.percentiles(&self.queue_dequeue_hist),
+            queue_wait: self.percentiles(&self.queue_wait_hist),
+            local_cache_hit: self.percentiles(
+                &latency.cache_hit_hist,
+            ),
+            local_cache_miss: self.percentiles(
+                &latency.cache_miss_hist,
+            ),
+            e2e_corrected: self.layer(
+                &latency.e2e_corrected_hist,
+                &latency.e2e_corrected_jitter,
+            ),
+            apdex,
 
-            total_requests: self.total_requests,
-            total_errors: self.total_errors,
-            total_reads: self.total_reads,
-            total_writes: self.total_writes,
-            requests_per_sec: rps,
+            total_requests: latency.total_requests,
+            total_errors: latency.total_errors,
+            total_timeouts: latency.total_timeouts,
+            errors: self.errors.counts(),
+            total_reads: latency.total_reads,
+            total_writes: latency.total_writes,
+            total_cache_hits: latency.total_cache_hits,
+            total_cache_misses: latency.total_cache_misses,
+            total_bytes_read: latency.total_bytes_read,
+            total_bytes_written: latency.total_bytes_written,
+            requests_per_sec: self.rolling.requests_per_sec(now_ms),
+            avg_requests_per_sec: per_sec(latency.total_requests),
+            read_bytes_per_sec: per_sec(latency.total_bytes_read),
+            write_bytes_per_sec: per_sec(latency.total_bytes_written),
+            bytes_in_per_sec,
+            bytes_out_per_sec,
             elapsed_secs,
+            started_at_ms: self.start_epoch_ms,
+            total_backoff_ms: self.total_backoff_us / 1_000,
+            shed_requests: self.shed_requests.clone(),
+            active_workers: self.active_workers,
+            stream_pending: self.stream_pending,
+            stream_pending_max: self.stream_pending_max,
+            queue_depth: self.queue_depth,
+            queue_depth_max: self.queue_depth_max,
+            queue_empty_polls: self.queue_empty_polls,
+            scan_iterations: self.scan_iterations,
+            scan_keys: self.scan_keys,
+            connects: self.connects,
+            connect_errors: self.connect_errors,
+            clients: self.clients,
+            client_omem_peak_bytes: self.client_omem_peak,
+            reply_decode_ns: self
+                .decode_hists
+                .iter()
+                .map(|(ty, h)| (ty.to_string(), SizeSet::from_histogram(h)))
+                .collect(),
+            expiry: self.expiry,
+            keyspace: self.keyspace_stats(),
+            workers: self.workers.values().cloned().collect(),
+            fairness: self.fairness(),
 
-            recent_samples: self.recent_samples.iter().cloned().collect(),
-            timeline,
-            distribution: Self::compute_distribution(&self.e2e_hist),
+            recent_samples: self.feed.records(),
+            recent_errors: self.errors.recent(),
+            outliers: self.outliers.worst(),
+            timeline: self.timeline.points(),
+            timeline_window_ms: self.timeline.window_ms(),
+            percentile_mode: self.percentile_mode,
+            stages: self.stages.clone(),
+            changes: self.changes.clone(),
+            distribution: Self::compute_distribution(
+                &latency.e2e_hist,
+                &bounds,
+            ),
+            heatmap: self.timeline.heatmap(&bounds),
+            metrics_memory_bytes: self.memory_bytes() as u64,
+            exporter_dropped: self.exporter_dropped.load(Ordering::Relaxed),
         }
     }
 
-    // ── Distribution histogram for the bar chart ────────────────
+    /// Estimate of what the sinks and the other histograms hold.
+    fn memory_bytes(&self) -> usize {
+        let hists = [
+            &self.framework_hist,
+            &self.pipeline_batch_hist,
+            &self.pipeline_per_cmd_hist,
+            &self.mget_per_key_hist,
+            &self.mset_per_key_hist,
+            &self.scan_iteration_hist,
+            &self.pool_wait_hist,
+            &self.connect_hist,
+            &self.auth_hist,
+            &self.pubsub_hist,
+            &self.stream_hist,
+            &self.queue_enqueue_hist,
+            &self.queue_dequeue_hist,
+            &self.queue_wait_hist,
+        ];
+        let workers = self.workers.len() * size_of::<WorkerClient>()
+            + self
+                .worker_loads
+                .values()
+                .map(|load| size_of::<WorkerLoad>() + hist_bytes(&load.hist))
+                .sum::<usize>();
+        self.latency.memory_bytes()
+            + self.timeline.memory_bytes()
+            + self.feed.memory_bytes()
+            + self.rolling.memory_bytes()
+            + self.errors.memory_bytes()
+            + self.slow.memory_bytes()
+            + self.outliers.memory_bytes()
+            + self.keyspace.memory_bytes()
+            + hists.into_iter().map(hist_bytes).sum::<usize>()
+            + self.decode_hists.values().map(hist_bytes).sum::<usize>()
+            + workers
+            + self.stages.capacity() * size_of::<StageMark>()
+            + self.changes.capacity() * size_of::<ChangeMark>()
+    }
 
-    /// Pre-defined bucket boundaries (μs).  Covers the typical
-    /// localhost Redis latency range with good resolution.
-    const DIST_BOUNDARIES: &'static [u64] = &[
-        25, 50, 100, 150, 200, 300, 400, 500, 750, 1_000, 1_500, 2_000,
-        3_000, 5_000, 10_000, 50_000,
-    ];
+    fn keyspace_stats(&self) -> KeyspaceStats {
+        let endpoints = self.keyspace.endpoints();
+        let hits = endpoints.values().map(|e| e.hits).sum();
+        let misses = endpoints.values().map(|e| e.misses).sum();
+        KeyspaceStats {
+            hits,
+            misses,
+            hit_pct: hit_pct(hits, misses),
+            endpoints,
+            server_hits: self.server_hits,
+            server_misses: self.server_misses,
+            server_hit_pct: hit_pct(self.server_hits, self.server_misses),
+        }
+    }
 
-    fn compute_distribution(hist: &Histogram<u64>) -> Vec<DistBucket> {
-        if hist.len() == 0 {
-            return Vec::new();
+    fn fairness(&self) -> Option<Fairness> {
+        if self.worker_loads.is_empty() {
+            return None;
         }
+        let workers: Vec<WorkerShare> = self
+            .worker_loads
+            .iter()
+            .map(|(id, load)| {
+                let secs = load.last.duration_since(load.first).as_secs_f64();
+                WorkerShare {
+                    worker_id: *id,
+                    ops: load.ops,
+                    errors: load.errors,
+                    ops_per_sec: if secs > 0.0 {
+                        load.ops as f64 / secs
+                    } else {
+                        0.0
+                    },
+                    latency: self.percentiles(&load.hist),
+                }
+            })
+            .collect();
 
-        let bounds = Self::DIST_BOUNDARIES;
-        let num_buckets = bounds.len() + 1; // +1 for overflow
-        let mut counts = vec![0u64; num_buckets];
+        let rates = workers.iter().map(|w| w.ops_per_sec);
+        let sum: f64 = rates.clone().sum();
+        let sum_sq: f64 = rates.clone().map(|r| r * r).sum();
+        let n = workers.len() as f64;
+        Some(Fairness {
+            jain_index: if sum_sq > 0.0 {
+                sum * sum / (n * sum_sq)
+            } else {
+                1.0
+            },
+            min_ops_per_sec: rates.clone().fold(f64::INFINITY, f64::min),
+            max_ops_per_sec: rates.fold(0.0, f64::max),
+            workers,
+        })
+    }
 
-        // Walk every recorded value in the histogram and bucket it
-        for iv in hist.iter_recorded() {
-            let val = iv.value_iterated_to();
-            let cnt = iv.count_at_value();
+    fn percentiles(&self, hist: &Histogram<u64>) -> PercentileSet {
+        PercentileSet::from_histogram_with(hist, self.percentile_mode)
+    }
 
-            // binary_search gives us the first boundary >= val
-            let idx = match bounds.binary_search(&val) {
-                Ok(i) => i,        // val == boundary  → bucket i
-                Err(i) => i,       // val < boundary[i] → bucket i
-            };
-            let idx = idx.min(bounds.len()); // clamp for overflow
-            counts[idx] += cnt;
+    /// `percentiles` plus the layer's sample-to-sample jitter.
+    fn layer(&self, hist: &Histogram<u64>, jitter: &Jitter) -> PercentileSet {
+        PercentileSet {
+            jitter: jitter.mean(),
+            ..self.percentiles(hist)
+        }
+    }
+
+    /// One command's Redis latency, with its jitter.
+    fn op_percentiles(
+        &self,
+        op: &str,
+        hist: &Histogram<u64>,
+    ) -> PercentileSet {
+        match self.latency.op_jitter.get(op) {
+            Some(jitter) => self.layer(hist, jitter),
+            None => self.percentiles(hist),
         }
+    }
+
+    // ── Distribution histogram for the bar chart ────────────────
+
+    fn compute_distribution(
+        hist: &Histogram<u64>,
+        bounds: &[u64],
+    ) -> Vec<DistBucket> {
+        if hist.is_empty() {
+            return Vec::new();
+        }
+
+        let num_buckets = bounds.len() + 1; // +1 for overflow
+        let counts = bucket_counts(hist, bounds);
 
         // Convert to output structs, skipping empty buckets
         let mut result = Vec::with_capacity(num_buckets);
@@ -378,4 +1529,4 @@ impl Inner {
 
         result
     }
-}
\ No newline at end of file
+}
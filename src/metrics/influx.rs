@@ -0,0 +1,230 @@
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+use hyper::header::{HeaderName, AUTHORIZATION, CONTENT_TYPE};
+use tokio::sync::mpsc;
+
+use super::collector::TimelinePoint;
+use super::push::{self, Streak, Target};
+use super::sink::{SampleEvent, SampleSink};
+use super::MetricsCollector;
+use crate::config::InfluxSettings;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// Samples waiting for the writer; beyond this they are dropped
+const QUEUE_CAPACITY: usize = 65_536;
+
+/// How often buffered lines and new timeline points are posted
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Lines that trigger an early post
+const MAX_BATCH_LINES: usize = 5_000;
+
+// ─── Sink ────────────────────────────────────────────────────────
+
+/// Hands samples to the writer task, which posts them with the
+/// collector's closed timeline points as InfluxDB line protocol.
+/// Subscribed to the collector for the whole process.
+pub struct InfluxSink {
+    tx: mpsc::Sender<Point>,
+    sample_rate: f64,
+}
+
+/// The parts of a sample written to `redis_bench_sample`.
+struct Point {
+    at_ns: i64,
+    endpoint: String,
+    redis_us: u64,
+    rust_us: u64,
+    total_us: u64,
+    success: bool,
+    timed_out: bool,
+}
+
+impl InfluxSink {
+    /// Spawn the writer and return the sink that feeds it. The writer
+    /// reads timeline points back from `metrics`.
+    pub fn start(
+        settings: InfluxSettings,
+        metrics: Arc<MetricsCollector>,
+    ) -> Result<Self, String> {
+        let target = Target::parse(&settings.url, "InfluxDB")?;
+        let mut headers =
+            vec![(CONTENT_TYPE, "text/plain; charset=utf-8".to_string())];
+        if let Some(token) = &settings.token {
+            headers.push((AUTHORIZATION, format!("Token {token}")));
+        }
+        let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+        tokio::spawn(write(target, headers, rx, metrics));
+        Ok(Self {
+            tx,
+            sample_rate: settings.sample_rate,
+        })
+    }
+}
+
+impl SampleSink for InfluxSink {
+    fn record(&mut self, event: &SampleEvent<'_>) {
+        if self.sample_rate < 1.0 && rand::random::<f64>() >= self.sample_rate
+        {
+            return;
+        }
+        let sample = event.sample;
+        // A full queue drops the sample; a closed one can't happen
+        // while the writer loops forever
+        let _ = self.tx.try_send(Point {
+            at_ns: event.at_ns,
+            endpoint: sample.endpoint.clone(),
+            redis_us: sample.redis_us,
+            rust_us: sample.rust_us,
+            total_us: sample.total_us,
+            success: sample.success,
+            timed_out: sample.timed_out,
+        });
+    }
+}
+
+// ─── Writer ──────────────────────────────────────────────────────
+
+/// Buffer sample lines and post them once a second, or sooner when
+/// `MAX_BATCH_LINES` pile up, along with any timeline points closed
+/// since the last post.
+async fn write(
+    target: Target,
+    headers: Vec<(HeaderName, String)>,
+    mut rx: mpsc::Receiver<Point>,
+    metrics: Arc<MetricsCollector>,
+) {
+    let mut ticker = tokio::time::interval(FLUSH_INTERVAL);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut body = String::new();
+    let mut lines = 0;
+    // Timestamps must be unique per series or InfluxDB keeps only the
+    // last point, so each sample is at least 1 ns after the previous
+    let mut last_ns = 0;
+    let mut timeline = TimelineCursor::default();
+    let mut streak = Streak::new("InfluxDB writes");
+
+    loop {
+        tokio::select! {
+            point = rx.recv() => {
+                let Some(point) = point else { return };
+                last_ns = point.at_ns.max(last_ns + 1);
+                sample_line(&mut body, &point, last_ns);
+                lines += 1;
+                if lines < MAX_BATCH_LINES {
+                    continue;
+                }
+            }
+            _ = ticker.tick() => timeline.append(&metrics, &mut body),
+        }
+        if body.is_empty() {
+            continue;
+        }
+        let body = std::mem::take(&mut body).into();
+        streak.note(&target, push::post(&target, &headers, body).await);
+        lines = 0;
+    }
+}
+
+/// Which timeline points have been written, so each is sent once per
+/// run.
+#[derive(Default)]
+struct TimelineCursor {
+    /// Epoch (ms) of the run the points belong to
+    run_start_ms: Option<i64>,
+    /// Window start of the newest point written
+    last_ms: Option<u64>,
+}
+
+impl TimelineCursor {
+    fn append(&mut self, metrics: &MetricsCollector, body: &mut String) {
+        let (run_id, start_ms, points) = metrics.closed_timeline();
+        let Some(start_ms) = start_ms else {
+            return;
+        };
+        if self.run_start_ms != Some(start_ms) {
+            *self = Self {
+                run_start_ms: Some(start_ms),
+                last_ms: None,
+            };
+        }
+        // Compaction merges old windows into wider ones that start no
+        // later, so they are skipped rather than written twice
+        let after = self.last_ms;
+        let fresh = points
+            .iter()
+            .filter(|p| after.is_none_or(|last| p.timestamp_ms > last));
+        for point in fresh {
+            timeline_line(body, run_id.as_deref(), start_ms, point);
+            self.last_ms = Some(point.timestamp_ms);
+        }
+    }
+}
+
+fn sample_line(body: &mut String, point: &Point, at_ns: i64) {
+    let _ = writeln!(
+        body,
+        "redis_bench_sample,endpoint={} redis_us={}i,rust_us={}i,\
+         total_us={}i,success={},timed_out={} {at_ns}",
+        escape_tag(&point.endpoint),
+        point.redis_us,
+        point.rust_us,
+        point.total_us,
+        point.success,
+        point.timed_out,
+    );
+}
+
+fn timeline_line(
+    body: &mut String,
+    run_id: Option<&str>,
+    start_ms: i64,
+    point: &TimelinePoint,
+) {
+    let at_ns = (start_ms + point.timestamp_ms as i64) * 1_000_000;
+    let tags = match run_id {
+        Some(id) => format!(",run_id={}", escape_tag(id)),
+        None => String::new(),
+    };
+    let _ = writeln!(
+        body,
+        "redis_bench_timeline{tags} window_ms={}i,count={}i,\
+         avg_redis_us={},avg_rust_us={},avg_total_us={},\
+         p50_total_us={}i,p95_total_us={}i,p99_total_us={}i,\
+         requests_per_sec={},reads_per_sec={},writes_per_sec={},\
+         read_errors={}i,write_errors={}i,active_workers={}i,\
+         queue_depth={}i,payload_bytes={}i,timeouts={}i {at_ns}",
+        point.window_ms,
+        point.count,
+        point.avg_redis_us,
+        point.avg_rust_us,
+        point.avg_total_us,
+        point.p50_total_us,
+        point.p95_total_us,
+        point.p99_total_us,
+        point.requests_per_sec,
+        point.reads_per_sec,
+        point.writes_per_sec,
+        point.read_errors,
+        point.write_errors,
+        point.active_workers,
+        point.queue_depth,
+        point.payload_bytes,
+        point.timeouts,
+    );
+}
+
+/// Tag values escape `,`, `=` and spaces.
+fn escape_tag(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, ',' | '=' | ' ') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
@@ -1,8 +1,24 @@
+pub mod apdex;
+pub mod buckets;
 pub mod collector;
+pub mod influx;
 pub mod percentiles;
+pub mod probe;
+pub mod prometheus;
+pub mod push;
+pub mod remote_write;
+pub mod select;
+pub mod sink;
+pub mod statsd;
 pub mod stream;
+pub mod units;
 
-pub use collector::{MetricsCollector, MetricsSnapshot};
+use serde::Serialize;
+
+pub use collector::{
+    ClientStats, MetricsCollector, MetricsLimits, MetricsSnapshot,
+    WorkerClient,
+};
 
 /// A single timing observation recorded by a handler.
 /// This is the "write" side — handlers create these and push them in.
@@ -10,6 +26,11 @@ pub use collector::{MetricsCollector, MetricsSnapshot};
 pub struct Sample {
     /// e.g. "GET /api/users/:id"
     pub endpoint: String,
+    /// Redis command issued, e.g. "HGETALL" — keys the per-op histograms
+    pub op: &'static str,
+    /// Redis key the command touched, its first one for multi-key
+    /// commands; `None` for keyless commands such as `SCAN`
+    pub key: Option<String>,
     /// Microseconds spent inside the Redis round-trip
     pub redis_us: u64,
     /// Microseconds of Rust overhead (serialization, validation, etc.)
@@ -20,4 +41,187 @@ pub struct Sample {
     pub is_read: bool,
     /// false when the request hit a not-found or Redis error
     pub success: bool,
-}
\ No newline at end of file
+    /// true when the Redis call gave up at the per-op timeout
+    pub timed_out: bool,
+    /// What went wrong; `None` when `success` is true
+    pub error: Option<SampleError>,
+    /// Status the HTTP handler answered with; `None` for samples the
+    /// load generator records
+    pub http_status: Option<u16>,
+    /// Bytes moved by the command: reply data for reads, arguments after
+    /// the key for writes; 0 when nothing came back or Redis was skipped
+    pub payload_bytes: u64,
+    /// Whether a local cache was consulted, and what it answered
+    pub cache: CacheOutcome,
+    /// Open-loop only: how late the request was sent relative to its
+    /// intended send time (coordinated-omission correction)
+    pub schedule_lag_us: u64,
+}
+
+/// `timed_out` of a Redis call made through `redis_client::timed`.
+pub fn timed_out<T>(result: &redis::RedisResult<T>) -> bool {
+    result.as_ref().is_err_and(|e| e.is_timeout())
+}
+
+/// `error` of a Redis call: `None` when it succeeded.
+pub fn sample_error<T>(result: &redis::RedisResult<T>) -> Option<SampleError> {
+    result.as_ref().err().map(SampleError::redis)
+}
+
+/// `key` of a command built with `redis::cmd`: the first key argument,
+/// after the script hash and key count for `EVALSHA`.
+pub fn command_key(cmd: &redis::Cmd) -> Option<String> {
+    let mut args = cmd.args_iter().map(|arg| match arg {
+        redis::Arg::Simple(bytes) => Some(bytes),
+        redis::Arg::Cursor => None,
+    });
+    let name = args.next()??;
+    let skip = match name.to_ascii_uppercase().as_slice() {
+        b"SCAN" => return None,
+        b"EVAL" | b"EVALSHA" => 2,
+        _ => 0,
+    };
+    let key = args.nth(skip)??;
+    Some(String::from_utf8_lossy(key).into_owned())
+}
+
+/// `payload_bytes` of a write: every argument after the key.
+pub fn write_bytes(cmd: &redis::Cmd) -> u64 {
+    cmd.args_iter()
+        .skip(2)
+        .map(|arg| match arg {
+            redis::Arg::Simple(bytes) => bytes.len() as u64,
+            redis::Arg::Cursor => 0,
+        })
+        .sum()
+}
+
+/// `payload_bytes` of a read: every bulk and verbatim string in the
+/// reply, RESP3 maps, sets and attributes included.
+pub fn reply_bytes(value: &redis::Value) -> u64 {
+    use redis::Value;
+    match value {
+        Value::BulkString(bytes) => bytes.len() as u64,
+        Value::VerbatimString { text, .. } => text.len() as u64,
+        Value::Array(items) | Value::Set(items) => {
+            items.iter().map(reply_bytes).sum()
+        }
+        Value::Map(pairs) => pairs
+            .iter()
+            .map(|(k, v)| reply_bytes(k) + reply_bytes(v))
+            .sum(),
+        Value::Attribute { data, .. } => reply_bytes(data),
+        _ => 0,
+    }
+}
+
+/// RESP type of a reply, keying `reply_decode_ns`.
+pub fn reply_type(value: &redis::Value) -> &'static str {
+    use redis::Value;
+    match value {
+        Value::Nil => "nil",
+        Value::Int(_) => "integer",
+        Value::BulkString(_) => "bulk_string",
+        Value::Array(_) => "array",
+        Value::SimpleString(_) | Value::Okay => "simple_string",
+        Value::Map(_) => "map",
+        Value::Attribute { .. } => "attribute",
+        Value::Set(_) => "set",
+        Value::Double(_) => "double",
+        Value::Boolean(_) => "boolean",
+        Value::VerbatimString { .. } => "verbatim_string",
+        Value::BigNumber(_) => "big_number",
+        Value::Push { .. } => "push",
+        Value::ServerError(_) => "error",
+    }
+}
+
+/// `payload_bytes` of an HGETALL reply already decoded to a map.
+pub fn hash_bytes<'a>(
+    fields: impl IntoIterator<Item = (&'a String, &'a String)>,
+) -> u64 {
+    fields
+        .into_iter()
+        .map(|(k, v)| (k.len() + v.len()) as u64)
+        .sum()
+}
+
+/// Result of the local-cache lookup that preceded a read, if any.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheOutcome {
+    /// No local cache in front of this request
+    #[default]
+    Bypass,
+    /// Served locally without a Redis round-trip
+    Hit,
+    /// Looked up locally, then fetched from Redis
+    Miss,
+}
+
+/// Why a sample failed.
+#[derive(Debug, Clone)]
+pub struct SampleError {
+    pub category: ErrorCategory,
+    pub message: String,
+}
+
+impl SampleError {
+    /// The key (or hash) asked for does not exist.
+    pub fn not_found() -> Self {
+        Self {
+            category: ErrorCategory::NotFound,
+            message: "key not found".into(),
+        }
+    }
+
+    /// Classify a failed Redis call.
+    pub fn redis(e: &redis::RedisError) -> Self {
+        use redis::ErrorKind;
+        let category = if e.is_timeout() {
+            ErrorCategory::Timeout
+        } else if e.is_io_error()
+            || e.is_connection_refusal()
+            || e.is_connection_dropped()
+            || matches!(
+                e.kind(),
+                ErrorKind::BusyLoadingError
+                    | ErrorKind::ClusterDown
+                    | ErrorKind::MasterDown
+            )
+        {
+            ErrorCategory::Connection
+        } else if e.code() == Some("WRONGTYPE") {
+            ErrorCategory::WrongType
+        } else if matches!(
+            e.kind(),
+            ErrorKind::ResponseError | ErrorKind::TypeError
+        ) {
+            ErrorCategory::Parse
+        } else {
+            ErrorCategory::Other
+        };
+        Self {
+            category,
+            message: e.to_string(),
+        }
+    }
+}
+
+/// Failure classes counted separately in the snapshot, so a cache miss
+/// is not mistaken for a broken server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    /// Key missing: nil from GET, an empty hash from HGETALL
+    NotFound,
+    /// Connection refused, dropped or not ready (loading, cluster down)
+    Connection,
+    /// No reply within the per-op timeout
+    Timeout,
+    /// `WRONGTYPE`: the key holds another data type
+    WrongType,
+    /// A reply the client could not parse or convert
+    Parse,
+    /// Any other server or client error
+    Other,
+}
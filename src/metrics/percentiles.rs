@@ -1,13 +1,36 @@
 use hdrhistogram::Histogram;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+/// How a percentile is read out of a histogram. Other load tools differ
+/// here, so matching their method makes numbers directly comparable.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PercentileMode {
+    /// HdrHistogram's own: highest value equivalent to the rank's bucket
+    /// (wrk, HdrHistogram-based tools)
+    #[default]
+    Hdr,
+    /// Interpolate between the two closest ranks, like numpy's default
+    /// (memtier_benchmark-style reporting)
+    Linear,
+    /// The value at rank ⌈p/100 · N⌉ (redis-benchmark)
+    NearestRank,
+}
 
 /// A complete percentile breakdown for one measurement layer.
 /// Serialized straight into the SSE JSON and into the summary table.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PercentileSet {
     pub min: u64,
     pub max: u64,
     pub mean: f64,
+    /// Standard deviation around `mean`
+    #[serde(default)]
+    pub stddev: f64,
+    /// Mean absolute difference between consecutive samples of the
+    /// same endpoint; 0 for layers that do not track sample order
+    #[serde(default)]
+    pub jitter: f64,
     pub p50: u64,
     pub p95: u64,
     pub p99: u64,
@@ -19,7 +42,7 @@ impl PercentileSet {
     /// Extract a full percentile set from an HdrHistogram.
     /// Returns zeroed values if the histogram is empty.
     pub fn from_histogram(hist: &Histogram<u64>) -> Self {
-        if hist.len() == 0 {
+        if hist.is_empty() {
             return Self::empty();
         }
 
@@ -27,6 +50,8 @@ impl PercentileSet {
             min: hist.min(),
             max: hist.max(),
             mean: hist.mean(),
+            stddev: hist.stdev(),
+            jitter: 0.0,
             p50: hist.value_at_percentile(50.0),
             p95: hist.value_at_percentile(95.0),
             p99: hist.value_at_percentile(99.0),
@@ -35,12 +60,37 @@ impl PercentileSet {
         }
     }
 
+    /// Like `from_histogram`, computing percentiles with `mode`.
+    pub fn from_histogram_with(
+        hist: &Histogram<u64>,
+        mode: PercentileMode,
+    ) -> Self {
+        if mode == PercentileMode::Hdr || hist.is_empty() {
+            return Self::from_histogram(hist);
+        }
+        let at = |p: f64| percentile(hist, p, mode);
+        Self {
+            min: hist.min(),
+            max: hist.max(),
+            mean: hist.mean(),
+            stddev: hist.stdev(),
+            jitter: 0.0,
+            p50: at(50.0),
+            p95: at(95.0),
+            p99: at(99.0),
+            p999: at(99.9),
+            count: hist.len(),
+        }
+    }
+
     /// All-zero placeholder used before any samples are recorded.
     pub fn empty() -> Self {
         Self {
             min: 0,
             max: 0,
             mean: 0.0,
+            stddev: 0.0,
+            jitter: 0.0,
             p50: 0,
             p95: 0,
             p99: 0,
@@ -53,4 +103,37 @@ impl PercentileSet {
     pub fn has_data(&self) -> bool {
         self.count > 0
     }
-}
\ No newline at end of file
+}
+
+// ─── Alternative percentile methods ──────────────────────────────
+
+fn percentile(hist: &Histogram<u64>, p: f64, mode: PercentileMode) -> u64 {
+    let n = hist.len();
+    match mode {
+        PercentileMode::Hdr => hist.value_at_percentile(p),
+        PercentileMode::NearestRank => {
+            let rank = ((p / 100.0) * n as f64).ceil().max(1.0) as u64;
+            value_at_rank(hist, rank - 1)
+        }
+        PercentileMode::Linear => {
+            let pos = (p / 100.0) * (n - 1) as f64;
+            let lo = pos.floor() as u64;
+            let lo_v = value_at_rank(hist, lo) as f64;
+            let hi_v = value_at_rank(hist, (lo + 1).min(n - 1)) as f64;
+            (lo_v + (hi_v - lo_v) * pos.fract()).round() as u64
+        }
+    }
+}
+
+/// The 0-based `rank`-th smallest sample, represented by the midpoint
+/// of its bucket rather than the bucket's upper edge.
+fn value_at_rank(hist: &Histogram<u64>, rank: u64) -> u64 {
+    let mut seen = 0u64;
+    for iv in hist.iter_recorded() {
+        seen += iv.count_at_value();
+        if seen > rank {
+            return hist.median_equivalent(iv.value_iterated_to());
+        }
+    }
+    hist.max()
+}
@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+
+use hdrhistogram::Histogram;
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use super::percentiles::PercentileSet;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// Probe timeline resolution — coarse, this is a health trend
+const PROBE_WINDOW_MS: i64 = 10_000;
+
+/// Keep 24 h of windows
+const MAX_PROBE_WINDOWS: usize = 8_640;
+
+const HIST_LOW: u64 = 1;
+const HIST_HIGH: u64 = 60_000_000;
+const HIST_SIGFIG: u8 = 3;
+
+// ─── Public types ────────────────────────────────────────────────
+
+/// Long-horizon store for the background prober. Unlike
+/// `MetricsCollector` it is never reset when a benchmark starts.
+pub struct ProbeMetrics {
+    inner: Mutex<Inner>,
+}
+
+/// One probe timeline window. `timestamp_ms` is Unix epoch time.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProbePoint {
+    pub timestamp_ms: i64,
+    pub avg_us: f64,
+    pub max_us: u64,
+    pub count: u64,
+    pub errors: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProbeSnapshot {
+    /// False when probing is disabled by configuration
+    pub enabled: bool,
+    pub interval_ms: u64,
+    /// Round-trip latency of every successful probe since startup
+    pub latency: PercentileSet,
+    pub total_probes: u64,
+    pub total_errors: u64,
+    /// Epoch ms of the last successful probe
+    pub last_ok_at_ms: Option<i64>,
+    pub last_error: Option<String>,
+    pub timeline: Vec<ProbePoint>,
+}
+
+// ─── Internal state ──────────────────────────────────────────────
+
+struct Inner {
+    interval_ms: u64,
+    hist: Histogram<u64>,
+    total_probes: u64,
+    total_errors: u64,
+    last_ok_at_ms: Option<i64>,
+    last_error: Option<String>,
+    timeline: VecDeque<ProbePoint>,
+    /// Sum of latencies in the newest window, for its running average
+    window_sum_us: u64,
+}
+
+// ─── ProbeMetrics impl ───────────────────────────────────────────
+
+impl ProbeMetrics {
+    /// `interval_ms` of 0 means the prober is disabled.
+    pub fn new(interval_ms: u64) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                interval_ms,
+                hist: Histogram::<u64>::new_with_bounds(
+                    HIST_LOW, HIST_HIGH, HIST_SIGFIG,
+                )
+                .expect("histogram creation"),
+                total_probes: 0,
+                total_errors: 0,
+                last_ok_at_ms: None,
+                last_error: None,
+                timeline: VecDeque::with_capacity(MAX_PROBE_WINDOWS + 1),
+                window_sum_us: 0,
+            }),
+        }
+    }
+
+    /// Record one probe round trip, or the error it failed with.
+    pub fn record(&self, result: Result<u64, String>) {
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let mut inner = self.inner.lock();
+        inner.total_probes += 1;
+
+        let latency_us = match result {
+            Ok(us) => {
+                let _ = inner.hist.record(us.max(1));
+                inner.last_ok_at_ms = Some(now_ms);
+                Some(us)
+            }
+            Err(e) => {
+                inner.total_errors += 1;
+                inner.last_error = Some(e);
+                None
+            }
+        };
+        inner.push_to_timeline(now_ms, latency_us);
+    }
+
+    pub fn snapshot(&self) -> ProbeSnapshot {
+        let inner = self.inner.lock();
+        ProbeSnapshot {
+            enabled: inner.interval_ms > 0,
+            interval_ms: inner.interval_ms,
+            latency: PercentileSet::from_histogram(&inner.hist),
+            total_probes: inner.total_probes,
+            total_errors: inner.total_errors,
+            last_ok_at_ms: inner.last_ok_at_ms,
+            last_error: inner.last_error.clone(),
+            timeline: inner.timeline.iter().cloned().collect(),
+        }
+    }
+}
+
+impl Inner {
+    fn push_to_timeline(&mut self, now_ms: i64, latency_us: Option<u64>) {
+        let window_start = now_ms - now_ms.rem_euclid(PROBE_WINDOW_MS);
+
+        let same_window = self
+            .timeline
+            .back()
+            .is_some_and(|p| p.timestamp_ms == window_start);
+        if !same_window {
+            self.window_sum_us = 0;
+            self.timeline.push_back(ProbePoint {
+                timestamp_ms: window_start,
+                avg_us: 0.0,
+                max_us: 0,
+                count: 0,
+                errors: 0,
+            });
+            if self.timeline.len() > MAX_PROBE_WINDOWS {
+                self.timeline.pop_front();
+            }
+        }
+
+        let point = self.timeline.back_mut().expect("window just ensured");
+        match latency_us {
+            Some(us) => {
+                self.window_sum_us += us;
+                point.count += 1;
+                point.max_us = point.max_us.max(us);
+                point.avg_us = self.window_sum_us as f64 / point.count as f64;
+            }
+            None => point.errors += 1,
+        }
+    }
+}
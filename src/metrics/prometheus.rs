@@ -0,0 +1,236 @@
+use axum::{extract::State, http::header, response::IntoResponse};
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use super::sink::{SampleEvent, SampleSink};
+use super::CacheOutcome;
+use crate::AppState;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// Histogram bucket upper bounds, μs (100 μs → 5 s)
+const BUCKETS_US: [u64; 15] = [
+    100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000,
+    250_000, 500_000, 1_000_000, 2_500_000, 5_000_000,
+];
+
+/// Measurement layers, in exposition order
+const LAYERS: [&str; 3] = ["redis", "rust_overhead", "e2e"];
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+// ─── Sink ────────────────────────────────────────────────────────
+
+/// Counters and fixed-bucket histograms per endpoint for `/metrics`.
+/// Clones share the series: the collector subscribes one for the
+/// whole process and reads the others back at scrape time.
+#[derive(Clone, Default)]
+pub struct PrometheusSink {
+    endpoints: Arc<Mutex<BTreeMap<String, EndpointSeries>>>,
+}
+
+#[derive(Default)]
+struct EndpointSeries {
+    requests: u64,
+    errors: u64,
+    timeouts: u64,
+    /// One per entry in `LAYERS`
+    layers: [Buckets; 3],
+}
+
+/// Every series sharing one metric name, with its help text.
+pub struct Family {
+    name: String,
+    help: &'static str,
+    kind: &'static str,
+    pub series: Vec<Series>,
+}
+
+/// One labelled value; histogram families hold `_bucket`, `_sum` and
+/// `_count` series.
+pub struct Series {
+    pub name: String,
+    pub labels: Vec<(&'static str, String)>,
+    pub value: f64,
+}
+
+impl Family {
+    fn new(name: String, help: &'static str, kind: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            kind,
+            series: Vec::new(),
+        }
+    }
+
+    fn push(
+        &mut self,
+        suffix: &str,
+        labels: Vec<(&'static str, String)>,
+        value: f64,
+    ) {
+        self.series.push(Series {
+            name: format!("{}{suffix}", self.name),
+            labels,
+            value,
+        });
+    }
+}
+
+#[derive(Default)]
+struct Buckets {
+    /// Non-cumulative; the last slot counts values above every bound
+    counts: [u64; BUCKETS_US.len() + 1],
+    sum_us: u64,
+}
+
+impl Buckets {
+    fn record(&mut self, us: u64) {
+        let slot = BUCKETS_US
+            .iter()
+            .position(|&bound| us <= bound)
+            .unwrap_or(BUCKETS_US.len());
+        self.counts[slot] += 1;
+        self.sum_us += us;
+    }
+}
+
+impl SampleSink for PrometheusSink {
+    fn record(&mut self, event: &SampleEvent<'_>) {
+        let sample = event.sample;
+        let mut endpoints = self.endpoints.lock();
+        let series = match endpoints.get_mut(&sample.endpoint) {
+            Some(series) => series,
+            None => endpoints.entry(sample.endpoint.clone()).or_default(),
+        };
+        series.requests += 1;
+        series.errors += !sample.success as u64;
+        series.timeouts += sample.timed_out as u64;
+        // A local cache hit made no Redis round trip to time
+        if sample.cache != CacheOutcome::Hit {
+            series.layers[0].record(sample.redis_us);
+        }
+        series.layers[1].record(sample.rust_us);
+        series.layers[2].record(sample.total_us);
+    }
+}
+
+impl PrometheusSink {
+    /// Every series, one family per metric name.
+    pub fn families(&self) -> Vec<Family> {
+        let endpoints = self.endpoints.lock();
+        let mut families = Vec::new();
+        let counters = [
+            ("requests", "Requests recorded", 0),
+            ("errors", "Requests that failed", 1),
+            ("timeouts", "Requests that hit the per-op timeout", 2),
+        ];
+        for (name, help, which) in counters {
+            let name = format!("redis_bench_{name}_total");
+            let mut family = Family::new(name, help, "counter");
+            for (endpoint, series) in endpoints.iter() {
+                let value = [series.requests, series.errors, series.timeouts];
+                let labels = vec![("endpoint", endpoint.clone())];
+                family.push("", labels, value[which] as f64);
+            }
+            families.push(family);
+        }
+
+        let mut family = Family::new(
+            "redis_bench_latency_seconds".into(),
+            "Request latency per layer",
+            "histogram",
+        );
+        for (endpoint, series) in endpoints.iter() {
+            for (layer, buckets) in LAYERS.iter().zip(&series.layers) {
+                let labels = vec![
+                    ("layer", layer.to_string()),
+                    ("endpoint", endpoint.clone()),
+                ];
+                let bucket = |le: String| {
+                    let mut labels = labels.clone();
+                    labels.push(("le", le));
+                    labels
+                };
+                let mut cumulative = 0;
+                for (bound, count) in BUCKETS_US.iter().zip(&buckets.counts) {
+                    cumulative += count;
+                    let le = (*bound as f64 / 1e6).to_string();
+                    family.push("_bucket", bucket(le), cumulative as f64);
+                }
+                let total = cumulative + buckets.counts[BUCKETS_US.len()];
+                let total = total as f64;
+                family.push("_bucket", bucket("+Inf".into()), total);
+                let sum = buckets.sum_us as f64 / 1e6;
+                family.push("_sum", labels.clone(), sum);
+                family.push("_count", labels, total);
+            }
+        }
+        families.push(family);
+        families
+    }
+}
+
+/// Every family `/metrics` serves: the per-endpoint series plus gauges
+/// for the run in progress.
+pub fn families(state: &AppState) -> Vec<Family> {
+    let mut families = state.metrics.prometheus();
+    let running = state.load_running.load(Ordering::SeqCst);
+    let (_, active_workers) = state.metrics.progress();
+    let gauges = [
+        ("benchmark_running", "1 while a run is going", running as u64),
+        ("active_workers", "Workers running", active_workers as u64),
+    ];
+    for (name, help, value) in gauges {
+        let mut family =
+            Family::new(format!("redis_bench_{name}"), help, "gauge");
+        family.push("", Vec::new(), value as f64);
+        families.push(family);
+    }
+    families
+}
+
+/// The text exposition format.
+fn render(families: &[Family]) -> String {
+    let mut out = String::new();
+    for family in families {
+        let name = &family.name;
+        let _ = writeln!(out, "# HELP {name} {}", family.help);
+        let _ = writeln!(out, "# TYPE {name} {}", family.kind);
+        for series in &family.series {
+            out.push_str(&series.name);
+            if !series.labels.is_empty() {
+                let labels: Vec<String> = series
+                    .labels
+                    .iter()
+                    .map(|(k, v)| format!("{k}=\"{}\"", escape(v)))
+                    .collect();
+                let _ = write!(out, "{{{}}}", labels.join(","));
+            }
+            let _ = writeln!(out, " {}", series.value);
+        }
+    }
+    out
+}
+
+/// Label values escape `\`, `"` and newlines.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+// ─── GET /metrics ────────────────────────────────────────────────
+
+/// Prometheus scrape target.
+pub async fn get_prometheus(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    let out = render(&families(&state));
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], out)
+}
@@ -0,0 +1,194 @@
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use axum::body::Bytes;
+use http_body_util::Full;
+use hyper::header::{HeaderName, HOST};
+use hyper::{Request, Uri};
+use hyper_util::rt::TokioIo;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// Gives up on a post after this long; its data is dropped
+const POST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// ─── Target ──────────────────────────────────────────────────────
+
+/// An `http://` or `https://` endpoint the exporters post to.
+pub struct Target {
+    /// `host:port`, for the socket
+    pub authority: String,
+    /// As written in the URL, for the `Host` header
+    host_header: String,
+    /// Set for `https://`: the name the certificate must match
+    tls: Option<ServerName<'static>>,
+    path: String,
+}
+
+impl Target {
+    /// `what` names the URL in errors, e.g. "InfluxDB".
+    pub fn parse(url: &str, what: &str) -> Result<Self, String> {
+        let uri: Uri = url
+            .parse()
+            .map_err(|e| format!("bad {what} URL \"{url}\": {e}"))?;
+        let (tls, default_port) = match uri.scheme_str() {
+            Some("http") => (false, 80),
+            Some("https") => (true, 443),
+            _ => {
+                return Err(format!(
+                    "{what} URL \"{url}\" must start with http:// or \
+                     https://"
+                ))
+            }
+        };
+        let host = uri.host().unwrap_or_default();
+        let tls = match tls {
+            true => Some(
+                ServerName::try_from(host.to_owned())
+                    .map_err(|e| format!("bad {what} host \"{host}\": {e}"))?,
+            ),
+            false => None,
+        };
+        let port = uri.port_u16().unwrap_or(default_port);
+        Ok(Self {
+            authority: format!("{host}:{port}"),
+            host_header: uri
+                .authority()
+                .map_or(host.to_owned(), |a| a.as_str().to_owned()),
+            tls,
+            path: uri
+                .path_and_query()
+                .map_or("/".into(), |p| p.as_str().to_string()),
+        })
+    }
+}
+
+// ─── POST ────────────────────────────────────────────────────────
+
+/// One POST on a fresh connection; at one every few seconds there is
+/// nothing to gain from keeping it open. Times out after
+/// `POST_TIMEOUT`.
+pub async fn post(
+    target: &Target,
+    headers: &[(HeaderName, String)],
+    body: Bytes,
+) -> Result<(), String> {
+    tokio::time::timeout(POST_TIMEOUT, send(target, headers, body))
+        .await
+        .unwrap_or_else(|_| Err("timed out".into()))
+}
+
+async fn send(
+    target: &Target,
+    headers: &[(HeaderName, String)],
+    body: Bytes,
+) -> Result<(), String> {
+    let mut request = Request::post(target.path.as_str())
+        .header(HOST, target.host_header.as_str());
+    for (name, value) in headers {
+        request = request.header(name, value.as_str());
+    }
+    let request = request
+        .body(Full::new(body))
+        .map_err(|e| e.to_string())?;
+
+    let stream = TcpStream::connect(&target.authority)
+        .await
+        .map_err(|e| e.to_string())?;
+    match &target.tls {
+        Some(name) => {
+            let stream = TlsConnector::from(tls_config())
+                .connect(name.clone(), stream)
+                .await
+                .map_err(|e| format!("TLS: {e}"))?;
+            exchange(stream, request).await
+        }
+        None => exchange(stream, request).await,
+    }
+}
+
+async fn exchange<S>(
+    stream: S,
+    request: Request<Full<Bytes>>,
+) -> Result<(), String>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (mut sender, conn) =
+        hyper::client::conn::http1::handshake(TokioIo::new(stream))
+            .await
+            .map_err(|e| e.to_string())?;
+    tokio::spawn(async move {
+        let _ = conn.await;
+    });
+    let response = sender
+        .send_request(request)
+        .await
+        .map_err(|e| e.to_string())?;
+    match response.status().is_success() {
+        true => Ok(()),
+        false => Err(format!("HTTP {}", response.status())),
+    }
+}
+
+/// Client config trusting the system's root certificates, loaded on
+/// first use.
+fn tls_config() -> Arc<ClientConfig> {
+    static CONFIG: OnceLock<Arc<ClientConfig>> = OnceLock::new();
+    CONFIG
+        .get_or_init(|| {
+            let mut roots = RootCertStore::empty();
+            let certs = rustls_native_certs::load_native_certs()
+                .unwrap_or_default();
+            roots.add_parsable_certificates(certs);
+            let config = ClientConfig::builder()
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+            Arc::new(config)
+        })
+        .clone()
+}
+
+// ─── Failure logging ─────────────────────────────────────────────
+
+/// Logs the first failure of a streak and the recovery that ends it,
+/// rather than every failed post.
+pub struct Streak {
+    /// e.g. "InfluxDB writes"
+    what: &'static str,
+    failing: bool,
+}
+
+impl Streak {
+    pub fn new(what: &'static str) -> Self {
+        Self {
+            what,
+            failing: false,
+        }
+    }
+
+    pub fn note(&mut self, target: &Target, result: Result<(), String>) {
+        let what = self.what;
+        match result {
+            Ok(()) if self.failing => {
+                self.failing = false;
+                println!("📤 {what} to {} recovered", target.authority);
+            }
+            Ok(()) => {}
+            Err(e) if !self.failing => {
+                self.failing = true;
+                eprintln!(
+                    "⚠️  {what} to {} failing, dropping data until they \
+                     recover: {e}",
+                    target.authority
+                );
+            }
+            Err(_) => {}
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use hyper::header::{HeaderName, AUTHORIZATION, CONTENT_ENCODING, CONTENT_TYPE};
+use prost::Message;
+
+use super::prometheus::{self, Family};
+use super::push::{self, Streak, Target};
+use crate::config::RemoteWriteSettings;
+use crate::AppState;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// `job` label on every pushed series, same as the scrape job the
+/// README suggests
+const JOB: &str = "redis-bench";
+
+/// `instance` label when `HOSTNAME` is unset
+const DEFAULT_INSTANCE: &str = "localhost:3000";
+
+// ─── Wire format ─────────────────────────────────────────────────
+//
+// The subset of `prometheus.WriteRequest` (remote-write 1.0) that
+// plain samples need.
+
+#[derive(Clone, PartialEq, Message)]
+struct WriteRequest {
+    #[prost(message, repeated, tag = "1")]
+    timeseries: Vec<TimeSeries>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct TimeSeries {
+    /// Sorted by name, `__name__` included
+    #[prost(message, repeated, tag = "1")]
+    labels: Vec<Label>,
+    #[prost(message, repeated, tag = "2")]
+    samples: Vec<Point>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Label {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(string, tag = "2")]
+    value: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Point {
+    #[prost(double, tag = "1")]
+    value: f64,
+    /// Epoch milliseconds
+    #[prost(int64, tag = "2")]
+    timestamp: i64,
+}
+
+// ─── Pusher ──────────────────────────────────────────────────────
+
+/// Spawn a task that pushes everything `/metrics` serves to a
+/// remote-write endpoint every `interval_ms`, for boxes no Prometheus
+/// can reach to scrape.
+pub fn start(
+    settings: RemoteWriteSettings,
+    state: Arc<AppState>,
+) -> Result<(), String> {
+    let target = Target::parse(&settings.url, "remote-write")?;
+    let mut headers = vec![
+        (CONTENT_TYPE, "application/x-protobuf".to_string()),
+        (CONTENT_ENCODING, "snappy".to_string()),
+        (
+            HeaderName::from_static("x-prometheus-remote-write-version"),
+            "0.1.0".to_string(),
+        ),
+    ];
+    if let Some(token) = &settings.token {
+        headers.push((AUTHORIZATION, format!("Bearer {token}")));
+    }
+    let instance = std::env::var("HOSTNAME")
+        .unwrap_or_else(|_| DEFAULT_INSTANCE.into());
+    let interval = Duration::from_millis(settings.interval_ms);
+    tokio::spawn(push(target, headers, instance, interval, state));
+    Ok(())
+}
+
+async fn push(
+    target: Target,
+    headers: Vec<(HeaderName, String)>,
+    instance: String,
+    interval: Duration,
+    state: Arc<AppState>,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut streak = Streak::new("Remote writes");
+
+    loop {
+        ticker.tick().await;
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let families = prometheus::families(&state);
+        let result = match encode(&families, &instance, now_ms) {
+            Ok(body) => push::post(&target, &headers, body.into()).await,
+            Err(e) => Err(e),
+        };
+        streak.note(&target, result);
+    }
+}
+
+/// Every series as one sample at `now_ms`, protobuf then snappy.
+fn encode(
+    families: &[Family],
+    instance: &str,
+    now_ms: i64,
+) -> Result<Vec<u8>, String> {
+    let label = |name: &str, value: &str| Label {
+        name: name.to_owned(),
+        value: value.to_owned(),
+    };
+    let timeseries = families
+        .iter()
+        .flat_map(|family| &family.series)
+        .map(|series| {
+            let mut labels = vec![
+                label("__name__", &series.name),
+                label("job", JOB),
+                label("instance", instance),
+            ];
+            labels.extend(series.labels.iter().map(|(k, v)| label(k, v)));
+            labels.sort_by(|a, b| a.name.cmp(&b.name));
+            TimeSeries {
+                labels,
+                samples: vec![Point {
+                    value: series.value,
+                    timestamp: now_ms,
+                }],
+            }
+        })
+        .collect();
+    let raw = WriteRequest { timeseries }.encode_to_vec();
+    snap::raw::Encoder::new()
+        .compress_vec(&raw)
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,68 @@
+use serde_json::{Map, Value};
+
+/// Pick leaf fields out of a serialized snapshot into a flat object.
+///
+/// `fields` is a comma-separated list of dotted paths:
+///   * `e2e.p99`            — object member access
+///   * `timeline.0.count`   — numeric segments index into arrays
+///   * `e2e.*`              — `*` expands every child at that level
+///
+/// The result maps each concrete path to its value; unknown paths map
+/// to `null` so scripts get a stable shape.
+pub fn select(value: &Value, fields: &str) -> Value {
+    let mut out = Map::new();
+    for path in fields.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let segments: Vec<&str> = path.split('.').collect();
+        let before = out.len();
+        collect(value, &segments, String::new(), &mut out);
+        if out.len() == before && !path.contains('*') {
+            out.insert(path.to_owned(), Value::Null);
+        }
+    }
+    Value::Object(out)
+}
+
+fn collect(
+    value: &Value,
+    segments: &[&str],
+    prefix: String,
+    out: &mut Map<String, Value>,
+) {
+    let Some((head, rest)) = segments.split_first() else {
+        out.insert(prefix, value.clone());
+        return;
+    };
+
+    let join = |key: &str| {
+        if prefix.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{prefix}.{key}")
+        }
+    };
+
+    match (value, *head) {
+        (Value::Object(map), "*") => {
+            for (key, child) in map {
+                collect(child, rest, join(key), out);
+            }
+        }
+        (Value::Array(items), "*") => {
+            for (i, child) in items.iter().enumerate() {
+                collect(child, rest, join(&i.to_string()), out);
+            }
+        }
+        (Value::Object(map), key) => {
+            if let Some(child) = map.get(key) {
+                collect(child, rest, join(key), out);
+            }
+        }
+        (Value::Array(items), idx) => {
+            let child = idx.parse::<usize>().ok().and_then(|i| items.get(i));
+            if let Some(child) = child {
+                collect(child, rest, join(idx), out);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,1321 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BinaryHeap, HashMap, VecDeque};
+use std::sync::atomic::{self, AtomicU64};
+use std::sync::Arc;
+
+use hdrhistogram::Histogram;
+use tokio::sync::mpsc;
+
+use super::buckets::{bucket_counts, regroup};
+use super::collector::{
+    ErrorCounts, ErrorRecord, Heatmap, HeatmapColumn, KeyHits, SampleRecord,
+    RequestDetail, SlowRequests, TimelinePoint, DIST_BOUNDARIES, HIST_HIGH,
+    HIST_LOW, HIST_SIGFIG,
+};
+use super::{CacheOutcome, ErrorCategory, Sample};
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// How many failed requests we keep, with their messages
+const MAX_RECENT_ERRORS: usize = 50;
+
+/// Requests slower than this (end-to-end μs) go to the slow log until
+/// a config sets `slow_threshold_us`
+const SLOW_THRESHOLD_US: u64 = 5_000;
+
+/// Slowest requests kept for the whole run until a config sets
+/// `top_outliers`
+const TOP_OUTLIERS: usize = 10;
+
+/// Aggregate timeline resolution (one point per window) until a
+/// config sets `timeline_window_ms`
+const TIMELINE_WINDOW_MS: u64 = 500;
+
+/// Closed windows are merged into wider ones as they age: into 10 s
+/// windows after `FULL_RES_AGE_MS`, into 60 s windows after
+/// `COARSE_AGE_MS`
+const FULL_RES_AGE_MS: u64 = 10 * 60_000;
+const MID_WINDOW_MS: u64 = 10_000;
+const COARSE_AGE_MS: u64 = 60 * 60_000;
+const COARSE_WINDOW_MS: u64 = 60_000;
+
+/// Full-resolution windows kept however narrow they are, so a 10 ms
+/// timeline does not keep 60,000 of them
+const MAX_FULL_RES_POINTS: u64 = 1_200;
+
+/// Per-window histograms trade precision for size: 1% is plenty for a
+/// chart
+const WINDOW_HIST_SIGFIG: u8 = 2;
+
+/// Payload size histograms: up to 1 GiB at 1%
+pub(super) const SIZE_HIST_HIGH: u64 = 1 << 30;
+const SIZE_HIST_SIGFIG: u8 = 2;
+
+/// Rolling windows as (slot width ms, slots): the last 10 s in 1 s
+/// slots and the last 60 s in 5 s slots
+const ROLLING_WINDOWS: [(u64, usize); 2] = [(1_000, 10), (5_000, 12)];
+
+/// Events a subscribed sink may fall behind by; past it they are
+/// dropped and counted
+const SUBSCRIBER_QUEUE: usize = 65_536;
+
+/// 1 s slots the headline request rate is taken over: the current,
+/// partial one and the four before it
+const RATE_SLOTS: u64 = 5;
+
+// ─── Sink interface ──────────────────────────────────────────────
+
+/// One output of the collector. The snapshot's own sinks are handed
+/// every sample in turn, under the collector's lock, so a snapshot
+/// always sees them all at the same sample. Any other output is
+/// subscribed with `MetricsCollector::subscribe` and runs on its own
+/// task, behind a `Subscription`.
+pub trait SampleSink {
+    fn record(&mut self, event: &SampleEvent<'_>);
+}
+
+/// A sample plus the run context sinks need to place it.
+pub struct SampleEvent<'a> {
+    /// Milliseconds since the run's time zero
+    pub elapsed_ms: u64,
+    /// Wall clock when it was recorded, ns since the Unix epoch, for
+    /// subscribers that run behind
+    pub at_ns: i64,
+    pub sample: &'a Sample,
+    pub gauges: Gauges,
+    /// Id the next snapshot will carry
+    pub next_snapshot_id: u64,
+}
+
+/// Point-in-time readings that are not per-sample but get bucketed
+/// alongside samples (timeline maxima).
+#[derive(Debug, Clone, Copy)]
+pub struct Gauges {
+    pub active_workers: u32,
+    pub queue_depth: u64,
+    pub client_omem_bytes: u64,
+    pub expired_per_sec: f64,
+}
+
+// ─── Subscriptions ───────────────────────────────────────────────
+
+/// A `SampleEvent` that owns its sample, queued for a subscriber.
+struct QueuedEvent {
+    elapsed_ms: u64,
+    at_ns: i64,
+    sample: Sample,
+    gauges: Gauges,
+    next_snapshot_id: u64,
+}
+
+/// The collector's end of a subscribed sink: copies each event onto
+/// the sink's queue without waiting for it. A sink that falls
+/// `SUBSCRIBER_QUEUE` events behind misses samples, counted in
+/// `dropped`, rather than slowing the workers down.
+pub struct Subscription {
+    tx: Option<mpsc::Sender<QueuedEvent>>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl Subscription {
+    /// Run `sink` on a task of its own, fed by the returned end.
+    pub fn spawn(
+        mut sink: impl SampleSink + Send + 'static,
+        dropped: Arc<AtomicU64>,
+    ) -> Self {
+        let (tx, mut rx) = mpsc::channel::<QueuedEvent>(SUBSCRIBER_QUEUE);
+        tokio::spawn(async move {
+            while let Some(queued) = rx.recv().await {
+                sink.record(&SampleEvent {
+                    elapsed_ms: queued.elapsed_ms,
+                    at_ns: queued.at_ns,
+                    sample: &queued.sample,
+                    gauges: queued.gauges,
+                    next_snapshot_id: queued.next_snapshot_id,
+                });
+            }
+        });
+        Self {
+            tx: Some(tx),
+            dropped,
+        }
+    }
+}
+
+impl SampleSink for Subscription {
+    fn record(&mut self, event: &SampleEvent<'_>) {
+        let Some(tx) = &self.tx else {
+            return;
+        };
+        let queued = QueuedEvent {
+            elapsed_ms: event.elapsed_ms,
+            at_ns: event.at_ns,
+            sample: event.sample.clone(),
+            gauges: event.gauges,
+            next_snapshot_id: event.next_snapshot_id,
+        };
+        match tx.try_send(queued) {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, atomic::Ordering::Relaxed);
+            }
+            // The sink's task is gone; stop copying for it
+            Err(mpsc::error::TrySendError::Closed(_)) => self.tx = None,
+        }
+    }
+}
+
+// ─── Latency histograms and counters ─────────────────────────────
+
+/// The per-request measurement layers and totals behind the snapshot's
+/// percentiles.
+pub struct LatencySink {
+    pub redis_read_hist: Histogram<u64>,
+    pub redis_write_hist: Histogram<u64>,
+    pub rust_overhead_hist: Histogram<u64>,
+    pub e2e_hist: Histogram<u64>,
+    pub e2e_corrected_hist: Histogram<u64>,
+    pub op_hists: BTreeMap<&'static str, Histogram<u64>>,
+    /// Calls behind each of `op_hists`
+    pub op_counts: BTreeMap<&'static str, OpCounts>,
+    /// `payload_bytes` of each command's calls
+    pub op_sizes: BTreeMap<&'static str, Histogram<u64>>,
+    pub cache_hit_hist: Histogram<u64>,
+    pub cache_miss_hist: Histogram<u64>,
+
+    // Sample-to-sample variation of the layers above
+    pub redis_read_jitter: Jitter,
+    pub redis_write_jitter: Jitter,
+    pub rust_overhead_jitter: Jitter,
+    pub e2e_jitter: Jitter,
+    pub e2e_corrected_jitter: Jitter,
+    pub op_jitter: BTreeMap<&'static str, Jitter>,
+
+    pub total_requests: u64,
+    pub total_errors: u64,
+    pub total_timeouts: u64,
+    pub total_reads: u64,
+    pub total_writes: u64,
+    pub total_cache_hits: u64,
+    pub total_cache_misses: u64,
+    pub total_bytes_read: u64,
+    pub total_bytes_written: u64,
+}
+
+/// Redis calls of one command; local cache hits made none.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OpCounts {
+    pub calls: u64,
+    pub errors: u64,
+    pub timeouts: u64,
+    /// `payload_bytes` summed
+    pub bytes: u64,
+}
+
+/// Mean absolute difference between consecutive samples of the same
+/// endpoint, so interleaved endpoints of different speeds do not count
+/// as jitter.
+#[derive(Debug, Default)]
+pub struct Jitter {
+    /// Latest value per endpoint
+    last: HashMap<String, u64>,
+    sum: u64,
+    pairs: u64,
+}
+
+impl Jitter {
+    fn add(&mut self, endpoint: &str, value: u64) {
+        match self.last.get_mut(endpoint) {
+            Some(last) => {
+                self.sum += last.abs_diff(value);
+                self.pairs += 1;
+                *last = value;
+            }
+            None => {
+                self.last.insert(endpoint.to_owned(), value);
+            }
+        }
+    }
+
+    pub fn mean(&self) -> f64 {
+        match self.pairs {
+            0 => 0.0,
+            n => self.sum as f64 / n as f64,
+        }
+    }
+
+    fn memory_bytes(&self) -> usize {
+        self.last.keys().map(|k| entry_bytes::<u64>(k)).sum()
+    }
+}
+
+pub(super) fn new_hist() -> Histogram<u64> {
+    Histogram::<u64>::new_with_bounds(HIST_LOW, HIST_HIGH, HIST_SIGFIG)
+        .expect("histogram creation")
+}
+
+pub(super) fn new_size_hist() -> Histogram<u64> {
+    Histogram::<u64>::new_with_bounds(1, SIZE_HIST_HIGH, SIZE_HIST_SIGFIG)
+        .expect("histogram creation")
+}
+
+/// Bytes held by a histogram's counts.
+pub fn hist_bytes(hist: &Histogram<u64>) -> usize {
+    hist.distinct_values() * size_of::<u64>()
+}
+
+/// Rough bytes held by a string-keyed map entry: the key, the value
+/// and the map's own bookkeeping.
+fn entry_bytes<V>(key: &str) -> usize {
+    key.len() + size_of::<String>() + size_of::<V>() + 16
+}
+
+impl LatencySink {
+    pub fn new() -> Self {
+        Self {
+            redis_read_hist: new_hist(),
+            redis_write_hist: new_hist(),
+            rust_overhead_hist: new_hist(),
+            e2e_hist: new_hist(),
+            e2e_corrected_hist: new_hist(),
+            op_hists: BTreeMap::new(),
+            op_counts: BTreeMap::new(),
+            op_sizes: BTreeMap::new(),
+            cache_hit_hist: new_hist(),
+            cache_miss_hist: new_hist(),
+            redis_read_jitter: Jitter::default(),
+            redis_write_jitter: Jitter::default(),
+            rust_overhead_jitter: Jitter::default(),
+            e2e_jitter: Jitter::default(),
+            e2e_corrected_jitter: Jitter::default(),
+            op_jitter: BTreeMap::new(),
+            total_requests: 0,
+            total_errors: 0,
+            total_timeouts: 0,
+            total_reads: 0,
+            total_writes: 0,
+            total_cache_hits: 0,
+            total_cache_misses: 0,
+            total_bytes_read: 0,
+            total_bytes_written: 0,
+        }
+    }
+
+    /// Estimated bytes held by the histograms and jitter state.
+    pub fn memory_bytes(&self) -> usize {
+        let hists = [
+            &self.redis_read_hist,
+            &self.redis_write_hist,
+            &self.rust_overhead_hist,
+            &self.e2e_hist,
+            &self.e2e_corrected_hist,
+            &self.cache_hit_hist,
+            &self.cache_miss_hist,
+        ];
+        let jitters = [
+            &self.redis_read_jitter,
+            &self.redis_write_jitter,
+            &self.rust_overhead_jitter,
+            &self.e2e_jitter,
+            &self.e2e_corrected_jitter,
+        ];
+        hists
+            .into_iter()
+            .chain(self.op_hists.values())
+            .chain(self.op_sizes.values())
+            .map(hist_bytes)
+            .sum::<usize>()
+            + jitters
+                .into_iter()
+                .chain(self.op_jitter.values())
+                .map(Jitter::memory_bytes)
+                .sum::<usize>()
+            + self.op_counts.len() * size_of::<OpCounts>()
+    }
+}
+
+impl SampleSink for LatencySink {
+    fn record(&mut self, event: &SampleEvent<'_>) {
+        let sample = event.sample;
+
+        // ── Counters ────────────────────────────────────────────
+        self.total_requests += 1;
+        if !sample.success {
+            self.total_errors += 1;
+        }
+        if sample.timed_out {
+            self.total_timeouts += 1;
+        }
+
+        // ── Histograms (clamp to ≥ 1 μs) ───────────────────────
+        let redis_us = sample.redis_us.max(1);
+        let rust_us = sample.rust_us.max(1);
+        let total_us = sample.total_us.max(1);
+        let endpoint = sample.endpoint.as_str();
+
+        if sample.is_read && sample.cache == CacheOutcome::Hit {
+            // Served locally — no Redis round-trip to attribute
+            self.total_reads += 1;
+            self.total_cache_hits += 1;
+            let _ = self.cache_hit_hist.record(total_us);
+        } else if sample.is_read {
+            self.total_reads += 1;
+            self.total_bytes_read += sample.payload_bytes;
+            let _ = self.redis_read_hist.record(redis_us);
+            self.redis_read_jitter.add(endpoint, redis_us);
+            if sample.cache == CacheOutcome::Miss {
+                self.total_cache_misses += 1;
+                let _ = self.cache_miss_hist.record(total_us);
+            }
+        } else {
+            self.total_writes += 1;
+            self.total_bytes_written += sample.payload_bytes;
+            let _ = self.redis_write_hist.record(redis_us);
+            self.redis_write_jitter.add(endpoint, redis_us);
+        }
+        if sample.cache != CacheOutcome::Hit {
+            let _ = self
+                .op_hists
+                .entry(sample.op)
+                .or_insert_with(new_hist)
+                .record(redis_us);
+            self.op_jitter
+                .entry(sample.op)
+                .or_default()
+                .add(endpoint, redis_us);
+            let counts = self.op_counts.entry(sample.op).or_default();
+            counts.calls += 1;
+            counts.errors += !sample.success as u64;
+            counts.timeouts += sample.timed_out as u64;
+            counts.bytes += sample.payload_bytes;
+            let _ = self
+                .op_sizes
+                .entry(sample.op)
+                .or_insert_with(new_size_hist)
+                .record(sample.payload_bytes.min(SIZE_HIST_HIGH));
+        }
+        let corrected_us = total_us.saturating_add(sample.schedule_lag_us);
+        let _ = self.rust_overhead_hist.record(rust_us);
+        let _ = self.e2e_hist.record(total_us);
+        let _ = self.e2e_corrected_hist.record(corrected_us);
+        self.rust_overhead_jitter.add(endpoint, rust_us);
+        self.e2e_jitter.add(endpoint, total_us);
+        self.e2e_corrected_jitter.add(endpoint, corrected_us);
+    }
+}
+
+// ─── Timeline ────────────────────────────────────────────────────
+
+/// Averages per window for the timeline chart. Recent windows keep
+/// full resolution and older ones are merged into coarser tiers, so a
+/// run that goes on for hours keeps a bounded timeline.
+pub struct TimelineSink {
+    closed: Vec<TimelinePoint>,
+    /// Counts per `grid` bucket of each closed window, in step with
+    /// `closed`
+    closed_counts: Vec<Vec<u64>>,
+    current: Option<WindowAccumulator>,
+    window_ms: u64,
+    /// Bounds windows are counted against; regrouped into the
+    /// snapshot's bounds for the heatmap
+    grid: Vec<u64>,
+    /// Downsampling of older windows, finest first
+    tiers: [Tier; 2],
+    /// Closed windows kept at most: past it the coarsest tier doubles
+    /// its width, and the oldest windows are dropped if that is not
+    /// enough
+    max_points: usize,
+}
+
+/// Closed windows whose span, rounded out to `width_ms`, ended more
+/// than `age_ms` ago are merged into one window `width_ms` wide.
+#[derive(Debug, Clone, Copy)]
+struct Tier {
+    age_ms: u64,
+    width_ms: u64,
+}
+
+/// Tiers for `window_ms`-wide windows. Widths are rounded up to a
+/// multiple of the next finer width, so merged windows never straddle
+/// a boundary.
+fn tiers(window_ms: u64) -> [Tier; 2] {
+    let mid = Tier {
+        age_ms: FULL_RES_AGE_MS.min(MAX_FULL_RES_POINTS * window_ms),
+        width_ms: MID_WINDOW_MS.div_ceil(window_ms) * window_ms,
+    };
+    let coarse = Tier {
+        age_ms: COARSE_AGE_MS.max(mid.age_ms),
+        width_ms: COARSE_WINDOW_MS.div_ceil(mid.width_ms) * mid.width_ms,
+    };
+    [mid, coarse]
+}
+
+impl TimelineSink {
+    pub fn new(max_points: usize) -> Self {
+        Self {
+            closed: Vec::new(),
+            closed_counts: Vec::new(),
+            current: None,
+            window_ms: TIMELINE_WINDOW_MS,
+            grid: DIST_BOUNDARIES.to_vec(),
+            tiers: tiers(TIMELINE_WINDOW_MS),
+            max_points,
+        }
+    }
+}
+
+/// Running totals for the current timeline window.
+struct WindowAccumulator {
+    window_start_ms: u64,
+    window_ms: u64,
+    redis_sum: u64,
+    rust_sum: u64,
+    total_sum: u64,
+    count: u64,
+    reads: u64,
+    read_errors: u64,
+    write_errors: u64,
+    /// Run time of the latest request, for the rate of an open window
+    last_ms: u64,
+    max_workers: u32,
+    max_queue_depth: u64,
+    bytes_sum: u64,
+    timeouts: u64,
+    max_client_omem: u64,
+    max_expired_per_sec: f64,
+    /// End-to-end latency, for the window's percentiles
+    hist: Histogram<u64>,
+}
+
+impl WindowAccumulator {
+    /// `recycled` is the previous window's histogram, reset here rather
+    /// than reallocated for every window.
+    fn open(
+        window_start_ms: u64,
+        window_ms: u64,
+        gauges: Gauges,
+        recycled: Option<Histogram<u64>>,
+    ) -> Self {
+        let hist = match recycled {
+            Some(mut hist) => {
+                hist.reset();
+                hist
+            }
+            None => Histogram::new_with_bounds(
+                HIST_LOW,
+                HIST_HIGH,
+                WINDOW_HIST_SIGFIG,
+            )
+            .expect("histogram creation"),
+        };
+        Self {
+            window_start_ms,
+            window_ms,
+            redis_sum: 0,
+            rust_sum: 0,
+            total_sum: 0,
+            count: 0,
+            reads: 0,
+            read_errors: 0,
+            write_errors: 0,
+            last_ms: window_start_ms,
+            max_workers: gauges.active_workers,
+            max_queue_depth: gauges.queue_depth,
+            bytes_sum: 0,
+            timeouts: 0,
+            max_client_omem: gauges.client_omem_bytes,
+            max_expired_per_sec: gauges.expired_per_sec,
+            hist,
+        }
+    }
+
+    fn add(&mut self, sample: &Sample, gauges: Gauges, elapsed_ms: u64) {
+        self.redis_sum += sample.redis_us.max(1);
+        self.rust_sum += sample.rust_us.max(1);
+        self.total_sum += sample.total_us.max(1);
+        let _ = self.hist.record(sample.total_us.max(1));
+        self.count += 1;
+        match sample.is_read {
+            true => {
+                self.reads += 1;
+                self.read_errors += !sample.success as u64;
+            }
+            false => self.write_errors += !sample.success as u64,
+        }
+        self.last_ms = elapsed_ms;
+        self.bytes_sum += sample.payload_bytes;
+        self.timeouts += sample.timed_out as u64;
+        self.max_workers = self.max_workers.max(gauges.active_workers);
+        self.max_queue_depth = self.max_queue_depth.max(gauges.queue_depth);
+        self.max_client_omem =
+            self.max_client_omem.max(gauges.client_omem_bytes);
+        self.max_expired_per_sec =
+            self.max_expired_per_sec.max(gauges.expired_per_sec);
+    }
+
+    fn point(&self, finalized_in: Option<u64>) -> TimelinePoint {
+        let count = self.count as f64;
+        // A closed window covers its full width; an open one only the
+        // part that has gone by
+        let span_ms = match finalized_in {
+            Some(_) => self.window_ms,
+            None => (self.last_ms - self.window_start_ms + 1)
+                .min(self.window_ms),
+        };
+        let per_sec = |n: u64| n as f64 * 1_000.0 / span_ms as f64;
+        TimelinePoint {
+            timestamp_ms: self.window_start_ms,
+            window_ms: self.window_ms,
+            avg_redis_us: self.redis_sum as f64 / count,
+            avg_rust_us: self.rust_sum as f64 / count,
+            avg_total_us: self.total_sum as f64 / count,
+            p50_total_us: self.hist.value_at_quantile(0.50),
+            p95_total_us: self.hist.value_at_quantile(0.95),
+            p99_total_us: self.hist.value_at_quantile(0.99),
+            count: self.count,
+            requests_per_sec: per_sec(self.count),
+            reads_per_sec: per_sec(self.reads),
+            writes_per_sec: per_sec(self.count - self.reads),
+            read_errors: self.read_errors,
+            write_errors: self.write_errors,
+            active_workers: self.max_workers,
+            queue_depth: self.max_queue_depth,
+            payload_bytes: self.bytes_sum,
+            client_omem_bytes: self.max_client_omem,
+            timeouts: self.timeouts,
+            expired_per_sec: self.max_expired_per_sec,
+            finalized_in,
+        }
+    }
+}
+
+impl TimelineSink {
+    /// Windows that will not change again until downsampled.
+    pub fn closed(&self) -> &[TimelinePoint] {
+        &self.closed
+    }
+
+    /// Closed windows plus the current partial one.
+    pub fn points(&self) -> Vec<TimelinePoint> {
+        let mut points = self.closed.clone();
+        if let Some(w) = self.current.as_ref().filter(|w| w.count > 0) {
+            points.push(w.point(None));
+        }
+        points
+    }
+
+    /// Count windows against `grid`, `window_ms` wide; only called
+    /// before the first sample.
+    pub fn configure(&mut self, window_ms: u64, grid: Vec<u64>) {
+        self.window_ms = window_ms;
+        self.grid = grid;
+        self.tiers = tiers(window_ms);
+    }
+
+    /// Width of the windows now being opened.
+    pub fn window_ms(&self) -> u64 {
+        self.window_ms
+    }
+
+    /// Estimated bytes held by the closed windows and the open one.
+    pub fn memory_bytes(&self) -> usize {
+        self.closed.capacity() * size_of::<TimelinePoint>()
+            + self
+                .closed_counts
+                .iter()
+                .map(|c| size_of_val(c.as_slice()) + size_of::<Vec<u64>>())
+                .sum::<usize>()
+            + self.current.as_ref().map_or(0, |w| {
+                size_of::<WindowAccumulator>() + hist_bytes(&w.hist)
+            })
+    }
+
+    /// Closed windows plus the current partial one, bucketed by
+    /// latency into `bounds`. Unlike the timeline percentiles,
+    /// downsampled columns are exact: bucket counts simply add up.
+    pub fn heatmap(&self, bounds: &[u64]) -> Heatmap {
+        let column = |p: &TimelinePoint, counts: &[u64]| HeatmapColumn {
+            timestamp_ms: p.timestamp_ms,
+            window_ms: p.window_ms,
+            counts: regroup(counts, &self.grid, bounds),
+        };
+        let mut columns: Vec<HeatmapColumn> = self
+            .closed
+            .iter()
+            .zip(&self.closed_counts)
+            .map(|(p, counts)| column(p, counts))
+            .collect();
+        if let Some(w) = self.current.as_ref().filter(|w| w.count > 0) {
+            let counts = bucket_counts(&w.hist, &self.grid);
+            columns.push(column(&w.point(None), &counts));
+        }
+        Heatmap {
+            bounds_us: bounds.to_vec(),
+            columns,
+        }
+    }
+
+    /// Width the closed window `point` should have at `now_ms`: that
+    /// of the coarsest tier it has aged into, or its own.
+    fn target_width(&self, point: &TimelinePoint, now_ms: u64) -> u64 {
+        self.tiers
+            .iter()
+            .rev()
+            .find(|tier| {
+                let end = (point.timestamp_ms / tier.width_ms + 1)
+                    * tier.width_ms;
+                end + tier.age_ms <= now_ms
+            })
+            .map_or(point.window_ms, |tier| {
+                tier.width_ms.max(point.window_ms)
+            })
+    }
+
+    /// Merge closed windows that have aged into a coarser tier. All
+    /// windows of one merged span come due together.
+    fn downsample(&mut self, now_ms: u64, next_snapshot_id: u64) {
+        let widths: Vec<u64> = self
+            .closed
+            .iter()
+            .map(|p| self.target_width(p, now_ms))
+            .collect();
+        let due: Vec<bool> = self
+            .closed
+            .iter()
+            .zip(&widths)
+            .map(|(p, &width)| width > p.window_ms)
+            .collect();
+        let Some(first) = due.iter().position(|&d| d) else { return };
+        // Windows in between that are not due pass through unchanged
+        let end = due.iter().rposition(|&d| d).map_or(first, |i| i + 1);
+
+        let mut merged: Vec<TimelinePoint> = Vec::with_capacity(end - first);
+        let mut merged_counts: Vec<Vec<u64>> =
+            Vec::with_capacity(merged.capacity());
+        let closed = self
+            .closed
+            .drain(first..end)
+            .zip(self.closed_counts.drain(first..end))
+            .zip(widths[first..end].iter().copied());
+        for ((point, counts), width) in closed {
+            let start = point.timestamp_ms / width * width;
+            match (merged.last_mut(), merged_counts.last_mut()) {
+                (Some(last), Some(last_counts))
+                    if last.timestamp_ms == start
+                        && last.window_ms == width =>
+                {
+                    absorb(last, &point);
+                    for (sum, n) in last_counts.iter_mut().zip(counts) {
+                        *sum += n;
+                    }
+                }
+                _ if width == point.window_ms => {
+                    merged.push(point);
+                    merged_counts.push(counts);
+                }
+                _ => {
+                    // Rates spread over the wider window; a neighbour
+                    // without requests never closed, so adds nothing.
+                    // The merged window is final again from the next
+                    // snapshot on.
+                    let share = point.window_ms as f64 / width as f64;
+                    merged.push(TimelinePoint {
+                        timestamp_ms: start,
+                        window_ms: width,
+                        requests_per_sec: point.requests_per_sec * share,
+                        reads_per_sec: point.reads_per_sec * share,
+                        writes_per_sec: point.writes_per_sec * share,
+                        finalized_in: Some(next_snapshot_id),
+                        ..point
+                    });
+                    merged_counts.push(counts);
+                }
+            }
+        }
+        self.closed.splice(first..first, merged);
+        self.closed_counts.splice(first..first, merged_counts);
+    }
+
+    /// Keep at most `max_points` closed windows. A multi-day run
+    /// widens the coarsest tier; with a cap below what the finer tiers
+    /// hold, the oldest windows are evicted instead.
+    fn enforce_cap(&mut self, now_ms: u64, next_snapshot_id: u64) {
+        if self.closed.len() < self.max_points {
+            return;
+        }
+        let coarse = self.tiers[1].width_ms;
+        if self.closed.first().is_some_and(|p| p.window_ms >= coarse) {
+            self.tiers[1].width_ms *= 2;
+            self.downsample(now_ms, next_snapshot_id);
+        }
+        // Room for the window about to close
+        let excess = (self.closed.len() + 1).saturating_sub(self.max_points);
+        self.closed.drain(..excess);
+        self.closed_counts.drain(..excess);
+    }
+}
+
+/// Fold `other` into `into`: averages weighted by count, counters
+/// summed, rates added in proportion to width, per-window maxima kept.
+/// Percentiles can't be merged without the histograms, so the higher
+/// of each is kept: never better than the worst window folded in.
+fn absorb(into: &mut TimelinePoint, other: &TimelinePoint) {
+    let (a, b) = (into.count as f64, other.count as f64);
+    let mean = |x: f64, y: f64| (x * a + y * b) / (a + b);
+    into.avg_redis_us = mean(into.avg_redis_us, other.avg_redis_us);
+    into.avg_rust_us = mean(into.avg_rust_us, other.avg_rust_us);
+    into.avg_total_us = mean(into.avg_total_us, other.avg_total_us);
+    into.p50_total_us = into.p50_total_us.max(other.p50_total_us);
+    into.p95_total_us = into.p95_total_us.max(other.p95_total_us);
+    into.p99_total_us = into.p99_total_us.max(other.p99_total_us);
+    into.count += other.count;
+    let share = other.window_ms as f64 / into.window_ms as f64;
+    into.requests_per_sec += other.requests_per_sec * share;
+    into.reads_per_sec += other.reads_per_sec * share;
+    into.writes_per_sec += other.writes_per_sec * share;
+    into.read_errors += other.read_errors;
+    into.write_errors += other.write_errors;
+    into.active_workers = into.active_workers.max(other.active_workers);
+    into.queue_depth = into.queue_depth.max(other.queue_depth);
+    into.payload_bytes += other.payload_bytes;
+    into.client_omem_bytes =
+        into.client_omem_bytes.max(other.client_omem_bytes);
+    into.timeouts += other.timeouts;
+    into.expired_per_sec = into.expired_per_sec.max(other.expired_per_sec);
+}
+
+impl SampleSink for TimelineSink {
+    /// Bucket the sample into the current window, or roll over.
+    fn record(&mut self, event: &SampleEvent<'_>) {
+        let window_start = (event.elapsed_ms / self.window_ms) * self.window_ms;
+        match &self.current {
+            Some(w) if w.window_start_ms == window_start => {}
+            // New window — finalize the old one, start fresh
+            _ => {
+                let mut recycled = None;
+                if let Some(old) = self.current.take() {
+                    if old.count > 0 {
+                        // Snapshots are built under the same lock, so
+                        // the next one is the first to see this closed
+                        self.closed
+                            .push(old.point(Some(event.next_snapshot_id)));
+                        self.closed_counts
+                            .push(bucket_counts(&old.hist, &self.grid));
+                    }
+                    recycled = Some(old.hist);
+                }
+                self.downsample(event.elapsed_ms, event.next_snapshot_id);
+                self.enforce_cap(event.elapsed_ms, event.next_snapshot_id);
+                self.current = Some(WindowAccumulator::open(
+                    window_start,
+                    self.window_ms,
+                    event.gauges,
+                    recycled,
+                ));
+            }
+        }
+        if let Some(w) = &mut self.current {
+            w.add(event.sample, event.gauges, event.elapsed_ms);
+        }
+    }
+}
+
+// ─── Rolling windows ─────────────────────────────────────────────
+
+/// Latency over the last few seconds of a run, so a change late in a
+/// long run is not drowned out by everything before it.
+pub struct RollingSink {
+    /// One per entry in `ROLLING_WINDOWS`
+    windows: [RollingWindow; 2],
+}
+
+/// A ring of per-slot histograms. A slot whose time has come round
+/// again is reset and reused rather than reallocated.
+struct RollingWindow {
+    slot_ms: u64,
+    slots: Vec<Slot>,
+}
+
+#[derive(Default)]
+struct Slot {
+    /// `elapsed_ms / slot_ms` of the samples held; `None` until used
+    index: Option<u64>,
+    /// Allocated on first use
+    hists: Option<(Histogram<u64>, Histogram<u64>)>,
+    /// `payload_bytes` of the slot's reads and writes
+    bytes_read: u64,
+    bytes_written: u64,
+}
+
+/// Merged histograms of one rolling window.
+pub struct RollingHists {
+    pub window_secs: u64,
+    pub e2e: Histogram<u64>,
+    /// Redis round trip; local cache hits made none
+    pub redis: Histogram<u64>,
+}
+
+impl Default for RollingSink {
+    fn default() -> Self {
+        Self {
+            windows: ROLLING_WINDOWS.map(|(slot_ms, slots)| RollingWindow {
+                slot_ms,
+                slots: (0..slots).map(|_| Slot::default()).collect(),
+            }),
+        }
+    }
+}
+
+impl RollingSink {
+    /// Each window merged over the slots that still fall inside it at
+    /// `now_ms` (ms since time zero), shortest window first.
+    pub fn merged(&self, now_ms: u64) -> Vec<RollingHists> {
+        self.windows
+            .iter()
+            .map(|window| {
+                let mut e2e = new_hist();
+                let mut redis = new_hist();
+                let len = window.slots.len() as u64;
+                let oldest = (now_ms / window.slot_ms + 1).saturating_sub(len);
+                for slot in &window.slots {
+                    let live = slot.index.is_some_and(|i| i >= oldest);
+                    if let (true, Some((e, r))) = (live, &slot.hists) {
+                        let _ = e2e.add(e);
+                        let _ = redis.add(r);
+                    }
+                }
+                RollingHists {
+                    window_secs: window.slot_ms * len / 1_000,
+                    e2e,
+                    redis,
+                }
+            })
+            .collect()
+    }
+
+    /// Estimated bytes held by the slot histograms.
+    pub fn memory_bytes(&self) -> usize {
+        self.windows
+            .iter()
+            .flat_map(|window| &window.slots)
+            .filter_map(|slot| slot.hists.as_ref())
+            .map(|(e2e, redis)| hist_bytes(e2e) + hist_bytes(redis))
+            .sum()
+    }
+
+    /// Requests per second over the last `RATE_SLOTS` one-second slots
+    /// at `now_ms`, i.e. the last 4 to 5 s (less early in a run).
+    pub fn requests_per_sec(&self, now_ms: u64) -> f64 {
+        self.recent_rate(now_ms, |slot| {
+            slot.hists.as_ref().map_or(0, |(e2e, _)| e2e.len())
+        })
+    }
+
+    /// Payload bytes read and written per second, over the same slots
+    /// as `requests_per_sec`.
+    pub fn bytes_per_sec(&self, now_ms: u64) -> (f64, f64) {
+        (
+            self.recent_rate(now_ms, |slot| slot.bytes_read),
+            self.recent_rate(now_ms, |slot| slot.bytes_written),
+        )
+    }
+
+    fn recent_rate(&self, now_ms: u64, count: impl Fn(&Slot) -> u64) -> f64 {
+        let window = &self.windows[0];
+        let oldest = (now_ms / window.slot_ms + 1).saturating_sub(RATE_SLOTS);
+        let span_ms = now_ms - oldest * window.slot_ms;
+        if span_ms == 0 {
+            return 0.0;
+        }
+        let total: u64 = window
+            .slots
+            .iter()
+            .filter(|slot| slot.index.is_some_and(|i| i >= oldest))
+            .map(count)
+            .sum();
+        total as f64 * 1_000.0 / span_ms as f64
+    }
+}
+
+impl SampleSink for RollingSink {
+    fn record(&mut self, event: &SampleEvent<'_>) {
+        let sample = event.sample;
+        for window in &mut self.windows {
+            let index = event.elapsed_ms / window.slot_ms;
+            let len = window.slots.len() as u64;
+            let slot = &mut window.slots[(index % len) as usize];
+            let (e2e, redis) = slot
+                .hists
+                .get_or_insert_with(|| (new_hist(), new_hist()));
+            if slot.index != Some(index) {
+                e2e.reset();
+                redis.reset();
+                slot.bytes_read = 0;
+                slot.bytes_written = 0;
+                slot.index = Some(index);
+            }
+            if sample.is_read {
+                slot.bytes_read += sample.payload_bytes;
+            } else {
+                slot.bytes_written += sample.payload_bytes;
+            }
+            let _ = e2e.record(sample.total_us.max(1));
+            if sample.cache != CacheOutcome::Hit {
+                let _ = redis.record(sample.redis_us.max(1));
+            }
+        }
+    }
+}
+
+// ─── Live request feed ───────────────────────────────────────────
+
+/// The most recent individual requests.
+pub struct RecentFeed {
+    records: VecDeque<SampleRecord>,
+    max_records: usize,
+}
+
+impl RecentFeed {
+    pub fn new(max_records: usize) -> Self {
+        Self {
+            records: VecDeque::with_capacity(max_records + 1),
+            max_records,
+        }
+    }
+
+    pub fn records(&self) -> Vec<SampleRecord> {
+        self.records.iter().cloned().collect()
+    }
+
+    /// Estimated bytes held by the records and their text.
+    pub fn memory_bytes(&self) -> usize {
+        self.records.capacity() * size_of::<SampleRecord>()
+            + self
+                .records
+                .iter()
+                .map(|r| {
+                    r.endpoint.len() + r.error.as_ref().map_or(0, String::len)
+                })
+                .sum::<usize>()
+    }
+}
+
+impl SampleSink for RecentFeed {
+    fn record(&mut self, event: &SampleEvent<'_>) {
+        let sample = event.sample;
+        self.records.push_back(SampleRecord {
+            timestamp_ms: event.elapsed_ms,
+            endpoint: sample.endpoint.clone(),
+            redis_us: sample.redis_us,
+            rust_us: sample.rust_us,
+            total_us: sample.total_us,
+            is_read: sample.is_read,
+            success: sample.success,
+            timed_out: sample.timed_out,
+            error: sample.error.as_ref().map(|e| e.message.clone()),
+            status: sample.http_status,
+            payload_bytes: sample.payload_bytes,
+            cache_hit: match sample.cache {
+                CacheOutcome::Bypass => None,
+                CacheOutcome::Hit => Some(true),
+                CacheOutcome::Miss => Some(false),
+            },
+        });
+        if self.records.len() > self.max_records {
+            self.records.pop_front();
+        }
+    }
+}
+
+// ─── Error breakdown ─────────────────────────────────────────────
+
+/// Failed requests by category, plus the latest few with their
+/// messages.
+#[derive(Default)]
+pub struct ErrorSink {
+    counts: ErrorCounts,
+    recent: VecDeque<ErrorRecord>,
+}
+
+impl ErrorSink {
+    pub fn counts(&self) -> ErrorCounts {
+        self.counts
+    }
+
+    pub fn recent(&self) -> Vec<ErrorRecord> {
+        self.recent.iter().cloned().collect()
+    }
+
+    /// Estimated bytes held by the recent errors and their text.
+    pub fn memory_bytes(&self) -> usize {
+        self.recent.capacity() * size_of::<ErrorRecord>()
+            + self
+                .recent
+                .iter()
+                .map(|r| r.endpoint.len() + r.message.len())
+                .sum::<usize>()
+    }
+}
+
+impl SampleSink for ErrorSink {
+    fn record(&mut self, event: &SampleEvent<'_>) {
+        let sample = event.sample;
+        if sample.success {
+            return;
+        }
+        let (category, message) = match &sample.error {
+            Some(error) => (error.category, error.message.as_str()),
+            None => (ErrorCategory::Other, ""),
+        };
+        *self.counts.get_mut(category) += 1;
+        self.recent.push_back(ErrorRecord {
+            timestamp_ms: event.elapsed_ms,
+            endpoint: sample.endpoint.clone(),
+            op: sample.op,
+            category,
+            message: message.to_owned(),
+        });
+        if self.recent.len() > MAX_RECENT_ERRORS {
+            self.recent.pop_front();
+        }
+    }
+}
+
+// ─── Keyspace hits ───────────────────────────────────────────────
+
+/// Reads per endpoint that found their key. A miss is fast because
+/// nothing came back, so a miss-heavy run can look better than it is.
+#[derive(Default)]
+pub struct KeyspaceSink {
+    endpoints: BTreeMap<String, KeyHits>,
+}
+
+impl KeyspaceSink {
+    pub fn endpoints(&self) -> BTreeMap<String, KeyHits> {
+        self.endpoints.clone()
+    }
+
+    pub fn memory_bytes(&self) -> usize {
+        self.endpoints.keys().map(|k| entry_bytes::<KeyHits>(k)).sum()
+    }
+}
+
+impl SampleSink for KeyspaceSink {
+    fn record(&mut self, event: &SampleEvent<'_>) {
+        let sample = event.sample;
+        if !sample.is_read {
+            return;
+        }
+        let not_found = sample
+            .error
+            .as_ref()
+            .is_some_and(|e| e.category == ErrorCategory::NotFound);
+        if !sample.success && !not_found {
+            // Redis never answered; neither a hit nor a miss
+            return;
+        }
+        match self.endpoints.get_mut(&sample.endpoint) {
+            Some(hits) => hits.add(sample.success),
+            None => {
+                let mut hits = KeyHits::default();
+                hits.add(sample.success);
+                self.endpoints.insert(sample.endpoint.clone(), hits);
+            }
+        }
+    }
+}
+
+// ─── Slow-request log ────────────────────────────────────────────
+
+/// Every request slower than a threshold, with all its details, so the
+/// tail can be looked at after the live feed has moved on.
+pub struct SlowLog {
+    threshold_us: u64,
+    requests: VecDeque<RequestDetail>,
+    max_requests: usize,
+    /// Slow requests seen, evicted ones included
+    seen: u64,
+}
+
+impl SlowLog {
+    pub fn new(max_requests: usize) -> Self {
+        Self {
+            threshold_us: SLOW_THRESHOLD_US,
+            requests: VecDeque::new(),
+            max_requests,
+            seen: 0,
+        }
+    }
+
+    /// Log requests above `threshold_us` from now on.
+    pub fn set_threshold(&mut self, threshold_us: u64) {
+        self.threshold_us = threshold_us;
+    }
+
+    pub fn report(&self) -> SlowRequests {
+        SlowRequests {
+            threshold_us: self.threshold_us,
+            total: self.seen,
+            requests: self.requests.iter().cloned().collect(),
+        }
+    }
+
+    /// Estimated bytes held by the logged requests and their text.
+    pub fn memory_bytes(&self) -> usize {
+        self.requests.capacity() * size_of::<RequestDetail>()
+            + self.requests.iter().map(text_bytes).sum::<usize>()
+    }
+}
+
+/// Everything `event` tells about its request.
+fn request_detail(event: &SampleEvent<'_>) -> RequestDetail {
+    let sample = event.sample;
+    RequestDetail {
+        timestamp_ms: event.elapsed_ms,
+        endpoint: sample.endpoint.clone(),
+        op: sample.op,
+        key: sample.key.clone(),
+        redis_us: sample.redis_us,
+        rust_us: sample.rust_us,
+        total_us: sample.total_us,
+        schedule_lag_us: sample.schedule_lag_us,
+        is_read: sample.is_read,
+        success: sample.success,
+        timed_out: sample.timed_out,
+        error_category: sample.error.as_ref().map(|e| e.category),
+        error: sample.error.as_ref().map(|e| e.message.clone()),
+        status: sample.http_status,
+        payload_bytes: sample.payload_bytes,
+        cache_hit: match sample.cache {
+            CacheOutcome::Bypass => None,
+            CacheOutcome::Hit => Some(true),
+            CacheOutcome::Miss => Some(false),
+        },
+        active_workers: event.gauges.active_workers,
+    }
+}
+
+/// Bytes of a detail's strings.
+fn text_bytes(detail: &RequestDetail) -> usize {
+    detail.endpoint.len()
+        + detail.key.as_ref().map_or(0, String::len)
+        + detail.error.as_ref().map_or(0, String::len)
+}
+
+impl SampleSink for SlowLog {
+    fn record(&mut self, event: &SampleEvent<'_>) {
+        if event.sample.total_us <= self.threshold_us {
+            return;
+        }
+        self.seen += 1;
+        self.requests.push_back(request_detail(event));
+        if self.requests.len() > self.max_requests {
+            self.requests.pop_front();
+        }
+    }
+}
+
+// ─── Run outliers ────────────────────────────────────────────────
+
+/// The slowest requests of the whole run, with all their details.
+/// Unlike the slow log, nothing is evicted for being old: a request
+/// only makes way for a slower one.
+pub struct Outliers {
+    /// Fastest kept request on top, so it is the one replaced
+    heap: BinaryHeap<Reverse<Outlier>>,
+    capacity: usize,
+    /// Ties on `total_us` go to the earlier request
+    seq: u64,
+}
+
+struct Outlier {
+    total_us: u64,
+    seq: u64,
+    detail: RequestDetail,
+}
+
+impl Outlier {
+    fn rank(&self) -> (u64, Reverse<u64>) {
+        (self.total_us, Reverse(self.seq))
+    }
+}
+
+impl PartialEq for Outlier {
+    fn eq(&self, other: &Self) -> bool {
+        self.rank() == other.rank()
+    }
+}
+
+impl Eq for Outlier {}
+
+impl PartialOrd for Outlier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Outlier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+impl Default for Outliers {
+    fn default() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            capacity: TOP_OUTLIERS,
+            seq: 0,
+        }
+    }
+}
+
+impl Outliers {
+    /// Keep the `capacity` slowest from now on; 0 turns this off.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.heap.len() > capacity {
+            self.heap.pop();
+        }
+    }
+
+    /// Slowest first.
+    pub fn worst(&self) -> Vec<RequestDetail> {
+        let mut kept: Vec<&Outlier> =
+            self.heap.iter().map(|Reverse(o)| o).collect();
+        kept.sort_by(|a, b| b.cmp(a));
+        kept.into_iter().map(|o| o.detail.clone()).collect()
+    }
+
+    /// Estimated bytes held by the kept requests and their text.
+    pub fn memory_bytes(&self) -> usize {
+        self.heap.capacity() * size_of::<Outlier>()
+            + self
+                .heap
+                .iter()
+                .map(|Reverse(o)| text_bytes(&o.detail))
+                .sum::<usize>()
+    }
+}
+
+impl SampleSink for Outliers {
+    fn record(&mut self, event: &SampleEvent<'_>) {
+        let total_us = event.sample.total_us;
+        self.seq += 1;
+        if self.heap.len() >= self.capacity {
+            // Checked before building the detail: almost every sample
+            // is faster than the fastest kept one
+            match self.heap.peek() {
+                Some(Reverse(fastest)) if total_us > fastest.total_us => {
+                    self.heap.pop();
+                }
+                _ => return,
+            }
+        }
+        self.heap.push(Reverse(Outlier {
+            total_us,
+            seq: self.seq,
+            detail: request_detail(event),
+        }));
+    }
+}
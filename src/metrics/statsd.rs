@@ -0,0 +1,242 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc;
+
+use super::sink::{SampleEvent, SampleSink};
+use super::CacheOutcome;
+use crate::config::StatsdSettings;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// Samples waiting for the emitter; beyond this they are dropped
+const QUEUE_CAPACITY: usize = 65_536;
+
+/// Largest datagram sent, safe under a 1500-byte MTU
+const MAX_PACKET: usize = 1_432;
+
+/// Lines formatted before the emitter stops draining and sends
+const MAX_BATCH_LINES: usize = 256;
+
+// ─── Sink ────────────────────────────────────────────────────────
+
+/// Hands every sample to the emitter task, which formats and sends it
+/// to a StatsD or DogStatsD server over UDP. Subscribed to the
+/// collector for the whole process.
+pub struct StatsdSink {
+    tx: Option<mpsc::Sender<Point>>,
+    sample_rate: f64,
+    /// Samples that found the queue full, reported by the emitter
+    dropped: Arc<AtomicU64>,
+}
+
+/// The parts of a sample the emitter sends.
+struct Point {
+    endpoint: String,
+    redis_us: Option<u64>,
+    rust_us: u64,
+    total_us: u64,
+    success: bool,
+    timed_out: bool,
+}
+
+impl StatsdSink {
+    /// Spawn the emitter and return the sink that feeds it.
+    pub fn start(settings: StatsdSettings) -> Self {
+        let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let sample_rate = settings.sample_rate;
+        tokio::spawn(emit(settings, rx, dropped.clone()));
+        Self {
+            tx: Some(tx),
+            sample_rate,
+            dropped,
+        }
+    }
+}
+
+impl SampleSink for StatsdSink {
+    fn record(&mut self, event: &SampleEvent<'_>) {
+        let Some(tx) = &self.tx else {
+            return;
+        };
+        if self.sample_rate < 1.0 && rand::random::<f64>() >= self.sample_rate
+        {
+            return;
+        }
+        let sample = event.sample;
+        let point = Point {
+            endpoint: sample.endpoint.clone(),
+            // A local cache hit made no Redis round trip to time
+            redis_us: (sample.cache != CacheOutcome::Hit)
+                .then_some(sample.redis_us),
+            rust_us: sample.rust_us,
+            total_us: sample.total_us,
+            success: sample.success,
+            timed_out: sample.timed_out,
+        };
+        match tx.try_send(point) {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            // The emitter gave up (bad address); stop queueing
+            Err(mpsc::error::TrySendError::Closed(_)) => self.tx = None,
+        }
+    }
+}
+
+// ─── Emitter ─────────────────────────────────────────────────────
+
+/// Drain the queue into datagrams of up to `MAX_PACKET` bytes, one
+/// metric per line.
+async fn emit(
+    settings: StatsdSettings,
+    mut rx: mpsc::Receiver<Point>,
+    dropped: Arc<AtomicU64>,
+) {
+    let socket = match connect(&settings.addr).await {
+        Ok(socket) => socket,
+        Err(e) => {
+            eprintln!("⚠️  StatsD emitter off: {}: {e}", settings.addr);
+            return;
+        }
+    };
+    let format = Format::new(&settings);
+    let mut packet = String::with_capacity(MAX_PACKET);
+    let mut lines = Vec::new();
+
+    while let Some(point) = rx.recv().await {
+        format.lines(&point, &mut lines);
+        // Take whatever else is already queued before sending
+        while let Ok(point) = rx.try_recv() {
+            format.lines(&point, &mut lines);
+            if lines.len() >= MAX_BATCH_LINES {
+                break;
+            }
+        }
+        let lost = dropped.swap(0, Ordering::Relaxed);
+        if lost > 0 {
+            lines.push(format.counter("statsd_dropped", lost, None));
+        }
+        for line in lines.drain(..) {
+            if !packet.is_empty() && packet.len() + 1 + line.len() > MAX_PACKET
+            {
+                let _ = socket.send(packet.as_bytes()).await;
+                packet.clear();
+            }
+            if !packet.is_empty() {
+                packet.push('\n');
+            }
+            packet.push_str(&line);
+        }
+        if !packet.is_empty() {
+            let _ = socket.send(packet.as_bytes()).await;
+            packet.clear();
+        }
+    }
+}
+
+async fn connect(addr: &str) -> std::io::Result<UdpSocket> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect(addr).await?;
+    Ok(socket)
+}
+
+/// How metric lines are spelled.
+struct Format {
+    prefix: String,
+    /// DogStatsD `|#tag:value`; otherwise tags go into the metric name
+    tags: bool,
+    /// `|@rate` suffix when sampling
+    rate: String,
+}
+
+impl Format {
+    fn new(settings: &StatsdSettings) -> Self {
+        Self {
+            prefix: settings.prefix.clone(),
+            tags: settings.tags,
+            rate: match settings.sample_rate < 1.0 {
+                true => format!("|@{}", settings.sample_rate),
+                false => String::new(),
+            },
+        }
+    }
+
+    fn lines(&self, point: &Point, out: &mut Vec<String>) {
+        let endpoint = point.endpoint.as_str();
+        out.push(self.counter("requests", 1, Some(endpoint)));
+        if !point.success {
+            out.push(self.counter("errors", 1, Some(endpoint)));
+        }
+        if point.timed_out {
+            out.push(self.counter("timeouts", 1, Some(endpoint)));
+        }
+        let layers = [
+            ("redis", point.redis_us),
+            ("rust_overhead", Some(point.rust_us)),
+            ("e2e", Some(point.total_us)),
+        ];
+        for (layer, us) in layers {
+            if let Some(us) = us {
+                out.push(self.timing(endpoint, layer, us));
+            }
+        }
+    }
+
+    fn counter(&self, name: &str, n: u64, endpoint: Option<&str>) -> String {
+        let prefix = &self.prefix;
+        let rate = &self.rate;
+        match (endpoint, self.tags) {
+            (None, _) => format!("{prefix}.{name}:{n}|c"),
+            (Some(ep), true) => {
+                format!("{prefix}.{name}:{n}|c{rate}|#endpoint:{}", tag(ep))
+            }
+            (Some(ep), false) => {
+                format!("{prefix}.{}.{name}:{n}|c{rate}", slug(ep))
+            }
+        }
+    }
+
+    fn timing(&self, endpoint: &str, layer: &str, us: u64) -> String {
+        let (prefix, rate) = (&self.prefix, &self.rate);
+        let ms = us as f64 / 1_000.0;
+        match self.tags {
+            true => format!(
+                "{prefix}.latency:{ms:.3}|ms{rate}|#endpoint:{},layer:{layer}",
+                tag(endpoint)
+            ),
+            false => format!(
+                "{prefix}.{}.latency.{layer}:{ms:.3}|ms{rate}",
+                slug(endpoint)
+            ),
+        }
+    }
+}
+
+/// `GET /api/users/:id` → `GET_/api/users/:id`; DogStatsD splits tags
+/// on `,` and lines on `|`.
+fn tag(endpoint: &str) -> String {
+    endpoint
+        .chars()
+        .map(|c| match c {
+            ' ' | ',' | '|' | '#' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// `GET /api/users/:id` → `get_api_users_id`, for metric names.
+fn slug(endpoint: &str) -> String {
+    let mut out = String::with_capacity(endpoint.len());
+    for c in endpoint.chars() {
+        match c.is_ascii_alphanumeric() {
+            true => out.push(c.to_ascii_lowercase()),
+            false if !out.is_empty() && !out.ends_with('_') => out.push('_'),
+            false => {}
+        }
+    }
+    out.trim_end_matches('_').to_string()
+}
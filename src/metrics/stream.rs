@@ -1,41 +1,212 @@
 use axum::{
-    extract::State,
+    extract::{Query, State},
     response::sse::{Event, KeepAlive, Sse},
     Json,
 };
+use serde::{Deserialize, Serialize};
 use std::convert::Infallible;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio_stream::wrappers::IntervalStream;
 use tokio_stream::StreamExt;
 
-use super::collector::MetricsSnapshot;
+use super::select;
+use super::units::{self, Units};
+use super::MetricsSnapshot;
 use crate::AppState;
 
+/// Query string shared by the metrics endpoints.
+#[derive(Debug, Default, Deserialize)]
+pub struct MetricsQuery {
+    /// `?units=ms` converts every latency field to fractional milliseconds
+    #[serde(default)]
+    pub units: Units,
+
+    /// `?fields=e2e.p99,requests_per_sec` returns only those leaves as a
+    /// flat object
+    #[serde(default)]
+    pub fields: Option<String>,
+
+    /// `?recent_endpoint=GET /api/users/:id` keeps only that endpoint's
+    /// entries in `recent_samples`
+    #[serde(default)]
+    pub recent_endpoint: Option<String>,
+
+    /// `?recent_limit=50` keeps only the newest that many entries of
+    /// `recent_samples`, after `recent_endpoint`
+    #[serde(default)]
+    pub recent_limit: Option<usize>,
+}
+
+impl MetricsQuery {
+    /// Filter and size the live feed as asked; the feed itself holds
+    /// up to `--max-recent-samples` entries.
+    fn narrow_feed(&self, snapshot: &mut MetricsSnapshot) {
+        let feed = &mut snapshot.recent_samples;
+        if let Some(endpoint) = &self.recent_endpoint {
+            feed.retain(|record| &record.endpoint == endpoint);
+        }
+        if let Some(limit) = self.recent_limit {
+            feed.drain(..feed.len().saturating_sub(limit));
+        }
+    }
+}
+
+/// Serialize a snapshot, applying the requested presentation options.
+fn render(
+    mut snapshot: MetricsSnapshot,
+    query: &MetricsQuery,
+) -> serde_json::Value {
+    query.narrow_feed(&mut snapshot);
+    let mut value = serde_json::to_value(&snapshot).unwrap_or_default();
+    units::convert(&mut value, query.units);
+    match &query.fields {
+        Some(fields) => select::select(&value, fields),
+        None => value,
+    }
+}
+
 // ─── GET /api/metrics ────────────────────────────────────────────
 /// Returns a single JSON snapshot — useful for curl / debugging.
-
 pub async fn get_metrics(
     State(state): State<Arc<AppState>>,
-) -> Json<MetricsSnapshot> {
-    Json(state.metrics.snapshot())
+    Query(query): Query<MetricsQuery>,
+) -> Json<serde_json::Value> {
+    Json(render(state.metrics.snapshot(), &query))
+}
+
+// ─── GET /api/metrics/slow ───────────────────────────────────────
+/// Requests slower than the run's `slow_threshold_us`, oldest first.
+pub async fn get_slow(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<MetricsQuery>,
+) -> Json<serde_json::Value> {
+    let mut value = serde_json::to_value(state.metrics.slow_requests())
+        .unwrap_or_default();
+    units::convert(&mut value, query.units);
+    Json(value)
+}
+
+// ─── Subscriber load ─────────────────────────────────────────────
+
+/// Stream tick; every subscriber gets one event per tick at `Full`
+const TICK_MS: u64 = 500;
+
+/// At `Reduced`, one tick in this many is sent (every 2 s)
+const REDUCED_EVERY: u64 = 4;
+
+/// Connected SSE subscribers. Every snapshot is built and serialized
+/// per subscriber on the same runtime as the load workers, so a room
+/// full of dashboards shows up in the results; past `trim_after` each
+/// stream sends less.
+pub struct StreamClients {
+    connected: AtomicUsize,
+    trim_after: usize,
+}
+
+/// How far streams are cut back, from the current subscriber count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Degradation {
+    /// Every field, every 500 ms
+    Full,
+    /// More than `trim_after` subscribers: `recent_samples` and the
+    /// heatmap columns are dropped
+    Trimmed,
+    /// More than 3 × `trim_after`: trimmed, and sent every 2 s
+    Reduced,
+}
+
+/// Reported in every stream event as `stream`.
+#[derive(Debug, Serialize)]
+struct StreamStatus {
+    clients: usize,
+    degradation: Degradation,
+    interval_ms: u64,
+}
+
+impl StreamClients {
+    pub fn new(trim_after: usize) -> Self {
+        Self {
+            connected: AtomicUsize::new(0),
+            trim_after,
+        }
+    }
+
+    /// Count a subscriber until the returned guard is dropped.
+    fn join(self: &Arc<Self>) -> Subscription {
+        self.connected.fetch_add(1, Ordering::Relaxed);
+        Subscription(self.clone())
+    }
+
+    fn status(&self) -> StreamStatus {
+        let clients = self.connected.load(Ordering::Relaxed);
+        let degradation = if clients > self.trim_after * 3 {
+            Degradation::Reduced
+        } else if clients > self.trim_after {
+            Degradation::Trimmed
+        } else {
+            Degradation::Full
+        };
+        let interval_ms = match degradation {
+            Degradation::Reduced => TICK_MS * REDUCED_EVERY,
+            _ => TICK_MS,
+        };
+        StreamStatus {
+            clients,
+            degradation,
+            interval_ms,
+        }
+    }
+}
+
+/// One open stream; it leaves the count when the client disconnects
+/// and axum drops the stream.
+struct Subscription(Arc<StreamClients>);
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.0.connected.fetch_sub(1, Ordering::Relaxed);
+    }
 }
 
 // ─── GET /api/metrics/stream ─────────────────────────────────────
 /// Server-Sent Events endpoint.
-/// Pushes a full `MetricsSnapshot` as JSON every 500 ms.
+/// Pushes a `MetricsSnapshot` as JSON every 500 ms, less often and
+/// without `recent_samples` or heatmap columns when many subscribers
+/// are connected.
 /// The browser's `EventSource` connects here and feeds the charts.
-
 pub async fn metrics_stream(
     State(state): State<Arc<AppState>>,
+    Query(query): Query<MetricsQuery>,
 ) -> Sse<impl tokio_stream::Stream<Item = Result<Event, Infallible>>> {
-    // Tick every 500 ms → 2 updates per second to the dashboard
-    let interval = tokio::time::interval(Duration::from_millis(500));
+    let interval = tokio::time::interval(Duration::from_millis(TICK_MS));
+    let subscription = state.stream_clients.join();
+    let mut tick = 0u64;
 
-    let stream = IntervalStream::new(interval).map(move |_| {
-        let snapshot = state.metrics.snapshot();
-        let json = serde_json::to_string(&snapshot).unwrap_or_default();
-        Ok(Event::default().data(json))
+    let stream = IntervalStream::new(interval).filter_map(move |_| {
+        let status = subscription.0.status();
+        tick += 1;
+        if status.degradation == Degradation::Reduced
+            && tick % REDUCED_EVERY != 1
+        {
+            return None;
+        }
+        let mut snapshot = state.metrics.snapshot();
+        if status.degradation != Degradation::Full {
+            snapshot.recent_samples.clear();
+            snapshot.heatmap.columns.clear();
+        }
+        let mut value = render(snapshot, &query);
+        if let Some(fields) = value.as_object_mut() {
+            fields.insert(
+                "stream".into(),
+                serde_json::to_value(status).unwrap_or_default(),
+            );
+        }
+        let json = serde_json::to_string(&value).unwrap_or_default();
+        Some(Ok(Event::default().data(json)))
     });
 
     Sse::new(stream).keep_alive(
@@ -0,0 +1,67 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Output unit for latency fields, selected with `?units=`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+    /// Integer microseconds — the native representation
+    #[default]
+    Us,
+    /// Fractional milliseconds
+    Ms,
+}
+
+/// Fields of a `PercentileSet` that are counts, not latencies.
+const NON_LATENCY_KEYS: &[&str] = &["count"];
+
+/// Rewrite a serialized snapshot in place for the requested unit.
+///
+/// Two rules cover every latency field in the snapshot:
+///   * keys ending in `_us` become `_ms` with the value divided by 1000
+///   * objects shaped like a `PercentileSet` (they carry `p50` and `p999`)
+///     have every numeric field except the counts divided by 1000
+pub fn convert(value: &mut Value, units: Units) {
+    if units == Units::Ms {
+        to_millis(value);
+    }
+}
+
+fn to_millis(value: &mut Value) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(to_millis),
+        Value::Object(map) => {
+            let is_percentile_set =
+                map.contains_key("p50") && map.contains_key("p999");
+            let converted: Map<String, Value> = std::mem::take(map)
+                .into_iter()
+                .map(|(key, mut val)| {
+                    if let Some(stem) = key.strip_suffix("_us") {
+                        return (format!("{stem}_ms"), scale(&val));
+                    }
+                    if is_percentile_set
+                        && val.is_number()
+                        && !NON_LATENCY_KEYS.contains(&key.as_str())
+                    {
+                        return (key, scale(&val));
+                    }
+                    to_millis(&mut val);
+                    (key, val)
+                })
+                .collect();
+            *map = converted;
+        }
+        _ => {}
+    }
+}
+
+/// A number, or each number of an array such as `heatmap.bounds_us`.
+fn scale(val: &Value) -> Value {
+    if let Value::Array(items) = val {
+        return Value::Array(items.iter().map(scale).collect());
+    }
+    match val.as_f64() {
+        Some(us) => Value::from(us / 1_000.0),
+        None => val.clone(),
+    }
+}
@@ -1,13 +1,30 @@
-use axum::{extract::Request, middleware::Next, response::Response};
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
 use std::time::Instant;
 
+use crate::handlers::RequestTiming;
+use crate::metrics::MetricsCollector;
+
 /// Tower-compatible middleware that adds two response headers:
 ///
-///   X-Response-Time-Us  — total handler wall time in microseconds
-///   Server-Timing       — same value in the standard Server-Timing format
+///   X-Response-Time-Us  — total wall time through the stack in microseconds
+///   Server-Timing       — same value in the standard Server-Timing format,
+///                         plus handler / framework entries when known
+///
+/// When the handler attached its own `RequestTiming`, the difference
+/// (extraction, routing, response serialization) is recorded as
+/// framework overhead.
 ///
 /// Also prints a coloured one-liner to stdout for development.
-pub async fn timing_middleware(req: Request, next: Next) -> Response {
+pub async fn timing_middleware(
+    State(metrics): State<Arc<MetricsCollector>>,
+    req: Request,
+    next: Next,
+) -> Response {
     let method = req.method().clone();
     let path = req.uri().path().to_owned();
 
@@ -21,8 +38,17 @@ pub async fn timing_middleware(req: Request, next: Next) -> Response {
         response.headers_mut().insert("X-Response-Time-Us", val);
     }
 
-    let server_timing =
+    let mut server_timing =
         format!("total;dur={:.3}", elapsed.as_secs_f64() * 1000.0);
+    if let Some(timing) = response.extensions().get::<RequestTiming>() {
+        let framework_us = (us as u64).saturating_sub(timing.total_us);
+        metrics.record_framework(framework_us);
+        server_timing.push_str(&format!(
+            ", handler;dur={:.3}, framework;dur={:.3}",
+            timing.total_us as f64 / 1000.0,
+            framework_us as f64 / 1000.0,
+        ));
+    }
     if let Ok(val) = server_timing.parse() {
         response.headers_mut().insert("Server-Timing", val);
     }
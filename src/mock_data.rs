@@ -2,14 +2,45 @@ use rand::rngs::StdRng;
 use rand::Rng;
 use rand::SeedableRng;
 use redis::aio::ConnectionManager;
-use std::time::Instant;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
 
 // ─── Constants ───────────────────────────────────────────────────
 
-const NUM_USERS: usize = 10_000;
+pub const NUM_USERS: usize = 10_000;
 const NUM_PRODUCTS: usize = 500;
-/// Pipeline batch size — keeps Redis buffers comfortable.
+/// Sorted set of every user id, scored — read and updated by the
+/// leaderboard ops
+pub const LEADERBOARD_KEY: &str = "leaderboard:global";
+/// First pipeline batch size; each seeding task then resizes its own
+/// batches between `MIN_BATCH` and `MAX_BATCH`
 const BATCH: usize = 500;
+const MIN_BATCH: usize = 50;
+const MAX_BATCH: usize = 10_000;
+/// Batches are resized to keep each round trip near this
+const TARGET_BATCH_MS: u64 = 50;
+
+/// Bump whenever the generated data changes shape or content, so runs
+/// against old and new datasets are told apart
+pub const DATASET_VERSION: u32 = 2;
+/// RNG seed; re-runs with the same seed produce the same data
+const SEED: u64 = 42;
+/// Mixed into each record's RNG seed so users and products draw
+/// different numbers
+const USER_STREAM: u64 = 1;
+const PRODUCT_STREAM: u64 = 2;
+/// Where the manifest is written, as JSON, next to the data
+pub const MANIFEST_KEY: &str = "bench:dataset:manifest";
+/// Every Nth user and product goes into the checksum
+const USER_SAMPLE_EVERY: usize = 100;
+const PRODUCT_SAMPLE_EVERY: usize = 10;
+/// How often to check whether a BGSAVE has finished, and for how long
+const SNAPSHOT_POLL_MS: u64 = 200;
+const SNAPSHOT_TIMEOUT_SECS: u64 = 600;
 
 // ─── Name pools ──────────────────────────────────────────────────
 
@@ -140,121 +171,431 @@ static CAT: &[&str] = &[
     "gaming",
 ];
 
+// ─── Manifest ────────────────────────────────────────────────────
+
+/// What was seeded, and a checksum of a sample of it read back from
+/// Redis. Archived with every native run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetManifest {
+    pub version: u32,
+    pub seed: u64,
+    pub users: usize,
+    pub products: usize,
+    /// `ZCARD` of the leaderboard after seeding
+    pub leaderboard_entries: u64,
+    /// Keys hashed into `checksum`
+    pub sampled_keys: usize,
+    /// FNV-1a 64 over the sampled keys' contents, hex
+    pub checksum: String,
+    /// When seeding finished (RFC 3339, UTC)
+    pub seeded_at: String,
+    /// How fast the data went in; not part of the dataset's identity
+    pub load: SeedLoad,
+    /// RDB saved right after seeding, for `POST /api/seed/restore`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<RdbSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RdbSnapshot {
+    /// `LASTSAVE` once the BGSAVE finished. A later `LASTSAVE` means
+    /// the RDB file has been rewritten since and no longer holds just
+    /// the seeded data.
+    pub lastsave: i64,
+    pub secs: f64,
+    /// `DBSIZE` when the snapshot was taken
+    pub keys: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeedLoad {
+    pub connections: usize,
+    pub secs: f64,
+    /// Users and products written per second
+    pub records_per_sec: f64,
+    /// Largest pipeline batch any connection reached
+    pub max_batch: usize,
+}
+
 // ─── Public entry point ──────────────────────────────────────────
 
-pub async fn seed(conn: &ConnectionManager) {
+/// Seed users (with their leaderboard entries) and products over
+/// `connections` connections in parallel, then write the manifest.
+pub async fn seed(
+    client: &redis::Client,
+    conn: &ConnectionManager,
+    connections: usize,
+    snapshot: bool,
+) -> redis::RedisResult<DatasetManifest> {
     let start = Instant::now();
     println!(
-        "Seeding {} users and {} products into Redis...",
-        NUM_USERS, NUM_PRODUCTS
+        "Seeding {} users and {} products into Redis over {} connection{}...",
+        NUM_USERS,
+        NUM_PRODUCTS,
+        connections,
+        if connections == 1 { "" } else { "s" },
     );
 
-    let mut conn = conn.clone();
-    // Deterministic RNG so re-runs produce the same data.
-    let mut rng = StdRng::seed_from_u64(42);
+    let mut conns = vec![conn.clone()];
+    for _ in 1..connections {
+        conns.push(ConnectionManager::new(client.clone()).await?);
+    }
 
-    seed_users(&mut conn, &mut rng).await;
-    seed_products(&mut conn, &mut rng).await;
+    let _: () = redis::cmd("DEL")
+        .arg(LEADERBOARD_KEY)
+        .query_async(&mut conns[0])
+        .await?;
+    let users = fan_out(&conns, NUM_USERS, push_user).await?;
+    let products = fan_out(&conns, NUM_PRODUCTS, push_product).await?;
+    let secs = start.elapsed().as_secs_f64();
+    let records = NUM_USERS + NUM_PRODUCTS;
+    let load = SeedLoad {
+        connections,
+        secs,
+        records_per_sec: records as f64 / secs,
+        max_batch: users.max(products),
+    };
+    let mut manifest = write_manifest(&mut conns[0], load).await?;
 
     println!(
-        "   ✓ seed complete in {:.1}s",
-        start.elapsed().as_secs_f64()
+        "   ✓ seed complete in {:.1}s, {:.0} records/s, batches up to {} \
+         (dataset v{}, checksum {})",
+        secs,
+        manifest.load.records_per_sec,
+        manifest.load.max_batch,
+        manifest.version,
+        manifest.checksum,
     );
+    if snapshot {
+        match save_snapshot(&mut conns[0]).await {
+            Ok(saved) => {
+                println!(
+                    "   ✓ RDB snapshot of {} keys saved in {:.1}s",
+                    saved.keys, saved.secs
+                );
+                manifest.snapshot = Some(saved);
+            }
+            // Restores fall back to reseeding
+            Err(e) => eprintln!("   ⚠️  RDB snapshot failed: {e}"),
+        }
+    }
+    Ok(manifest)
 }
 
-// ─── Users ───────────────────────────────────────────────────────
+// ─── Snapshot ────────────────────────────────────────────────────
+
+/// `BGSAVE` and wait for it to land, so the RDB file holds exactly the
+/// seeded data.
+async fn save_snapshot(
+    conn: &mut ConnectionManager,
+) -> Result<RdbSnapshot, String> {
+    let start = Instant::now();
+    let before: i64 = redis::cmd("LASTSAVE")
+        .query_async(conn)
+        .await
+        .map_err(|e| format!("LASTSAVE: {e}"))?;
+    let _: String = redis::cmd("BGSAVE")
+        .query_async(conn)
+        .await
+        .map_err(|e| format!("BGSAVE: {e}"))?;
 
-async fn seed_users(conn: &mut ConnectionManager, rng: &mut StdRng) {
-    for batch_start in (0..NUM_USERS).step_by(BATCH) {
-        let batch_end = (batch_start + BATCH).min(NUM_USERS);
-        let mut pipe = redis::pipe();
-
-        for i in batch_start..batch_end {
-            let id = format!("usr_{:08}", i + 1);
-            let key = format!("user:{}", id);
-
-            let first = FIRST[rng.gen_range(0..FIRST.len())];
-            let last = LAST[rng.gen_range(0..LAST.len())];
-            let name = format!("{} {}", first, last);
-            let email = format!(
-                "{}.{}{}@example.com",
-                first.to_lowercase(),
-                last.to_lowercase(),
-                i + 1,
-            );
-            let role = ROLES[rng.gen_range(0..ROLES.len())];
-            let theme = if rng.gen_bool(0.5) { "dark" } else { "light" };
-            let notif = rng.gen_bool(0.7);
-            let prefs = format!(
-                r#"{{"theme":"{}","lang":"en","notifications":{}}}"#,
-                theme, notif,
-            );
-            let created = "2025-01-15T09:23:11Z";
-
-            pipe.cmd("HSET")
-                .arg(&key)
-                .arg("id")
-                .arg(&id)
-                .arg("name")
-                .arg(&name)
-                .arg("email")
-                .arg(&email)
-                .arg("role")
-                .arg(role)
-                .arg("prefs")
-                .arg(&prefs)
-                .arg("created_at")
-                .arg(created)
-                .ignore();
+    let timeout = Duration::from_secs(SNAPSHOT_TIMEOUT_SECS);
+    let lastsave = loop {
+        tokio::time::sleep(Duration::from_millis(SNAPSHOT_POLL_MS)).await;
+        let now: i64 = redis::cmd("LASTSAVE")
+            .query_async(conn)
+            .await
+            .map_err(|e| format!("LASTSAVE: {e}"))?;
+        if now != before {
+            break now;
+        }
+        if start.elapsed() > timeout {
+            return Err(format!(
+                "BGSAVE still running after {SNAPSHOT_TIMEOUT_SECS}s"
+            ));
         }
+    };
 
-        let _: () = pipe.query_async(conn).await.expect("Failed to seed users");
+    // LASTSAVE moves on failed saves too
+    let info: String = redis::cmd("INFO")
+        .arg("persistence")
+        .query_async(conn)
+        .await
+        .map_err(|e| format!("INFO persistence: {e}"))?;
+    if !info.contains("rdb_last_bgsave_status:ok") {
+        return Err("BGSAVE failed; see the Redis log".into());
     }
+    let keys: u64 = redis::cmd("DBSIZE")
+        .query_async(conn)
+        .await
+        .map_err(|e| format!("DBSIZE: {e}"))?;
+    Ok(RdbSnapshot {
+        lastsave,
+        secs: start.elapsed().as_secs_f64(),
+        keys,
+    })
 }
 
-// ─── Products ────────────────────────────────────────────────────
+// ─── Parallel loading ────────────────────────────────────────────
+
+/// Split records `0..total` across one task per connection. Tasks pull
+/// batches from a shared cursor, so a slow connection just takes fewer.
+/// Returns the largest batch size any task reached, or the first error.
+async fn fan_out(
+    conns: &[ConnectionManager],
+    total: usize,
+    push: fn(&mut redis::Pipeline, usize),
+) -> redis::RedisResult<usize> {
+    let cursor = Arc::new(AtomicUsize::new(0));
+    let mut tasks = JoinSet::<redis::RedisResult<usize>>::new();
+    for conn in conns {
+        let mut conn = conn.clone();
+        let cursor = cursor.clone();
+        tasks.spawn(async move {
+            let mut batch = BATCH;
+            let mut largest = 0;
+            loop {
+                let from = cursor.fetch_add(batch, Ordering::Relaxed);
+                if from >= total {
+                    return Ok(largest);
+                }
+                let to = (from + batch).min(total);
+                let mut pipe = redis::pipe();
+                for i in from..to {
+                    push(&mut pipe, i);
+                }
+                let sent = Instant::now();
+                let _: () = pipe.query_async(&mut conn).await?;
+                largest = largest.max(to - from);
+                batch = resize(batch, sent.elapsed());
+            }
+        });
+    }
 
-async fn seed_products(conn: &mut ConnectionManager, rng: &mut StdRng) {
-    let mut pipe = redis::pipe();
-
-    for i in 0..NUM_PRODUCTS {
-        let id = format!("prod_{:04}", i + 1);
-        let key = format!("product:{}", id);
-
-        let adj = ADJ[rng.gen_range(0..ADJ.len())];
-        let noun = NOUN[rng.gen_range(0..NOUN.len())];
-        let title = format!("{} {}", adj, noun);
-        let category = CAT[rng.gen_range(0..CAT.len())];
-        let price = rng.gen_range(999..=99_999u64); // cents
-        let stock = rng.gen_range(0..=1000u32);
-        let desc = format!(
-            "High-quality {} {} with advanced features. \
-             Perfect for {} use. Built with premium materials \
-             for long-lasting durability and peak performance.",
-            adj.to_lowercase(),
-            noun.to_lowercase(),
-            category,
-        );
-
-        pipe.cmd("HSET")
-            .arg(&key)
-            .arg("id")
-            .arg(&id)
-            .arg("title")
-            .arg(&title)
-            .arg("price")
-            .arg(price)
-            .arg("stock")
-            .arg(stock)
-            .arg("category")
-            .arg(category)
-            .arg("description")
-            .arg(&desc)
-            .ignore();
+    let mut largest = 0;
+    while let Some(done) = tasks.join_next().await {
+        let batch = done.map_err(|e| {
+            redis::RedisError::from((
+                redis::ErrorKind::ClientError,
+                "seed task failed",
+                e.to_string(),
+            ))
+        })??;
+        largest = largest.max(batch);
     }
+    Ok(largest)
+}
+
+/// Grow batches while round trips are cheap, shrink them once they
+/// get long enough to crowd Redis's buffers.
+fn resize(batch: usize, took: Duration) -> usize {
+    let target = Duration::from_millis(TARGET_BATCH_MS);
+    if took < target / 2 {
+        (batch * 2).min(MAX_BATCH)
+    } else if took > target * 2 {
+        (batch / 2).max(MIN_BATCH)
+    } else {
+        batch
+    }
+}
+
+/// Each record draws from its own RNG, so the data doesn't depend on
+/// which task built it or in what order.
+fn record_rng(stream: u64, index: usize) -> StdRng {
+    StdRng::seed_from_u64(SEED ^ (stream << 40) ^ index as u64)
+}
+
+// ─── Users ───────────────────────────────────────────────────────
 
-    let _: () = pipe
+/// User hash `i` and its leaderboard entry.
+fn push_user(pipe: &mut redis::Pipeline, i: usize) {
+    let mut rng = record_rng(USER_STREAM, i);
+    let id = format!("usr_{:08}", i + 1);
+    let key = format!("user:{}", id);
+
+    let first = FIRST[rng.gen_range(0..FIRST.len())];
+    let last = LAST[rng.gen_range(0..LAST.len())];
+    let name = format!("{} {}", first, last);
+    let email = format!(
+        "{}.{}{}@example.com",
+        first.to_lowercase(),
+        last.to_lowercase(),
+        i + 1,
+    );
+    let role = ROLES[rng.gen_range(0..ROLES.len())];
+    let theme = if rng.gen_bool(0.5) { "dark" } else { "light" };
+    let notif = rng.gen_bool(0.7);
+    let prefs = format!(
+        r#"{{"theme":"{}","lang":"en","notifications":{}}}"#,
+        theme, notif,
+    );
+    let created = "2025-01-15T09:23:11Z";
+
+    pipe.cmd("HSET")
+        .arg(&key)
+        .arg("id")
+        .arg(&id)
+        .arg("name")
+        .arg(&name)
+        .arg("email")
+        .arg(&email)
+        .arg("role")
+        .arg(role)
+        .arg("prefs")
+        .arg(&prefs)
+        .arg("created_at")
+        .arg(created)
+        .ignore();
+    pipe.cmd("ZADD")
+        .arg(LEADERBOARD_KEY)
+        .arg(rng.gen_range(0..1_000_000u32))
+        .arg(&id)
+        .ignore();
+}
+
+// ─── Products ────────────────────────────────────────────────────
+
+fn push_product(pipe: &mut redis::Pipeline, i: usize) {
+    let mut rng = record_rng(PRODUCT_STREAM, i);
+    let id = format!("prod_{:04}", i + 1);
+    let key = format!("product:{}", id);
+
+    let adj = ADJ[rng.gen_range(0..ADJ.len())];
+    let noun = NOUN[rng.gen_range(0..NOUN.len())];
+    let title = format!("{} {}", adj, noun);
+    let category = CAT[rng.gen_range(0..CAT.len())];
+    let price = rng.gen_range(999..=99_999u64); // cents
+    let stock = rng.gen_range(0..=1000u32);
+    let desc = format!(
+        "High-quality {} {} with advanced features. \
+         Perfect for {} use. Built with premium materials \
+         for long-lasting durability and peak performance.",
+        adj.to_lowercase(),
+        noun.to_lowercase(),
+        category,
+    );
+
+    pipe.cmd("HSET")
+        .arg(&key)
+        .arg("id")
+        .arg(&id)
+        .arg("title")
+        .arg(&title)
+        .arg("price")
+        .arg(price)
+        .arg("stock")
+        .arg(stock)
+        .arg("category")
+        .arg(category)
+        .arg("description")
+        .arg(&desc)
+        .ignore();
+}
+
+// ─── Checksum ────────────────────────────────────────────────────
+
+/// Read a sample of the seeded keys back, hash them, and store the
+/// manifest under [`MANIFEST_KEY`].
+async fn write_manifest(
+    conn: &mut ConnectionManager,
+    load: SeedLoad,
+) -> redis::RedisResult<DatasetManifest> {
+    let (checksum, sampled_keys) = sample_checksum(conn).await?;
+    let leaderboard_entries: u64 = redis::cmd("ZCARD")
+        .arg(LEADERBOARD_KEY)
         .query_async(conn)
-        .await
-        .expect("Failed to seed products");
+        .await?;
+
+    let manifest = DatasetManifest {
+        version: DATASET_VERSION,
+        seed: SEED,
+        users: NUM_USERS,
+        products: NUM_PRODUCTS,
+        leaderboard_entries,
+        sampled_keys,
+        checksum,
+        seeded_at: chrono::Utc::now().to_rfc3339(),
+        load,
+        snapshot: None,
+    };
+    let json = serde_json::to_string(&manifest).expect("manifest serializes");
+    let _: () = redis::cmd("SET")
+        .arg(MANIFEST_KEY)
+        .arg(json)
+        .query_async(conn)
+        .await?;
+    Ok(manifest)
+}
+
+/// Hash every Nth user (with its leaderboard score) and product as
+/// they are in Redis now. Returns the hex checksum and the number of
+/// keys sampled.
+pub async fn sample_checksum(
+    conn: &mut ConnectionManager,
+) -> redis::RedisResult<(String, usize)> {
+    let users = (0..NUM_USERS)
+        .step_by(USER_SAMPLE_EVERY)
+        .map(|i| format!("usr_{:08}", i + 1));
+    let products = (0..NUM_PRODUCTS)
+        .step_by(PRODUCT_SAMPLE_EVERY)
+        .map(|i| format!("product:prod_{:04}", i + 1));
+
+    let mut hash = Fnv1a::new();
+    let mut sampled_keys = 0;
+    for user in users {
+        let key = format!("user:{user}");
+        hash_hash(conn, &mut hash, &key).await?;
+        // The leaderboard is one key; its sample is these users' scores
+        let score: Option<f64> = redis::cmd("ZSCORE")
+            .arg(LEADERBOARD_KEY)
+            .arg(&user)
+            .query_async(conn)
+            .await?;
+        hash.write(format!("{score:?}").as_bytes());
+        sampled_keys += 1;
+    }
+    for key in products {
+        hash_hash(conn, &mut hash, &key).await?;
+        sampled_keys += 1;
+    }
+    Ok((format!("{:016x}", hash.finish()), sampled_keys))
+}
+
+/// Feed a hash key and its fields, in field order, into `hash`.
+async fn hash_hash(
+    conn: &mut ConnectionManager,
+    hash: &mut Fnv1a,
+    key: &str,
+) -> redis::RedisResult<()> {
+    let fields: BTreeMap<String, String> =
+        redis::cmd("HGETALL").arg(key).query_async(conn).await?;
+    hash.write(key.as_bytes());
+    for (field, value) in fields {
+        hash.write(field.as_bytes());
+        hash.write(value.as_bytes());
+    }
+    Ok(())
+}
+
+/// 64-bit FNV-1a. Stable across Rust releases, unlike `DefaultHasher`,
+/// so checksums from different builds can be compared.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    /// Hash `bytes` followed by a 0 separator, so `ab`+`c` and `a`+`bc`
+    /// differ
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes.iter().chain(&[0]) {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
 }
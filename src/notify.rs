@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use serde_json::json;
+
+use crate::config::NotifySettings;
+use crate::report::{RunOutcome, RunReport};
+use crate::webhook::Webhook;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// Used when `--notify-template` is not given
+pub const DEFAULT_TEMPLATE: &str = "{icon} redis-bench {name} {outcome}: \
+     {result}\n{rps} req/s · p50 {p50} · p99 {p99} · errors {error_rate}\
+     \n{reasons}";
+
+// ─── Notifier ────────────────────────────────────────────────────
+
+/// Posts a one-message summary of each finished benchmark or scenario
+/// run to a Slack or Discord incoming webhook.
+pub struct Notifier {
+    hook: Arc<Webhook>,
+    /// Discord wants `content`; Slack and its clones want `text`
+    discord: bool,
+    template: String,
+}
+
+impl Notifier {
+    pub fn new(settings: &NotifySettings) -> Result<Self, String> {
+        let discord = settings.url.contains("discord.com/")
+            || settings.url.contains("discordapp.com/");
+        let what = match discord {
+            true => "Discord notification",
+            false => "Slack notification",
+        };
+        Ok(Self {
+            hook: Arc::new(Webhook::json(&settings.url, what, Vec::new())?),
+            discord,
+            template: settings
+                .template
+                .as_deref()
+                .unwrap_or(DEFAULT_TEMPLATE)
+                .replace("\\n", "\n"),
+        })
+    }
+
+    /// Post the summary in the background.
+    pub fn send(&self, report: &RunReport) {
+        let text = render(&self.template, report);
+        let body = match self.discord {
+            true => json!({ "content": text }),
+            false => json!({ "text": text }),
+        };
+        self.hook.post(report.run_id.clone(), body.to_string().into());
+    }
+}
+
+// ─── Template ────────────────────────────────────────────────────
+
+/// Fill in every `{placeholder}` the README lists; unknown ones are
+/// left as written.
+fn render(template: &str, report: &RunReport) -> String {
+    let name = report
+        .config
+        .as_ref()
+        .and_then(|c| c.metadata.as_ref())
+        .and_then(|m| m.name.clone())
+        .unwrap_or_else(|| report.run_id.chars().take(8).collect());
+    let outcome = match report.outcome {
+        RunOutcome::Completed => "completed",
+        RunOutcome::Stopped => "stopped",
+        RunOutcome::Aborted => "aborted",
+    };
+
+    // Anything that went wrong, worded for the message
+    let mut reasons = Vec::new();
+    reasons.extend(report.abort_reason.clone());
+    if let Some(slo) = &report.slo {
+        reasons.extend(slo.violations.iter().map(|v| format!("SLO: {v}")));
+    }
+    if let Some(verdict) = &report.verdict {
+        reasons.extend(
+            verdict.regressions.iter().map(|r| format!("regression: {r}")),
+        );
+    }
+    let passed = reasons.is_empty();
+
+    let error_rate = match report.total_requests {
+        0 => 0.0,
+        n => 100.0 * report.total_errors as f64 / n as f64,
+    };
+    let values = [
+        ("{icon}", (if passed { "✅" } else { "❌" }).to_string()),
+        ("{name}", name),
+        ("{run_id}", report.run_id.clone()),
+        ("{outcome}", outcome.to_string()),
+        ("{result}", (if passed { "PASS" } else { "FAIL" }).to_string()),
+        ("{rps}", format!("{:.0}", report.requests_per_sec)),
+        ("{p50}", millis(report.e2e.p50)),
+        ("{p99}", millis(report.e2e.p99)),
+        ("{error_rate}", format!("{error_rate:.2}%")),
+        ("{duration}", format!("{:.0}s", report.duration_secs)),
+        ("{reasons}", reasons.join("\n")),
+    ];
+    let mut text = template.to_string();
+    for (placeholder, value) in values {
+        text = text.replace(placeholder, &value);
+    }
+    text.trim_end().to_string()
+}
+
+fn millis(us: u64) -> String {
+    format!("{:.2} ms", us as f64 / 1_000.0)
+}
@@ -0,0 +1,57 @@
+use redis::AsyncCommands;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::metrics::probe::ProbeMetrics;
+use crate::redis_client::RedisTarget;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// Single key the prober reads and writes
+const PROBE_KEY: &str = "probe:health";
+
+/// Let the key vanish on its own once the server stops
+const PROBE_TTL_SECS: u64 = 60;
+
+// ─── Background prober ───────────────────────────────────────────
+
+/// Issue one lightweight command every `interval`, alternating SET and
+/// GET, so the dashboard has a baseline even with no benchmark running.
+///
+/// Pauses while a benchmark is in flight — its own samples say far more
+/// and probe results would only measure the queue behind the workers.
+pub async fn run(
+    redis: Arc<RedisTarget>,
+    probe: Arc<ProbeMetrics>,
+    load_running: Arc<AtomicBool>,
+    interval: Duration,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut write = true;
+
+    loop {
+        ticker.tick().await;
+        if load_running.load(Ordering::Relaxed) {
+            continue;
+        }
+
+        let mut conn = redis.manager();
+        let t0 = Instant::now();
+        let result = if write {
+            conn.set_ex::<_, _, ()>(
+                PROBE_KEY,
+                chrono::Utc::now().timestamp_millis(),
+                PROBE_TTL_SECS,
+            )
+            .await
+        } else {
+            conn.get::<_, Option<String>>(PROBE_KEY).await.map(|_| ())
+        };
+        let us = t0.elapsed().as_micros() as u64;
+        write = !write;
+
+        probe.record(result.map(|_| us).map_err(|e| e.to_string()));
+    }
+}
@@ -1,22 +1,372 @@
+use parking_lot::RwLock;
 use redis::aio::ConnectionManager;
+use redis::sentinel::{Sentinel, SentinelNodeConnectionInfo};
+use redis::{
+    ClientTlsConfig, ConnectionAddr, ConnectionInfo, ErrorKind,
+    IntoConnectionInfo, RedisConnectionInfo, TlsCertificates, TlsMode,
+};
+use serde::Serialize;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::{TcpStream, UnixStream};
+
+use crate::config::{AuthSettings, SentinelSettings, Settings, TlsSettings};
+use crate::events::{EventKind, EventLog};
+
+/// How often the Sentinel watcher re-resolves the master address.
+const SENTINEL_POLL: Duration = Duration::from_secs(1);
+
+/// How the benchmark reaches Redis, measured once at startup.
+/// Served at `GET /api/connection` so the dashboard can label runs.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionProfile {
+    /// "tcp", "tls" or "unix"
+    pub transport: &'static str,
+    /// Resolved server address (host:port or socket path)
+    pub address: String,
+    /// Bare socket connect to the same address (μs) — TCP, or the
+    /// Unix socket itself for `redis+unix://`
+    pub tcp_connect_us: u64,
+    /// Full client connect — socket, TLS and RESP setup (μs)
+    pub client_connect_us: u64,
+    /// `client_connect_us − tcp_connect_us`, only reported for TLS
+    pub tls_handshake_us: Option<u64>,
+}
+
+/// Everything `main` needs from a successful connect.
+pub struct Connected {
+    /// Kept around for modes that open their own dedicated connections
+    pub client: redis::Client,
+    pub manager: ConnectionManager,
+    pub profile: ConnectionProfile,
+}
+
+/// The Redis server every handler and run talks to. Under Sentinel the
+/// watcher swaps in the promoted master's client and connection; a
+/// direct connection is never swapped.
+pub struct RedisTarget {
+    current: RwLock<(redis::Client, ConnectionManager)>,
+    /// Bumped on every swap, so long-lived users can tell that the
+    /// connection they hold points at the old master
+    generation: AtomicU64,
+}
+
+impl RedisTarget {
+    pub fn new(client: redis::Client, manager: ConnectionManager) -> Self {
+        Self {
+            current: RwLock::new((client, manager)),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    /// Shared connection to the current master.
+    pub fn manager(&self) -> ConnectionManager {
+        self.current.read().1.clone()
+    }
+
+    /// Client for the current master, for dedicated connections.
+    pub fn client(&self) -> redis::Client {
+        self.current.read().0.clone()
+    }
+
+    /// Read before `manager` or `client`, so a swap in between shows
+    /// up as a changed generation on the next check.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    fn swap(&self, client: redis::Client, manager: ConnectionManager) {
+        *self.current.write() = (client, manager);
+        self.generation.fetch_add(1, Ordering::Release);
+    }
+}
 
 /// Creates a single `ConnectionManager` that auto-reconnects on failure.
 ///
 /// `ConnectionManager` is cheaply cloneable — every clone shares the same
 /// underlying multiplexed TCP connection.  This is sufficient for localhost
 /// benchmarking; for production you'd front it with a connection pool.
-pub async fn connect(url: &str) -> ConnectionManager {
-    let client = redis::Client::open(url).unwrap_or_else(|e| {
-        eprintln!("❌ Invalid Redis URL \"{url}\": {e}");
-        std::process::exit(1);
-    });
-
-    ConnectionManager::new(client).await.unwrap_or_else(|e| {
-        eprintln!("❌ Cannot connect to Redis: {e}");
-        eprintln!("   Make sure redis-server is running on localhost:6379");
-        eprintln!("   → brew services start redis");
-        eprintln!("   → sudo systemctl start redis");
-        eprintln!("   → redis-server");
+pub async fn connect(settings: &Settings) -> Connected {
+    let url = &settings.redis_url;
+    let client = build_client(url, &settings.tls, &settings.auth)
+        .unwrap_or_else(|e| {
+            eprintln!("❌ Invalid Redis URL \"{url}\": {e}");
+            std::process::exit(1);
+        });
+
+    let manager = ConnectionManager::new(client.clone())
+        .await
+        .unwrap_or_else(|e| {
+            if e.kind() == ErrorKind::AuthenticationFailed {
+                eprintln!("❌ Redis rejected the credentials: {e}");
+                eprintln!("   → set --username/--password or REDIS_PASSWORD");
+                std::process::exit(1);
+            }
+            eprintln!("❌ Cannot connect to Redis: {e}");
+            eprintln!("   Make sure redis-server is reachable at {url}");
+            eprintln!("   → brew services start redis");
+            eprintln!("   → sudo systemctl start redis");
+            eprintln!("   → redis-server");
+            std::process::exit(1);
+        });
+
+    let profile = probe(&client).await;
+    Connected {
+        client,
+        manager,
+        profile,
+    }
+}
+
+/// `op_timeout_ms` as a `timed` limit; 0 = none.
+pub fn op_timeout(ms: u64) -> Option<Duration> {
+    (ms > 0).then(|| Duration::from_millis(ms))
+}
+
+/// Run one Redis call under `limit` (`None` = wait forever). A call that
+/// runs out of time fails with an I/O `TimedOut` error, which
+/// `RedisError::is_timeout` recognises. Dropping the call is safe on a
+/// multiplexed connection: the late reply is discarded.
+pub async fn timed<T>(
+    limit: Option<Duration>,
+    call: impl Future<Output = redis::RedisResult<T>>,
+) -> redis::RedisResult<T> {
+    let Some(limit) = limit else {
+        return call.await;
+    };
+    tokio::time::timeout(limit, call).await.unwrap_or_else(|_| {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            format!("no reply within {} ms", limit.as_millis()),
+        )
+        .into())
+    })
+}
+
+/// Time a bare socket connect against a full client connect on a
+/// throwaway connection, so the TLS handshake cost is reported on its own.
+pub async fn probe(client: &redis::Client) -> ConnectionProfile {
+    let addr = client.get_connection_info().addr.clone();
+
+    let t_socket = Instant::now();
+    let (transport, address, socket_ok) = match &addr {
+        ConnectionAddr::Tcp(host, port) => {
+            let ok = TcpStream::connect((host.as_str(), *port)).await.is_ok();
+            ("tcp", format!("{host}:{port}"), ok)
+        }
+        ConnectionAddr::TcpTls { host, port, .. } => {
+            let ok = TcpStream::connect((host.as_str(), *port)).await.is_ok();
+            ("tls", format!("{host}:{port}"), ok)
+        }
+        ConnectionAddr::Unix(path) => {
+            let ok = UnixStream::connect(path).await.is_ok();
+            ("unix", path.display().to_string(), ok)
+        }
+    };
+    let tcp_connect_us = t_socket.elapsed().as_micros() as u64;
+
+    let t_client = Instant::now();
+    let client_ok = client.get_multiplexed_async_connection().await.is_ok();
+    let client_connect_us = t_client.elapsed().as_micros() as u64;
+
+    let tls_handshake_us = (transport == "tls" && socket_ok && client_ok)
+        .then(|| client_connect_us.saturating_sub(tcp_connect_us));
+
+    ConnectionProfile {
+        transport,
+        address,
+        tcp_connect_us,
+        client_connect_us,
+        tls_handshake_us,
+    }
+}
+
+/// `rediss://` URLs with custom certificates go through `build_with_tls`;
+/// everything else (including `rediss://` with system roots) is a plain open.
+///
+/// Credentials live in the `ConnectionInfo`, so `ConnectionManager`
+/// re-sends AUTH on every reconnect without any extra bookkeeping.
+fn build_client(
+    url: &str,
+    tls: &TlsSettings,
+    auth: &AuthSettings,
+) -> redis::RedisResult<redis::Client> {
+    let mut info: ConnectionInfo = url.into_connection_info()?;
+    apply_auth(&mut info.redis, auth);
+    open_client(info, certificates(tls).as_ref())
+}
+
+/// Plain open, or `build_with_tls` when there are custom certificates.
+fn open_client(
+    info: ConnectionInfo,
+    certs: Option<&TlsCertificates>,
+) -> redis::RedisResult<redis::Client> {
+    match certs {
+        Some(certs) => redis::Client::build_with_tls(info, certs.clone()),
+        None => redis::Client::open(info),
+    }
+}
+
+/// The PEM files `tls` names, read once; `None` without any. Exits if
+/// one can't be read.
+fn certificates(tls: &TlsSettings) -> Option<TlsCertificates> {
+    if !tls.is_custom() {
+        return None;
+    }
+    let read = |path: &String| {
+        std::fs::read(path).unwrap_or_else(|e| {
+            eprintln!("❌ Cannot read TLS file \"{path}\": {e}");
+            std::process::exit(1);
+        })
+    };
+    let client_tls = match (&tls.cert, &tls.key) {
+        (Some(cert), Some(key)) => Some(ClientTlsConfig {
+            client_cert: read(cert),
+            client_key: read(key),
+        }),
+        _ => None,
+    };
+    Some(TlsCertificates {
+        client_tls,
+        root_cert: tls.ca.as_ref().map(read),
+    })
+}
+
+/// Resolves the current master through Sentinel and connects to it.
+///
+/// Returns the connection plus the resolved address so the caller can
+/// hand it to [`watch_sentinel_master`].
+pub async fn connect_sentinel(
+    settings: &SentinelSettings,
+    auth: &AuthSettings,
+    tls: &TlsSettings,
+) -> (Connected, ConnectionAddr) {
+    let mut sentinel = build_sentinel(settings);
+    let node_info = node_info(settings, auth);
+    let certs = certificates(tls);
+
+    let client = sentinel
+        .async_master_for(&settings.master_name, Some(&node_info))
+        .await
+        .and_then(|client| {
+            open_client(client.get_connection_info().clone(), certs.as_ref())
+        })
+        .unwrap_or_else(|e| {
+            eprintln!(
+                "❌ Sentinel could not resolve master \"{}\": {e}",
+                settings.master_name
+            );
+            std::process::exit(1);
+        });
+    let addr = client.get_connection_info().addr.clone();
+
+    let manager = ConnectionManager::new(client.clone())
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("❌ Cannot connect to Sentinel master {addr}: {e}");
+            std::process::exit(1);
+        });
+
+    let profile = probe(&client).await;
+    let connected = Connected {
+        client,
+        manager,
+        profile,
+    };
+    (connected, addr)
+}
+
+/// Background task that polls Sentinel and, when the master moves,
+/// connects to the promoted one and swaps it into `target`. Workers
+/// pick up the new connection on their next iteration, so the run shows
+/// the failover as a burst of errors and a latency spike rather than
+/// an outage. Every switch is logged to the console and, as a
+/// `failover` event, to the current run's event log.
+pub async fn watch_sentinel_master(
+    settings: SentinelSettings,
+    auth: AuthSettings,
+    tls: TlsSettings,
+    mut current: ConnectionAddr,
+    target: Arc<RedisTarget>,
+    events: Arc<EventLog>,
+) {
+    let mut sentinel = build_sentinel(&settings);
+    let node_info = node_info(&settings, &auth);
+    let certs = certificates(&tls);
+    let mut ticker = tokio::time::interval(SENTINEL_POLL);
+
+    loop {
+        ticker.tick().await;
+        let client = match sentinel
+            .async_master_for(&settings.master_name, Some(&node_info))
+            .await
+            .and_then(|client| {
+                let info = client.get_connection_info().clone();
+                open_client(info, certs.as_ref())
+            }) {
+            Ok(client) => client,
+            Err(e) => {
+                eprintln!("  \x1b[31m✗ sentinel lookup failed: {e}\x1b[0m");
+                continue;
+            }
+        };
+        let addr = client.get_connection_info().addr.clone();
+        if addr == current {
+            continue;
+        }
+
+        // Not yet accepting connections: retry on the next poll
+        let manager = match ConnectionManager::new(client.clone()).await {
+            Ok(manager) => manager,
+            Err(e) => {
+                eprintln!(
+                    "  \x1b[31m✗ cannot connect to promoted master \
+                     {addr}: {e}\x1b[0m"
+                );
+                continue;
+            }
+        };
+        target.swap(client, manager);
+        let msg = format!(
+            "master \"{}\" moved {} → {}",
+            settings.master_name, current, addr
+        );
+        println!("  \x1b[33m⚠ sentinel failover: {msg}\x1b[0m");
+        events.push(EventKind::Failover, msg);
+        current = addr;
+    }
+}
+
+/// Credentials and TLS for the master Sentinel resolves; the Sentinels
+/// themselves are reached without them. Custom certificates are added
+/// by `open_client` once the master is known.
+fn node_info(
+    settings: &SentinelSettings,
+    auth: &AuthSettings,
+) -> SentinelNodeConnectionInfo {
+    let mut redis_info = RedisConnectionInfo::default();
+    apply_auth(&mut redis_info, auth);
+    SentinelNodeConnectionInfo {
+        tls_mode: settings.tls.then_some(TlsMode::Secure),
+        redis_connection_info: Some(redis_info),
+    }
+}
+
+/// Flags / env override whatever the URL carried.
+fn apply_auth(info: &mut RedisConnectionInfo, auth: &AuthSettings) {
+    if auth.username.is_some() {
+        info.username = auth.username.clone();
+    }
+    if auth.password.is_some() {
+        info.password = auth.password.clone();
+    }
+}
+
+fn build_sentinel(settings: &SentinelSettings) -> Sentinel {
+    Sentinel::build(settings.nodes.clone()).unwrap_or_else(|e| {
+        eprintln!("❌ Invalid Sentinel address list {:?}: {e}", settings.nodes);
         std::process::exit(1);
     })
-}
\ No newline at end of file
+}
@@ -0,0 +1,547 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::archive::{ArchivedOp, ArchivedRun};
+use crate::compare::Verdict;
+use crate::events::{EventKind, RunEvent};
+use crate::handlers::benchmark::BenchmarkConfig;
+use crate::load_generator::STOP_REQUESTED;
+use crate::metrics::apdex::ApdexScore;
+use crate::metrics::collector::{DistBucket, RequestDetail};
+use crate::metrics::percentiles::{PercentileMode, PercentileSet};
+use crate::metrics::MetricsSnapshot;
+use crate::slo::SloResult;
+
+// ─── Report ──────────────────────────────────────────────────────
+
+/// End-of-run aggregate, built once when a benchmark or scenario run
+/// finishes and never updated after. Served at
+/// `GET /api/benchmark/report`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunReport {
+    pub run_id: String,
+    /// When the report was built (RFC 3339, UTC)
+    pub finished_at: String,
+    /// Unix epoch (ms) of the run's first sample
+    pub started_at_ms: Option<i64>,
+    pub duration_secs: f64,
+    pub outcome: RunOutcome,
+    /// `aborted` runs: the health-guard breach that stopped them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub abort_reason: Option<String>,
+    /// Config of the run's last stage, with any `/adjust` changes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<BenchmarkConfig>,
+
+    pub total_requests: u64,
+    pub total_errors: u64,
+    pub total_timeouts: u64,
+    pub total_reads: u64,
+    pub total_writes: u64,
+    pub requests_per_sec: f64,
+
+    /// End-to-end latency over the whole run
+    pub e2e: PercentileSet,
+    /// `e2e` from the intended send time (open-loop runs)
+    pub e2e_corrected: PercentileSet,
+    pub redis_read: PercentileSet,
+    pub redis_write: PercentileSet,
+    pub rust_overhead: PercentileSet,
+    /// Apdex per layer, when the config set `apdex` targets
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub apdex: BTreeMap<String, ApdexScore>,
+    /// Per-command rate and latency
+    pub ops: BTreeMap<String, ArchivedOp>,
+    pub percentile_mode: PercentileMode,
+    /// The run's slowest requests, slowest first, with their keys and
+    /// errors
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub outliers: Vec<RequestDetail>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slo: Option<SloResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<Verdict>,
+}
+
+/// How a run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunOutcome {
+    /// Ran for its full duration
+    Completed,
+    /// Ended early by `/api/benchmark/stop`
+    Stopped,
+    /// Ended early by the health guard
+    Aborted,
+}
+
+impl RunReport {
+    /// Freeze a finished run: `run` for what was judged, `snap` for
+    /// the layers the archive leaves out, `log` for how it ended.
+    pub fn new(
+        run: &ArchivedRun,
+        snap: &MetricsSnapshot,
+        log: &[RunEvent],
+    ) -> Self {
+        let abort_reason = log
+            .iter()
+            .find(|e| e.kind == EventKind::Aborted)
+            .map(|e| e.message.clone());
+        let stopped = log.iter().any(|e| {
+            e.kind == EventKind::Stopped && e.message == STOP_REQUESTED
+        });
+        let outcome = match (&abort_reason, stopped) {
+            (Some(_), _) => RunOutcome::Aborted,
+            (None, true) => RunOutcome::Stopped,
+            (None, false) => RunOutcome::Completed,
+        };
+        Self {
+            run_id: run.run_id.clone(),
+            finished_at: chrono::Utc::now().to_rfc3339(),
+            started_at_ms: snap.started_at_ms,
+            duration_secs: snap.elapsed_secs,
+            outcome,
+            abort_reason,
+            config: snap.config.clone(),
+            total_requests: snap.total_requests,
+            total_errors: snap.total_errors,
+            total_timeouts: snap.total_timeouts,
+            total_reads: snap.total_reads,
+            total_writes: snap.total_writes,
+            requests_per_sec: snap.avg_requests_per_sec,
+            e2e: snap.e2e.clone(),
+            e2e_corrected: snap.e2e_corrected.clone(),
+            redis_read: snap.redis_read.clone(),
+            redis_write: snap.redis_write.clone(),
+            rust_overhead: snap.rust_overhead.clone(),
+            apdex: snap.apdex.clone(),
+            outliers: snap.outliers.clone(),
+            ops: run.ops.clone(),
+            percentile_mode: snap.percentile_mode,
+            slo: run.slo.clone(),
+            verdict: run.verdict.clone(),
+        }
+    }
+}
+
+// ─── Document export ─────────────────────────────────────────────
+
+/// The parts of a saved snapshot the document export reads; the rest
+/// of the saved JSON is ignored.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SavedDetail {
+    /// Kept as JSON: it is only printed back
+    pub config: Option<serde_json::Value>,
+    pub e2e: Option<PercentileSet>,
+    pub e2e_corrected: Option<PercentileSet>,
+    pub redis_read: Option<PercentileSet>,
+    pub redis_write: Option<PercentileSet>,
+    pub rust_overhead: Option<PercentileSet>,
+    pub framework_overhead: Option<PercentileSet>,
+    pub distribution: Vec<DistBucket>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Markdown,
+    Html,
+}
+
+/// How numbers are written: the decimal separator and the thousands
+/// separator, if any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Numbers {
+    decimal: char,
+    group: Option<char>,
+}
+
+/// Locale tags `?locale=` accepts, with their separators. A full tag
+/// is tried first, then its language, so `de-AT` reads as `de`.
+const LOCALES: [(&str, char, Option<char>); 11] = [
+    ("en", '.', Some(',')),
+    ("de", ',', Some('.')),
+    ("de-ch", '.', Some('’')),
+    ("es", ',', Some('.')),
+    ("it", ',', Some('.')),
+    ("nl", ',', Some('.')),
+    ("pt", ',', Some('.')),
+    ("fr", ',', Some('\u{202f}')),
+    ("pl", ',', Some('\u{a0}')),
+    ("sv", ',', Some('\u{a0}')),
+    // No grouping and `.` decimals, for reports read by scripts
+    ("raw", '.', None),
+];
+
+impl Numbers {
+    /// `en`: `1,234.5`
+    pub const EN: Self = Self {
+        decimal: '.',
+        group: Some(','),
+    };
+
+    /// Separators for a locale tag like `de`, `fr-FR` or `de_CH`;
+    /// `None` when it is not one of `LOCALES`.
+    pub fn for_locale(tag: &str) -> Option<Self> {
+        let tag = tag.trim().to_ascii_lowercase().replace('_', "-");
+        let language = tag.split('-').next().unwrap_or_default();
+        let known =
+            |want: &str| LOCALES.iter().find(|(name, ..)| *name == want);
+        let &(_, decimal, group) = known(&tag).or_else(|| known(language))?;
+        Some(Self { decimal, group })
+    }
+
+    /// The tags `for_locale` knows, for error messages.
+    pub fn locales() -> Vec<&'static str> {
+        LOCALES.iter().map(|(name, ..)| *name).collect()
+    }
+
+    fn int(&self, n: u64) -> String {
+        self.grouped(&n.to_string())
+    }
+
+    /// `n` with `places` decimals, e.g. `1.234,50` in `de`.
+    fn fixed(&self, n: f64, places: usize) -> String {
+        let text = format!("{n:.places$}");
+        let (sign, text) = match text.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", text.as_str()),
+        };
+        let (whole, frac) = text.split_once('.').unwrap_or((text, ""));
+        let mut out = format!("{sign}{}", self.grouped(whole));
+        if !frac.is_empty() {
+            out.push(self.decimal);
+            out.push_str(frac);
+        }
+        out
+    }
+
+    /// Digits with the group separator every three from the right.
+    fn grouped(&self, digits: &str) -> String {
+        let Some(group) = self.group else {
+            return digits.to_string();
+        };
+        let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                out.push(group);
+            }
+            out.push(digit);
+        }
+        out
+    }
+}
+
+/// Render an archived run as a standalone document. `detail` comes
+/// from the run's saved snapshot; without one the per-layer table
+/// shows end-to-end latency only and the distribution is left out.
+/// Counts, rates and latencies are written with `numbers`; ids, times
+/// and the config are not.
+pub fn render(
+    run: &ArchivedRun,
+    detail: Option<&SavedDetail>,
+    format: Format,
+    numbers: Numbers,
+) -> String {
+    let mut doc = Doc::new(format);
+    let name = run.metadata.as_ref().and_then(|m| m.name.as_deref());
+    doc.heading(1, &format!("Run {}", name.unwrap_or(&run.run_id)));
+
+    doc.heading(2, "Summary");
+    doc.table(&["", ""], &summary(run, numbers));
+
+    doc.heading(2, "Latency by layer (μs)");
+    let mut layers = Vec::new();
+    match detail {
+        Some(detail) => {
+            let named = [
+                ("end to end", &detail.e2e),
+                ("end to end, corrected", &detail.e2e_corrected),
+                ("Redis read", &detail.redis_read),
+                ("Redis write", &detail.redis_write),
+                ("Rust overhead", &detail.rust_overhead),
+                ("framework overhead", &detail.framework_overhead),
+            ];
+            for (layer, set) in named {
+                if let Some(set) = set.as_ref().filter(|s| s.count > 0) {
+                    layers.push(percentile_row(layer, set, numbers));
+                }
+            }
+        }
+        None => {
+            if let Some(set) = &run.latency {
+                layers.push(percentile_row("end to end", set, numbers));
+            }
+        }
+    }
+    doc.table(&PERCENTILE_HEADER, &layers);
+
+    if !run.ops.is_empty() {
+        doc.heading(2, "Latency by command (μs)");
+        let mut header = PERCENTILE_HEADER.to_vec();
+        header.insert(1, "req/s");
+        let rows: Vec<Vec<String>> = run
+            .ops
+            .iter()
+            .map(|(op, summary)| {
+                let mut row = percentile_row(op, &summary.latency, numbers);
+                let rate = summary
+                    .requests_per_sec
+                    .map_or("–".into(), |r| numbers.fixed(r, 0));
+                row.insert(1, rate);
+                row
+            })
+            .collect();
+        doc.table(&header, &rows);
+    }
+
+    let buckets = detail.map_or(&[][..], |d| &d.distribution[..]);
+    let total: u64 = buckets.iter().map(|b| b.count).sum();
+    if total > 0 {
+        doc.heading(2, "Distribution");
+        let rows: Vec<Vec<String>> = buckets
+            .iter()
+            .map(|b| {
+                let share = 100.0 * b.count as f64 / total as f64;
+                vec![
+                    format!(
+                        "{}–{} μs",
+                        numbers.int(b.range_start_us),
+                        numbers.int(b.range_end_us)
+                    ),
+                    numbers.int(b.count),
+                    format!("{}%", numbers.fixed(share, 2)),
+                ]
+            })
+            .collect();
+        doc.table(&["range", "count", "share"], &rows);
+    }
+
+    if !run.analysis.is_empty() {
+        doc.heading(2, "Findings");
+        let findings: Vec<String> =
+            run.analysis.iter().map(|f| f.message.clone()).collect();
+        doc.list(&findings);
+    }
+
+    if let Some(config) = detail.and_then(|d| d.config.as_ref()) {
+        doc.heading(2, "Config");
+        let json = serde_json::to_string_pretty(config).unwrap_or_default();
+        doc.code(&json);
+    }
+    doc.finish()
+}
+
+const PERCENTILE_HEADER: [&str; 9] =
+    ["", "count", "min", "mean", "p50", "p95", "p99", "p99.9", "max"];
+
+fn percentile_row(
+    label: &str,
+    set: &PercentileSet,
+    numbers: Numbers,
+) -> Vec<String> {
+    vec![
+        label.to_string(),
+        numbers.int(set.count),
+        numbers.int(set.min),
+        numbers.fixed(set.mean, 0),
+        numbers.int(set.p50),
+        numbers.int(set.p95),
+        numbers.int(set.p99),
+        numbers.int(set.p999),
+        numbers.int(set.max),
+    ]
+}
+
+fn summary(run: &ArchivedRun, numbers: Numbers) -> Vec<Vec<String>> {
+    let mut rows = vec![
+        vec!["run id".into(), run.run_id.clone()],
+        vec!["source".into(), run.source.name().into()],
+        vec!["archived at".into(), run.archived_at.clone()],
+    ];
+    let mut add = |field: &str, value: String| {
+        rows.push(vec![field.into(), value]);
+    };
+    if let Some(label) = &run.label {
+        add("label", label.clone());
+    }
+    if let Some(meta) = &run.metadata {
+        let fields = [
+            ("git sha", &meta.git_sha),
+            ("Redis version", &meta.redis_version),
+            ("notes", &meta.notes),
+        ];
+        for (field, value) in fields {
+            if let Some(value) = value {
+                add(field, value.clone());
+            }
+        }
+    }
+    if let Some(secs) = run.duration_secs {
+        add("duration", format!("{} s", numbers.fixed(secs, 1)));
+    }
+    add("requests", numbers.int(run.total_requests));
+    let error_pct = match run.total_requests {
+        0 => 0.0,
+        n => 100.0 * run.total_errors as f64 / n as f64,
+    };
+    add(
+        "errors",
+        format!(
+            "{} ({}%)",
+            numbers.int(run.total_errors),
+            numbers.fixed(error_pct, 2)
+        ),
+    );
+    add("requests/sec", numbers.fixed(run.requests_per_sec, 0));
+    if let Some(slo) = &run.slo {
+        add(
+            "SLO",
+            match slo.passed {
+                true => "passed".into(),
+                false => format!("failed: {}", slo.violations.join("; ")),
+            },
+        );
+    }
+    if let Some(verdict) = &run.verdict {
+        let outcome = match verdict.regressed {
+            true => format!("regressed: {}", verdict.regressions.join("; ")),
+            false => "within tolerance".into(),
+        };
+        add(
+            "baseline",
+            format!("{} ({outcome})", verdict.baseline_run_id),
+        );
+    }
+    rows
+}
+
+/// A document built block by block in either format.
+struct Doc {
+    format: Format,
+    out: String,
+}
+
+impl Doc {
+    fn new(format: Format) -> Self {
+        let out = match format {
+            Format::Markdown => String::new(),
+            Format::Html => HTML_HEAD.to_string(),
+        };
+        Self { format, out }
+    }
+
+    fn heading(&mut self, level: u8, text: &str) {
+        match self.format {
+            Format::Markdown => {
+                let hashes = "#".repeat(level as usize);
+                self.out.push_str(&format!("{hashes} {}\n\n", md(text)));
+            }
+            Format::Html => self.out.push_str(&format!(
+                "<h{level}>{}</h{level}>\n",
+                html(text)
+            )),
+        }
+    }
+
+    fn table(&mut self, header: &[&str], rows: &[Vec<String>]) {
+        match self.format {
+            Format::Markdown => {
+                let line = |cells: Vec<String>| {
+                    format!("| {} |\n", cells.join(" | "))
+                };
+                let head = header.iter().map(|h| md(h)).collect();
+                self.out.push_str(&line(head));
+                let rule = header.iter().map(|_| "---".to_string()).collect();
+                self.out.push_str(&line(rule));
+                for row in rows {
+                    let cells = row.iter().map(|c| md(c)).collect();
+                    self.out.push_str(&line(cells));
+                }
+                self.out.push('\n');
+            }
+            Format::Html => {
+                let cells = |tag: &str, cells: Vec<String>| {
+                    let inner: String = cells
+                        .iter()
+                        .map(|c| format!("<{tag}>{}</{tag}>", html(c)))
+                        .collect();
+                    format!("<tr>{inner}</tr>\n")
+                };
+                self.out.push_str("<table>\n");
+                let head = header.iter().map(|h| h.to_string()).collect();
+                self.out.push_str(&cells("th", head));
+                for row in rows {
+                    self.out.push_str(&cells("td", row.clone()));
+                }
+                self.out.push_str("</table>\n");
+            }
+        }
+    }
+
+    fn list(&mut self, items: &[String]) {
+        match self.format {
+            Format::Markdown => {
+                for item in items {
+                    self.out.push_str(&format!("- {}\n", md(item)));
+                }
+                self.out.push('\n');
+            }
+            Format::Html => {
+                self.out.push_str("<ul>\n");
+                for item in items {
+                    self.out.push_str(&format!("<li>{}</li>\n", html(item)));
+                }
+                self.out.push_str("</ul>\n");
+            }
+        }
+    }
+
+    fn code(&mut self, text: &str) {
+        match self.format {
+            Format::Markdown => {
+                self.out.push_str(&format!("```json\n{text}\n```\n"));
+            }
+            Format::Html => {
+                self.out.push_str(&format!("<pre>{}</pre>\n", html(text)));
+            }
+        }
+    }
+
+    fn finish(mut self) -> String {
+        if self.format == Format::Html {
+            self.out.push_str("</body>\n</html>\n");
+        }
+        self.out
+    }
+}
+
+const HTML_HEAD: &str = "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>rust-redis-bench run report</title>
+<style>
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 1.5em; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: right; }
+td:first-child { text-align: left; }
+pre { background: #f5f5f5; padding: 1em; }
+</style>
+</head>
+<body>
+";
+
+/// Keep a cell on one line and stop `|` from ending it early.
+fn md(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,195 @@
+use std::collections::VecDeque;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use crate::handlers::benchmark::{self, BenchmarkConfig};
+use crate::handlers::AppError;
+use crate::AppState;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// Most runs that may wait at once
+pub const MAX_QUEUED: usize = 100;
+
+/// How often the dispatcher checks whether the active run is done
+const POLL: Duration = Duration::from_millis(500);
+
+/// Failed starts kept for `GET /api/benchmark/queue`
+const MAX_FAILED: usize = 20;
+
+// ─── Queue ───────────────────────────────────────────────────────
+
+/// Validated configs waiting for the active run to finish, started in
+/// order by `drive`.
+pub struct RunQueue {
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    waiting: VecDeque<QueuedRun>,
+    failed: VecDeque<FailedRun>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QueuedRun {
+    /// Run id the run will get once it starts
+    pub id: String,
+    /// Status line the start will show
+    pub summary: String,
+    pub queued_at: String,
+    pub config: BenchmarkConfig,
+}
+
+/// A queued run whose start failed; the queue moved on without it.
+#[derive(Debug, Clone, Serialize)]
+pub struct FailedRun {
+    pub id: String,
+    pub summary: String,
+    pub error: String,
+    pub failed_at: String,
+}
+
+/// Served at `GET /api/benchmark/queue`.
+#[derive(Debug, Serialize)]
+pub struct QueueListing {
+    /// Next to start first
+    pub queued: Vec<QueuedRun>,
+    /// Most recent last
+    pub failed: Vec<FailedRun>,
+}
+
+impl RunQueue {
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                waiting: VecDeque::new(),
+                failed: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Append an already validated config; returns its 1-based
+    /// position.
+    pub fn push(&self, run: QueuedRun) -> Result<usize, AppError> {
+        let mut inner = self.inner.lock();
+        if inner.waiting.len() >= MAX_QUEUED {
+            return Err(AppError::BadRequest(format!(
+                "the queue is full ({MAX_QUEUED} runs)"
+            )));
+        }
+        inner.waiting.push_back(run);
+        Ok(inner.waiting.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.lock().waiting.is_empty()
+    }
+
+    pub fn listing(&self) -> QueueListing {
+        let inner = self.inner.lock();
+        QueueListing {
+            queued: inner.waiting.iter().cloned().collect(),
+            failed: inner.failed.iter().cloned().collect(),
+        }
+    }
+
+    /// Drop one waiting run; `None` if it isn't waiting.
+    pub fn cancel(&self, id: &str) -> Option<QueuedRun> {
+        let mut inner = self.inner.lock();
+        let index = inner.waiting.iter().position(|run| run.id == id)?;
+        inner.waiting.remove(index)
+    }
+
+    /// Drop every waiting run; returns how many there were.
+    pub fn clear(&self) -> usize {
+        let mut inner = self.inner.lock();
+        let dropped = inner.waiting.len();
+        inner.waiting.clear();
+        dropped
+    }
+
+    fn pop(&self) -> Option<QueuedRun> {
+        self.inner.lock().waiting.pop_front()
+    }
+
+    /// Put back a run that lost the race for the runner.
+    fn unpop(&self, run: QueuedRun) {
+        self.inner.lock().waiting.push_front(run);
+    }
+
+    fn fail(&self, run: QueuedRun, error: String) {
+        let mut inner = self.inner.lock();
+        if inner.failed.len() == MAX_FAILED {
+            inner.failed.pop_front();
+        }
+        inner.failed.push_back(FailedRun {
+            id: run.id,
+            summary: run.summary,
+            error,
+            failed_at: chrono::Utc::now().to_rfc3339(),
+        });
+    }
+}
+
+impl QueuedRun {
+    pub fn new(config: BenchmarkConfig) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            summary: benchmark::describe(&config),
+            queued_at: chrono::Utc::now().to_rfc3339(),
+            config,
+        }
+    }
+}
+
+// ─── Dispatcher ──────────────────────────────────────────────────
+
+/// Start the next queued run whenever no run is active. A run counts
+/// as active until its task has archived it, so the next one never
+/// resets metrics that are still being read.
+pub async fn drive(state: Arc<AppState>) {
+    let mut ticker = tokio::time::interval(POLL);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        ticker.tick().await;
+        if state.load_running.load(Ordering::SeqCst) || !idle(&state) {
+            continue;
+        }
+        let Some(run) = state.queue.pop() else {
+            continue;
+        };
+        let (config, id) = (run.config.clone(), run.id.clone());
+        match benchmark::launch(&state, config, id).await {
+            Ok(_) => {}
+            // Someone started a run between the check and the launch
+            Err(AppError::AlreadyRunning) => state.queue.unpop(run),
+            Err(e) => {
+                // Opening the tracking connection is the only other
+                // way a validated config can fail to start
+                let error = match e {
+                    AppError::Redis(msg) => format!("Redis: {msg}"),
+                    other => format!("{other:?}"),
+                };
+                eprintln!(
+                    "⚠️  queued run {} failed to start: {error}",
+                    run.id
+                );
+                state.queue.fail(run, error);
+            }
+        }
+    }
+}
+
+/// Whether the last run's task has finished; a start or stop holding
+/// the handle counts as busy.
+fn idle(state: &AppState) -> bool {
+    match state.load_handle.try_lock() {
+        Ok(handle) => handle.as_ref().is_none_or(|h| h.is_finished()),
+        Err(_) => false,
+    }
+}
@@ -1,55 +1,214 @@
 use axum::{
+    error_handling::HandleErrorLayer,
     middleware as axum_mw,
-    routing::{get, post},
-    Router,
+    routing::{delete, get, post, MethodRouter},
+    BoxError, Router,
 };
 use std::sync::Arc;
+use tower::ServiceBuilder;
 use tower_http::cors::CorsLayer;
 use tower_http::services::ServeDir;
 
-use crate::handlers;
-use crate::metrics::stream;
+use crate::handlers::{self, AppError};
+use crate::metrics::{prometheus, stream};
 use crate::middleware::timing;
 use crate::AppState;
 
 /// Builds the full Axum `Router` with all routes, middleware, and static serving.
-pub fn create_router(state: Arc<AppState>) -> Router {
+///
+/// `endpoint_concurrency` caps in-flight requests per CRUD route; excess
+/// requests are shed with 503 and counted in the metrics snapshot.
+pub fn create_router(
+    state: Arc<AppState>,
+    endpoint_concurrency: Option<usize>,
+) -> Router {
+    let metrics = state.metrics.clone();
+    let limit = |endpoint: &'static str, route| {
+        limited(route, endpoint, endpoint_concurrency, &state)
+    };
+
     Router::new()
         // ── User endpoints ──────────────────────────────────────
-        .route("/api/users/:id", get(handlers::users::get_user))
-        .route("/api/users", post(handlers::users::create_user))
+        .route(
+            "/api/users/:id",
+            limit("GET /api/users/:id", get(handlers::users::get_user)),
+        )
+        .route(
+            "/api/users",
+            limit("POST /api/users", post(handlers::users::create_user)),
+        )
         // ── Session endpoints ───────────────────────────────────
         .route(
             "/api/sessions/:id",
-            get(handlers::sessions::get_session),
+            limit(
+                "GET /api/sessions/:id",
+                get(handlers::sessions::get_session),
+            ),
+        )
+        .route(
+            "/api/sessions",
+            limit(
+                "POST /api/sessions",
+                post(handlers::sessions::create_session),
+            ),
         )
-        .route("/api/sessions", post(handlers::sessions::create_session))
         // ── Product endpoints ───────────────────────────────────
         .route(
             "/api/products/:id",
-            get(handlers::products::get_product),
+            limit(
+                "GET /api/products/:id",
+                get(handlers::products::get_product),
+            ),
         )
         // ── Benchmark control ───────────────────────────────────
         .route(
             "/api/benchmark/start",
             post(handlers::benchmark::start_benchmark),
         )
+        .route(
+            "/api/benchmark/plan",
+            post(handlers::benchmark::plan_benchmark),
+        )
+        .route(
+            "/api/benchmark/scenario",
+            post(handlers::scenario::run_scenario),
+        )
         .route(
             "/api/benchmark/stop",
             post(handlers::benchmark::stop_benchmark),
         )
+        .route(
+            "/api/benchmark/adjust",
+            post(handlers::benchmark::adjust_benchmark),
+        )
         .route(
             "/api/benchmark/status",
             get(handlers::benchmark::benchmark_status),
         )
+        .route(
+            "/api/benchmark/report",
+            get(handlers::benchmark::benchmark_report),
+        )
+        .route(
+            "/api/benchmark/queue",
+            get(handlers::benchmark::list_queue)
+                .delete(handlers::benchmark::clear_queue),
+        )
+        .route(
+            "/api/benchmark/queue/:id",
+            delete(handlers::benchmark::cancel_queued),
+        )
+        // ── Experiments ─────────────────────────────────────────
+        .route(
+            "/api/experiments/eviction",
+            post(handlers::experiments::start_eviction)
+                .get(handlers::experiments::get_eviction),
+        )
+        .route(
+            "/api/experiments/sweep",
+            post(handlers::experiments::start_sweep)
+                .get(handlers::experiments::get_sweep),
+        )
+        .route(
+            "/api/experiments/capacity",
+            post(handlers::experiments::start_capacity)
+                .get(handlers::experiments::get_capacity),
+        )
+        // ── Deployment self-test ────────────────────────────────
+        .route("/api/selftest", post(handlers::selftest::run_selftest))
+        // ── Workload translation ────────────────────────────────
+        .route(
+            "/api/workload/ycsb/export",
+            post(handlers::workload::export_ycsb),
+        )
+        .route(
+            "/api/workload/ycsb/import",
+            post(handlers::workload::import_ycsb),
+        )
+        // ── Seeded dataset ──────────────────────────────────────
+        .route("/api/seed/manifest", get(handlers::seed::get_manifest))
+        .route("/api/seed/restore", post(handlers::seed::restore))
+        // ── Connection info ─────────────────────────────────────
+        .route(
+            "/api/connection",
+            get(handlers::connection::get_connection),
+        )
+        // ── Background probe ────────────────────────────────────
+        .route("/api/probe", get(handlers::probe::get_probe))
+        // ── Run history ─────────────────────────────────────────
+        .route("/api/runs", get(handlers::runs::list_runs))
+        .route("/api/runs/import", post(handlers::runs::import_run))
+        .route("/api/runs/shared", get(handlers::runs::list_shared_runs))
+        .route("/api/runs/compare", get(handlers::runs::compare_runs))
+        .route(
+            "/api/runs/baseline",
+            get(handlers::runs::get_baseline)
+                .post(handlers::runs::set_baseline)
+                .delete(handlers::runs::clear_baseline),
+        )
+        .route("/api/runs/:id", get(handlers::runs::get_run))
+        .route(
+            "/api/runs/:id/report",
+            get(handlers::runs::get_run_report),
+        )
+        .route(
+            "/api/runs/:id/snapshot",
+            get(handlers::runs::get_run_snapshot),
+        )
+        .route(
+            "/api/runs/:id/events",
+            get(handlers::runs::get_run_events)
+                .post(handlers::runs::annotate_run),
+        )
+        .route("/api/trends/aggregate", get(handlers::trends::aggregate))
         // ── Metrics ─────────────────────────────────────────────
+        .route("/metrics", get(prometheus::get_prometheus))
         .route("/api/metrics", get(stream::get_metrics))
         .route("/api/metrics/stream", get(stream::metrics_stream))
+        .route("/api/metrics/slow", get(stream::get_slow))
+        // ── Grafana simple-JSON datasource ──────────────────────
+        .route("/grafana", get(handlers::grafana::health))
+        .route("/grafana/", get(handlers::grafana::health))
+        .route("/grafana/search", post(handlers::grafana::search))
+        .route("/grafana/query", post(handlers::grafana::query))
+        .route(
+            "/grafana/annotations",
+            post(handlers::grafana::annotations),
+        )
         // ── Provide shared state to all routes above ────────────
         .with_state(state)
         // ── Serve static/ directory for the dashboard ───────────
         .fallback_service(ServeDir::new("static"))
         // ── Global middleware (applied bottom-up) ───────────────
-        .layer(axum_mw::from_fn(timing::timing_middleware))
+        .layer(axum_mw::from_fn_with_state(
+            metrics,
+            timing::timing_middleware,
+        ))
         .layer(CorsLayer::permissive())
-}
\ No newline at end of file
+}
+
+/// Wrap one route in load-shed + concurrency-limit when a cap is set.
+fn limited(
+    route: MethodRouter<Arc<AppState>>,
+    endpoint: &'static str,
+    max_in_flight: Option<usize>,
+    state: &Arc<AppState>,
+) -> MethodRouter<Arc<AppState>> {
+    let Some(max) = max_in_flight else {
+        return route;
+    };
+    let metrics = state.metrics.clone();
+
+    route.layer(
+        ServiceBuilder::new()
+            .layer(HandleErrorLayer::new(move |_: BoxError| {
+                let metrics = metrics.clone();
+                async move {
+                    metrics.record_shed(endpoint);
+                    AppError::Overloaded
+                }
+            }))
+            .load_shed()
+            .concurrency_limit(max),
+    )
+}
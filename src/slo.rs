@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+use crate::archive::ArchivedRun;
+use crate::validation::Violations;
+
+// ─── Public types ────────────────────────────────────────────────
+
+/// Pass/fail thresholds checked once a run ends. Unlike the health
+/// guard they never stop a run early. Every field is optional; an
+/// empty SLO passes every run.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Slo {
+    /// Highest acceptable end-to-end p99, μs
+    pub max_p99_us: Option<u64>,
+    /// Lowest acceptable overall throughput, requests/sec
+    pub min_rps: Option<f64>,
+    /// Fraction of failed requests over the whole run (0.0–1.0)
+    pub max_error_rate: Option<f64>,
+}
+
+/// How a finished run did against its config's `slo`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SloResult {
+    pub passed: bool,
+    /// Every broken threshold, worded for a status line
+    pub violations: Vec<String>,
+}
+
+impl Slo {
+    /// Field paths are relative to the SLO object.
+    pub fn check(&self, problems: &mut Violations) {
+        if let Some(rps) = self.min_rps {
+            if !(rps >= 0.0 && rps.is_finite()) {
+                problems.push("min_rps", "min_rps must be a number ≥ 0");
+            }
+        }
+        if let Some(rate) = self.max_error_rate {
+            problems.range("max_error_rate", rate, 0.0, 1.0);
+        }
+    }
+
+    /// Check a finished run's totals against every threshold.
+    pub fn evaluate(&self, run: &ArchivedRun) -> SloResult {
+        let mut violations = Vec::new();
+        if let Some(max) = self.max_p99_us {
+            let p99 = run.latency.as_ref().map_or(0, |l| l.p99);
+            if p99 > max {
+                violations.push(format!("p99 {p99} μs > {max} μs"));
+            }
+        }
+        if let Some(min) = self.min_rps {
+            if run.requests_per_sec < min {
+                violations.push(format!(
+                    "throughput {:.0} req/s < {min} req/s",
+                    run.requests_per_sec
+                ));
+            }
+        }
+        if let Some(max) = self.max_error_rate {
+            let rate = match run.total_requests {
+                0 => 0.0,
+                n => run.total_errors as f64 / n as f64,
+            };
+            if rate > max {
+                violations.push(format!(
+                    "error rate {:.2}% > {:.2}%",
+                    100.0 * rate,
+                    100.0 * max
+                ));
+            }
+        }
+        SloResult {
+            passed: violations.is_empty(),
+            violations,
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use serde::{Deserialize, Serialize};
+
+use crate::experiment::ExperimentState;
+use crate::handlers::benchmark::BenchmarkConfig;
+use crate::metrics::percentiles::PercentileSet;
+use crate::metrics::MetricsSnapshot;
+use crate::validation::Violations;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// Most steps one sweep may run
+const MAX_LEVELS: usize = 32;
+
+/// The setting a sweep steps through; everything else in the step
+/// stays fixed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SweepAxis {
+    #[default]
+    Concurrency,
+    PipelineDepth,
+}
+
+impl SweepAxis {
+    /// Levels tried when the request names none: 1, 2, 4 … 256 workers,
+    /// or 1, 2, 4 … 32 commands per batch
+    pub fn default_levels(self) -> Vec<u32> {
+        let top = match self {
+            SweepAxis::Concurrency => 8,
+            SweepAxis::PipelineDepth => 5,
+        };
+        (0..=top).map(|i| 1 << i).collect()
+    }
+
+    /// Same upper bound as the field in a single run
+    fn max_level(self) -> u32 {
+        match self {
+            SweepAxis::Concurrency => 500,
+            SweepAxis::PipelineDepth => 1_000,
+        }
+    }
+
+    pub fn apply(self, step: &mut BenchmarkConfig, level: u32) {
+        match self {
+            SweepAxis::Concurrency => step.concurrency = level,
+            SweepAxis::PipelineDepth => step.pipeline_depth = level,
+        }
+    }
+
+    /// Request field the axis replaces
+    pub fn field(self) -> &'static str {
+        match self {
+            SweepAxis::Concurrency => "concurrency",
+            SweepAxis::PipelineDepth => "pipeline_depth",
+        }
+    }
+
+    /// Human name for one level, for stage events
+    pub fn describe(self, level: u32) -> String {
+        match self {
+            SweepAxis::Concurrency => format!("{level} workers"),
+            SweepAxis::PipelineDepth => format!("pipeline depth {level}"),
+        }
+    }
+
+    /// Archive id suffix: `-c16`, `-p16`
+    pub fn id_suffix(self, level: u32) -> String {
+        match self {
+            SweepAxis::Concurrency => format!("c{level}"),
+            SweepAxis::PipelineDepth => format!("p{level}"),
+        }
+    }
+}
+
+/// The default 70 % read workload, in steps long enough for stable
+/// percentiles
+fn default_step() -> BenchmarkConfig {
+    serde_json::from_value(serde_json::json!({ "duration_secs": 10 }))
+        .expect("default step is a valid config")
+}
+
+// ─── Request ─────────────────────────────────────────────────────
+
+/// Run the same workload once per worker count or pipeline depth.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Sweep {
+    #[serde(default)]
+    pub axis: SweepAxis,
+    /// Empty means the axis's defaults
+    #[serde(default)]
+    pub levels: Vec<u32>,
+    /// Workload for every step; the axis's field is replaced by each
+    /// level in turn
+    #[serde(default = "default_step")]
+    pub step: BenchmarkConfig,
+}
+
+impl Sweep {
+    /// Fill in default levels and set the step to the largest level, so
+    /// checking the step catches conflicts such as `MULTI` with
+    /// pipelining.
+    pub fn prepare(&mut self) {
+        if self.levels.is_empty() {
+            self.levels = self.axis.default_levels();
+        }
+        if let Some(&top) = self.levels.iter().max() {
+            self.axis.apply(&mut self.step, top);
+        }
+    }
+
+    pub fn check(&self, problems: &mut Violations) {
+        if self.levels.len() > MAX_LEVELS {
+            problems.bounds(
+                "levels",
+                format!("levels takes at most {MAX_LEVELS} entries"),
+                1,
+                MAX_LEVELS,
+            );
+        }
+        for (i, level) in self.levels.iter().enumerate() {
+            let max = self.axis.max_level();
+            problems.range(&format!("levels[{i}]"), *level, 1, max);
+        }
+        // A ramp sets its own worker count
+        if self.step.ramp.is_some() {
+            problems.push("step.ramp", "ramp is not supported in a sweep");
+        }
+        if self.step.duration_secs == 0 {
+            problems.push(
+                "step.duration_secs",
+                "sweep steps cannot run until stopped",
+            );
+        }
+        if self.step.client_cache {
+            problems.push(
+                "step.client_cache",
+                "client_cache is not supported in a sweep",
+            );
+        }
+    }
+}
+
+// ─── Report ──────────────────────────────────────────────────────
+
+/// Progress and the throughput-vs-latency curve, served at
+/// `GET /api/experiments/sweep`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SweepReport {
+    pub run_id: String,
+    pub state: ExperimentState,
+    pub axis: SweepAxis,
+    /// One point per finished step, in run order
+    pub steps: Vec<SweepPoint>,
+    /// Level with the highest throughput so far. Past it, more workers
+    /// only add queueing; deeper pipelines only add batch latency
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_throughput_at: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SweepPoint {
+    /// Level on the sweep's axis
+    pub level: u32,
+    pub concurrency: u32,
+    pub pipeline_depth: u32,
+    pub total_requests: u64,
+    pub total_errors: u64,
+    pub requests_per_sec: f64,
+    pub e2e: PercentileSet,
+    pub redis_read: PercentileSet,
+    pub redis_write: PercentileSet,
+    /// Round trip of a whole batch, when pipelined
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pipeline_batch: Option<PercentileSet>,
+}
+
+impl SweepPoint {
+    pub fn new(
+        level: u32,
+        step: &BenchmarkConfig,
+        snap: &MetricsSnapshot,
+    ) -> Self {
+        Self {
+            level,
+            concurrency: step.concurrency,
+            pipeline_depth: step.pipeline_depth,
+            total_requests: snap.total_requests,
+            total_errors: snap.total_errors,
+            requests_per_sec: snap.avg_requests_per_sec,
+            e2e: snap.e2e.clone(),
+            redis_read: snap.redis_read.clone(),
+            redis_write: snap.redis_write.clone(),
+            pipeline_batch: (step.pipeline_depth > 1)
+                .then(|| snap.pipeline_batch.clone()),
+        }
+    }
+}
+
+impl SweepReport {
+    /// Record a finished step and update the peak.
+    pub fn push(&mut self, point: SweepPoint) {
+        self.steps.push(point);
+        self.peak_throughput_at = self
+            .steps
+            .iter()
+            .max_by(|a, b| a.requests_per_sec.total_cmp(&b.requests_per_sec))
+            .map(|p| p.level);
+    }
+}
@@ -0,0 +1,104 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt::Display;
+
+use crate::handlers::AppError;
+
+// ─── Config violations ───────────────────────────────────────────
+
+/// One problem with a submitted config, addressed by field path so the
+/// dashboard can put the message next to the input it belongs to.
+#[derive(Debug, Clone, Serialize)]
+pub struct Violation {
+    /// Dotted path into the request body, e.g. `value_size.min` or
+    /// `stages[2].concurrency`; empty when no single field is at fault
+    pub field: String,
+    pub message: String,
+    /// Accepted range, when the field has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<Value>,
+}
+
+/// Collects every violation in a config instead of stopping at the
+/// first, so one round-trip shows the user everything to fix.
+#[derive(Debug, Default)]
+pub struct Violations(Vec<Violation>);
+
+impl Violations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, field: &str, message: impl Into<String>) {
+        self.0.push(Violation {
+            field: field.into(),
+            message: message.into(),
+            min: None,
+            max: None,
+        });
+    }
+
+    /// A violation with a custom message that still reports the range.
+    pub fn bounds(
+        &mut self,
+        field: &str,
+        message: impl Into<String>,
+        min: impl Into<Value>,
+        max: impl Into<Value>,
+    ) {
+        self.0.push(Violation {
+            field: field.into(),
+            message: message.into(),
+            min: Some(min.into()),
+            max: Some(max.into()),
+        });
+    }
+
+    /// Require `min <= value <= max`.
+    pub fn range<T>(&mut self, field: &str, value: T, min: T, max: T)
+    where
+        T: PartialOrd + Display + Into<Value> + Copy,
+    {
+        // Written so NaN lands outside the range
+        if !(value >= min && value <= max) {
+            self.0.push(Violation {
+                field: field.into(),
+                message: format!("{field} must be between {min} and {max}"),
+                min: Some(min.into()),
+                max: Some(max.into()),
+            });
+        }
+    }
+
+    /// Take over `other`'s violations with `prefix` in front of their
+    /// paths (`stages[0]` + `concurrency` → `stages[0].concurrency`) and
+    /// `label` in front of their messages.
+    pub fn nest(&mut self, prefix: &str, label: &str, other: Violations) {
+        for mut v in other.0 {
+            v.field = match v.field.is_empty() {
+                true => prefix.to_string(),
+                false => format!("{prefix}.{}", v.field),
+            };
+            v.message = format!("{label}: {}", v.message);
+            self.0.push(v);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_vec(self) -> Vec<Violation> {
+        self.0
+    }
+
+    /// `Ok` when nothing was found, else a 400 listing every violation.
+    pub fn into_result(self) -> Result<(), AppError> {
+        match self.0.is_empty() {
+            true => Ok(()),
+            false => Err(AppError::Invalid(self.0)),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use hyper::header::{HeaderName, AUTHORIZATION, CONTENT_TYPE};
+
+use crate::config::WebhookSettings;
+use crate::metrics::push::{self, Target};
+use crate::report::RunReport;
+
+// ─── Configuration ───────────────────────────────────────────────
+
+/// Wait before each retry of a failed delivery
+const RETRY_DELAYS: [Duration; 2] =
+    [Duration::from_secs(2), Duration::from_secs(10)];
+
+// ─── Webhook ─────────────────────────────────────────────────────
+
+/// POSTs each benchmark or scenario run's final report, as JSON, to a
+/// fixed URL once the run has ended, however it ended.
+pub struct Webhook {
+    target: Target,
+    headers: Vec<(HeaderName, String)>,
+    /// Names the hook in log lines, e.g. "Webhook"
+    what: &'static str,
+}
+
+impl Webhook {
+    pub fn new(settings: &WebhookSettings) -> Result<Self, String> {
+        let mut headers = Vec::new();
+        if let Some(token) = &settings.token {
+            headers.push((AUTHORIZATION, format!("Bearer {token}")));
+        }
+        Self::json(&settings.url, "Webhook", headers)
+    }
+
+    /// A hook that POSTs JSON bodies with `headers` added.
+    pub fn json(
+        url: &str,
+        what: &'static str,
+        mut headers: Vec<(HeaderName, String)>,
+    ) -> Result<Self, String> {
+        let target = Target::parse(url, what)?;
+        headers.insert(0, (CONTENT_TYPE, "application/json".to_string()));
+        Ok(Self {
+            target,
+            headers,
+            what,
+        })
+    }
+
+    /// Deliver the report in the background. The run's archiving does
+    /// not wait on it.
+    pub fn send(self: &Arc<Self>, report: &RunReport) {
+        match serde_json::to_vec(report) {
+            Ok(body) => self.post(report.run_id.clone(), body),
+            Err(e) => {
+                let (what, run_id) = (self.what, &report.run_id);
+                eprintln!("⚠️  {what} skipped for run {run_id}: {e}");
+            }
+        }
+    }
+
+    /// POST `body` in the background, retrying a failed POST twice.
+    /// `run_id` is only for the log line when it gives up.
+    pub fn post(self: &Arc<Self>, run_id: String, body: Vec<u8>) {
+        let hook = self.clone();
+        tokio::spawn(async move {
+            let (what, authority) = (hook.what, &hook.target.authority);
+            let mut delays = RETRY_DELAYS.iter();
+            loop {
+                let body = body.clone().into();
+                let result =
+                    push::post(&hook.target, &hook.headers, body).await;
+                let e = match result {
+                    Ok(()) => return,
+                    Err(e) => e,
+                };
+                match delays.next() {
+                    Some(delay) => tokio::time::sleep(*delay).await,
+                    None => {
+                        eprintln!(
+                            "⚠️  {what} to {authority} gave up on run \
+                             {run_id}: {e}"
+                        );
+                        return;
+                    }
+                }
+            }
+        });
+    }
+}
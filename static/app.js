@@ -6,6 +6,7 @@
 
 let evtSource = null;
 let benchRunning = false;
+let progressTimer = null;
 
 // ─── Chart.js defaults ──────────────────────────────────────
 
@@ -71,6 +72,38 @@ const timelineChart = new Chart(timelineCtx, {
         pointRadius: 0,
         borderWidth: 2,
       },
+      {
+        label: 'Active Workers',
+        data: [],
+        borderColor: C.cyan,
+        backgroundColor: C.cyanA,
+        fill: false,
+        stepped: true,
+        pointRadius: 0,
+        borderWidth: 1,
+        borderDash: [4, 4],
+        yAxisID: 'workers',
+      },
+      {
+        label: 'E2E p99 (μs)',
+        data: [],
+        borderColor: C.red,
+        fill: false,
+        tension: 0.3,
+        pointRadius: 0,
+        borderWidth: 1.5,
+        borderDash: [6, 3],
+      },
+      {
+        label: 'Throughput (req/s)',
+        data: [],
+        borderColor: C.purple,
+        fill: false,
+        tension: 0.3,
+        pointRadius: 0,
+        borderWidth: 1.5,
+        yAxisID: 'rps',
+      },
     ],
   },
   options: {
@@ -81,7 +114,14 @@ const timelineChart = new Chart(timelineCtx, {
       legend: { position: 'top', labels: { usePointStyle: true, padding: 20 } },
       tooltip: {
         callbacks: {
-          label: (ctx) => `${ctx.dataset.label}: ${Math.round(ctx.raw)} μs`,
+          label: (ctx) => {
+            const { label, yAxisID } = ctx.dataset;
+            if (yAxisID === 'workers') return `${label}: ${ctx.raw}`;
+            if (yAxisID === 'rps') {
+              return `${label}: ${Math.round(ctx.raw).toLocaleString()}`;
+            }
+            return `${label}: ${Math.round(ctx.raw)} μs`;
+          },
         },
       },
     },
@@ -95,6 +135,18 @@ const timelineChart = new Chart(timelineCtx, {
         beginAtZero: true,
         grid: { color: '#1f2233' },
       },
+      workers: {
+        position: 'right',
+        title: { display: true, text: 'Workers' },
+        beginAtZero: true,
+        grid: { display: false },
+      },
+      rps: {
+        position: 'right',
+        title: { display: true, text: 'req/s' },
+        beginAtZero: true,
+        grid: { display: false },
+      },
     },
   },
 });
@@ -127,6 +179,12 @@ const percentileChart = new Chart(percCtx, {
         backgroundColor: C.green,
         borderRadius: 4,
       },
+      {
+        label: 'Framework',
+        data: [0, 0, 0],
+        backgroundColor: C.cyan,
+        borderRadius: 4,
+      },
       {
         label: 'E2E',
         data: [0, 0, 0],
@@ -244,6 +302,60 @@ const donutChart = new Chart(donutCtx, {
   },
 });
 
+// ═════════════════════════════════════════════════════════════
+// 5. LATENCY HEATMAP — drawn by hand on a plain canvas
+// ═════════════════════════════════════════════════════════════
+
+const heatmapCanvas = document.getElementById('heatmapCanvas');
+
+/**
+ * One column per timeline window, one row per latency bucket (fastest
+ * at the bottom). A cell's brightness is its share of the window's
+ * requests, so a busier window doesn't wash out a quieter one.
+ */
+function drawHeatmap(heatmap) {
+  const ctx = heatmapCanvas.getContext('2d');
+  const ratio = window.devicePixelRatio || 1;
+  const width = heatmapCanvas.clientWidth;
+  const height = heatmapCanvas.clientHeight;
+  heatmapCanvas.width = width * ratio;
+  heatmapCanvas.height = height * ratio;
+  ctx.setTransform(ratio, 0, 0, ratio, 0, 0);
+  ctx.clearRect(0, 0, width, height);
+  if (!heatmap || heatmap.columns.length === 0) return;
+
+  // Row labels on the left: the upper bound of each bucket
+  const gutter = 52;
+  const rows = heatmap.bounds_us.length + 1;
+  const rowH = height / rows;
+  const colW = (width - gutter) / heatmap.columns.length;
+  ctx.font = '10px Inter, sans-serif';
+  ctx.fillStyle = Chart.defaults.color;
+  ctx.textBaseline = 'middle';
+  heatmap.bounds_us.forEach((bound, i) => {
+    if (i % 2 === 0) {
+      ctx.fillText(`≤${fmtUs(bound)}`, 0, height - (i + 0.5) * rowH);
+    }
+  });
+
+  heatmap.columns.forEach((col, x) => {
+    const total = col.counts.reduce((a, b) => a + b, 0);
+    if (total === 0) return;
+    col.counts.forEach((count, i) => {
+      if (count === 0) return;
+      // Square root lifts the thin tail buckets into view
+      const share = Math.sqrt(count / total);
+      ctx.fillStyle = `rgba(59, 130, 246, ${0.1 + 0.9 * share})`;
+      ctx.fillRect(
+        gutter + x * colW,
+        height - (i + 1) * rowH,
+        Math.max(colW, 1),
+        rowH - 1
+      );
+    });
+  });
+}
+
 // ═════════════════════════════════════════════════════════════
 // SSE CONNECTION
 // ═════════════════════════════════════════════════════════════
@@ -273,6 +385,56 @@ function connectSSE() {
 // Start SSE immediately on page load
 connectSSE();
 
+// ═════════════════════════════════════════════════════════════
+// CONNECTION INFO — transport label (tcp / tls / unix)
+// ═════════════════════════════════════════════════════════════
+
+async function loadConnectionInfo() {
+  try {
+    const res = await fetch('/api/connection');
+    const info = await res.json();
+    const badge = document.getElementById('transportBadge');
+    badge.textContent = info.transport;
+    badge.title = `${info.address} — connect ${fmtUs(info.client_connect_us)}` +
+      (info.tls_handshake_us != null
+        ? `, TLS handshake ${fmtUs(info.tls_handshake_us)}`
+        : '');
+  } catch (e) {
+    console.warn('Connection info unavailable:', e);
+  }
+}
+
+loadConnectionInfo();
+
+// Baseline health from the always-on background prober
+async function loadProbe() {
+  try {
+    const res = await fetch('/api/probe');
+    const probe = await res.json();
+    const badge = document.getElementById('probeBadge');
+    if (!probe.enabled) {
+      badge.textContent = 'baseline off';
+      return;
+    }
+    const last = probe.timeline[probe.timeline.length - 1];
+    const down = last && last.count === 0 && last.errors > 0;
+    badge.classList.toggle('probe-down', Boolean(down));
+    badge.textContent = down
+      ? 'baseline DOWN'
+      : `baseline p50 ${fmtUs(probe.latency.p50)}`;
+    badge.title =
+      `Background probe every ${probe.interval_ms}ms — ` +
+      `p99 ${fmtUs(probe.latency.p99)}, ` +
+      `${probe.total_errors}/${probe.total_probes} errors` +
+      (probe.last_error ? `\nLast error: ${probe.last_error}` : '');
+  } catch (e) {
+    console.warn('Probe unavailable:', e);
+  }
+}
+
+loadProbe();
+setInterval(loadProbe, 5000);
+
 // ═════════════════════════════════════════════════════════════
 // DASHBOARD UPDATE — called on every SSE tick (~500ms)
 // ═════════════════════════════════════════════════════════════
@@ -308,7 +470,7 @@ function updateDashboard(snap) {
   }
 
   // ── Timeline chart ────────────────────────────────────────
-  updateTimeline(snap.timeline);
+  updateTimeline(snap.timeline, snap.timeline_window_ms);
 
   // ── Percentile chart ──────────────────────────────────────
   updatePercentiles(snap);
@@ -316,6 +478,9 @@ function updateDashboard(snap) {
   // ── Distribution chart ────────────────────────────────────
   updateDistribution(snap.distribution);
 
+  // ── Latency heatmap ───────────────────────────────────────
+  updateHeatmap(snap.heatmap);
+
   // ── Donut chart ───────────────────────────────────────────
   updateDonut(snap);
 
@@ -324,24 +489,35 @@ function updateDashboard(snap) {
 
   // ── Live feed ─────────────────────────────────────────────
   updateFeed(snap.recent_samples);
+
+  // ── Stream trimming under many viewers ────────────────────
+  updateStreamBadge(snap.stream);
 }
 
 // ═════════════════════════════════════════════════════════════
 // INDIVIDUAL UPDATE FUNCTIONS
 // ═════════════════════════════════════════════════════════════
 
-function updateTimeline(timeline) {
+function updateTimeline(timeline, windowMs) {
   if (!timeline || timeline.length === 0) return;
 
-  const labels = timeline.map((p) => (p.timestamp_ms / 1000).toFixed(1));
+  // Enough decimals to tell neighbouring windows apart
+  const digits = windowMs < 100 ? 2 : windowMs < 1000 ? 1 : 0;
+  const labels = timeline.map((p) => (p.timestamp_ms / 1000).toFixed(digits));
   const e2e   = timeline.map((p) => Math.round(p.avg_total_us));
   const redis = timeline.map((p) => Math.round(p.avg_redis_us));
   const rust  = timeline.map((p) => Math.round(p.avg_rust_us));
+  const workers = timeline.map((p) => p.active_workers);
+  const p99   = timeline.map((p) => p.p99_total_us);
+  const rps   = timeline.map((p) => p.requests_per_sec);
 
   timelineChart.data.labels = labels;
   timelineChart.data.datasets[0].data = e2e;
   timelineChart.data.datasets[1].data = redis;
   timelineChart.data.datasets[2].data = rust;
+  timelineChart.data.datasets[3].data = workers;
+  timelineChart.data.datasets[4].data = p99;
+  timelineChart.data.datasets[5].data = rps;
   timelineChart.update('none');
 }
 
@@ -349,12 +525,14 @@ function updatePercentiles(snap) {
   const rr = snap.redis_read;
   const rw = snap.redis_write;
   const ro = snap.rust_overhead;
+  const fw = snap.framework_overhead;
   const e  = snap.e2e;
 
   percentileChart.data.datasets[0].data = [rr.p50, rr.p95, rr.p99];
   percentileChart.data.datasets[1].data = [rw.p50, rw.p95, rw.p99];
   percentileChart.data.datasets[2].data = [ro.p50, ro.p95, ro.p99];
-  percentileChart.data.datasets[3].data = [e.p50,  e.p95,  e.p99];
+  percentileChart.data.datasets[3].data = [fw.p50, fw.p95, fw.p99];
+  percentileChart.data.datasets[4].data = [e.p50,  e.p95,  e.p99];
   percentileChart.update('none');
 }
 
@@ -371,6 +549,12 @@ function updateDistribution(distribution) {
   distChart.update('none');
 }
 
+function updateHeatmap(heatmap) {
+  // Trimmed streams leave the columns out; keep the last drawing
+  if (!heatmap || heatmap.columns.length === 0) return;
+  drawHeatmap(heatmap);
+}
+
 function updateDonut(snap) {
   const readMean  = snap.redis_read.count > 0  ? snap.redis_read.mean  : 0;
   const writeMean = snap.redis_write.count > 0 ? snap.redis_write.mean : 0;
@@ -391,6 +575,7 @@ function updateSummaryTable(snap) {
   fillRow('rowRedisRead',    snap.redis_read);
   fillRow('rowRedisWrite',   snap.redis_write);
   fillRow('rowRustOverhead', snap.rust_overhead);
+  fillRow('rowFramework',    snap.framework_overhead);
   fillRow('rowE2E',          snap.e2e);
 }
 
@@ -417,6 +602,19 @@ function fillRow(rowId, ps) {
 
 let lastFeedLen = 0;
 
+function updateStreamBadge(stream) {
+  const badge = document.getElementById('streamBadge');
+  if (!stream || stream.degradation === 'full') {
+    badge.hidden = true;
+    return;
+  }
+  badge.hidden = false;
+  badge.textContent = `${stream.clients} viewers · ${stream.degradation}`;
+  badge.title =
+    'Many dashboards are connected, so the server sends less: no live ' +
+    `feed, one update every ${stream.interval_ms}ms`;
+}
+
 function updateFeed(samples) {
   if (!samples || samples.length === 0) return;
 
@@ -439,13 +637,17 @@ function updateFeed(samples) {
       const path = parts.slice(1).join(' ') || s.endpoint;
       const methodClass = method.toLowerCase();
       const statusClass = s.success ? 'ok' : 'fail';
-      const statusText  = s.success ? 'OK' : 'ERR';
+      const statusText  = s.status ?? (s.success ? 'OK' : 'ERR');
+      const error = s.error
+        ? `<span class="feed-error" title="${escapeHtml(s.error)}">${escapeHtml(s.error)}</span>`
+        : '';
 
       return `<div class="feed-line">
         <span class="feed-method ${methodClass}">${method}</span>
         <span class="feed-path">${path}</span>
         <span class="feed-time">${fmtUs(s.total_us)}</span>
         <span class="feed-status ${statusClass}">${statusText}</span>
+        ${error}
       </div>`;
     })
     .join('');
@@ -462,7 +664,9 @@ function updateFeed(samples) {
 
 async function startBenchmark() {
   const concurrency  = parseInt(document.getElementById('concurrency').value, 10) || 10;
-  const durationSecs = parseInt(document.getElementById('duration').value, 10) || 30;
+  // 0 is meaningful: run until stopped
+  const durationRaw  = parseInt(document.getElementById('duration').value, 10);
+  const durationSecs = Number.isNaN(durationRaw) ? 30 : durationRaw;
   const readPct      = parseInt(document.getElementById('readPct').value, 10);
 
   try {
@@ -485,13 +689,7 @@ async function startBenchmark() {
     benchRunning = true;
     document.getElementById('btnStart').disabled = true;
     document.getElementById('btnStop').disabled = false;
-
-    // Auto-stop UI after duration + buffer
-    setTimeout(() => {
-      benchRunning = false;
-      document.getElementById('btnStart').disabled = false;
-      document.getElementById('btnStop').disabled = true;
-    }, (durationSecs + 2) * 1000);
+    pollProgress();
 
   } catch (e) {
     alert(`Network error: ${e.message}`);
@@ -510,6 +708,41 @@ async function stopBenchmark() {
   document.getElementById('btnStop').disabled = true;
 }
 
+// Poll /status while a run is going: progress badge, and the buttons
+// flip back once the server reports the run over
+async function pollProgress() {
+  clearTimeout(progressTimer);
+  const badge = document.getElementById('progressBadge');
+  let status = null;
+  try {
+    const res = await fetch('/api/benchmark/status');
+    status = await res.json();
+  } catch (e) {
+    console.error('Status error:', e);
+  }
+
+  const p = status && status.progress;
+  if (status && !status.running) {
+    benchRunning = false;
+    document.getElementById('btnStart').disabled = false;
+    document.getElementById('btnStop').disabled = true;
+  }
+  if (!benchRunning) {
+    badge.hidden = true;
+    return;
+  }
+  if (p) {
+    badge.hidden = false;
+    badge.textContent = p.percent_complete == null
+      ? `${p.elapsed_secs.toFixed(0)}s elapsed`
+      : `${p.percent_complete.toFixed(0)}% · ${p.remaining_secs.toFixed(0)}s left`;
+    badge.title =
+      `${p.concurrency} workers, ` +
+      `${p.total_requests.toLocaleString()} requests so far`;
+  }
+  progressTimer = setTimeout(pollProgress, 1000);
+}
+
 function resetDashboard() {
   // Reset all charts to empty
   timelineChart.data.labels = [];
@@ -526,6 +759,8 @@ function resetDashboard() {
   donutChart.data.datasets[0].data = [1, 1, 1];
   donutChart.update('none');
 
+  drawHeatmap(null);
+
   // Reset KPIs
   document.getElementById('kpiTotal').textContent = '0';
   document.getElementById('kpiRps').innerHTML = '0 <small>req/s</small>';
@@ -564,4 +799,13 @@ function fmtUs(us) {
   if (us < 1000) return `${us}μs`;
   if (us < 1_000_000) return `${(us / 1000).toFixed(2)}ms`;
   return `${(us / 1_000_000).toFixed(2)}s`;
-}
\ No newline at end of file
+}
+
+/** Escape text for use inside HTML, e.g. a Redis error message. */
+function escapeHtml(text) {
+  return String(text)
+    .replace(/&/g, '&amp;')
+    .replace(/</g, '&lt;')
+    .replace(/>/g, '&gt;')
+    .replace(/"/g, '&quot;');
+}